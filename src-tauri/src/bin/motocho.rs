@@ -0,0 +1,6 @@
+// Headless CLI over the dashboard commands, e.g. `motocho analytics --format json`
+
+fn main() {
+  let args: Vec<String> = std::env::args().skip(1).collect();
+  std::process::exit(app_lib::cli::run(args));
+}
@@ -0,0 +1,466 @@
+/**
+ * Headless command line interface
+ *
+ * Backs the `motocho` binary: runs the same command functions the desktop app
+ * invokes and prints their results as JSON, a plain-text table or Markdown,
+ * so analytics, history, reports and file changes can be used from scripts.
+ */
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::commands::{
+    analytics_v2::get_analytics_v2,
+    files::get_all_file_changes,
+    history::search_history,
+    reports::generate_report,
+    sources::CODEX_SOURCE,
+    transcripts::{get_project_sessions, get_projects, Session},
+};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+const USAGE: &str = "\
+Usage: motocho <command> [options]
+
+Commands:
+  analytics                         Usage summary and per-model totals
+  search-history <query>            Search prompt history
+      --project <path>              Only prompts from projects matching <path>
+      --limit <n>                   Maximum number of results
+      --source <id>                 Transcript source: code (default) or codex
+      --codex                       Same as --source codex
+  report --from <date> --to <date>  Work report for a date range (YYYY-MM-DD)
+      --project <id>                Limit to one project
+  sessions                          List sessions, newest first
+      --project <id>                Limit to one project
+      --source <id>                 Transcript source: code (default) or codex
+  file-changes                      List changes from all file-changing tools, newest first
+      --project <id>                Limit to one project
+      --session <id>                Limit to one session
+      --limit <n>                   Maximum number of results
+
+Options:
+  --format <json|table|markdown>    Output format (default: table, report: markdown)
+  -h, --help                        Show this help
+";
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OutputFormat {
+    Json,
+    Table,
+    Markdown,
+}
+
+/// Parsed command line: positional arguments, `--key value` options and `--flag` switches
+struct ParsedArgs {
+    positional: Vec<String>,
+    options: HashMap<String, String>,
+    flags: Vec<String>,
+}
+
+/// Options that are switches and never take a value
+const SWITCHES: &[&str] = &["codex", "help"];
+
+impl ParsedArgs {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut positional = Vec::new();
+        let mut options = HashMap::new();
+        let mut flags = Vec::new();
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if arg == "-h" {
+                flags.push("help".to_string());
+            } else if let Some(name) = arg.strip_prefix("--") {
+                if let Some((key, value)) = name.split_once('=') {
+                    options.insert(key.to_string(), value.to_string());
+                } else if SWITCHES.contains(&name) {
+                    flags.push(name.to_string());
+                } else {
+                    let value = iter.next().ok_or_else(|| format!("Missing value for --{}", name))?;
+                    options.insert(name.to_string(), value.clone());
+                }
+            } else {
+                positional.push(arg.clone());
+            }
+        }
+
+        Ok(ParsedArgs { positional, options, flags })
+    }
+
+    fn option(&self, name: &str) -> Option<String> {
+        self.options.get(name).cloned()
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|f| f == name)
+    }
+
+    fn limit(&self) -> Result<Option<usize>, String> {
+        self.option("limit")
+            .map(|l| l.parse::<usize>().map_err(|_| format!("Invalid --limit: {}", l)))
+            .transpose()
+    }
+
+    fn format(&self, default: OutputFormat) -> Result<OutputFormat, String> {
+        match self.option("format").as_deref() {
+            None => Ok(default),
+            Some("json") => Ok(OutputFormat::Json),
+            Some("table") => Ok(OutputFormat::Table),
+            Some("markdown") | Some("md") => Ok(OutputFormat::Markdown),
+            Some(other) => Err(format!("Unknown format: {}", other)),
+        }
+    }
+}
+
+// ============================================================================
+// Output Helpers
+// ============================================================================
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|e| format!("Failed to serialize output: {}", e))
+}
+
+/// Render rows as an aligned plain-text table or a Markdown table
+fn render_rows(format: OutputFormat, headers: &[&str], rows: &[Vec<String>]) -> String {
+    if format == OutputFormat::Markdown {
+        let mut out = format!("| {} |\n", headers.join(" | "));
+        out.push_str(&format!("|{}\n", " --- |".repeat(headers.len())));
+        for row in rows {
+            let cells: Vec<String> = row.iter().map(|c| c.replace('|', "\\|")).collect();
+            out.push_str(&format!("| {} |\n", cells.join(" | ")));
+        }
+        return out;
+    }
+
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.chars().count());
+        }
+    }
+
+    let format_line = |cells: Vec<String>| -> String {
+        cells
+            .iter()
+            .enumerate()
+            .map(|(i, c)| format!("{:width$}", c, width = widths[i]))
+            .collect::<Vec<String>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut out = format_line(headers.iter().map(|h| h.to_string()).collect());
+    out.push('\n');
+    out.push_str(&format_line(widths.iter().map(|w| "-".repeat(*w)).collect()));
+    out.push('\n');
+    for row in rows {
+        out.push_str(&format_line(row.clone()));
+        out.push('\n');
+    }
+    out
+}
+
+/// Collapse whitespace and cut long text so table cells stay on one line
+fn one_line(text: &str, max_chars: usize) -> String {
+    let collapsed = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+    if collapsed.chars().count() > max_chars {
+        format!("{}...", collapsed.chars().take(max_chars.saturating_sub(3)).collect::<String>())
+    } else {
+        collapsed
+    }
+}
+
+fn format_ms(timestamp_ms: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(timestamp_ms)
+        .map(|dt| dt.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+// ============================================================================
+// Subcommands
+// ============================================================================
+
+async fn run_analytics(args: &ParsedArgs) -> Result<String, String> {
+    let format = args.format(OutputFormat::Table)?;
+    let analytics = get_analytics_v2().await?;
+
+    if format == OutputFormat::Json {
+        return to_json(&analytics);
+    }
+
+    let s = &analytics.summary;
+    let summary_rows = vec![
+        vec!["Sessions".to_string(), s.total_sessions.to_string()],
+        vec!["Messages".to_string(), s.total_messages.to_string()],
+        vec!["Tool calls".to_string(), s.total_tool_calls.to_string()],
+        vec!["Tokens".to_string(), s.total_tokens.to_string()],
+        vec!["Cost (USD)".to_string(), format!("{:.2}", s.total_cost)],
+        vec!["Days active".to_string(), s.days_active.to_string()],
+        vec!["First session".to_string(), s.first_session_date.clone()],
+        vec!["Last active".to_string(), s.last_active_date.clone()],
+    ];
+
+    let mut models = analytics.model_usage.clone();
+    models.sort_by(|a, b| b.cost_usd.partial_cmp(&a.cost_usd).unwrap_or(std::cmp::Ordering::Equal));
+    let model_rows: Vec<Vec<String>> = models
+        .iter()
+        .map(|m| {
+            vec![
+                m.source.clone(),
+                m.model_id.clone(),
+                m.message_count.to_string(),
+                m.input_tokens.to_string(),
+                m.output_tokens.to_string(),
+                (m.cache_read_input_tokens + m.cache_creation_input_tokens).to_string(),
+                format!("{:.2}", m.cost_usd),
+            ]
+        })
+        .collect();
+
+    let heading = |title: &str| {
+        if format == OutputFormat::Markdown {
+            format!("## {}\n\n", title)
+        } else {
+            format!("{}\n\n", title)
+        }
+    };
+
+    Ok(format!(
+        "{}{}\n{}{}",
+        heading("Summary"),
+        render_rows(format, &["Metric", "Value"], &summary_rows),
+        heading("Models"),
+        render_rows(
+            format,
+            &["Source", "Model", "Messages", "Input", "Output", "Cache", "Cost (USD)"],
+            &model_rows,
+        ),
+    ))
+}
+
+async fn run_search_history(args: &ParsedArgs) -> Result<String, String> {
+    let format = args.format(OutputFormat::Table)?;
+    let query = args
+        .positional
+        .get(1)
+        .cloned()
+        .ok_or_else(|| "search-history requires a query".to_string())?;
+
+    let source = if args.flag("codex") { Some(CODEX_SOURCE.to_string()) } else { args.option("source") };
+    let results = search_history(query, args.option("project"), args.limit()?, source).await?;
+
+    if format == OutputFormat::Json {
+        return to_json(&results);
+    }
+
+    let rows: Vec<Vec<String>> = results
+        .iter()
+        .map(|r| {
+            vec![
+                format!("{} {}", r.formatted_date, r.formatted_time),
+                r.project_name.clone(),
+                one_line(&r.entry.display, 80),
+            ]
+        })
+        .collect();
+
+    Ok(render_rows(format, &["Date", "Project", "Prompt"], &rows))
+}
+
+async fn run_report(args: &ParsedArgs) -> Result<String, String> {
+    let format = args.format(OutputFormat::Markdown)?;
+    let from = args.option("from").ok_or_else(|| "report requires --from <YYYY-MM-DD>".to_string())?;
+    let to = args.option("to").ok_or_else(|| "report requires --to <YYYY-MM-DD>".to_string())?;
+
+    let report = generate_report(args.option("project"), from, to, Some(false)).await?;
+
+    match format {
+        OutputFormat::Json => to_json(&report),
+        OutputFormat::Markdown => Ok(report.markdown),
+        OutputFormat::Table => {
+            let rows: Vec<Vec<String>> = report
+                .work_items
+                .iter()
+                .map(|g| {
+                    vec![
+                        g.work_type.clone(),
+                        one_line(&g.subject, 70),
+                        g.count.to_string(),
+                        format_ms(g.last_timestamp),
+                    ]
+                })
+                .collect();
+            Ok(render_rows(format, &["Type", "Subject", "Count", "Last"], &rows))
+        }
+    }
+}
+
+async fn run_sessions(args: &ParsedArgs) -> Result<String, String> {
+    let format = args.format(OutputFormat::Table)?;
+
+    let source = args.option("source");
+    let mut sessions: Vec<Session> = match args.option("project") {
+        Some(project_id) => get_project_sessions(project_id, source).await?,
+        None => {
+            let mut all = Vec::new();
+            for project in get_projects(source.clone()).await? {
+                all.extend(get_project_sessions(project.id, source.clone()).await?);
+            }
+            all
+        }
+    };
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.last_modified));
+
+    if format == OutputFormat::Json {
+        return to_json(&sessions);
+    }
+
+    let rows: Vec<Vec<String>> = sessions
+        .iter()
+        .map(|s| {
+            let cost = s.stats.as_ref().map(|st| st.total_cost_usd).unwrap_or(0.0);
+            vec![
+                format_ms(s.last_modified),
+                s.host.clone(),
+                s.project_id.clone(),
+                s.id.clone(),
+                s.message_count.to_string(),
+                format!("{:.2}", cost),
+                one_line(&s.summary, 60),
+            ]
+        })
+        .collect();
+
+    Ok(render_rows(
+        format,
+        &["Modified", "Host", "Project", "Session", "Messages", "Cost (USD)", "Summary"],
+        &rows,
+    ))
+}
+
+async fn run_file_changes(args: &ParsedArgs) -> Result<String, String> {
+    let format = args.format(OutputFormat::Table)?;
+    let project = args.option("project");
+    let session = args.option("session");
+
+    let mut changes: Vec<_> = get_all_file_changes()
+        .await?
+        .into_iter()
+        .filter(|c| project.as_ref().map(|p| &c.project_id == p).unwrap_or(true))
+        .filter(|c| session.as_ref().map(|s| &c.session_id == s).unwrap_or(true))
+        .collect();
+    if let Some(limit) = args.limit()? {
+        changes.truncate(limit);
+    }
+
+    if format == OutputFormat::Json {
+        return to_json(&changes);
+    }
+
+    let rows: Vec<Vec<String>> = changes
+        .iter()
+        .map(|c| {
+            vec![
+                c.timestamp.clone(),
+                c.change_type.clone(),
+                c.file_path.clone(),
+                c.session_id.clone(),
+            ]
+        })
+        .collect();
+
+    Ok(render_rows(format, &["Timestamp", "Type", "File", "Session"], &rows))
+}
+
+// ============================================================================
+// Entry Point
+// ============================================================================
+
+/// Run the CLI with the given arguments (excluding the program name).
+/// Returns the process exit code.
+pub fn run(args: Vec<String>) -> i32 {
+    let parsed = match ParsedArgs::parse(&args) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            return 2;
+        }
+    };
+
+    let command = match parsed.positional.first() {
+        Some(command) if !parsed.flag("help") => command.clone(),
+        _ => {
+            print!("{}", USAGE);
+            return if parsed.flag("help") { 0 } else { 2 };
+        }
+    };
+
+    let result = tauri::async_runtime::block_on(async {
+        match command.as_str() {
+            "analytics" => run_analytics(&parsed).await,
+            "search-history" => run_search_history(&parsed).await,
+            "report" => run_report(&parsed).await,
+            "sessions" => run_sessions(&parsed).await,
+            "file-changes" => run_file_changes(&parsed).await,
+            other => Err(format!("Unknown command: {}\n\n{}", other, USAGE)),
+        }
+    });
+
+    match result {
+        Ok(output) => {
+            println!("{}", output.trim_end());
+            0
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            1
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_options_and_switches() {
+        let parsed = ParsedArgs::parse(&args(&[
+            "search-history", "deploy", "--limit", "5", "--codex", "--format=json",
+        ]))
+        .unwrap();
+
+        assert_eq!(parsed.positional, vec!["search-history", "deploy"]);
+        assert_eq!(parsed.limit().unwrap(), Some(5));
+        assert!(parsed.flag("codex"));
+        assert_eq!(parsed.format(OutputFormat::Table).unwrap(), OutputFormat::Json);
+        assert!(ParsedArgs::parse(&args(&["report", "--from"])).is_err());
+    }
+
+    #[test]
+    fn test_render_rows() {
+        let rows = vec![vec!["a|b".to_string(), "1".to_string()]];
+
+        let table = render_rows(OutputFormat::Table, &["Name", "N"], &rows);
+        assert_eq!(table, "Name  N\n----  -\na|b   1\n");
+
+        let markdown = render_rows(OutputFormat::Markdown, &["Name", "N"], &rows);
+        assert_eq!(markdown, "| Name | N |\n| --- | --- |\n| a\\|b | 1 |\n");
+    }
+}
@@ -11,6 +11,8 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use super::data_roots::local_root;
+
 // ============================================================================
 // Type Definitions - Match TypeScript interfaces
 // ============================================================================
@@ -120,16 +122,16 @@ pub fn parse_agent_file(content: &str, path: &str, agent_type: &str, plugin_name
     })
 }
 
+/// User agent folder of the local Claude config directory
+fn get_user_agents_dir() -> Result<PathBuf, String> {
+    Ok(PathBuf::from(local_root()?.claude_dir).join("agents"))
+}
+
 /// Get user agents from ~/.claude/agents/
 fn get_user_agents() -> Vec<Agent> {
     let mut agents = Vec::new();
 
-    let home_dir = match dirs::home_dir() {
-        Some(dir) => dir,
-        None => return agents,
-    };
-
-    let agents_dir = home_dir.join(".claude").join("agents");
+    let Ok(agents_dir) = get_user_agents_dir() else { return agents };
 
     if !agents_dir.exists() {
         return agents;
@@ -167,12 +169,8 @@ fn get_plugin_agents() -> Vec<Agent> {
 
     let mut agents_map: HashMap<String, Agent> = HashMap::new();
 
-    let home_dir = match dirs::home_dir() {
-        Some(dir) => dir,
-        None => return Vec::new(),
-    };
-
-    let plugins_cache_dir = home_dir.join(".claude").join("plugins").join("cache");
+    let Ok(root) = local_root() else { return Vec::new() };
+    let plugins_cache_dir = PathBuf::from(root.claude_dir).join("plugins").join("cache");
 
     if !plugins_cache_dir.exists() {
         return Vec::new();
@@ -276,8 +274,7 @@ pub async fn get_agents_data() -> Result<AgentsDashboardData, String> {
 
 #[tauri::command]
 pub async fn get_agent_by_name(name: String) -> Result<Agent, String> {
-    let home_dir = dirs::home_dir().ok_or("Failed to get home directory")?;
-    let agent_path = home_dir.join(".claude").join("agents").join(format!("{}.md", name));
+    let agent_path = get_user_agents_dir()?.join(format!("{}.md", name));
 
     if !agent_path.exists() {
         return Err(format!("Agent '{}' not found", name));
@@ -305,8 +302,7 @@ pub async fn update_agent(
     model: String,
     content: String,
 ) -> Result<(), String> {
-    let home_dir = dirs::home_dir().ok_or("Failed to get home directory")?;
-    let agent_path = home_dir.join(".claude").join("agents").join(format!("{}.md", name));
+    let agent_path = get_user_agents_dir()?.join(format!("{}.md", name));
 
     // Build frontmatter
     let mut frontmatter = String::from("---\n");
@@ -3,11 +3,14 @@
  *
  * Invokes Claude Code CLI to answer questions about transcript content.
  * Uses `claude --print` for non-interactive responses.
+ * Secrets in the context and history are redacted before they leave the app.
  */
 
 use serde::{Deserialize, Serialize};
 use std::process::Command;
 
+use super::secrets::redact_text;
+
 // ============================================================================
 // Type Definitions
 // ============================================================================
@@ -34,7 +37,7 @@ pub async fn ask_claude_cli(
     let mut prompt = format!(
         "You are helping a user understand content from a Claude Code session transcript.\n\n\
         ## Context (from transcript)\n```\n{}\n```\n\n",
-        context
+        redact_text(&context)
     );
 
     // Add conversation history if any
@@ -42,7 +45,7 @@ pub async fn ask_claude_cli(
         prompt.push_str("## Previous conversation\n");
         for msg in &history {
             let role_label = if msg.role == "user" { "User" } else { "Assistant" };
-            prompt.push_str(&format!("{}: {}\n\n", role_label, msg.content));
+            prompt.push_str(&format!("{}: {}\n\n", role_label, redact_text(&msg.content)));
         }
     }
 
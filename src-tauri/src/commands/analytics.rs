@@ -1,7 +1,8 @@
 /**
  * Analytics service commands
  *
- * Reads and processes statistics from ~/.claude/stats-cache.json
+ * Reads statistics from ~/.claude/stats-cache.json, or aggregates them from
+ * the usage entries of every transcript source
  * Calculates cost metrics and usage summaries
  */
 
@@ -9,6 +10,11 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tokio::fs;
 
+use super::context_window::context_limit;
+use super::data_roots::{data_roots, DataRoot};
+use super::pricing::model_usage_cost;
+use super::sources::{registered_sources, TranscriptSource};
+
 // ============================================================================
 // Type Definitions
 // ============================================================================
@@ -29,7 +35,7 @@ pub struct DailyModelTokens {
     pub tokens_by_model: HashMap<String, i64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct ModelUsage {
     pub input_tokens: i64,
@@ -102,87 +108,9 @@ pub struct AnalyticsSummary {
 }
 
 // ============================================================================
-// Pricing Configuration
+// Aggregation
 // ============================================================================
 
-struct ModelPricing {
-    input: f64,
-    output: f64,
-    cache_write: f64,
-    cache_read: f64,
-}
-
-fn get_model_pricing(model_id: &str) -> ModelPricing {
-    match model_id {
-        m if m.contains("opus") => ModelPricing {
-            input: 15.0,
-            output: 75.0,
-            cache_write: 18.75,
-            cache_read: 1.5,
-        },
-        m if m.contains("sonnet") => ModelPricing {
-            input: 3.0,
-            output: 15.0,
-            cache_write: 3.75,
-            cache_read: 0.3,
-        },
-        m if m.contains("haiku") => ModelPricing {
-            input: 0.25,
-            output: 1.25,
-            cache_write: 0.3125,
-            cache_read: 0.03,
-        },
-        _ => ModelPricing {
-            input: 3.0,
-            output: 15.0,
-            cache_write: 3.75,
-            cache_read: 0.3,
-        },
-    }
-}
-
-fn calculate_model_cost(model_id: &str, usage: &ModelUsage) -> f64 {
-    if !model_id.contains("claude") {
-        return 0.0;
-    }
-    let pricing = get_model_pricing(model_id);
-
-    let input_cost = (usage.input_tokens as f64 / 1_000_000.0) * pricing.input;
-    let output_cost = (usage.output_tokens as f64 / 1_000_000.0) * pricing.output;
-    let cache_write_cost =
-        (usage.cache_creation_input_tokens as f64 / 1_000_000.0) * pricing.cache_write;
-    let cache_read_cost =
-        (usage.cache_read_input_tokens as f64 / 1_000_000.0) * pricing.cache_read;
-
-    input_cost + output_cost + cache_write_cost + cache_read_cost
-}
-
-// ========================================================================
-// Codex Aggregation
-// ========================================================================
-
-fn get_codex_sessions_dir() -> Result<std::path::PathBuf, String> {
-    let home_dir = dirs::home_dir()
-        .ok_or_else(|| "Could not determine home directory".to_string())?;
-    Ok(home_dir.join(".codex").join("sessions"))
-}
-
-fn list_codex_session_files(root: &std::path::Path) -> Vec<std::path::PathBuf> {
-    let mut files = Vec::new();
-    let Ok(entries) = std::fs::read_dir(root) else { return files };
-
-    for entry in entries.flatten() {
-        let path = entry.path();
-        if path.is_dir() {
-            files.extend(list_codex_session_files(&path));
-        } else if path.extension().map(|e| e == "jsonl").unwrap_or(false) {
-            files.push(path);
-        }
-    }
-
-    files
-}
-
 fn update_daily_activity(
     daily_activity: &mut HashMap<String, DailyActivity>,
     date: &str,
@@ -214,8 +142,9 @@ fn update_daily_model_tokens(
     *entry.tokens_by_model.entry(model.to_string()).or_insert(0) += tokens;
 }
 
-fn aggregate_codex_stats() -> Result<StatsCache, String> {
-    let sessions_dir = get_codex_sessions_dir()?;
+/// Stats of one source across `roots`, aggregated from its usage entries.
+/// A session counts on the date of its first message.
+fn aggregate_source_stats(source: &dyn TranscriptSource, roots: &[DataRoot]) -> Result<StatsCache, String> {
     let mut total_sessions = 0;
     let mut total_messages = 0;
     let mut model_usage: HashMap<String, ModelUsage> = HashMap::new();
@@ -223,133 +152,57 @@ fn aggregate_codex_stats() -> Result<StatsCache, String> {
     let mut daily_model_tokens: HashMap<String, DailyModelTokens> = HashMap::new();
     let mut hour_counts: HashMap<String, i32> = HashMap::new();
     let mut first_session_date: Option<String> = None;
+    let mut current_session: Option<(String, String, String)> = None;
+    let mut session_dated = false;
 
-    if !sessions_dir.exists() {
-        return Ok(StatsCache {
-            version: 1,
-            last_computed_date: chrono::Utc::now().format("%Y-%m-%d").to_string(),
-            daily_activity: Vec::new(),
-            daily_model_tokens: Vec::new(),
-            model_usage: HashMap::new(),
-            total_sessions: 0,
-            total_messages: 0,
-            longest_session: default_longest_session(),
-            first_session_date: chrono::Utc::now().format("%Y-%m-%d").to_string(),
-            hour_counts: HashMap::new(),
-            hour_counts_by_source: HashMap::new(),
+    source.usage_entries(roots, &mut |entry| {
+        let same_session = current_session.as_ref().is_some_and(|(root, project_id, session_id)| {
+            root == entry.root && project_id == entry.project_id && session_id == entry.session_id
         });
-    }
-
-    let files = list_codex_session_files(&sessions_dir);
-    for file in files {
-        total_sessions += 1;
-        let content = match std::fs::read_to_string(&file) {
-            Ok(c) => c,
-            Err(_) => continue,
-        };
-        let mut current_model: Option<String> = None;
-        let mut session_dates: std::collections::HashSet<String> = std::collections::HashSet::new();
+        if !same_session {
+            current_session = Some((entry.root.to_string(), entry.project_id.to_string(), entry.session_id.to_string()));
+            session_dated = false;
+            total_sessions += 1;
+        }
 
-        for line in content.lines() {
-            if line.trim().is_empty() {
-                continue;
-            }
-            let Ok(value) = serde_json::from_str::<serde_json::Value>(line) else { continue };
-            let entry_type = value.get("type").and_then(|v| v.as_str()).unwrap_or("");
-            let timestamp = value.get("timestamp").and_then(|v| v.as_str()).unwrap_or("");
-
-            if entry_type == "turn_context" {
-                if let Some(model) = value
-                    .get("payload")
-                    .and_then(|p| p.get("model"))
-                    .and_then(|v| v.as_str())
-                {
-                    current_model = Some(model.to_string());
-                }
-                continue;
-            }
+        if entry.is_message {
+            total_messages += 1;
+        }
 
-            if entry_type == "event_msg" {
-                let payload = value.get("payload").cloned().unwrap_or(serde_json::Value::Null);
-                if payload.get("type").and_then(|v| v.as_str()) == Some("token_count") {
-                    let info = payload.get("info").cloned().unwrap_or(serde_json::Value::Null);
-                    let last = info.get("last_token_usage").cloned().unwrap_or(serde_json::Value::Null);
-                    let input = last.get("input_tokens").and_then(|v| v.as_i64()).unwrap_or(0);
-                    let cached = last.get("cached_input_tokens").and_then(|v| v.as_i64()).unwrap_or(0);
-                    let output = last.get("output_tokens").and_then(|v| v.as_i64()).unwrap_or(0);
-                    let reasoning = last.get("reasoning_output_tokens").and_then(|v| v.as_i64()).unwrap_or(0);
-                    let total = last.get("total_tokens").and_then(|v| v.as_i64()).unwrap_or(input + cached + output + reasoning);
-                    let context_window = info.get("model_context_window").and_then(|v| v.as_i64()).unwrap_or(0) as i32;
-
-                    let model_id = current_model.clone().unwrap_or_else(|| "codex".to_string());
-                    let usage = model_usage.entry(model_id.clone()).or_insert(ModelUsage {
-                        input_tokens: 0,
-                        output_tokens: 0,
-                        cache_read_input_tokens: 0,
-                        cache_creation_input_tokens: 0,
-                        message_count: 0,
-                        web_search_requests: 0,
-                        cost_usd: 0.0,
-                        context_window: 0,
-                    });
-                    usage.input_tokens += input;
-                    usage.output_tokens += output + reasoning;
-                    usage.cache_read_input_tokens += cached;
-                    usage.context_window = context_window.max(usage.context_window);
-
-                    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(timestamp) {
-                        let date = dt.format("%Y-%m-%d").to_string();
-                        update_daily_model_tokens(&mut daily_model_tokens, &date, &model_id, total);
-                    }
+        let date = chrono::DateTime::parse_from_rfc3339(entry.timestamp).ok().map(|dt| {
+            let date = dt.format("%Y-%m-%d").to_string();
+            if entry.is_message {
+                if first_session_date.as_ref().is_none_or(|first| date < *first) {
+                    first_session_date = Some(date.clone());
                 }
-                continue;
+                update_daily_activity(&mut daily_activity, &date, 1, 0, if session_dated { 0 } else { 1 });
+                *hour_counts.entry(dt.format("%H").to_string()).or_insert(0) += 1;
+                session_dated = true;
             }
-
-            if entry_type != "response_item" {
-                continue;
+            if entry.tool_calls > 0 {
+                update_daily_activity(&mut daily_activity, &date, 0, entry.tool_calls as i32, 0);
             }
+            date
+        });
 
-            let payload = value.get("payload").cloned().unwrap_or(serde_json::Value::Null);
-            let payload_type = payload.get("type").and_then(|v| v.as_str()).unwrap_or("");
-            let role = payload.get("role").and_then(|v| v.as_str()).unwrap_or("");
-
-            if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(timestamp) {
-                let date = dt.format("%Y-%m-%d").to_string();
-                let hour = dt.format("%H").to_string();
-                if first_session_date.is_none() || date < *first_session_date.as_ref().unwrap() {
-                    first_session_date = Some(date.clone());
-                }
-
-                if payload_type == "message" {
-                    total_messages += 1;
-                    update_daily_activity(&mut daily_activity, &date, 1, 0, 0);
-                    *hour_counts.entry(hour).or_insert(0) += 1;
-                    session_dates.insert(date);
-
-                    if role == "assistant" {
-                        let model_id = current_model.clone().unwrap_or_else(|| "codex".to_string());
-                        let usage = model_usage.entry(model_id).or_insert(ModelUsage {
-                            input_tokens: 0,
-                            output_tokens: 0,
-                            cache_read_input_tokens: 0,
-                            cache_creation_input_tokens: 0,
-                            message_count: 0,
-                            web_search_requests: 0,
-                            cost_usd: 0.0,
-                            context_window: 0,
-                        });
-                        usage.message_count += 1;
-                    }
-                } else if payload_type == "function_call" {
-                    update_daily_activity(&mut daily_activity, &date, 0, 1, 0);
-                }
-            }
+        let Some(model) = entry.model else { return };
+        let usage = model_usage.entry(model.to_string()).or_default();
+        if entry.is_response {
+            usage.message_count += 1;
         }
-
-        for date in session_dates {
-            update_daily_activity(&mut daily_activity, &date, 0, 0, 1);
+        usage.context_window = usage.context_window.max(entry.context_window);
+
+        if let Some(tokens) = &entry.tokens {
+            usage.input_tokens += tokens.input as i64;
+            usage.output_tokens += tokens.output as i64;
+            usage.cache_read_input_tokens += tokens.cache_read as i64;
+            usage.cache_creation_input_tokens += (tokens.cache_write_5m + tokens.cache_write_1h) as i64;
+            usage.cost_usd += entry.cost_usd;
+            if let Some(date) = &date {
+                update_daily_model_tokens(&mut daily_model_tokens, date, model, tokens.total() as i64);
+            }
         }
-    }
+    })?;
 
     let mut daily_activity_vec: Vec<DailyActivity> = daily_activity.into_values().collect();
     daily_activity_vec.sort_by(|a, b| a.date.cmp(&b.date));
@@ -358,14 +211,7 @@ fn aggregate_codex_stats() -> Result<StatsCache, String> {
     daily_model_tokens_vec.sort_by(|a, b| a.date.cmp(&b.date));
 
     let mut hour_counts_by_source: HashMap<String, HashMap<String, i32>> = HashMap::new();
-    hour_counts_by_source.insert("code".to_string(), hour_counts.clone());
-
-    let hour_counts_snapshot = hour_counts.clone();
-    let mut hour_counts_by_source: HashMap<String, HashMap<String, i32>> = HashMap::new();
-    hour_counts_by_source.insert("code".to_string(), hour_counts.clone());
-
-    let mut hour_counts_by_source: HashMap<String, HashMap<String, i32>> = HashMap::new();
-    hour_counts_by_source.insert("code".to_string(), hour_counts.clone());
+    hour_counts_by_source.insert(source.id().to_string(), hour_counts.clone());
 
     Ok(StatsCache {
         version: 1,
@@ -378,24 +224,47 @@ fn aggregate_codex_stats() -> Result<StatsCache, String> {
         longest_session: default_longest_session(),
         first_session_date: first_session_date.unwrap_or_else(|| chrono::Utc::now().format("%Y-%m-%d").to_string()),
         hour_counts,
-        hour_counts_by_source: {
-            let mut map = HashMap::new();
-            map.insert("codex".to_string(), hour_counts_snapshot);
-            map
-        },
+        hour_counts_by_source,
     })
 }
 
-fn merge_stats(mut base: StatsCache, codex: StatsCache) -> StatsCache {
-    base.total_sessions += codex.total_sessions;
-    base.total_messages += codex.total_messages;
+/// Stats of one source from a stats cache in the stats-cache.json format
+async fn read_stats_cache(path: &std::path::Path, source: &str) -> Result<StatsCache, String> {
+    let content = fs::read_to_string(path)
+        .await
+        .map_err(|e| format!("Failed to read stats-cache.json: {}", e))?;
+
+    let mut stats: StatsCache = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse stats-cache.json: {}", e))?;
+
+    if stats.hour_counts_by_source.is_empty() && !stats.hour_counts.is_empty() {
+        stats
+            .hour_counts_by_source
+            .insert(source.to_string(), stats.hour_counts.clone());
+    }
+
+    // The cache only keeps per-model totals, so price them at current rates
+    // and fill in the context window it leaves at 0
+    for (model_id, usage) in stats.model_usage.iter_mut() {
+        usage.cost_usd = model_usage_cost(model_id, usage);
+        if usage.context_window == 0 {
+            usage.context_window = context_limit(model_id, 0);
+        }
+    }
+
+    Ok(stats)
+}
+
+fn merge_stats(mut base: StatsCache, other: StatsCache) -> StatsCache {
+    base.total_sessions += other.total_sessions;
+    base.total_messages += other.total_messages;
 
     let mut daily_activity: HashMap<String, DailyActivity> = base
         .daily_activity
         .into_iter()
         .map(|d| (d.date.clone(), d))
         .collect();
-    for item in codex.daily_activity {
+    for item in other.daily_activity {
         update_daily_activity(
             &mut daily_activity,
             &item.date,
@@ -410,50 +279,32 @@ fn merge_stats(mut base: StatsCache, codex: StatsCache) -> StatsCache {
         .into_iter()
         .map(|d| (d.date.clone(), d))
         .collect();
-    for item in codex.daily_model_tokens {
+    for item in other.daily_model_tokens {
         for (model, tokens) in item.tokens_by_model {
             update_daily_model_tokens(&mut daily_model_tokens, &item.date, &model, tokens);
         }
     }
 
-    for (model, usage) in codex.model_usage {
-        let entry = base.model_usage.entry(model).or_insert(ModelUsage {
-            input_tokens: 0,
-            output_tokens: 0,
-            cache_read_input_tokens: 0,
-            cache_creation_input_tokens: 0,
-            message_count: 0,
-            web_search_requests: 0,
-            cost_usd: 0.0,
-            context_window: 0,
-        });
+    for (model, usage) in other.model_usage {
+        let entry = base.model_usage.entry(model).or_default();
         entry.input_tokens += usage.input_tokens;
         entry.output_tokens += usage.output_tokens;
         entry.cache_read_input_tokens += usage.cache_read_input_tokens;
         entry.cache_creation_input_tokens += usage.cache_creation_input_tokens;
         entry.message_count += usage.message_count;
         entry.web_search_requests += usage.web_search_requests;
+        entry.cost_usd += usage.cost_usd;
         entry.context_window = entry.context_window.max(usage.context_window);
     }
 
-    if base.hour_counts_by_source.is_empty() && !base.hour_counts.is_empty() {
-        base
-            .hour_counts_by_source
-            .insert("code".to_string(), base.hour_counts.clone());
-    }
-
-    if !codex.hour_counts.is_empty() {
-        base.hour_counts_by_source
-            .entry("codex".to_string())
-            .and_modify(|existing| {
-                for (hour, count) in &codex.hour_counts {
-                    *existing.entry(hour.clone()).or_insert(0) += *count;
-                }
-            })
-            .or_insert(codex.hour_counts.clone());
+    for (source, counts) in other.hour_counts_by_source {
+        let existing = base.hour_counts_by_source.entry(source).or_default();
+        for (hour, count) in counts {
+            *existing.entry(hour).or_insert(0) += count;
+        }
     }
 
-    for (hour, count) in codex.hour_counts {
+    for (hour, count) in other.hour_counts {
         *base.hour_counts.entry(hour).or_insert(0) += count;
     }
 
@@ -462,215 +313,40 @@ fn merge_stats(mut base: StatsCache, codex: StatsCache) -> StatsCache {
     base.daily_model_tokens = daily_model_tokens.into_values().collect();
     base.daily_model_tokens.sort_by(|a, b| a.date.cmp(&b.date));
 
-    if codex.first_session_date < base.first_session_date {
-        base.first_session_date = codex.first_session_date;
-    }
-
-    // Recalculate costs after merging
-    for (model_id, usage) in base.model_usage.iter_mut() {
-        usage.cost_usd = calculate_model_cost(model_id, usage);
+    if other.first_session_date < base.first_session_date {
+        base.first_session_date = other.first_session_date;
     }
 
     base
 }
 
-async fn aggregate_stats_from_projects() -> Result<StatsCache, String> {
-    println!("[Analytics] Aggregating stats from projects...");
-    let home_dir = dirs::home_dir()
-        .ok_or_else(|| "Could not determine home directory".to_string())?;
-    let projects_dir = home_dir.join(".claude").join("projects");
-
-    let mut total_sessions = 0;
-    let mut total_messages = 0;
-    let mut model_usage: HashMap<String, ModelUsage> = HashMap::new();
-    let mut daily_activity: HashMap<String, DailyActivity> = HashMap::new();
-    let mut daily_model_tokens: HashMap<String, DailyModelTokens> = HashMap::new();
-    let mut hour_counts: HashMap<String, i32> = HashMap::new();
-    let mut first_session_date: Option<String> = None;
-
-    if projects_dir.exists() {
-        println!("[Analytics] Projects directory exists: {:?}", projects_dir);
-        let mut entries = std::fs::read_dir(projects_dir).map_err(|e| e.to_string())?;
-        while let Some(Ok(project_entry)) = entries.next() {
-            if project_entry.path().is_dir() {
-                println!("[Analytics] Processing project: {:?}", project_entry.path());
-                let mut session_entries = std::fs::read_dir(project_entry.path()).map_err(|e| e.to_string())?;
-                while let Some(Ok(session_entry)) = session_entries.next() {
-                    let path = session_entry.path();
-                    if path.extension().map_or(false, |ext| ext == "jsonl") {
-                        total_sessions += 1;
-                        let file = match fs::File::open(&path).await {
-                            Ok(f) => f,
-                            Err(e) => {
-                                println!("[Analytics] Failed to open session file {:?}: {}", path, e);
-                                continue;
-                            }
-                        };
-                        let reader = tokio::io::BufReader::new(file);
-                        let mut lines = tokio::io::AsyncBufReadExt::lines(reader);
-
-                        while let Some(line) = lines.next_line().await.map_err(|e| e.to_string())? {
-                            if let Ok(val) = serde_json::from_str::<serde_json::Value>(&line) {
-                                if val.get("type").and_then(|v| v.as_str()) == Some("user") || 
-                                   val.get("type").and_then(|v| v.as_str()) == Some("assistant") {
-                                    total_messages += 1;
-
-                                    if let Some(ts) = val.get("timestamp").and_then(|v| v.as_str()) {
-                                        if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(ts) {
-                                            let date = dt.format("%Y-%m-%d").to_string();
-                                            let hour = dt.format("%H").to_string();
-                                            
-                                            if first_session_date.is_none() || date < *first_session_date.as_ref().unwrap() {
-                                                first_session_date = Some(date.clone());
-                                            }
-
-                                            let activity = daily_activity.entry(date.clone()).or_insert(DailyActivity {
-                                                date: date.clone(),
-                                                message_count: 0,
-                                                session_count: 0,
-                                                tool_call_count: 0,
-                                            });
-                                            activity.message_count += 1;
-                                            *hour_counts.entry(hour).or_insert(0) += 1;
-                                        }
-                                    }
-
-                                    if let Some(message) = val.get("message") {
-                                        if let Some(model) = message.get("model").and_then(|v| v.as_str()) {
-                                            let usage = model_usage.entry(model.to_string()).or_insert(ModelUsage {
-                                                input_tokens: 0,
-                                                output_tokens: 0,
-                                                cache_read_input_tokens: 0,
-                                                cache_creation_input_tokens: 0,
-                                                message_count: 0,
-                                                web_search_requests: 0,
-                                                cost_usd: 0.0,
-                                                context_window: 0,
-                                            });
-                                            
-                                            if let Some(u) = message.get("usage") {
-                                                let input = u.get("input_tokens").and_then(|v| v.as_i64()).unwrap_or(0);
-                                                let output = u.get("output_tokens").and_then(|v| v.as_i64()).unwrap_or(0);
-                                                let cache_read = u.get("cache_read_input_tokens").and_then(|v| v.as_i64()).unwrap_or(0);
-                                                let cache_creation = u.get("cache_creation_input_tokens").and_then(|v| v.as_i64()).unwrap_or(0);
-
-                                                usage.input_tokens += input;
-                                                usage.output_tokens += output;
-                                                usage.cache_read_input_tokens += cache_read;
-                                                usage.cache_creation_input_tokens += cache_creation;
-                                                usage.message_count += 1;
-
-                                                // Update daily model tokens
-                                                if let Some(ts) = val.get("timestamp").and_then(|v| v.as_str()) {
-                                                    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(ts) {
-                                                        let date = dt.format("%Y-%m-%d").to_string();
-                                                        let daily_tokens = daily_model_tokens.entry(date.clone()).or_insert(DailyModelTokens {
-                                                            date: date.clone(),
-                                                            tokens_by_model: HashMap::new(),
-                                                        });
-                                                        let model_total = input + output + cache_read + cache_creation;
-                                                        *daily_tokens.tokens_by_model.entry(model.to_string()).or_insert(0) += model_total;
-                                                    }
-                                                }
-                                            }
-                                        }
-
-                                        if let Some(content) = message.get("content").and_then(|v| v.as_array()) {
-                                            for block in content {
-                                                if block.get("type").and_then(|v| v.as_str()) == Some("tool_use") {
-                                                    // Count tool use in activity
-                                                    if let Some(ts) = val.get("timestamp").and_then(|v| v.as_str()) {
-                                                        if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(ts) {
-                                                            let date = dt.format("%Y-%m-%d").to_string();
-                                                            if let Some(activity) = daily_activity.get_mut(&date) {
-                                                                activity.tool_call_count += 1;
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        println!("[Analytics] Stats aggregation complete. Total sessions: {}", total_sessions);
-    } else {
-        println!("[Analytics] Projects directory NOT found: {:?}", projects_dir);
-    }
-    let mut daily_activity_vec: Vec<DailyActivity> = daily_activity.into_values().collect();
-    daily_activity_vec.sort_by(|a, b| a.date.cmp(&b.date));
-
-    // Convert daily_model_tokens map to sorted vec
-    let mut daily_model_tokens_vec: Vec<DailyModelTokens> = daily_model_tokens.into_values().collect();
-    daily_model_tokens_vec.sort_by(|a, b| a.date.cmp(&b.date));
-
-    // Calculate costs for model_usage
-    for (model_id, usage) in model_usage.iter_mut() {
-        usage.cost_usd = calculate_model_cost(model_id, usage);
-    }
-
-    // Build hour_counts_by_source with "code" as the source
-    let mut hour_counts_by_source: HashMap<String, HashMap<String, i32>> = HashMap::new();
-    hour_counts_by_source.insert("code".to_string(), hour_counts.clone());
-
-    Ok(StatsCache {
-        version: 1,
-        last_computed_date: chrono::Utc::now().format("%Y-%m-%d").to_string(),
-        daily_activity: daily_activity_vec,
-        daily_model_tokens: daily_model_tokens_vec,
-        model_usage,
-        total_sessions,
-        total_messages,
-        longest_session: LongestSession {
-            session_id: String::new(),
-            duration: 0,
-            message_count: 0,
-            timestamp: String::new(),
-        },
-        first_session_date: first_session_date.unwrap_or_else(|| chrono::Utc::now().format("%Y-%m-%d").to_string()),
-        hour_counts,
-        hour_counts_by_source,
-    })
-}
-
 // ============================================================================
 // Tauri Commands
 // ============================================================================
 
-/// Get raw analytics data from stats-cache.json
+/// Get raw analytics data of every source, merged.
+/// A stats cache only covers the local data root, so transcripts are aggregated instead when other roots are configured.
 #[tauri::command]
 pub async fn get_analytics_data() -> Result<StatsCache, String> {
-    let home_dir = dirs::home_dir()
-        .ok_or_else(|| "Could not determine home directory".to_string())?;
+    let roots = data_roots();
+    let mut merged: Option<StatsCache> = None;
 
-    let stats_cache_path = home_dir.join(".claude").join("stats-cache.json");
-
-    if !stats_cache_path.exists() {
-        let base = aggregate_stats_from_projects().await?;
-        let codex = aggregate_codex_stats()?;
-        return Ok(merge_stats(base, codex));
-    }
-
-    let content = fs::read_to_string(&stats_cache_path)
-        .await
-        .map_err(|e| format!("Failed to read stats-cache.json: {}", e))?;
-
-    let mut stats: StatsCache = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse stats-cache.json: {}", e))?;
-
-    if stats.hour_counts_by_source.is_empty() && !stats.hour_counts.is_empty() {
-        stats
-            .hour_counts_by_source
-            .insert("code".to_string(), stats.hour_counts.clone());
+    for source in registered_sources() {
+        let cache_file = match roots.as_slice() {
+            [root] => source.stats_cache_file(root),
+            _ => None,
+        };
+        let stats = match cache_file {
+            Some(path) => read_stats_cache(&path, source.id()).await?,
+            None => aggregate_source_stats(source.as_ref(), &roots)?,
+        };
+        merged = Some(match merged {
+            Some(base) => merge_stats(base, stats),
+            None => stats,
+        });
     }
 
-    let codex = aggregate_codex_stats()?;
-    Ok(merge_stats(stats, codex))
+    merged.ok_or_else(|| "No transcript sources registered".to_string())
 }
 
 /// Get computed analytics summary
@@ -689,13 +365,13 @@ pub async fn get_analytics_summary() -> Result<AnalyticsSummary, String> {
     let mut total_tokens: i64 = 0;
     let mut total_cost: f64 = 0.0;
 
-    for (model_id, usage) in &stats.model_usage {
+    for usage in stats.model_usage.values() {
         let model_tokens = usage.input_tokens
             + usage.output_tokens
             + usage.cache_read_input_tokens
             + usage.cache_creation_input_tokens;
         total_tokens += model_tokens;
-        total_cost += calculate_model_cost(model_id, usage);
+        total_cost += usage.cost_usd;
     }
 
     // Find most active hour
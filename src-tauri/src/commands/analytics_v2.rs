@@ -1,14 +1,25 @@
 /**
  * Multi-agent analytics (v2)
  *
- * Aggregates usage across multiple agents (Claude Code + Codex) using a
- * normalized schema and per-source breakdowns.
+ * Aggregates the usage entries of every registered transcript source across
+ * data roots using a normalized schema and per-source breakdowns. Model usage
+ * rows are kept per root so they can be attributed to the machine they came from.
+ *
+ * The same pricing also yields a daily usage series per project, session and
+ * model, which budgets and spend forecasts are evaluated against.
  */
 
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use tokio::fs;
-use tokio::io::{AsyncBufReadExt, BufReader};
+
+use super::analytics::ModelUsage;
+use super::context_window::{context_limit, context_tokens};
+use super::data_roots::{data_roots, DataRoot};
+use super::pricing::{calculate_cost, model_usage_cost, TokenCounts};
+use super::sources::{list_codex_session_files, registered_sources, TranscriptSource, UsageEntry};
+use super::transcript_index::{with_index, IndexedEntry, IndexedUsage};
+use super::transcripts::codex_session_identity;
 
 // ============================================================================
 // Types
@@ -38,24 +49,13 @@ pub struct DailyTokens {
     pub tokens_by_source: HashMap<String, i64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-#[serde(rename_all = "camelCase")]
-pub struct ModelUsage {
-    pub input_tokens: i64,
-    pub output_tokens: i64,
-    pub cache_read_input_tokens: i64,
-    pub cache_creation_input_tokens: i64,
-    pub message_count: i64,
-    pub web_search_requests: i32,
-    #[serde(rename = "costUSD")]
-    pub cost_usd: f64,
-    pub context_window: i32,
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ModelUsageEntry {
     pub source: String,
+    /// Data root and host the usage was recorded under
+    pub root: String,
+    pub host: String,
     pub model_id: String,
     pub input_tokens: i64,
     pub output_tokens: i64,
@@ -94,8 +94,37 @@ pub struct AnalyticsV2 {
     pub model_usage: Vec<ModelUsageEntry>,
 }
 
+/// Spend of one project on one local calendar day, per source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyProjectCost {
+    pub date: String,
+    /// Data root the project belongs to
+    pub root: String,
+    pub project_id: String,
+    pub source: String,
+    pub cost_usd: f64,
+}
+
+/// Usage of one session with one model on one local calendar day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyUsage {
+    pub date: String,
+    /// Data root the session belongs to
+    pub root: String,
+    pub project_id: String,
+    pub session_id: String,
+    pub source: String,
+    pub model: String,
+    pub cost_usd: f64,
+    pub tokens: i64,
+}
+
 struct SourceStats {
     source: String,
+    root: String,
+    host: String,
     daily_activity: HashMap<String, ActivityCounts>,
     daily_tokens: HashMap<String, i64>,
     model_usage: HashMap<String, ModelUsage>,
@@ -105,63 +134,49 @@ struct SourceStats {
     total_tool_calls: i32,
     first_session_date: Option<String>,
     last_active_date: Option<String>,
+    /// Session of the last entry added, and whether it was counted on a date yet
+    current_session: Option<(String, String)>,
+    session_dated: bool,
 }
 
 // ============================================================================
-// Pricing (Claude only)
+// Pricing
 // ============================================================================
 
-struct ModelPricing {
-    input: f64,
-    output: f64,
-    cache_write: f64,
-    cache_read: f64,
+/// Token counts of an indexed Claude usage record, with cache writes split by TTL
+fn indexed_token_counts(u: &IndexedUsage) -> TokenCounts {
+    TokenCounts {
+        input: u.input_tokens as u64,
+        output: u.output_tokens as u64,
+        cache_read: u.cache_read_input_tokens as u64,
+        cache_write_5m: u.cache_creation_input_tokens.saturating_sub(u.cache_creation_1h_input_tokens) as u64,
+        cache_write_1h: u.cache_creation_1h_input_tokens as u64,
+    }
 }
 
-fn get_model_pricing(model_id: &str) -> ModelPricing {
-    match model_id {
-        m if m.contains("opus") => ModelPricing {
-            input: 15.0,
-            output: 75.0,
-            cache_write: 18.75,
-            cache_read: 1.5,
-        },
-        m if m.contains("sonnet") => ModelPricing {
-            input: 3.0,
-            output: 15.0,
-            cache_write: 3.75,
-            cache_read: 0.3,
-        },
-        m if m.contains("haiku") => ModelPricing {
-            input: 0.25,
-            output: 1.25,
-            cache_write: 0.3125,
-            cache_read: 0.03,
-        },
-        _ => ModelPricing {
-            input: 3.0,
-            output: 15.0,
-            cache_write: 3.75,
-            cache_read: 0.3,
-        },
+/// Token counts of a Codex `last_token_usage` object.
+/// OpenAI counts cached tokens inside input and reasoning tokens inside output.
+fn codex_token_counts(last: &serde_json::Value) -> TokenCounts {
+    let input = last.get("input_tokens").and_then(|v| v.as_i64()).unwrap_or(0);
+    let cached = last.get("cached_input_tokens").and_then(|v| v.as_i64()).unwrap_or(0);
+    let output = last.get("output_tokens").and_then(|v| v.as_i64()).unwrap_or(0);
+    TokenCounts {
+        input: (input - cached).max(0) as u64,
+        output: output.max(0) as u64,
+        cache_read: cached.max(0) as u64,
+        ..Default::default()
     }
 }
 
-fn calculate_model_cost(model_id: &str, usage: &ModelUsage) -> f64 {
-    if !model_id.contains("claude") {
-        return 0.0;
+/// Cost of an indexed entry: the logged cost when present, else priced from its usage
+pub(crate) fn indexed_entry_cost(entry: &IndexedEntry) -> f64 {
+    if let Some(cost) = entry.cost_usd {
+        return cost;
     }
-
-    let pricing = get_model_pricing(model_id);
-
-    let input_cost = (usage.input_tokens as f64 / 1_000_000.0) * pricing.input;
-    let output_cost = (usage.output_tokens as f64 / 1_000_000.0) * pricing.output;
-    let cache_write_cost =
-        (usage.cache_creation_input_tokens as f64 / 1_000_000.0) * pricing.cache_write;
-    let cache_read_cost =
-        (usage.cache_read_input_tokens as f64 / 1_000_000.0) * pricing.cache_read;
-
-    input_cost + output_cost + cache_write_cost + cache_read_cost
+    let (Some(model), Some(usage)) = (&entry.model, &entry.usage) else { return 0.0 };
+    usage
+        .cost_usd
+        .unwrap_or_else(|| calculate_cost(model, Some(&entry.timestamp), &indexed_token_counts(usage)))
 }
 
 // ============================================================================
@@ -189,7 +204,113 @@ fn update_daily_tokens(map: &mut HashMap<String, i64>, date: &str, tokens: i64)
 }
 
 // ============================================================================
-// Claude Code Stats (from stats-cache or project scan)
+// Usage Entries
+// ============================================================================
+
+/// Usage entries of the Claude Code sessions under `roots`, from the transcript index
+pub(crate) fn claude_usage_entries(roots: &[DataRoot], visit: &mut dyn FnMut(&UsageEntry)) -> Result<(), String> {
+    with_index(|index| {
+        for session in index.sessions.values().filter(|s| roots.iter().any(|r| r.id == s.root)) {
+            for entry in &session.entries {
+                if entry.entry_type != "user" && entry.entry_type != "assistant" {
+                    continue;
+                }
+                let model = entry.model.as_deref();
+                let context_window = match (model, &entry.usage) {
+                    (Some(model), Some(u)) => context_limit(
+                        model,
+                        context_tokens(u.input_tokens as u64, u.cache_read_input_tokens as u64, u.cache_creation_input_tokens as u64),
+                    ),
+                    _ => 0,
+                };
+
+                visit(&UsageEntry {
+                    root: &session.root,
+                    project_id: &session.project_id,
+                    session_id: &session.session_id,
+                    timestamp: &entry.timestamp,
+                    is_message: true,
+                    tool_calls: entry.tool_uses.len(),
+                    model,
+                    is_response: entry.usage.is_some(),
+                    tokens: entry.usage.as_ref().map(indexed_token_counts),
+                    cost_usd: indexed_entry_cost(entry),
+                    context_window,
+                });
+            }
+        }
+    })
+}
+
+/// Usage entries of the Codex rollouts under `roots`, each token_count event priced on its own
+pub(crate) fn codex_usage_entries(roots: &[DataRoot], visit: &mut dyn FnMut(&UsageEntry)) {
+    for root in roots {
+        let Some(sessions_dir) = root.codex_sessions_dir() else { continue };
+
+        for path in list_codex_session_files(&sessions_dir) {
+            let Some((local_project_id, session_id, _)) = codex_session_identity(&path) else { continue };
+            let Ok(content) = std::fs::read_to_string(&path) else { continue };
+            let project_id = root.qualify_id(&local_project_id);
+            let mut current_model = "codex".to_string();
+
+            for line in content.lines() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let Ok(value) = serde_json::from_str::<serde_json::Value>(line) else { continue };
+                let timestamp = value.get("timestamp").and_then(|v| v.as_str()).unwrap_or("");
+                let payload = value.get("payload").unwrap_or(&serde_json::Value::Null);
+                let payload_type = payload.get("type").and_then(|v| v.as_str());
+
+                let mut entry = UsageEntry {
+                    root: &root.id,
+                    project_id: &project_id,
+                    session_id: &session_id,
+                    timestamp,
+                    is_message: false,
+                    tool_calls: 0,
+                    model: None,
+                    is_response: false,
+                    tokens: None,
+                    cost_usd: 0.0,
+                    context_window: 0,
+                };
+
+                match (value.get("type").and_then(|v| v.as_str()), payload_type) {
+                    (Some("turn_context"), _) => {
+                        if let Some(model) = payload.get("model").and_then(|v| v.as_str()) {
+                            current_model = model.to_string();
+                        }
+                        continue;
+                    }
+                    (Some("event_msg"), Some("token_count")) => {
+                        let Some(info) = payload.get("info") else { continue };
+                        let Some(last) = info.get("last_token_usage") else { continue };
+                        let counts = codex_token_counts(last);
+                        entry.model = Some(&current_model);
+                        entry.cost_usd = calculate_cost(&current_model, Some(timestamp), &counts);
+                        entry.tokens = Some(counts);
+                        entry.context_window = info.get("model_context_window").and_then(|v| v.as_i64()).unwrap_or(0) as i32;
+                    }
+                    (Some("response_item"), Some("message")) => {
+                        entry.is_message = true;
+                        if payload.get("role").and_then(|v| v.as_str()) == Some("assistant") {
+                            entry.model = Some(&current_model);
+                            entry.is_response = true;
+                        }
+                    }
+                    (Some("response_item"), Some("function_call")) => entry.tool_calls = 1,
+                    _ => continue,
+                }
+
+                visit(&entry);
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Source Stats (from a stats cache or the usage entries)
 // ============================================================================
 
 #[derive(Debug, Deserialize)]
@@ -236,339 +357,136 @@ struct ModelUsageFile {
     context_window: i32,
 }
 
-async fn load_code_stats() -> Result<SourceStats, String> {
-    let home_dir = dirs::home_dir().ok_or_else(|| "Could not determine home directory".to_string())?;
-    let stats_cache_path = home_dir.join(".claude").join("stats-cache.json");
+impl SourceStats {
+    fn new(source: &str, root: &DataRoot) -> Self {
+        SourceStats {
+            source: source.to_string(),
+            root: root.id.clone(),
+            host: root.host.clone(),
+            daily_activity: HashMap::new(),
+            daily_tokens: HashMap::new(),
+            model_usage: HashMap::new(),
+            hour_counts: HashMap::new(),
+            total_sessions: 0,
+            total_messages: 0,
+            total_tool_calls: 0,
+            first_session_date: None,
+            last_active_date: None,
+            current_session: None,
+            session_dated: false,
+        }
+    }
 
-    if stats_cache_path.exists() {
-        let content = fs::read_to_string(&stats_cache_path)
-            .await
-            .map_err(|e| format!("Failed to read stats-cache.json: {}", e))?;
-        let stats: StatsCacheFile = serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse stats-cache.json: {}", e))?;
+    /// Count one usage entry; entries arrive session by session, and a
+    /// session counts on the date of its first message
+    fn add_entry(&mut self, entry: &UsageEntry) {
+        let same_session = self
+            .current_session
+            .as_ref()
+            .is_some_and(|(project_id, session_id)| project_id == entry.project_id && session_id == entry.session_id);
+        if !same_session {
+            self.current_session = Some((entry.project_id.to_string(), entry.session_id.to_string()));
+            self.session_dated = false;
+            self.total_sessions += 1;
+        }
 
-        let mut daily_activity = HashMap::new();
-        let mut daily_tokens = HashMap::new();
-        let mut model_usage = HashMap::new();
+        let date = chrono::DateTime::parse_from_rfc3339(entry.timestamp)
+            .ok()
+            .map(|dt| (dt.format("%Y-%m-%d").to_string(), dt.format("%H").to_string()));
 
-        for item in stats.daily_activity {
-            update_activity(&mut daily_activity, &item.date, item.message_count, item.tool_call_count, item.session_count);
+        if entry.is_message {
+            self.total_messages += 1;
         }
 
-        for item in stats.daily_model_tokens {
-            let total_tokens = item.tokens_by_model.values().sum::<i64>();
-            update_daily_tokens(&mut daily_tokens, &item.date, total_tokens);
+        if let Some((date, hour)) = &date {
+            if entry.is_message {
+                update_first_last_date(&mut self.first_session_date, &mut self.last_active_date, date);
+                update_activity(&mut self.daily_activity, date, 1, 0, 0);
+                *self.hour_counts.entry(hour.clone()).or_insert(0) += 1;
+                if !self.session_dated {
+                    update_activity(&mut self.daily_activity, date, 0, 0, 1);
+                    self.session_dated = true;
+                }
+            }
+            if entry.tool_calls > 0 {
+                update_activity(&mut self.daily_activity, date, 0, entry.tool_calls as i32, 0);
+                self.total_tool_calls += entry.tool_calls as i32;
+            }
         }
 
-        for (model, usage) in stats.model_usage {
-            let mut entry = ModelUsage {
-                input_tokens: usage.input_tokens,
-                output_tokens: usage.output_tokens,
-                cache_read_input_tokens: usage.cache_read_input_tokens,
-                cache_creation_input_tokens: usage.cache_creation_input_tokens,
-                message_count: 0,
-                web_search_requests: usage.web_search_requests,
-                cost_usd: 0.0,
-                context_window: usage.context_window,
-            };
-            entry.cost_usd = calculate_model_cost(&model, &entry);
-            model_usage.insert(model, entry);
+        let Some(model) = entry.model else { return };
+        let usage = self.model_usage.entry(model.to_string()).or_default();
+        if entry.is_response {
+            usage.message_count += 1;
         }
-
-        let total_tool_calls = daily_activity.values().map(|a| a.tool_calls).sum::<i32>();
-
-        return Ok(SourceStats {
-            source: "code".to_string(),
-            daily_activity,
-            daily_tokens,
-            model_usage,
-            hour_counts: stats.hour_counts,
-            total_sessions: stats.total_sessions,
-            total_messages: stats.total_messages,
-            total_tool_calls,
-            first_session_date: Some(stats.first_session_date),
-            last_active_date: Some(stats.last_computed_date),
-        });
-    }
-
-    aggregate_code_stats_from_projects().await
-}
-
-async fn aggregate_code_stats_from_projects() -> Result<SourceStats, String> {
-    let home_dir = dirs::home_dir().ok_or_else(|| "Could not determine home directory".to_string())?;
-    let projects_dir = home_dir.join(".claude").join("projects");
-
-    let mut total_sessions = 0;
-    let mut total_messages = 0;
-    let mut total_tool_calls = 0;
-    let mut model_usage: HashMap<String, ModelUsage> = HashMap::new();
-    let mut daily_activity: HashMap<String, ActivityCounts> = HashMap::new();
-    let mut daily_tokens: HashMap<String, i64> = HashMap::new();
-    let mut hour_counts: HashMap<String, i32> = HashMap::new();
-    let mut first_session_date: Option<String> = None;
-    let mut last_active_date: Option<String> = None;
-
-    if projects_dir.exists() {
-        let mut entries = std::fs::read_dir(projects_dir).map_err(|e| e.to_string())?;
-        while let Some(Ok(project_entry)) = entries.next() {
-            if project_entry.path().is_dir() {
-                let mut session_entries = std::fs::read_dir(project_entry.path()).map_err(|e| e.to_string())?;
-                while let Some(Ok(session_entry)) = session_entries.next() {
-                    let path = session_entry.path();
-                    if !path.extension().map_or(false, |ext| ext == "jsonl") {
-                        continue;
-                    }
-
-                    total_sessions += 1;
-                    let file = fs::File::open(&path).await.map_err(|e| e.to_string())?;
-                    let reader = BufReader::new(file);
-                    let mut lines = reader.lines();
-                    let mut session_first_date: Option<String> = None;
-
-                    while let Some(line) = lines.next_line().await.map_err(|e| e.to_string())? {
-                        if let Ok(val) = serde_json::from_str::<serde_json::Value>(&line) {
-                            let entry_type = val.get("type").and_then(|v| v.as_str()).unwrap_or("");
-                            if entry_type != "user" && entry_type != "assistant" {
-                                continue;
-                            }
-
-                            total_messages += 1;
-
-                            if let Some(ts) = val.get("timestamp").and_then(|v| v.as_str()) {
-                                if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(ts) {
-                                    let date = dt.format("%Y-%m-%d").to_string();
-                                    let hour = dt.format("%H").to_string();
-                                    update_first_last_date(&mut first_session_date, &mut last_active_date, &date);
-                                    update_activity(&mut daily_activity, &date, 1, 0, 0);
-                                    *hour_counts.entry(hour).or_insert(0) += 1;
-                                    if session_first_date.is_none() {
-                                        session_first_date = Some(date);
-                                    }
-                                }
-                            }
-
-                            if let Some(message) = val.get("message") {
-                                if let Some(model) = message.get("model").and_then(|v| v.as_str()) {
-                                    let usage = model_usage.entry(model.to_string()).or_insert_with(ModelUsage::default);
-                                    if let Some(u) = message.get("usage") {
-                                        let input = u.get("input_tokens").and_then(|v| v.as_i64()).unwrap_or(0);
-                                        let output = u.get("output_tokens").and_then(|v| v.as_i64()).unwrap_or(0);
-                                        let cache_read = u.get("cache_read_input_tokens").and_then(|v| v.as_i64()).unwrap_or(0);
-                                        let cache_creation = u.get("cache_creation_input_tokens").and_then(|v| v.as_i64()).unwrap_or(0);
-
-                                        usage.input_tokens += input;
-                                        usage.output_tokens += output;
-                                        usage.cache_read_input_tokens += cache_read;
-                                        usage.cache_creation_input_tokens += cache_creation;
-                                        usage.message_count += 1;
-                                        usage.cost_usd = calculate_model_cost(model, usage);
-
-                                        if let Some(ts) = val.get("timestamp").and_then(|v| v.as_str()) {
-                                            if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(ts) {
-                                                let date = dt.format("%Y-%m-%d").to_string();
-                                                let total = input + output + cache_read + cache_creation;
-                                                update_daily_tokens(&mut daily_tokens, &date, total);
-                                            }
-                                        }
-                                    }
-                                }
-
-                                if let Some(content) = message.get("content").and_then(|v| v.as_array()) {
-                                    for block in content {
-                                        if block.get("type").and_then(|v| v.as_str()) == Some("tool_use") {
-                                            if let Some(ts) = val.get("timestamp").and_then(|v| v.as_str()) {
-                                                if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(ts) {
-                                                    let date = dt.format("%Y-%m-%d").to_string();
-                                                    update_activity(&mut daily_activity, &date, 0, 1, 0);
-                                                    total_tool_calls += 1;
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-
-                    if let Some(date) = session_first_date {
-                        update_activity(&mut daily_activity, &date, 0, 0, 1);
-                    }
-                }
+        usage.context_window = usage.context_window.max(entry.context_window);
+
+        if let Some(tokens) = &entry.tokens {
+            usage.input_tokens += tokens.input as i64;
+            usage.output_tokens += tokens.output as i64;
+            usage.cache_read_input_tokens += tokens.cache_read as i64;
+            usage.cache_creation_input_tokens += (tokens.cache_write_5m + tokens.cache_write_1h) as i64;
+            usage.cost_usd += entry.cost_usd;
+            if let Some((date, _)) = &date {
+                update_daily_tokens(&mut self.daily_tokens, date, tokens.total() as i64);
             }
         }
     }
-
-    Ok(SourceStats {
-        source: "code".to_string(),
-        daily_activity,
-        daily_tokens,
-        model_usage,
-        hour_counts,
-        total_sessions,
-        total_messages,
-        total_tool_calls,
-        first_session_date,
-        last_active_date,
-    })
 }
 
-// ============================================================================
-// Codex Stats
-// ============================================================================
-
-fn get_codex_sessions_dir() -> Result<std::path::PathBuf, String> {
-    let home_dir = dirs::home_dir().ok_or_else(|| "Could not determine home directory".to_string())?;
-    Ok(home_dir.join(".codex").join("sessions"))
-}
+/// Stats of one root from a stats cache in the stats-cache.json format
+async fn load_cached_stats(path: &std::path::Path, source: &str, root: &DataRoot) -> Result<SourceStats, String> {
+    let content = fs::read_to_string(path)
+        .await
+        .map_err(|e| format!("Failed to read stats-cache.json: {}", e))?;
+    let stats: StatsCacheFile = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse stats-cache.json: {}", e))?;
 
-fn list_codex_session_files(root: &std::path::Path) -> Vec<std::path::PathBuf> {
-    let mut files = Vec::new();
-    let Ok(entries) = std::fs::read_dir(root) else { return files };
+    let mut source_stats = SourceStats::new(source, root);
 
-    for entry in entries.flatten() {
-        let path = entry.path();
-        if path.is_dir() {
-            files.extend(list_codex_session_files(&path));
-        } else if path.extension().map(|e| e == "jsonl").unwrap_or(false) {
-            files.push(path);
-        }
+    for item in stats.daily_activity {
+        update_activity(&mut source_stats.daily_activity, &item.date, item.message_count, item.tool_call_count, item.session_count);
     }
 
-    files
-}
-
-fn aggregate_codex_stats() -> Result<SourceStats, String> {
-    let sessions_dir = get_codex_sessions_dir()?;
-    let mut total_sessions = 0;
-    let mut total_messages = 0;
-    let mut total_tool_calls = 0;
-    let mut model_usage: HashMap<String, ModelUsage> = HashMap::new();
-    let mut daily_activity: HashMap<String, ActivityCounts> = HashMap::new();
-    let mut daily_tokens: HashMap<String, i64> = HashMap::new();
-    let mut hour_counts: HashMap<String, i32> = HashMap::new();
-    let mut first_session_date: Option<String> = None;
-    let mut last_active_date: Option<String> = None;
-
-    if !sessions_dir.exists() {
-        return Ok(SourceStats {
-            source: "codex".to_string(),
-            daily_activity,
-            daily_tokens,
-            model_usage,
-            hour_counts,
-            total_sessions: 0,
-            total_messages: 0,
-            total_tool_calls: 0,
-            first_session_date: None,
-            last_active_date: None,
-        });
+    for item in stats.daily_model_tokens {
+        let total_tokens = item.tokens_by_model.values().sum::<i64>();
+        update_daily_tokens(&mut source_stats.daily_tokens, &item.date, total_tokens);
     }
 
-    let files = list_codex_session_files(&sessions_dir);
-    for file in files {
-        total_sessions += 1;
-        let content = match std::fs::read_to_string(&file) {
-            Ok(c) => c,
-            Err(_) => continue,
+    for (model, usage) in stats.model_usage {
+        let mut entry = ModelUsage {
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            cache_read_input_tokens: usage.cache_read_input_tokens,
+            cache_creation_input_tokens: usage.cache_creation_input_tokens,
+            message_count: 0,
+            web_search_requests: usage.web_search_requests,
+            cost_usd: 0.0,
+            // The stats cache leaves the window at 0 for Claude models
+            context_window: if usage.context_window > 0 { usage.context_window } else { context_limit(&model, 0) },
         };
+        entry.cost_usd = model_usage_cost(&model, &entry);
+        source_stats.model_usage.insert(model, entry);
+    }
 
-        let mut current_model: Option<String> = None;
-        let mut session_dates: HashSet<String> = HashSet::new();
-
-        for line in content.lines() {
-            if line.trim().is_empty() {
-                continue;
-            }
-            let Ok(value) = serde_json::from_str::<serde_json::Value>(line) else { continue };
-            let entry_type = value.get("type").and_then(|v| v.as_str()).unwrap_or("");
-            let timestamp = value.get("timestamp").and_then(|v| v.as_str()).unwrap_or("");
-
-            if entry_type == "turn_context" {
-                if let Some(model) = value
-                    .get("payload")
-                    .and_then(|p| p.get("model"))
-                    .and_then(|v| v.as_str())
-                {
-                    current_model = Some(model.to_string());
-                }
-                continue;
-            }
-
-            if entry_type == "event_msg" {
-                let payload = value.get("payload").cloned().unwrap_or(serde_json::Value::Null);
-                if payload.get("type").and_then(|v| v.as_str()) == Some("token_count") {
-                    let info = payload.get("info").cloned().unwrap_or(serde_json::Value::Null);
-                    let last = info.get("last_token_usage").cloned().unwrap_or(serde_json::Value::Null);
-                    let input = last.get("input_tokens").and_then(|v| v.as_i64()).unwrap_or(0);
-                    let cached = last.get("cached_input_tokens").and_then(|v| v.as_i64()).unwrap_or(0);
-                    let output = last.get("output_tokens").and_then(|v| v.as_i64()).unwrap_or(0);
-                    let reasoning = last.get("reasoning_output_tokens").and_then(|v| v.as_i64()).unwrap_or(0);
-                    let total = last.get("total_tokens").and_then(|v| v.as_i64()).unwrap_or(input + cached + output + reasoning);
-                    let context_window = info.get("model_context_window").and_then(|v| v.as_i64()).unwrap_or(0) as i32;
-
-                    let model_id = current_model.clone().unwrap_or_else(|| "codex".to_string());
-                    let usage = model_usage.entry(model_id.clone()).or_insert_with(ModelUsage::default);
-                    usage.input_tokens += input;
-                    usage.output_tokens += output + reasoning;
-                    usage.cache_read_input_tokens += cached;
-                    usage.context_window = usage.context_window.max(context_window);
-
-                    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(timestamp) {
-                        let date = dt.format("%Y-%m-%d").to_string();
-                        update_daily_tokens(&mut daily_tokens, &date, total);
-                    }
-                }
-                continue;
-            }
-
-            if entry_type != "response_item" {
-                continue;
-            }
-
-            let payload = value.get("payload").cloned().unwrap_or(serde_json::Value::Null);
-            let payload_type = payload.get("type").and_then(|v| v.as_str()).unwrap_or("");
-            let role = payload.get("role").and_then(|v| v.as_str()).unwrap_or("");
-
-            if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(timestamp) {
-                let date = dt.format("%Y-%m-%d").to_string();
-                let hour = dt.format("%H").to_string();
-                update_first_last_date(&mut first_session_date, &mut last_active_date, &date);
-
-                if payload_type == "message" {
-                    total_messages += 1;
-                    update_activity(&mut daily_activity, &date, 1, 0, 0);
-                    *hour_counts.entry(hour).or_insert(0) += 1;
-                    session_dates.insert(date.clone());
-
-                    if role == "assistant" {
-                        let model_id = current_model.clone().unwrap_or_else(|| "codex".to_string());
-                        let usage = model_usage.entry(model_id).or_insert_with(ModelUsage::default);
-                        usage.message_count += 1;
-                    }
-                } else if payload_type == "function_call" {
-                    update_activity(&mut daily_activity, &date, 0, 1, 0);
-                    total_tool_calls += 1;
-                }
-            }
-        }
+    source_stats.total_tool_calls = source_stats.daily_activity.values().map(|a| a.tool_calls).sum::<i32>();
+    source_stats.hour_counts = stats.hour_counts;
+    source_stats.total_sessions = stats.total_sessions;
+    source_stats.total_messages = stats.total_messages;
+    source_stats.first_session_date = Some(stats.first_session_date);
+    source_stats.last_active_date = Some(stats.last_computed_date);
+    Ok(source_stats)
+}
 
-        for date in session_dates {
-            update_activity(&mut daily_activity, &date, 0, 0, 1);
+/// Stats of one source per root, aggregated from its usage entries in one pass over all of `roots`
+fn aggregate_source_stats(source: &dyn TranscriptSource, roots: &[DataRoot]) -> Result<Vec<SourceStats>, String> {
+    let mut stats: Vec<SourceStats> = roots.iter().map(|root| SourceStats::new(source.id(), root)).collect();
+    source.usage_entries(roots, &mut |entry| {
+        if let Some(root_stats) = stats.iter_mut().find(|s| s.root == entry.root) {
+            root_stats.add_entry(entry);
         }
-    }
-
-    Ok(SourceStats {
-        source: "codex".to_string(),
-        daily_activity,
-        daily_tokens,
-        model_usage,
-        hour_counts,
-        total_sessions,
-        total_messages,
-        total_tool_calls,
-        first_session_date,
-        last_active_date,
-    })
+    })?;
+    Ok(stats)
 }
 
 // ============================================================================
@@ -601,7 +519,10 @@ fn build_analytics(sources: Vec<SourceStats>) -> AnalyticsV2 {
             update_first_last_date(&mut first_session_date, &mut last_active_date, last);
         }
 
-        hour_activity.insert(source.source.clone(), source.hour_counts.clone());
+        let hours = hour_activity.entry(source.source.clone()).or_default();
+        for (hour, count) in &source.hour_counts {
+            *hours.entry(hour.clone()).or_insert(0) += *count;
+        }
 
         for (date, counts) in &source.daily_activity {
             dates.insert(date.clone());
@@ -611,15 +532,15 @@ fn build_analytics(sources: Vec<SourceStats>) -> AnalyticsV2 {
                 tool_call_counts: HashMap::new(),
                 session_counts: HashMap::new(),
             });
-            entry.message_counts.insert(source.source.clone(), counts.messages);
-            entry.tool_call_counts.insert(source.source.clone(), counts.tool_calls);
-            entry.session_counts.insert(source.source.clone(), counts.sessions);
+            *entry.message_counts.entry(source.source.clone()).or_insert(0) += counts.messages;
+            *entry.tool_call_counts.entry(source.source.clone()).or_insert(0) += counts.tool_calls;
+            *entry.session_counts.entry(source.source.clone()).or_insert(0) += counts.sessions;
         }
 
         for (date, tokens) in &source.daily_tokens {
             dates.insert(date.clone());
             let entry = daily_tokens_map.entry(date.clone()).or_insert_with(HashMap::new);
-            entry.insert(source.source.clone(), *tokens);
+            *entry.entry(source.source.clone()).or_insert(0) += *tokens;
         }
 
         for (model_id, usage) in source.model_usage {
@@ -631,6 +552,8 @@ fn build_analytics(sources: Vec<SourceStats>) -> AnalyticsV2 {
             total_cost += usage.cost_usd;
             model_usage_entries.push(ModelUsageEntry {
                 source: source.source.clone(),
+                root: source.root.clone(),
+                host: source.host.clone(),
                 model_id: model_id.clone(),
                 input_tokens: usage.input_tokens,
                 output_tokens: usage.output_tokens,
@@ -707,13 +630,107 @@ fn build_analytics(sources: Vec<SourceStats>) -> AnalyticsV2 {
     }
 }
 
+// ============================================================================
+// Daily Usage Series
+// ============================================================================
+
+/// (date, root, project id, session id, source, model)
+type UsageKey = (String, String, String, String, String, String);
+
+pub(crate) fn local_date(timestamp: &str) -> Option<String> {
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|dt| dt.with_timezone(&chrono::Local).format("%Y-%m-%d").to_string())
+}
+
+fn add_usage(usage: &mut HashMap<UsageKey, (f64, i64)>, key: UsageKey, cost: f64, tokens: i64) {
+    if cost > 0.0 || tokens > 0 {
+        let totals = usage.entry(key).or_insert((0.0, 0));
+        totals.0 += cost;
+        totals.1 += tokens;
+    }
+}
+
+/// Cost and tokens per local day, project, session and model across every data root and source, oldest first.
+/// Claude Code costs match `ProjectStats.total_cost`; every source is priced like the analytics.
+pub(crate) fn daily_usage() -> Result<Vec<DailyUsage>, String> {
+    let roots = data_roots();
+    let mut usage: HashMap<UsageKey, (f64, i64)> = HashMap::new();
+
+    for source in registered_sources() {
+        let source_id = source.id();
+        source.usage_entries(&roots, &mut |entry| {
+            if entry.tokens.is_none() && entry.cost_usd == 0.0 {
+                return;
+            }
+            let Some(date) = local_date(entry.timestamp) else { return };
+            let key = (
+                date,
+                entry.root.to_string(),
+                entry.project_id.to_string(),
+                entry.session_id.to_string(),
+                source_id.to_string(),
+                entry.model.unwrap_or("unknown").to_string(),
+            );
+            add_usage(&mut usage, key, entry.cost_usd, entry.tokens.map(|t| t.total() as i64).unwrap_or(0));
+        })?;
+    }
+
+    let mut series: Vec<DailyUsage> = usage
+        .into_iter()
+        .map(|((date, root, project_id, session_id, source, model), (cost_usd, tokens))| DailyUsage {
+            date,
+            root,
+            project_id,
+            session_id,
+            source,
+            model,
+            cost_usd,
+            tokens,
+        })
+        .collect();
+    series.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.project_id.cmp(&b.project_id)));
+    Ok(series)
+}
+
+/// Spend per project and local day across every data root, oldest first
+pub(crate) fn daily_project_costs() -> Result<Vec<DailyProjectCost>, String> {
+    let mut costs: HashMap<(String, String, String, String), f64> = HashMap::new();
+    for row in daily_usage()? {
+        *costs.entry((row.date, row.root, row.project_id, row.source)).or_insert(0.0) += row.cost_usd;
+    }
+
+    let mut series: Vec<DailyProjectCost> = costs
+        .into_iter()
+        .filter(|(_, cost_usd)| *cost_usd > 0.0)
+        .map(|((date, root, project_id, source), cost_usd)| DailyProjectCost { date, root, project_id, source, cost_usd })
+        .collect();
+    series.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.project_id.cmp(&b.project_id)));
+    Ok(series)
+}
+
 // ============================================================================
 // Tauri Command
 // ============================================================================
 
 #[tauri::command]
 pub async fn get_analytics_v2() -> Result<AnalyticsV2, String> {
-    let code = load_code_stats().await?;
-    let codex = aggregate_codex_stats()?;
-    Ok(build_analytics(vec![code, codex]))
+    let roots = data_roots();
+    let mut stats = Vec::new();
+
+    for source in registered_sources() {
+        // Roots with a stats cache are read from it; the rest are aggregated in one pass
+        let mut uncached = Vec::new();
+        for root in &roots {
+            match source.stats_cache_file(root) {
+                Some(path) => stats.push(load_cached_stats(&path, source.id(), root).await?),
+                None => uncached.push(root.clone()),
+            }
+        }
+        if !uncached.is_empty() {
+            stats.extend(aggregate_source_stats(source.as_ref(), &uncached)?);
+        }
+    }
+
+    Ok(build_analytics(stats))
 }
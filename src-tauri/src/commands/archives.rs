@@ -0,0 +1,598 @@
+/**
+ * Session archives
+ *
+ * Exports chosen projects and sessions to a single gzip-compressed archive
+ * (.motocho.gz) that can be handed to a colleague or attached to a bug report.
+ * An archive holds the session JSONL, subagent transcripts, referenced plans
+ * and a manifest with the computed `SessionStats` of every session.
+ *
+ * Files are laid out like the original data folders (claude/projects/...,
+ * claude/plans/..., codex/sessions/...). Importing extracts an archive below
+ * ~/.claude-dashboard/imports/ and registers it as a read-only data root, so
+ * every command that reads data roots works against it without touching
+ * ~/.claude.
+ *
+ * Secrets detected in exported files are redacted unless the caller opts out.
+ */
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use super::data_roots::{data_roots, register_data_root, remove_data_root, resolve_qualified_id, slugify, DataRoot};
+use super::secrets::{redact_jsonl, redact_text};
+use super::sources::{find_source, CODEX_SOURCE};
+use super::transcripts::{find_codex_session_file, SessionStats};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+const ARCHIVE_VERSION: u32 = 1;
+
+/// Largest decompressed archive accepted on import
+const MAX_ARCHIVE_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+const CLAUDE_PREFIX: &str = "claude";
+const CODEX_PREFIX: &str = "codex";
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveSessionRef {
+    #[serde(rename = "projectId")]
+    pub project_id: String,
+    #[serde(rename = "sessionId")]
+    pub session_id: String,
+    /// Transcript source id; defaults to Claude Code
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveProjectRef {
+    #[serde(rename = "projectId")]
+    pub project_id: String,
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub version: u32,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    /// Host the archive was exported on
+    pub host: String,
+    pub sessions: Vec<ArchivedSession>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedSession {
+    /// Project id inside the archive (unqualified)
+    #[serde(rename = "projectId")]
+    pub project_id: String,
+    #[serde(rename = "sessionId")]
+    pub session_id: String,
+    pub source: String,
+    pub summary: String,
+    pub stats: Option<SessionStats>,
+    /// Data root and host the session was exported from
+    pub root: String,
+    pub host: String,
+    /// Archive paths of the files belonging to this session
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ArchiveFile {
+    path: String,
+    content: String,
+}
+
+/// On-disk archive document (gzip-compressed JSON)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SessionArchive {
+    manifest: ArchiveManifest,
+    files: Vec<ArchiveFile>,
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn get_imports_dir() -> Result<PathBuf, String> {
+    let home_dir = dirs::home_dir().ok_or_else(|| "Could not determine home directory".to_string())?;
+    Ok(home_dir.join(".claude-dashboard").join("imports"))
+}
+
+fn read_text(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+}
+
+/// Archive paths must stay inside the archive: relative, no `..`, under a known prefix
+fn is_safe_archive_path(path: &str) -> bool {
+    let path = Path::new(path);
+    let mut components = path.components();
+    let prefix_ok = matches!(
+        components.next(),
+        Some(Component::Normal(first)) if first == CLAUDE_PREFIX || first == CODEX_PREFIX
+    );
+    let rest: Vec<Component> = components.collect();
+    prefix_ok && !rest.is_empty() && rest.iter().all(|c| matches!(c, Component::Normal(_)))
+}
+
+/// Plan names referenced by a session: the `slug` of its entries and any
+/// `plans/<name>.md` path mentioned in tool calls
+fn referenced_plan_names(content: &str) -> HashSet<String> {
+    let mut names = HashSet::new();
+
+    for line in content.lines() {
+        if let Ok(value) = serde_json::from_str::<Value>(line) {
+            if let Some(slug) = value.get("slug").and_then(|v| v.as_str()) {
+                names.insert(slug.to_string());
+            }
+        }
+
+        let mut rest = line;
+        while let Some(pos) = rest.find("/plans/") {
+            rest = &rest[pos + "/plans/".len()..];
+            let name: String = rest
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_' || *c == '.')
+                .collect();
+            if let Some(stem) = name.strip_suffix(".md") {
+                if !stem.is_empty() {
+                    names.insert(stem.to_string());
+                }
+            }
+        }
+    }
+
+    names
+}
+
+/// Whether a legacy top-level agent-*.jsonl transcript belongs to the session
+fn is_subagent_of(path: &Path, session_id: &str) -> bool {
+    let Ok(file) = fs::File::open(path) else { return false };
+    use std::io::BufRead;
+    std::io::BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter(|l| !l.trim().is_empty())
+        .take(5)
+        .filter_map(|l| serde_json::from_str::<Value>(&l).ok())
+        .any(|v| v.get("sessionId").and_then(|s| s.as_str()) == Some(session_id))
+}
+
+/// All regular files below `dir`
+fn collect_files(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(&path, out);
+        } else if path.is_file() {
+            out.push(path);
+        }
+    }
+}
+
+/// Push a file into the archive under `archive_path` unless it is already there
+fn add_file(files: &mut Vec<ArchiveFile>, paths: &mut HashSet<String>, archive_path: String, disk_path: &Path) -> Result<String, String> {
+    if !paths.contains(&archive_path) {
+        let content = read_text(disk_path)?;
+        paths.insert(archive_path.clone());
+        files.push(ArchiveFile { path: archive_path.clone(), content });
+    }
+    Ok(archive_path)
+}
+
+/// Collect the files of a Claude Code session: transcript, subagents and referenced plans
+fn collect_claude_session(
+    root: &DataRoot,
+    project_dir_name: &str,
+    session_id: &str,
+    files: &mut Vec<ArchiveFile>,
+    paths: &mut HashSet<String>,
+) -> Result<Vec<String>, String> {
+    let project_dir = root.claude_projects_dir().join(project_dir_name);
+    let session_path = project_dir.join(format!("{}.jsonl", session_id));
+    if !session_path.exists() {
+        return Err(format!("Session not found: {}", session_id));
+    }
+
+    let project_prefix = format!("{}/projects/{}", CLAUDE_PREFIX, project_dir_name);
+    let mut session_files = Vec::new();
+
+    let content = read_text(&session_path)?;
+    let plan_names = referenced_plan_names(&content);
+    session_files.push(add_file(files, paths, format!("{}/{}.jsonl", project_prefix, session_id), &session_path)?);
+
+    // Current layout: <project>/<session-id>/subagents/agent-*.jsonl (and other per-session files)
+    let mut nested = Vec::new();
+    collect_files(&project_dir.join(session_id), &mut nested);
+    for path in nested {
+        let Ok(rel) = path.strip_prefix(&project_dir) else { continue };
+        let archive_path = format!("{}/{}", project_prefix, rel.to_string_lossy().replace('\\', "/"));
+        // Binary attachments are skipped; archives only carry text
+        match add_file(files, paths, archive_path, &path) {
+            Ok(archive_path) => session_files.push(archive_path),
+            Err(e) => log::warn!("Skipping {}: {}", path.display(), e),
+        }
+    }
+
+    // Legacy layout: <project>/agent-*.jsonl tagged with the parent session id
+    if let Ok(entries) = fs::read_dir(&project_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else { continue };
+            if name.starts_with("agent-") && name.ends_with(".jsonl") && is_subagent_of(&path, session_id) {
+                session_files.push(add_file(files, paths, format!("{}/{}", project_prefix, name), &path)?);
+            }
+        }
+    }
+
+    let plans_dir = root.claude_plans_dir();
+    for name in plan_names {
+        let plan_path = plans_dir.join(format!("{}.md", name));
+        if plan_path.is_file() {
+            session_files.push(add_file(files, paths, format!("{}/plans/{}.md", CLAUDE_PREFIX, name), &plan_path)?);
+        }
+    }
+
+    Ok(session_files)
+}
+
+fn collect_codex_session(
+    root: &DataRoot,
+    project_id: &str,
+    session_id: &str,
+    files: &mut Vec<ArchiveFile>,
+    paths: &mut HashSet<String>,
+) -> Result<Vec<String>, String> {
+    let session_path = find_codex_session_file(project_id, session_id)?;
+    let sessions_dir = root.codex_sessions_dir().unwrap_or_default();
+    let rel = session_path
+        .strip_prefix(&sessions_dir)
+        .map(|p| p.to_string_lossy().replace('\\', "/"))
+        .unwrap_or_else(|_| session_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default());
+
+    let archive_path = format!("{}/sessions/{}", CODEX_PREFIX, rel);
+    Ok(vec![add_file(files, paths, archive_path, &session_path)?])
+}
+
+fn write_compressed(path: &Path, json: &[u8]) -> Result<(), String> {
+    let file = fs::File::create(path).map_err(|e| format!("Failed to create archive: {}", e))?;
+    let mut encoder = GzEncoder::new(file, Compression::default());
+    encoder.write_all(json).map_err(|e| format!("Failed to write archive: {}", e))?;
+    let file = encoder.finish().map_err(|e| format!("Failed to write archive: {}", e))?;
+    file.sync_all().map_err(|e| format!("Failed to write archive: {}", e))?;
+    Ok(())
+}
+
+/// Write the archive to a sibling temp file and move it into place once complete,
+/// so a failed export never leaves a truncated archive (or clobbers an existing one) at `path`
+fn write_archive(path: &Path, archive: &SessionArchive) -> Result<(), String> {
+    let json = serde_json::to_vec(archive).map_err(|e| format!("Failed to serialize archive: {}", e))?;
+
+    let mut tmp_name = path.file_name().ok_or_else(|| "Invalid archive path".to_string())?.to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let result = write_compressed(&tmp_path, &json)
+        .and_then(|_| fs::rename(&tmp_path, path).map_err(|e| format!("Failed to replace archive: {}", e)));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+/// Decompress at most `limit` bytes; a larger archive is rejected instead of filling memory
+fn decompress(compressed: impl Read, limit: u64) -> Result<Vec<u8>, String> {
+    let mut json = Vec::new();
+    GzDecoder::new(compressed)
+        .take(limit + 1)
+        .read_to_end(&mut json)
+        .map_err(|e| format!("Failed to decompress archive: {}", e))?;
+
+    if json.len() as u64 > limit {
+        return Err(format!("Archive is larger than {} MB uncompressed", limit / (1024 * 1024)));
+    }
+    Ok(json)
+}
+
+fn read_archive(path: &Path) -> Result<SessionArchive, String> {
+    let file = fs::File::open(path).map_err(|e| format!("Failed to open archive: {}", e))?;
+    let json = decompress(file, MAX_ARCHIVE_BYTES)?;
+
+    let archive: SessionArchive = serde_json::from_slice(&json).map_err(|e| format!("Failed to parse archive: {}", e))?;
+    if archive.manifest.version > ARCHIVE_VERSION {
+        return Err(format!("Unsupported archive version: {}", archive.manifest.version));
+    }
+    Ok(archive)
+}
+
+fn write_archive_files(archive: &SessionArchive, target: &Path) -> Result<(), String> {
+    for file in &archive.files {
+        let path = target.join(&file.path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+        }
+        fs::write(&path, &file.content).map_err(|e| format!("Failed to write {}: {}", file.path, e))?;
+    }
+
+    let manifest_json = serde_json::to_string_pretty(&archive.manifest)
+        .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    fs::create_dir_all(target).map_err(|e| format!("Failed to create directory: {}", e))?;
+    fs::write(target.join("manifest.json"), manifest_json).map_err(|e| format!("Failed to write manifest: {}", e))?;
+    fs::create_dir_all(target.join(CLAUDE_PREFIX)).map_err(|e| format!("Failed to create directory: {}", e))?;
+
+    Ok(())
+}
+
+/// Extract an archive into `target`, removing whatever was written if any step fails
+fn extract_archive(archive: &SessionArchive, target: &Path) -> Result<(), String> {
+    let result = write_archive_files(archive, target);
+    if result.is_err() {
+        let _ = fs::remove_dir_all(target);
+    }
+    result
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Export sessions (and every session of the given projects) to a compressed archive at `output_path`
+#[tauri::command]
+pub async fn export_session_archive(
+    sessions: Vec<ArchiveSessionRef>,
+    projects: Option<Vec<ArchiveProjectRef>>,
+    output_path: String,
+    redact: Option<bool>,
+) -> Result<ArchiveManifest, String> {
+    let mut refs = sessions;
+    for project in projects.unwrap_or_default() {
+        let source = find_source(project.source.as_deref())?;
+        for session in source.project_sessions(project.project_id.clone()).await? {
+            refs.push(ArchiveSessionRef {
+                project_id: project.project_id.clone(),
+                session_id: session.id,
+                source: Some(source.id().to_string()),
+            });
+        }
+    }
+
+    if refs.is_empty() {
+        return Err("No sessions selected".to_string());
+    }
+
+    let mut files: Vec<ArchiveFile> = Vec::new();
+    let mut paths: HashSet<String> = HashSet::new();
+    let mut archived: Vec<ArchivedSession> = Vec::new();
+    let mut seen: HashSet<(String, String, String)> = HashSet::new();
+
+    for r in refs {
+        let source = find_source(r.source.as_deref())?;
+        if !seen.insert((source.id().to_string(), r.project_id.clone(), r.session_id.clone())) {
+            continue;
+        }
+
+        let (root, dir_name) = resolve_qualified_id(&r.project_id)?;
+        let session_files = if source.id() == CODEX_SOURCE {
+            collect_codex_session(&root, &r.project_id, &r.session_id, &mut files, &mut paths)?
+        } else {
+            collect_claude_session(&root, &dir_name, &r.session_id, &mut files, &mut paths)?
+        };
+
+        let transcript = source.session_transcript(r.project_id.clone(), r.session_id.clone()).await?;
+        archived.push(ArchivedSession {
+            project_id: dir_name,
+            session_id: r.session_id,
+            source: source.id().to_string(),
+            summary: transcript.session.summary,
+            stats: transcript.session.stats,
+            root: root.id,
+            host: root.host,
+            files: session_files,
+        });
+    }
+
+    if redact.unwrap_or(true) {
+        for file in &mut files {
+            file.content = if file.path.ends_with(".jsonl") {
+                redact_jsonl(&file.content)
+            } else {
+                redact_text(&file.content)
+            };
+        }
+        for session in &mut archived {
+            session.summary = redact_text(&session.summary);
+        }
+    }
+
+    let manifest = ArchiveManifest {
+        version: ARCHIVE_VERSION,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        host: whoami::fallible::hostname().unwrap_or_else(|_| "localhost".to_string()),
+        sessions: archived,
+    };
+
+    let archive = SessionArchive { manifest, files };
+    write_archive(Path::new(&output_path), &archive)?;
+
+    Ok(archive.manifest)
+}
+
+/// Read the manifest of an archive without importing it
+#[tauri::command]
+pub async fn read_archive_manifest(archive_path: String) -> Result<ArchiveManifest, String> {
+    Ok(read_archive(Path::new(&archive_path))?.manifest)
+}
+
+/// Extract an archive and mount it as a read-only data root
+#[tauri::command]
+pub async fn import_session_archive(archive_path: String, name: Option<String>) -> Result<DataRoot, String> {
+    let archive = read_archive(Path::new(&archive_path))?;
+
+    if let Some(bad) = archive.files.iter().find(|f| !is_safe_archive_path(&f.path)) {
+        return Err(format!("Archive contains an invalid path: {}", bad.path));
+    }
+
+    let name = name.filter(|n| !n.trim().is_empty()).unwrap_or_else(|| {
+        Path::new(&archive_path)
+            .file_name()
+            .map(|n| n.to_string_lossy().trim_end_matches(".gz").trim_end_matches(".motocho").to_string())
+            .unwrap_or_else(|| "Imported archive".to_string())
+    });
+
+    // Pick a directory that no existing root uses
+    let imports_dir = get_imports_dir()?;
+    let existing: HashSet<String> = data_roots().into_iter().map(|r| r.id).collect();
+    let base_id = format!("import-{}", slugify(&name));
+    let mut id = base_id.clone();
+    let mut n = 2;
+    while existing.contains(&id) || imports_dir.join(&id).exists() {
+        id = format!("{}-{}", base_id, n);
+        n += 1;
+    }
+    let target = imports_dir.join(&id);
+    extract_archive(&archive, &target)?;
+
+    let has_codex = archive.manifest.sessions.iter().any(|s| s.source == CODEX_SOURCE);
+
+    let root = register_data_root(DataRoot {
+        id,
+        name,
+        host: archive.manifest.host.clone(),
+        claude_dir: target.join(CLAUDE_PREFIX).to_string_lossy().to_string(),
+        codex_dir: has_codex.then(|| target.join(CODEX_PREFIX).to_string_lossy().to_string()),
+        read_only: true,
+    })
+    .await;
+
+    if root.is_err() {
+        let _ = fs::remove_dir_all(&target);
+    }
+    root
+}
+
+/// Unmount an imported archive and delete its extracted files
+#[tauri::command]
+pub async fn remove_imported_archive(root_id: String) -> Result<bool, String> {
+    let root = data_roots()
+        .into_iter()
+        .find(|r| r.id == root_id && r.read_only)
+        .ok_or_else(|| format!("Not an imported archive: {}", root_id))?;
+
+    let removed = remove_data_root(root.id.clone()).await?;
+
+    let imports_dir = get_imports_dir()?;
+    let target = imports_dir.join(&root.id);
+    if target.starts_with(&imports_dir) && target.exists() {
+        tokio::fs::remove_dir_all(&target)
+            .await
+            .map_err(|e| format!("Failed to delete imported archive: {}", e))?;
+    }
+
+    Ok(removed)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_paths_stay_inside() {
+        assert!(is_safe_archive_path("claude/projects/-home-me-app/abc.jsonl"));
+        assert!(is_safe_archive_path("codex/sessions/2025/01/02/rollout.jsonl"));
+        assert!(!is_safe_archive_path("claude/../../etc/passwd"));
+        assert!(!is_safe_archive_path("/etc/passwd"));
+        assert!(!is_safe_archive_path("other/file"));
+        assert!(!is_safe_archive_path("claude"));
+    }
+
+    #[test]
+    fn test_referenced_plan_names() {
+        let content = concat!(
+            r#"{"type":"user","slug":"quiet-river"}"#,
+            "\n",
+            r#"{"type":"assistant","message":{"content":[{"type":"tool_use","input":{"file_path":"/home/me/.claude/plans/bold-fox.md"}}]}}"#,
+        );
+        let names = referenced_plan_names(content);
+        assert!(names.contains("quiet-river"));
+        assert!(names.contains("bold-fox"));
+        assert_eq!(names.len(), 2);
+    }
+
+    #[test]
+    fn test_decompress_is_capped() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&[b'x'; 4096]).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        assert_eq!(decompress(compressed.as_slice(), 4096).unwrap().len(), 4096);
+        assert!(decompress(compressed.as_slice(), 4095).is_err());
+    }
+
+    #[test]
+    fn test_failed_extraction_removes_target() {
+        let target = std::env::temp_dir().join(format!("archive-import-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&target);
+        let file = |path: &str| ArchiveFile { path: path.to_string(), content: "{}\n".to_string() };
+        // The second file needs a directory where the first one is a file
+        let archive = SessionArchive {
+            manifest: ArchiveManifest {
+                version: ARCHIVE_VERSION,
+                created_at: "2025-06-01T10:00:00Z".to_string(),
+                host: "box".to_string(),
+                sessions: Vec::new(),
+            },
+            files: vec![file("claude/projects/p"), file("claude/projects/p/s.jsonl")],
+        };
+
+        assert!(extract_archive(&archive, &target).is_err());
+        assert!(!target.exists());
+    }
+
+    #[test]
+    fn test_archive_is_written_atomically() {
+        let dir = std::env::temp_dir().join(format!("archive-export-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("sessions.tar.gz");
+        let archive = SessionArchive {
+            manifest: ArchiveManifest {
+                version: ARCHIVE_VERSION,
+                created_at: "2025-06-01T10:00:00Z".to_string(),
+                host: "box".to_string(),
+                sessions: Vec::new(),
+            },
+            files: vec![ArchiveFile { path: "claude/projects/p/s.jsonl".to_string(), content: "{}\n".to_string() }],
+        };
+
+        write_archive(&path, &archive).unwrap();
+        assert_eq!(read_archive(&path).unwrap().files.len(), 1);
+        assert!(!dir.join("sessions.tar.gz.tmp").exists());
+
+        // A failed write leaves neither a temp file nor a partial archive behind
+        let missing = dir.join("missing").join("sessions.tar.gz");
+        assert!(write_archive(&missing, &archive).is_err());
+        assert!(!dir.join("missing").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
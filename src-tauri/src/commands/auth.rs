@@ -13,6 +13,9 @@ pub struct AuthStatus {
     pub authenticated: bool,
     pub email: Option<String>,
     pub plan: Option<String>,
+    /// Raw rate limit tier, e.g. "default_claude_max_20x"; tells the Max plans apart
+    #[serde(rename = "rateLimitTier")]
+    pub rate_limit_tier: Option<String>,
     pub username: Option<String>,
 }
 
@@ -44,11 +47,16 @@ pub async fn get_auth_status() -> Result<AuthStatus, String> {
                     .and_then(|o| o.get("subscriptionType"))
                     .and_then(|p| p.as_str())
                     .map(|s| capitalize_first(s));
+                let rate_limit_tier = json.get("claudeAiOauth")
+                    .and_then(|o| o.get("rateLimitTier"))
+                    .and_then(|t| t.as_str())
+                    .map(String::from);
 
                 return Ok(AuthStatus {
                     authenticated: true,
                     email: None, // Email not accessible via public API
                     plan,
+                    rate_limit_tier,
                     username: Some(username.clone()),
                 });
             }
@@ -58,6 +66,7 @@ pub async fn get_auth_status() -> Result<AuthStatus, String> {
                 authenticated: true,
                 email: None,
                 plan: None,
+                rate_limit_tier: None,
                 username: Some(username.clone()),
             })
         }
@@ -67,6 +76,7 @@ pub async fn get_auth_status() -> Result<AuthStatus, String> {
                 authenticated: false,
                 email: None,
                 plan: None,
+                rate_limit_tier: None,
                 username: None,
             })
         }
@@ -0,0 +1,476 @@
+/**
+ * Cost budgets
+ *
+ * Daily, weekly and monthly spend limits, globally or per project, stored in
+ * ~/.claude-dashboard/budgets.json. Budgets are evaluated against the daily
+ * project cost series of the analytics (local calendar days; weeks start on
+ * Monday). A global budget counts the local data root only: imported archives
+ * and other machines' folders are not spend of this machine.
+ *
+ * When spend crosses one of a budget's thresholds (80% and 100% by default) a
+ * desktop notification is shown once per budget, period and threshold, and a
+ * `budget-alert` event is emitted so an open window can refresh. Budgets are
+ * checked by a background monitor and whenever their status is requested.
+ */
+
+use chrono::{Datelike, Duration as ChronoDuration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_notification::NotificationExt;
+
+use super::analytics_v2::{daily_project_costs, DailyProjectCost};
+use super::data_roots::LOCAL_ROOT;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+const BUDGET_ALERT_EVENT: &str = "budget-alert";
+const MONITOR_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+pub const PERIOD_DAILY: &str = "daily";
+pub const PERIOD_WEEKLY: &str = "weekly";
+pub const PERIOD_MONTHLY: &str = "monthly";
+
+pub const BUDGET_OK: &str = "ok";
+pub const BUDGET_WARNING: &str = "warning";
+pub const BUDGET_EXCEEDED: &str = "exceeded";
+
+/// Serializes read-modify-write cycles of the budget file
+static BUDGET_FILE_LOCK: Mutex<()> = Mutex::new(());
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Budget {
+    /// Assigned on save when empty
+    #[serde(default)]
+    pub id: String,
+    /// Root-qualified project id; None for total spend of the local root
+    #[serde(default)]
+    pub project_id: Option<String>,
+    /// "daily", "weekly" or "monthly"
+    pub period: String,
+    pub limit_usd: f64,
+    /// Fractions of the limit that trigger an alert
+    #[serde(default = "default_thresholds")]
+    pub thresholds: Vec<f64>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BudgetFile {
+    #[serde(default)]
+    budgets: Vec<Budget>,
+    /// Highest threshold already alerted, keyed by "<budget id>/<period start>"
+    #[serde(default)]
+    alerted: HashMap<String, f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetStatus {
+    pub budget: Budget,
+    pub period_start: String,
+    /// Last day of the period
+    pub period_end: String,
+    pub spent_usd: f64,
+    pub remaining_usd: f64,
+    /// Spent as a fraction of the limit
+    pub used: f64,
+    /// Spend at the end of the period if the current daily rate holds
+    pub projected_usd: f64,
+    /// Thresholds already reached, ascending
+    pub crossed_thresholds: Vec<f64>,
+    /// "ok", "warning" or "exceeded"
+    pub status: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetAlert {
+    pub budget_id: String,
+    pub project_id: Option<String>,
+    pub period: String,
+    pub period_start: String,
+    pub threshold: f64,
+    pub spent_usd: f64,
+    pub limit_usd: f64,
+    pub title: String,
+    pub body: String,
+}
+
+fn default_thresholds() -> Vec<f64> {
+    vec![0.8, 1.0]
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn get_budgets_path() -> Result<PathBuf, String> {
+    let home_dir = dirs::home_dir().ok_or_else(|| "Could not determine home directory".to_string())?;
+    Ok(home_dir.join(".claude-dashboard").join("budgets.json"))
+}
+
+fn read_budget_file() -> BudgetFile {
+    let Ok(path) = get_budgets_path() else { return BudgetFile::default() };
+    let Ok(content) = std::fs::read_to_string(&path) else { return BudgetFile::default() };
+
+    match serde_json::from_str::<BudgetFile>(&content) {
+        Ok(file) => file,
+        Err(e) => {
+            log::warn!("Ignoring invalid budgets in {}: {}", path.display(), e);
+            BudgetFile::default()
+        }
+    }
+}
+
+fn write_budget_file(file: &BudgetFile) -> Result<(), String> {
+    let path = get_budgets_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+
+    let content = serde_json::to_string_pretty(file).map_err(|e| format!("Failed to serialize budgets: {}", e))?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write budgets: {}", e))
+}
+
+fn validate(budget: &Budget) -> Result<(), String> {
+    if ![PERIOD_DAILY, PERIOD_WEEKLY, PERIOD_MONTHLY].contains(&budget.period.as_str()) {
+        return Err(format!("Invalid budget period: {}", budget.period));
+    }
+    if !budget.limit_usd.is_finite() || budget.limit_usd <= 0.0 {
+        return Err("Budget limit must be greater than zero".to_string());
+    }
+    if budget.thresholds.iter().any(|t| !t.is_finite() || *t <= 0.0) {
+        return Err("Budget thresholds must be greater than zero".to_string());
+    }
+    Ok(())
+}
+
+fn alert_key(budget_id: &str, period_start: &str) -> String {
+    format!("{}/{}", budget_id, period_start)
+}
+
+fn scope_label(project_id: Option<&str>) -> String {
+    match project_id {
+        // Project ids encode the working directory; its last segment reads best
+        Some(id) => id.rsplit(['-', ':']).find(|s| !s.is_empty()).unwrap_or(id).to_string(),
+        None => "All projects".to_string(),
+    }
+}
+
+/// First and last day of the period containing `today`
+pub fn period_bounds(period: &str, today: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+    match period {
+        PERIOD_DAILY => Some((today, today)),
+        PERIOD_WEEKLY => {
+            let start = today - ChronoDuration::days(today.weekday().num_days_from_monday() as i64);
+            Some((start, start + ChronoDuration::days(6)))
+        }
+        PERIOD_MONTHLY => {
+            let start = today.with_day(1)?;
+            let next = if start.month() == 12 {
+                NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)?
+            } else {
+                NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)?
+            };
+            Some((start, next.pred_opt()?))
+        }
+        _ => None,
+    }
+}
+
+/// Spend against one budget in the period containing `today`
+pub fn evaluate(budget: &Budget, costs: &[DailyProjectCost], today: NaiveDate) -> Option<BudgetStatus> {
+    let (start, end) = period_bounds(&budget.period, today)?;
+    let (from, to) = (start.format("%Y-%m-%d").to_string(), end.format("%Y-%m-%d").to_string());
+
+    let spent_usd: f64 = costs
+        .iter()
+        .filter(|c| c.date >= from && c.date <= to)
+        .filter(|c| match budget.project_id.as_deref() {
+            Some(project_id) => c.project_id == project_id,
+            None => c.root == LOCAL_ROOT,
+        })
+        .map(|c| c.cost_usd)
+        .sum();
+
+    let elapsed_days = ((today - start).num_days() + 1) as f64;
+    let period_days = ((end - start).num_days() + 1) as f64;
+    let used = spent_usd / budget.limit_usd;
+
+    let mut crossed_thresholds: Vec<f64> = budget.thresholds.iter().copied().filter(|t| used >= *t).collect();
+    crossed_thresholds.sort_by(|a, b| a.total_cmp(b));
+
+    let status = if used >= 1.0 {
+        BUDGET_EXCEEDED
+    } else if !crossed_thresholds.is_empty() {
+        BUDGET_WARNING
+    } else {
+        BUDGET_OK
+    };
+
+    Some(BudgetStatus {
+        budget: budget.clone(),
+        period_start: from,
+        period_end: to,
+        spent_usd,
+        remaining_usd: (budget.limit_usd - spent_usd).max(0.0),
+        used,
+        projected_usd: spent_usd / elapsed_days * period_days,
+        crossed_thresholds,
+        status: status.to_string(),
+    })
+}
+
+/// Alerts for thresholds crossed since the last check; records them in `alerted`
+pub fn new_alerts(statuses: &[BudgetStatus], alerted: &mut HashMap<String, f64>) -> Vec<BudgetAlert> {
+    let mut alerts = Vec::new();
+
+    for status in statuses {
+        let Some(&threshold) = status.crossed_thresholds.last() else { continue };
+        let key = alert_key(&status.budget.id, &status.period_start);
+        if alerted.get(&key).map(|previous| *previous >= threshold).unwrap_or(false) {
+            continue;
+        }
+        alerted.insert(key, threshold);
+
+        let budget = &status.budget;
+        let scope = scope_label(budget.project_id.as_deref());
+        let title = if threshold >= 1.0 {
+            format!("{} {} budget exceeded", scope, budget.period)
+        } else {
+            format!("{} {} budget at {:.0}%", scope, budget.period, threshold * 100.0)
+        };
+        alerts.push(BudgetAlert {
+            budget_id: budget.id.clone(),
+            project_id: budget.project_id.clone(),
+            period: budget.period.clone(),
+            period_start: status.period_start.clone(),
+            threshold,
+            spent_usd: status.spent_usd,
+            limit_usd: budget.limit_usd,
+            title,
+            body: format!("${:.2} of ${:.2} spent since {}", status.spent_usd, budget.limit_usd, status.period_start),
+        });
+    }
+
+    alerts
+}
+
+fn lock_budget_file() -> Result<std::sync::MutexGuard<'static, ()>, String> {
+    BUDGET_FILE_LOCK.lock().map_err(|_| "Budget file lock poisoned".to_string())
+}
+
+/// Record alerts in the budget file and return those not recorded yet. Budgets may
+/// have been saved while costs were scanned, so the file is re-read under the lock
+/// and only alert keys are merged; `current` are the keys of the evaluated periods.
+fn record_alerts(alerts: Vec<BudgetAlert>, current: &[String]) -> Result<Vec<BudgetAlert>, String> {
+    let _guard = lock_budget_file()?;
+    let mut file = read_budget_file();
+
+    let mut recorded = Vec::new();
+    for alert in alerts {
+        // Deleted during the scan
+        if !file.budgets.iter().any(|b| b.id == alert.budget_id) {
+            continue;
+        }
+        let key = alert_key(&alert.budget_id, &alert.period_start);
+        // Another check got there first
+        if file.alerted.get(&key).map(|previous| *previous >= alert.threshold).unwrap_or(false) {
+            continue;
+        }
+        file.alerted.insert(key, alert.threshold);
+        recorded.push(alert);
+    }
+
+    // Past periods never alert again; budgets saved during the scan keep their keys
+    let budget_id = |key: &str| key.split('/').next().unwrap_or("").to_string();
+    let evaluated: Vec<String> = current.iter().map(|key| budget_id(key)).collect();
+    file.alerted.retain(|key, _| current.contains(key) || !evaluated.contains(&budget_id(key)));
+    write_budget_file(&file)?;
+    Ok(recorded)
+}
+
+/// Evaluate every enabled budget for today and emit alerts for newly crossed thresholds
+fn check_budgets(app: &AppHandle) -> Result<Vec<BudgetStatus>, String> {
+    let mut file = read_budget_file();
+    if file.budgets.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let costs = daily_project_costs()?;
+    let today = chrono::Local::now().date_naive();
+    let statuses: Vec<BudgetStatus> = file
+        .budgets
+        .iter()
+        .filter_map(|budget| evaluate(budget, &costs, today))
+        .collect();
+
+    let enabled: Vec<BudgetStatus> = statuses.iter().filter(|s| s.budget.enabled).cloned().collect();
+    let mut alerts = new_alerts(&enabled, &mut file.alerted);
+    if !alerts.is_empty() {
+        let current: Vec<String> = statuses.iter().map(|s| alert_key(&s.budget.id, &s.period_start)).collect();
+        alerts = record_alerts(alerts, &current)?;
+    }
+    for alert in alerts {
+        if let Err(e) = app.notification().builder().title(&alert.title).body(&alert.body).show() {
+            log::warn!("Failed to show budget notification: {}", e);
+        }
+        if let Err(e) = app.emit(BUDGET_ALERT_EVENT, alert) {
+            log::warn!("Failed to emit budget alert: {}", e);
+        }
+    }
+
+    Ok(statuses)
+}
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+/// Check budgets in the background for as long as the app runs
+pub fn start_budget_monitor(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        if let Err(e) = check_budgets(&app) {
+            log::warn!("Failed to check budgets: {}", e);
+        }
+        std::thread::sleep(MONITOR_INTERVAL);
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_budgets() -> Result<Vec<Budget>, String> {
+    Ok(read_budget_file().budgets)
+}
+
+/// Replace all budgets; new budgets get an id. Alert history of removed budgets is dropped.
+#[tauri::command]
+pub async fn save_budgets(budgets: Vec<Budget>) -> Result<Vec<Budget>, String> {
+    let mut budgets = budgets;
+    for budget in &mut budgets {
+        validate(budget)?;
+        if budget.id.is_empty() {
+            budget.id = uuid::Uuid::new_v4().to_string();
+        }
+    }
+
+    let _guard = lock_budget_file()?;
+    let mut file = read_budget_file();
+
+    file.alerted
+        .retain(|key, _| budgets.iter().any(|b| key.split('/').next() == Some(b.id.as_str())));
+    file.budgets = budgets.clone();
+    write_budget_file(&file)?;
+    Ok(budgets)
+}
+
+/// Current spend against every budget; also emits alerts for newly crossed thresholds
+#[tauri::command]
+pub async fn get_budget_status(app: AppHandle) -> Result<Vec<BudgetStatus>, String> {
+    tokio::task::spawn_blocking(move || check_budgets(&app))
+        .await
+        .map_err(|e| format!("Budget check failed: {}", e))?
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cost(date: &str, project_id: &str, cost_usd: f64) -> DailyProjectCost {
+        DailyProjectCost {
+            date: date.to_string(),
+            root: LOCAL_ROOT.to_string(),
+            project_id: project_id.to_string(),
+            source: "code".to_string(),
+            cost_usd,
+        }
+    }
+
+    fn budget(period: &str, project_id: Option<&str>, limit_usd: f64) -> Budget {
+        Budget {
+            id: "b1".to_string(),
+            project_id: project_id.map(String::from),
+            period: period.to_string(),
+            limit_usd,
+            thresholds: default_thresholds(),
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_period_bounds() {
+        let day = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        // 2025-12-18 is a Thursday
+        assert_eq!(period_bounds(PERIOD_WEEKLY, day("2025-12-18")), Some((day("2025-12-15"), day("2025-12-21"))));
+        assert_eq!(period_bounds(PERIOD_MONTHLY, day("2025-12-18")), Some((day("2025-12-01"), day("2025-12-31"))));
+        assert_eq!(period_bounds(PERIOD_MONTHLY, day("2024-02-10")), Some((day("2024-02-01"), day("2024-02-29"))));
+        assert_eq!(period_bounds("yearly", day("2024-02-10")), None);
+    }
+
+    #[test]
+    fn test_evaluate_and_alert_once() {
+        let costs = vec![
+            cost("2025-12-14", "p1", 50.0),
+            cost("2025-12-15", "p1", 30.0),
+            cost("2025-12-16", "p1", 12.0),
+            cost("2025-12-16", "p2", 40.0),
+        ];
+        let today = NaiveDate::from_ymd_opt(2025, 12, 16).unwrap();
+
+        let status = evaluate(&budget(PERIOD_WEEKLY, Some("p1"), 50.0), &costs, today).unwrap();
+        assert!((status.spent_usd - 42.0).abs() < 1e-9);
+        assert_eq!(status.status, BUDGET_WARNING);
+        assert_eq!(status.crossed_thresholds, vec![0.8]);
+        assert!((status.projected_usd - 42.0 / 2.0 * 7.0).abs() < 1e-9);
+
+        let mut alerted = HashMap::new();
+        assert_eq!(new_alerts(std::slice::from_ref(&status), &mut alerted).len(), 1);
+        assert!(new_alerts(std::slice::from_ref(&status), &mut alerted).is_empty());
+
+        let total = evaluate(&budget(PERIOD_DAILY, None, 50.0), &costs, today).unwrap();
+        assert_eq!(total.status, BUDGET_EXCEEDED);
+        let alerts = new_alerts(&[total], &mut alerted);
+        assert_eq!((alerts.len(), alerts[0].threshold), (1, 1.0));
+    }
+
+    #[test]
+    fn test_global_budget_counts_local_root_only() {
+        let today = NaiveDate::from_ymd_opt(2025, 12, 16).unwrap();
+        let costs = vec![
+            cost("2025-12-16", "p1", 30.0),
+            DailyProjectCost { root: "team-box".to_string(), ..cost("2025-12-16", "team-box:p1", 40.0) },
+        ];
+
+        let total = evaluate(&budget(PERIOD_DAILY, None, 50.0), &costs, today).unwrap();
+        assert!((total.spent_usd - 30.0).abs() < 1e-9);
+        assert_eq!(total.status, BUDGET_OK);
+
+        // A budget on the other root's project still counts it
+        let teammate = evaluate(&budget(PERIOD_DAILY, Some("team-box:p1"), 50.0), &costs, today).unwrap();
+        assert!((teammate.spent_usd - 40.0).abs() < 1e-9);
+    }
+}
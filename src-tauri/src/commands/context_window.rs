@@ -0,0 +1,389 @@
+/**
+ * Context window utilization
+ *
+ * Every API response reports the prompt it was given in its usage: input plus
+ * cache read plus cache creation tokens is the context the model saw on that
+ * turn. Plotted per assistant message this gives a session's context curve,
+ * which rises turn by turn and drops when the conversation is compacted.
+ *
+ * Compactions are recognized from:
+ * - `system` entries with subtype `compact_boundary` (trigger and pre-compaction tokens)
+ * - user entries flagged `isCompactSummary`, for transcripts without boundaries
+ * - Codex `compacted` entries and `context_compacted` events
+ *
+ * `summary` entries are not compactions: resumed and continued sessions start
+ * with the titles of their predecessor, whose leaves point outside the file.
+ *
+ * Claude models have a 200k window, or 1M for the long-context variants; Codex
+ * reports its window with every token count.
+ */
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use super::sources::{claude_project_dir, CODEX_SOURCE};
+use super::transcript_index::UsageDedup;
+use super::transcripts::find_codex_session_file;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+const STANDARD_CONTEXT: i32 = 200_000;
+const LONG_CONTEXT: i32 = 1_000_000;
+
+/// Turns using at least this fraction of the window count as near the limit
+const NEAR_LIMIT: f64 = 0.8;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextPoint {
+    pub uuid: Option<String>,
+    pub timestamp: String,
+    pub model: Option<String>,
+    /// Input plus cache read plus cache creation tokens
+    pub context_tokens: u64,
+    pub output_tokens: u64,
+    pub context_limit: i32,
+    /// Context as a fraction of the limit
+    pub utilization: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Compaction {
+    pub timestamp: Option<String>,
+    /// "auto" or "manual", when recorded
+    pub trigger: Option<String>,
+    /// Context before compacting: as recorded, else the last turn before it
+    pub pre_tokens: Option<u64>,
+    /// Context of the first turn after compacting
+    pub post_tokens: Option<u64>,
+    /// Index into the points of the first turn after compacting
+    pub next_point: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionContext {
+    pub project_id: String,
+    pub session_id: String,
+    pub context_limit: i32,
+    pub peak_tokens: u64,
+    pub peak_utilization: f64,
+    pub compactions: usize,
+    pub auto_compactions: usize,
+    /// One per API response, in file order
+    pub points: Vec<ContextPoint>,
+    pub compaction_events: Vec<Compaction>,
+    /// Indices into the points of turns at or above 80% of the limit
+    pub near_limit: Vec<usize>,
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn as_u64(value: Option<&Value>) -> u64 {
+    value.and_then(|v| v.as_u64()).unwrap_or(0)
+}
+
+fn str_field(value: &Value, key: &str) -> Option<String> {
+    value.get(key).and_then(|v| v.as_str()).map(String::from)
+}
+
+/// Context window of a model, given the largest context seen with it; 0 when unknown
+pub fn context_limit(model: &str, observed_tokens: u64) -> i32 {
+    let model = model.to_lowercase();
+    if model.contains("[1m]") || observed_tokens > STANDARD_CONTEXT as u64 {
+        return LONG_CONTEXT;
+    }
+    if ["claude", "opus", "sonnet", "haiku"].iter().any(|family| model.contains(family)) {
+        return STANDARD_CONTEXT;
+    }
+    0
+}
+
+/// Context the model saw, from a Claude usage object
+pub fn context_tokens(input: u64, cache_read: u64, cache_creation: u64) -> u64 {
+    input + cache_read + cache_creation
+}
+
+fn point(uuid: Option<String>, timestamp: String, model: Option<String>, context: u64, output: u64, limit: i32) -> ContextPoint {
+    ContextPoint {
+        uuid,
+        timestamp,
+        model,
+        context_tokens: context,
+        output_tokens: output,
+        context_limit: limit,
+        utilization: if limit > 0 { context as f64 / limit as f64 } else { 0.0 },
+    }
+}
+
+fn compaction(timestamp: Option<String>, trigger: Option<String>, pre_tokens: Option<u64>) -> Compaction {
+    Compaction {
+        timestamp,
+        trigger,
+        pre_tokens,
+        post_tokens: None,
+        next_point: None,
+    }
+}
+
+/// Context points and compactions of a Claude Code session, from its JSONL lines
+pub fn claude_context(lines: &[Value]) -> (Vec<ContextPoint>, Vec<Compaction>) {
+    let mut points: Vec<ContextPoint> = Vec::new();
+    let mut compactions: Vec<Compaction> = Vec::new();
+    let mut dedup = UsageDedup::default();
+    // A boundary is followed by its summary message; count the pair once
+    let mut after_boundary = false;
+
+    for line in lines {
+        let entry_type = line.get("type").and_then(|v| v.as_str()).unwrap_or("");
+        let timestamp = str_field(line, "timestamp");
+
+        match entry_type {
+            "system" if line.get("subtype").and_then(|v| v.as_str()) == Some("compact_boundary") => {
+                let metadata = line.get("compactMetadata");
+                compactions.push(compaction(
+                    timestamp,
+                    metadata.and_then(|m| str_field(m, "trigger")),
+                    metadata.and_then(|m| m.get("preTokens")).and_then(|v| v.as_u64()),
+                ));
+                after_boundary = true;
+            }
+            "user" if line.get("isCompactSummary").and_then(|v| v.as_bool()).unwrap_or(false) => {
+                if !after_boundary {
+                    compactions.push(compaction(timestamp, None, None));
+                }
+                after_boundary = false;
+            }
+            "assistant" => {
+                // Subagent turns run in their own context
+                if line.get("isSidechain").and_then(|v| v.as_bool()).unwrap_or(false) {
+                    continue;
+                }
+                let Some(message) = line.get("message") else { continue };
+                let Some(usage) = message.get("usage").filter(|u| u.is_object()) else { continue };
+
+                // Responses are logged once per content block with the same usage
+                if !dedup.counts(message.get("id").and_then(|v| v.as_str())) {
+                    continue;
+                }
+
+                let context = context_tokens(
+                    as_u64(usage.get("input_tokens")),
+                    as_u64(usage.get("cache_read_input_tokens")),
+                    as_u64(usage.get("cache_creation_input_tokens")),
+                );
+                let model = str_field(message, "model");
+                let limit = context_limit(model.as_deref().unwrap_or(""), context);
+                points.push(point(
+                    str_field(line, "uuid"),
+                    timestamp.unwrap_or_default(),
+                    model,
+                    context,
+                    as_u64(usage.get("output_tokens")),
+                    limit,
+                ));
+                after_boundary = false;
+                link_compaction(&mut compactions, &points);
+            }
+            _ => {}
+        }
+    }
+
+    (points, compactions)
+}
+
+/// Context points and compactions of a Codex rollout, from its JSONL lines
+pub fn codex_context(lines: &[Value]) -> (Vec<ContextPoint>, Vec<Compaction>) {
+    let mut points: Vec<ContextPoint> = Vec::new();
+    let mut compactions: Vec<Compaction> = Vec::new();
+    let mut model: Option<String> = None;
+
+    for line in lines {
+        let entry_type = line.get("type").and_then(|v| v.as_str()).unwrap_or("");
+        let timestamp = str_field(line, "timestamp");
+        let payload = line.get("payload").cloned().unwrap_or(Value::Null);
+        let payload_type = payload.get("type").and_then(|v| v.as_str()).unwrap_or("");
+
+        match (entry_type, payload_type) {
+            ("turn_context", _) => model = str_field(&payload, "model").or(model),
+            ("compacted", _) | ("event_msg", "context_compacted") => {
+                // The event and the entry describe the same compaction
+                let duplicate = compactions.last().map(|c| c.next_point.is_none()).unwrap_or(false);
+                if !duplicate {
+                    compactions.push(compaction(timestamp, None, None));
+                }
+            }
+            ("event_msg", "token_count") => {
+                let Some(info) = payload.get("info").filter(|i| i.is_object()) else { continue };
+                let Some(last) = info.get("last_token_usage") else { continue };
+                // Cached tokens are part of the input
+                let context = as_u64(last.get("input_tokens"));
+                let limit = info.get("model_context_window").and_then(|v| v.as_i64()).unwrap_or(0) as i32;
+                points.push(point(None, timestamp.unwrap_or_default(), model.clone(), context, as_u64(last.get("output_tokens")), limit));
+                link_compaction(&mut compactions, &points);
+            }
+            _ => {}
+        }
+    }
+
+    (points, compactions)
+}
+
+/// Tie compactions still waiting for a turn to the newest point
+fn link_compaction(compactions: &mut [Compaction], points: &[ContextPoint]) {
+    let index = points.len() - 1;
+    for compaction in compactions.iter_mut().filter(|c| c.next_point.is_none()) {
+        compaction.next_point = Some(index);
+        compaction.post_tokens = Some(points[index].context_tokens);
+        if compaction.pre_tokens.is_none() && index > 0 {
+            compaction.pre_tokens = Some(points[index - 1].context_tokens);
+        }
+    }
+}
+
+fn read_json_lines(path: &Path) -> Result<Vec<Value>, String> {
+    let file = fs::File::open(path).map_err(|e| format!("Failed to open session file: {}", e))?;
+    Ok(BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<Value>(&line).ok())
+        .collect())
+}
+
+/// Summary of a session's context curve
+pub fn session_context(project_id: String, session_id: String, points: Vec<ContextPoint>, compaction_events: Vec<Compaction>) -> SessionContext {
+    let peak = points.iter().max_by_key(|p| p.context_tokens);
+    let context_limit = points.iter().map(|p| p.context_limit).max().unwrap_or(0);
+
+    SessionContext {
+        project_id,
+        session_id,
+        context_limit,
+        peak_tokens: peak.map(|p| p.context_tokens).unwrap_or(0),
+        peak_utilization: points.iter().map(|p| p.utilization).fold(0.0, f64::max),
+        compactions: compaction_events.len(),
+        auto_compactions: compaction_events.iter().filter(|c| c.trigger.as_deref() == Some("auto")).count(),
+        near_limit: points
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p.utilization >= NEAR_LIMIT)
+            .map(|(i, _)| i)
+            .collect(),
+        points,
+        compaction_events,
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Context size per turn, compactions and near-limit turns of a session of a transcript source (default: Claude Code)
+#[tauri::command]
+pub async fn get_session_context(
+    project_id: String,
+    session_id: String,
+    source: Option<String>,
+) -> Result<SessionContext, String> {
+    tokio::task::spawn_blocking(move || {
+        let (points, compactions) = if source.as_deref() == Some(CODEX_SOURCE) {
+            codex_context(&read_json_lines(&find_codex_session_file(&project_id, &session_id)?)?)
+        } else {
+            let path = claude_project_dir(&project_id)?.join(format!("{}.jsonl", session_id));
+            claude_context(&read_json_lines(&path)?)
+        };
+        Ok(session_context(project_id, session_id, points, compactions))
+    })
+    .await
+    .map_err(|e| format!("Context task failed: {}", e))?
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn assistant(id: &str, minute: u32, input: u64, cache_read: u64) -> Value {
+        json!({
+            "type": "assistant",
+            "uuid": format!("u-{}-{}", id, minute),
+            "timestamp": format!("2025-06-01T10:{:02}:00Z", minute),
+            "message": {
+                "id": id,
+                "model": "claude-sonnet-4-5-20250929",
+                "usage": {"input_tokens": input, "cache_read_input_tokens": cache_read, "cache_creation_input_tokens": 1000, "output_tokens": 500}
+            }
+        })
+    }
+
+    #[test]
+    fn test_claude_context_curve_and_compaction() {
+        let lines = vec![
+            assistant("m1", 0, 10, 100_000),
+            assistant("m1", 0, 10, 100_000),
+            assistant("m2", 5, 10, 169_000),
+            json!({"type": "system", "subtype": "compact_boundary", "timestamp": "2025-06-01T10:06:00Z",
+                   "compactMetadata": {"trigger": "auto", "preTokens": 170_500}}),
+            json!({"type": "user", "isCompactSummary": true, "timestamp": "2025-06-01T10:06:01Z",
+                   "message": {"role": "user", "content": "This session is being continued"}}),
+            assistant("m3", 7, 10, 20_000),
+        ];
+
+        let (points, compactions) = claude_context(&lines);
+        assert_eq!(points.len(), 3);
+        assert_eq!((points[1].context_tokens, points[1].context_limit), (170_010, 200_000));
+
+        assert_eq!(compactions.len(), 1);
+        assert_eq!((compactions[0].trigger.as_deref(), compactions[0].next_point), (Some("auto"), Some(2)));
+        assert_eq!((compactions[0].pre_tokens, compactions[0].post_tokens), (Some(170_500), Some(21_010)));
+
+        let context = session_context("p".to_string(), "s".to_string(), points, compactions);
+        assert_eq!((context.auto_compactions, context.near_limit.clone()), (1, vec![1]));
+        assert_eq!(context.peak_tokens, 170_010);
+
+        // Blocks of a response split by another response still count once
+        let (points, _) = claude_context(&[assistant("m4", 8, 10, 1000), assistant("m5", 9, 10, 2000), assistant("m4", 8, 10, 1000)]);
+        assert_eq!(points.len(), 2);
+    }
+
+    #[test]
+    fn test_resumed_session_titles_are_not_compactions() {
+        // A resumed session starts with the titles of the session it continues
+        let lines = vec![
+            json!({"type": "summary", "summary": "Fix login redirect", "leafUuid": "earlier-session-leaf"}),
+            json!({"type": "summary", "summary": "Add OAuth provider", "leafUuid": "older-session-leaf"}),
+            json!({"type": "user", "uuid": "u1", "timestamp": "2025-06-01T10:00:00Z",
+                   "message": {"role": "user", "content": "continue where we left off"}}),
+            assistant("m1", 1, 10, 90_000),
+            assistant("m2", 2, 10, 95_000),
+        ];
+
+        let (points, compactions) = claude_context(&lines);
+        assert_eq!(points.len(), 2);
+        assert!(compactions.is_empty());
+    }
+
+    #[test]
+    fn test_context_limit() {
+        assert_eq!(context_limit("claude-opus-4-1", 0), STANDARD_CONTEXT);
+        assert_eq!(context_limit("claude-sonnet-4-5[1m]", 0), LONG_CONTEXT);
+        assert_eq!(context_limit("claude-sonnet-4-5", 450_000), LONG_CONTEXT);
+        assert_eq!(context_limit("gpt-5-codex", 0), 0);
+    }
+}
@@ -0,0 +1,488 @@
+/**
+ * Conversation tree reconstruction
+ *
+ * Claude Code links every log entry to its predecessor through `parentUuid`.
+ * Rewinds and edited prompts start a new child of an earlier message, and
+ * legacy subagent runs are written as sidechains, so a session file is really
+ * a tree rather than the flat list the transcript view shows.
+ *
+ * This module rebuilds that tree over user and assistant messages (progress,
+ * hook and system entries hang off their nearest message ancestor), picks the
+ * active leaf (the most recently written non-sidechain leaf) and reports every
+ * other leaf as an abandoned branch or a sidechain, each with its own cost.
+ */
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use super::sources::find_source;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+const PREVIEW_LENGTH: usize = 120;
+
+pub const BRANCH_ACTIVE: &str = "active";
+pub const BRANCH_ABANDONED: &str = "abandoned";
+pub const BRANCH_SIDECHAIN: &str = "sidechain";
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// The parts of a log entry the tree needs, in file order
+#[derive(Debug, Clone, Default)]
+pub struct TreeEntry {
+    pub uuid: Option<String>,
+    pub parent_uuid: Option<String>,
+    /// Parent across a compaction boundary, where `parent_uuid` is null
+    pub logical_parent_uuid: Option<String>,
+    pub entry_type: String,
+    pub timestamp: String,
+    pub is_sidechain: bool,
+    /// User entry that only carries tool results
+    pub is_tool_result: bool,
+    pub cost_usd: f64,
+    pub tokens: u64,
+    pub preview: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TreeNode {
+    pub uuid: String,
+    /// Nearest message ancestor, skipping progress/system entries
+    pub parent_uuid: Option<String>,
+    #[serde(rename = "type")]
+    pub node_type: String,
+    pub timestamp: String,
+    /// Leaf uuid of the branch this node belongs to
+    pub branch_id: String,
+    pub on_active_path: bool,
+    pub is_sidechain: bool,
+    pub child_count: usize,
+    pub cost_usd: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preview: Option<String>,
+}
+
+/// One branch: the nodes from just after its fork point down to its leaf
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationBranch {
+    /// Uuid of the branch's leaf; pass it as `branch` to load this branch
+    pub id: String,
+    /// "active", "abandoned" or "sidechain"
+    pub kind: String,
+    /// Node the branch splits off from (None when it starts at a root)
+    pub fork_point: Option<String>,
+    pub uuids: Vec<String>,
+    pub message_count: usize,
+    pub cost_usd: f64,
+    pub tokens: u64,
+    pub started_at: String,
+    pub ended_at: String,
+    /// First user prompt of the branch, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_prompt: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationTree {
+    pub session_id: String,
+    pub active_leaf: Option<String>,
+    pub nodes: Vec<TreeNode>,
+    /// Active branch first, then other branches in the order they were written
+    pub branches: Vec<ConversationBranch>,
+    /// Cost of the path from the root to the active leaf
+    pub active_cost_usd: f64,
+    pub abandoned_cost_usd: f64,
+    pub sidechain_cost_usd: f64,
+}
+
+/// Message graph over entries; indices refer to `entries`
+struct Graph<'a> {
+    entries: &'a [TreeEntry],
+    /// Entry index of every uuid
+    by_uuid: HashMap<&'a str, usize>,
+    /// Message entries in file order
+    messages: Vec<usize>,
+    /// Nearest message ancestor of each message entry
+    parent: HashMap<usize, usize>,
+    children: HashMap<usize, Vec<usize>>,
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn is_message(entry: &TreeEntry) -> bool {
+    entry.uuid.is_some() && (entry.entry_type == "user" || entry.entry_type == "assistant")
+}
+
+fn direct_parent(entry: &TreeEntry) -> Option<&str> {
+    entry.parent_uuid.as_deref().or(entry.logical_parent_uuid.as_deref())
+}
+
+impl<'a> Graph<'a> {
+    fn new(entries: &'a [TreeEntry]) -> Graph<'a> {
+        let by_uuid: HashMap<&str, usize> = entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, e)| e.uuid.as_deref().map(|u| (u, i)))
+            .collect();
+
+        let mut graph = Graph {
+            entries,
+            by_uuid,
+            messages: Vec::new(),
+            parent: HashMap::new(),
+            children: HashMap::new(),
+        };
+
+        for (i, entry) in entries.iter().enumerate() {
+            if !is_message(entry) {
+                continue;
+            }
+            graph.messages.push(i);
+            if let Some(p) = graph.message_ancestor(direct_parent(entry)) {
+                graph.parent.insert(i, p);
+                graph.children.entry(p).or_default().push(i);
+            }
+        }
+
+        graph
+    }
+
+    /// Walk up from `uuid` (inclusive) to the first message entry
+    fn message_ancestor(&self, uuid: Option<&str>) -> Option<usize> {
+        let mut current = uuid;
+        let mut steps = 0;
+        while let Some(u) = current {
+            let &i = self.by_uuid.get(u)?;
+            if is_message(&self.entries[i]) {
+                return Some(i);
+            }
+            current = direct_parent(&self.entries[i]);
+            steps += 1;
+            if steps > self.entries.len() {
+                return None; // cycle
+            }
+        }
+        None
+    }
+
+    /// Message that owns an entry: itself if it is a message, else its nearest message ancestor
+    fn owner(&self, i: usize) -> Option<usize> {
+        let entry = &self.entries[i];
+        if is_message(entry) {
+            Some(i)
+        } else {
+            self.message_ancestor(direct_parent(entry))
+        }
+    }
+
+    /// Tool-result leaf next to siblings, as written for parallel tool calls; it
+    /// belongs with its parent rather than forming a branch of its own
+    fn is_attached(&self, i: usize) -> bool {
+        self.entries[i].is_tool_result
+            && !self.children.contains_key(&i)
+            && self
+                .parent
+                .get(&i)
+                .and_then(|p| self.children.get(p))
+                .is_some_and(|siblings| siblings.len() > 1)
+    }
+
+    fn leaves(&self) -> Vec<usize> {
+        self.messages
+            .iter()
+            .copied()
+            .filter(|&i| !self.children.contains_key(&i) && !self.is_attached(i))
+            .collect()
+    }
+
+    /// Messages on the path to `leaf` plus the tool results attached to them
+    fn path_with_attached(&self, leaf: usize) -> HashSet<usize> {
+        let path = self.path_to(leaf);
+        let attached: Vec<usize> = path
+            .iter()
+            .filter_map(|p| self.children.get(p))
+            .flatten()
+            .copied()
+            .filter(|&c| self.is_attached(c))
+            .collect();
+        path.into_iter().chain(attached).collect()
+    }
+
+    fn active_leaf(&self) -> Option<usize> {
+        self.leaves().into_iter().filter(|&i| !self.entries[i].is_sidechain).max()
+    }
+
+    /// Messages from the root down to `leaf`
+    fn path_to(&self, leaf: usize) -> Vec<usize> {
+        let mut path = vec![leaf];
+        let mut current = leaf;
+        while let Some(&p) = self.parent.get(&current) {
+            if path.len() > self.messages.len() {
+                break; // cycle
+            }
+            path.push(p);
+            current = p;
+        }
+        path.reverse();
+        path
+    }
+}
+
+fn truncate(text: &str) -> String {
+    let trimmed = text.trim();
+    match trimmed.char_indices().nth(PREVIEW_LENGTH) {
+        Some((i, _)) => format!("{}…", &trimmed[..i]),
+        None => trimmed.to_string(),
+    }
+}
+
+/// Cost and tokens of every message including the non-message entries it owns
+fn owned_totals(graph: &Graph) -> HashMap<usize, (f64, u64)> {
+    let mut totals: HashMap<usize, (f64, u64)> = HashMap::new();
+    for (i, entry) in graph.entries.iter().enumerate() {
+        if entry.cost_usd == 0.0 && entry.tokens == 0 {
+            continue;
+        }
+        if let Some(owner) = graph.owner(i) {
+            let t = totals.entry(owner).or_insert((0.0, 0));
+            t.0 += entry.cost_usd;
+            t.1 += entry.tokens;
+        }
+    }
+    totals
+}
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+/// Rebuild the message tree of a session from its entries (in file order)
+pub fn build_tree(session_id: &str, entries: &[TreeEntry]) -> ConversationTree {
+    let graph = Graph::new(entries);
+    let totals = owned_totals(&graph);
+    let active_leaf = graph.active_leaf();
+
+    let mut branch_of: HashMap<usize, String> = HashMap::new();
+    let mut active_path: HashSet<usize> = HashSet::new();
+    let mut branches: Vec<ConversationBranch> = Vec::new();
+
+    let make_branch = |kind: &str, leaf: usize, segment: Vec<usize>, fork: Option<usize>| {
+        let (cost_usd, tokens) = segment
+            .iter()
+            .filter_map(|i| totals.get(i))
+            .fold((0.0, 0), |acc, t| (acc.0 + t.0, acc.1 + t.1));
+        let first_prompt = segment
+            .iter()
+            .map(|&i| &entries[i])
+            .find(|e| e.entry_type == "user" && e.preview.is_some())
+            .and_then(|e| e.preview.clone());
+
+        ConversationBranch {
+            id: entries[leaf].uuid.clone().unwrap_or_default(),
+            kind: kind.to_string(),
+            fork_point: fork.and_then(|f| entries[f].uuid.clone()),
+            uuids: segment.iter().filter_map(|&i| entries[i].uuid.clone()).collect(),
+            message_count: segment.len(),
+            cost_usd,
+            tokens,
+            started_at: segment.first().map(|&i| entries[i].timestamp.clone()).unwrap_or_default(),
+            ended_at: segment.last().map(|&i| entries[i].timestamp.clone()).unwrap_or_default(),
+            first_prompt,
+        }
+    };
+
+    if let Some(leaf) = active_leaf {
+        let path = graph.path_to(leaf);
+        let id = entries[leaf].uuid.clone().unwrap_or_default();
+        for &i in &path {
+            branch_of.insert(i, id.clone());
+            active_path.insert(i);
+        }
+        branches.push(make_branch(BRANCH_ACTIVE, leaf, path, None));
+    }
+
+    for leaf in graph.leaves() {
+        if Some(leaf) == active_leaf {
+            continue;
+        }
+        let id = entries[leaf].uuid.clone().unwrap_or_default();
+        let mut segment = Vec::new();
+        let mut fork = None;
+        let mut current = Some(leaf);
+        while let Some(i) = current {
+            if branch_of.contains_key(&i) {
+                fork = Some(i);
+                break;
+            }
+            branch_of.insert(i, id.clone());
+            segment.push(i);
+            current = graph.parent.get(&i).copied();
+        }
+        segment.reverse();
+
+        let kind = if entries[leaf].is_sidechain { BRANCH_SIDECHAIN } else { BRANCH_ABANDONED };
+        branches.push(make_branch(kind, leaf, segment, fork));
+    }
+
+    let nodes = graph
+        .messages
+        .iter()
+        .map(|&i| {
+            let entry = &entries[i];
+            TreeNode {
+                uuid: entry.uuid.clone().unwrap_or_default(),
+                parent_uuid: graph.parent.get(&i).and_then(|&p| entries[p].uuid.clone()),
+                node_type: entry.entry_type.clone(),
+                timestamp: entry.timestamp.clone(),
+                branch_id: branch_of
+                    .get(&i)
+                    .or_else(|| graph.parent.get(&i).and_then(|p| branch_of.get(p)))
+                    .cloned()
+                    .unwrap_or_default(),
+                on_active_path: active_path.contains(&i)
+                    || (graph.is_attached(i) && graph.parent.get(&i).is_some_and(|p| active_path.contains(p))),
+                is_sidechain: entry.is_sidechain,
+                child_count: graph.children.get(&i).map(|c| c.len()).unwrap_or(0),
+                cost_usd: totals.get(&i).map(|t| t.0).unwrap_or(0.0),
+                preview: entry.preview.as_deref().map(truncate),
+            }
+        })
+        .collect();
+
+    let cost_of = |kind: &str| branches.iter().filter(|b| b.kind == kind).map(|b| b.cost_usd).sum::<f64>();
+
+    ConversationTree {
+        session_id: session_id.to_string(),
+        active_leaf: active_leaf.and_then(|i| entries[i].uuid.clone()),
+        active_cost_usd: cost_of(BRANCH_ACTIVE),
+        abandoned_cost_usd: cost_of(BRANCH_ABANDONED),
+        sidechain_cost_usd: cost_of(BRANCH_SIDECHAIN),
+        nodes,
+        branches,
+    }
+}
+
+/// Uuids of every entry on the path from the root to `leaf`, including the
+/// progress/system entries owned by messages on that path
+pub fn branch_members(entries: &[TreeEntry], leaf: &str) -> Result<HashSet<String>, String> {
+    let graph = Graph::new(entries);
+    let &leaf_index = graph
+        .by_uuid
+        .get(leaf)
+        .filter(|&&i| is_message(&entries[i]))
+        .ok_or_else(|| format!("Message not found in session: {}", leaf))?;
+    let path = graph.path_with_attached(leaf_index);
+
+    Ok(entries
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| graph.owner(*i).is_some_and(|o| path.contains(&o)))
+        .filter_map(|(_, e)| e.uuid.clone())
+        .collect())
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Rebuild the message tree of a session with its active leaf and abandoned branches
+#[tauri::command]
+pub async fn get_conversation_tree(
+    project_id: String,
+    session_id: String,
+    source: Option<String>,
+) -> Result<ConversationTree, String> {
+    let entries = find_source(source.as_deref())?
+        .tree_entries(project_id, session_id.clone())
+        .await?;
+    Ok(build_tree(&session_id, &entries))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(uuid: &str, parent: Option<&str>, entry_type: &str, cost: f64) -> TreeEntry {
+        TreeEntry {
+            uuid: Some(uuid.to_string()),
+            parent_uuid: parent.map(String::from),
+            entry_type: entry_type.to_string(),
+            timestamp: format!("2025-11-01T10:00:{}Z", uuid),
+            cost_usd: cost,
+            ..Default::default()
+        }
+    }
+
+    /// u1 -> a1 -> u2 -> a2, then the prompt u2 is edited: a1 -> u3 -> p1 (progress) -> a3
+    fn rewound_session() -> Vec<TreeEntry> {
+        vec![
+            entry("u1", None, "user", 0.0),
+            entry("a1", Some("u1"), "assistant", 1.0),
+            entry("u2", Some("a1"), "user", 0.0),
+            entry("a2", Some("u2"), "assistant", 2.0),
+            entry("u3", Some("a1"), "user", 0.0),
+            entry("p1", Some("u3"), "progress", 0.0),
+            entry("a3", Some("p1"), "assistant", 4.0),
+        ]
+    }
+
+    #[test]
+    fn test_active_leaf_and_abandoned_branch() {
+        let tree = build_tree("s", &rewound_session());
+        assert_eq!(tree.active_leaf.as_deref(), Some("a3"));
+        assert_eq!(tree.branches.len(), 2);
+
+        let abandoned = &tree.branches[1];
+        assert_eq!(abandoned.kind, BRANCH_ABANDONED);
+        assert_eq!(abandoned.fork_point.as_deref(), Some("a1"));
+        assert_eq!(abandoned.uuids, vec!["u2", "a2"]);
+        assert_eq!(tree.abandoned_cost_usd, 2.0);
+        assert_eq!(tree.active_cost_usd, 5.0);
+
+        let a3 = tree.nodes.iter().find(|n| n.uuid == "a3").unwrap();
+        assert_eq!(a3.parent_uuid.as_deref(), Some("u3"));
+    }
+
+    #[test]
+    fn test_parallel_tool_results_are_not_branches() {
+        let mut r1 = entry("r1", Some("a1"), "user", 0.0);
+        r1.is_tool_result = true;
+        let mut r2 = entry("r2", Some("a2"), "user", 0.0);
+        r2.is_tool_result = true;
+        let entries = vec![
+            entry("u1", None, "user", 0.0),
+            entry("a1", Some("u1"), "assistant", 1.0),
+            entry("a2", Some("a1"), "assistant", 1.0),
+            r1,
+            r2,
+            entry("a3", Some("r2"), "assistant", 1.0),
+        ];
+
+        let tree = build_tree("s", &entries);
+        assert_eq!(tree.branches.len(), 1);
+        assert!(tree.nodes.iter().all(|n| n.on_active_path));
+        assert!(branch_members(&entries, "a3").unwrap().contains("r1"));
+    }
+
+    #[test]
+    fn test_branch_members_include_owned_entries() {
+        let members = branch_members(&rewound_session(), "a3").unwrap();
+        let mut members: Vec<String> = members.into_iter().collect();
+        members.sort();
+        assert_eq!(members, vec!["a1", "a3", "p1", "u1", "u3"]);
+        assert!(branch_members(&rewound_session(), "p1").is_err());
+    }
+}
@@ -0,0 +1,304 @@
+/**
+ * Logical conversations across session files
+ *
+ * `claude --continue` and `--resume` start a new session file for what the
+ * user sees as one conversation. The files are linked by `parentUuid` chains
+ * that point into the earlier file, by entries copied over from it, and by
+ * `summary` entries whose `leafUuid` names a message in the earlier file.
+ *
+ * This module follows those links to group a project's session files into
+ * logical conversations, and attributes every summary to the file holding its
+ * leaf so a resumed session is not titled with its predecessor's summary.
+ */
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use super::sources::{find_source, CODEX_SOURCE};
+use super::transcript_index::IndexedSession;
+use super::transcripts::{list_claude_conversations, load_claude_conversation, paginate_messages, Message, Session, SessionStats};
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogicalConversation {
+    /// Id of the first session file of the conversation
+    pub id: String,
+    pub project_id: String,
+    /// Session files in chronological order
+    pub session_ids: Vec<String>,
+    pub summary: String,
+    pub last_modified: i64,
+    pub message_count: usize,
+    /// Stats over the merged timeline
+    pub stats: Option<SessionStats>,
+    pub root: String,
+    pub host: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationDetails {
+    pub conversation: LogicalConversation,
+    pub messages: Vec<Message>,
+    pub total_pages: usize,
+    pub current_page: usize,
+    pub total_messages: usize,
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn to_conversation(session: Session, session_ids: Vec<String>) -> LogicalConversation {
+    LogicalConversation {
+        id: session.id,
+        project_id: session.project_id,
+        session_ids,
+        summary: session.summary,
+        last_modified: session.last_modified,
+        message_count: session.message_count,
+        stats: session.stats,
+        root: session.root,
+        host: session.host,
+    }
+}
+
+fn find(parent: &mut [usize], i: usize) -> usize {
+    let mut root = i;
+    while parent[root] != root {
+        root = parent[root];
+    }
+    let mut current = i;
+    while parent[current] != root {
+        let next = parent[current];
+        parent[current] = root;
+        current = next;
+    }
+    root
+}
+
+fn union(parent: &mut [usize], a: usize, b: usize) {
+    let (ra, rb) = (find(parent, a), find(parent, b));
+    if ra != rb {
+        parent[ra.max(rb)] = ra.min(rb);
+    }
+}
+
+fn start_timestamp(file: &IndexedSession) -> &str {
+    file.entries.iter().map(|e| e.timestamp.as_str()).filter(|t| !t.is_empty()).min().unwrap_or("")
+}
+
+/// Files in chronological order, plus the earliest file holding each uuid
+fn chronological<'a>(files: &[&'a IndexedSession]) -> (Vec<usize>, HashMap<&'a str, usize>) {
+    let mut order: Vec<usize> = (0..files.len()).collect();
+    order.sort_by(|&a, &b| start_timestamp(files[a]).cmp(start_timestamp(files[b])));
+
+    let mut owner: HashMap<&str, usize> = HashMap::new();
+    for &i in &order {
+        for uuid in files[i].entries.iter().filter_map(|e| e.uuid.as_deref()) {
+            owner.entry(uuid).or_insert(i);
+        }
+    }
+
+    (order, owner)
+}
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+/// Group session files of one project into conversations. Each group lists
+/// indices into `files` in chronological order; groups are ordered by their
+/// most recent modification, newest first.
+pub fn group_sessions(files: &[&IndexedSession]) -> Vec<Vec<usize>> {
+    let (order, owner) = chronological(files);
+    let mut parent: Vec<usize> = (0..files.len()).collect();
+
+    for &i in &order {
+        let file = files[i];
+        let own: HashSet<&str> = file.entries.iter().filter_map(|e| e.uuid.as_deref()).collect();
+
+        for entry in &file.entries {
+            // Entry copied from an earlier file
+            if let Some(&j) = entry.uuid.as_deref().and_then(|u| owner.get(u)) {
+                if j != i {
+                    union(&mut parent, i, j);
+                }
+            }
+            // Parent chain continuing an earlier file
+            if let Some(parent_uuid) = entry.parent_uuid.as_deref().filter(|p| !own.contains(p)) {
+                if let Some(&j) = owner.get(parent_uuid) {
+                    union(&mut parent, i, j);
+                }
+            }
+        }
+
+        // Summary of an earlier file written at the top of the resumed one
+        for leaf in file.summaries.iter().filter_map(|s| s.leaf_uuid.as_deref()) {
+            if let Some(&j) = owner.get(leaf) {
+                if j != i {
+                    union(&mut parent, i, j);
+                }
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for &i in &order {
+        let root = find(&mut parent, i);
+        groups.entry(root).or_default().push(i);
+    }
+
+    let mut groups: Vec<Vec<usize>> = groups.into_values().collect();
+    groups.sort_by_key(|g| std::cmp::Reverse(g.iter().map(|&i| files[i].modified_ms).max().unwrap_or(0)));
+    groups
+}
+
+/// Summary texts per file (indices match `files`): each summary goes to the file
+/// holding its leaf message, or stays in its own file when the leaf is unknown
+pub fn attribute_summaries(files: &[&IndexedSession]) -> Vec<Vec<String>> {
+    let (_, owner) = chronological(files);
+    let mut result: Vec<Vec<String>> = vec![Vec::new(); files.len()];
+
+    for (i, file) in files.iter().enumerate() {
+        for summary in &file.summaries {
+            let target = match summary.leaf_uuid.as_deref() {
+                Some(leaf) if file.entries.iter().any(|e| e.uuid.as_deref() == Some(leaf)) => i,
+                Some(leaf) => owner.get(leaf).copied().unwrap_or(i),
+                None => i,
+            };
+            result[target].push(summary.summary.clone());
+        }
+    }
+
+    result
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// List the logical conversations of a project, merging resumed and continued sessions
+#[tauri::command]
+pub async fn get_project_conversations(project_id: String, source: Option<String>) -> Result<Vec<LogicalConversation>, String> {
+    let source = find_source(source.as_deref())?;
+
+    // Codex rollouts are never split across files
+    if source.id() == CODEX_SOURCE {
+        let sessions = source.project_sessions(project_id).await?;
+        return Ok(sessions
+            .into_iter()
+            .map(|s| {
+                let ids = vec![s.id.clone()];
+                to_conversation(s, ids)
+            })
+            .collect());
+    }
+
+    Ok(list_claude_conversations(project_id)
+        .await?
+        .into_iter()
+        .map(|(session, ids)| to_conversation(session, ids))
+        .collect())
+}
+
+/// Merged timeline of the conversation containing `session_id`, with combined stats
+#[tauri::command]
+pub async fn get_conversation_details(
+    project_id: String,
+    session_id: String,
+    page: Option<usize>,
+) -> Result<ConversationDetails, String> {
+    let (session, session_ids) = list_claude_conversations(project_id.clone())
+        .await?
+        .into_iter()
+        .find(|(_, ids)| ids.contains(&session_id))
+        .ok_or_else(|| "Session not found".to_string())?;
+
+    let (messages, stats) = load_claude_conversation(project_id, &session_ids).await?;
+    let page = paginate_messages(messages, page, true)?;
+
+    let mut conversation = to_conversation(session, session_ids);
+    conversation.message_count = page.total_messages;
+    conversation.stats = Some(stats);
+
+    Ok(ConversationDetails {
+        conversation,
+        messages: page.messages,
+        total_pages: page.total_pages,
+        current_page: page.current_page,
+        total_messages: page.total_messages,
+    })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::transcript_index::{IndexedEntry, IndexedSummary};
+
+    fn entry(uuid: &str, parent: Option<&str>, timestamp: &str) -> IndexedEntry {
+        IndexedEntry {
+            offset: 0,
+            uuid: Some(uuid.to_string()),
+            parent_uuid: parent.map(String::from),
+            entry_type: "user".to_string(),
+            timestamp: timestamp.to_string(),
+            has_message: true,
+            message_id: None,
+            model: None,
+            usage: None,
+            cost_usd: None,
+            logged_usage: None,
+            logged_cost_usd: None,
+            duration_ms: None,
+            text: None,
+            tool_uses: Vec::new(),
+            tool_results: Vec::new(),
+        }
+    }
+
+    fn file(id: &str, entries: Vec<IndexedEntry>, summaries: Vec<(&str, &str)>) -> IndexedSession {
+        IndexedSession {
+            root: "local".to_string(),
+            host: "host".to_string(),
+            project_id: "p".to_string(),
+            session_id: id.to_string(),
+            file_path: format!("/p/{}.jsonl", id),
+            size: 0,
+            modified_ms: 0,
+            offset: 0,
+            prefix_hash: 0,
+            cwd: None,
+            git_branch: None,
+            summaries: summaries
+                .into_iter()
+                .map(|(text, leaf)| IndexedSummary { summary: text.to_string(), leaf_uuid: Some(leaf.to_string()) })
+                .collect(),
+            entries,
+        }
+    }
+
+    #[test]
+    fn test_resumed_sessions_are_grouped() {
+        let a = file("a", vec![entry("a1", None, "2025-11-01T10:00:00Z"), entry("a2", Some("a1"), "2025-11-01T10:01:00Z")], vec![]);
+        let b = file("b", vec![entry("b1", Some("a2"), "2025-11-02T09:00:00Z")], vec![("Fix login flow", "a2")]);
+        let c = file("c", vec![entry("c1", None, "2025-11-03T09:00:00Z")], vec![]);
+        let files = vec![&c, &b, &a];
+
+        let mut groups = group_sessions(&files);
+        groups.sort_by_key(|g| g.len());
+        assert_eq!(groups, vec![vec![0], vec![2, 1]]);
+
+        let summaries = attribute_summaries(&files);
+        assert_eq!(summaries[2], vec!["Fix login flow".to_string()]);
+        assert!(summaries[1].is_empty());
+    }
+}
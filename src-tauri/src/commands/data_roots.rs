@@ -0,0 +1,336 @@
+/**
+ * Data roots
+ *
+ * A data root is one copy of agent data: a Claude config directory (the one
+ * holding projects/ and history.jsonl) plus a Codex home (holding sessions/).
+ * The local root always exists and honours CLAUDE_CONFIG_DIR and CODEX_HOME.
+ * Additional roots, e.g. a synced copy of a teammate's or a CI box's folders,
+ * or an imported session archive, are stored in ~/.claude-dashboard/data-roots.json.
+ *
+ * Ids of projects from non-local roots are qualified as "<root>:<id>" so they
+ * stay unique across roots; local ids are unchanged. A prefix only counts as a
+ * root when it names a known root, so local ids containing ':' (e.g. Windows
+ * drive paths) resolve to the local root.
+ */
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+pub const LOCAL_ROOT: &str = "local";
+
+/// Separator between root id and project id in qualified ids
+const ROOT_SEPARATOR: char = ':';
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataRoot {
+    pub id: String,
+    pub name: String,
+    /// Machine the data was recorded on
+    pub host: String,
+    /// Claude config directory (contains projects/ and history.jsonl)
+    #[serde(rename = "claudeDir")]
+    pub claude_dir: String,
+    /// Codex home directory (contains sessions/), if this root has Codex data
+    #[serde(rename = "codexDir", default, skip_serializing_if = "Option::is_none")]
+    pub codex_dir: Option<String>,
+    /// Imported archives are mounted read-only; commands must not modify them
+    #[serde(rename = "readOnly", default)]
+    pub read_only: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct DataRootsFile {
+    #[serde(default)]
+    roots: Vec<DataRoot>,
+}
+
+impl DataRoot {
+    pub fn is_local(&self) -> bool {
+        self.id == LOCAL_ROOT
+    }
+
+    pub fn claude_projects_dir(&self) -> PathBuf {
+        PathBuf::from(&self.claude_dir).join("projects")
+    }
+
+    pub fn claude_plans_dir(&self) -> PathBuf {
+        PathBuf::from(&self.claude_dir).join("plans")
+    }
+
+    pub fn claude_history_file(&self) -> PathBuf {
+        PathBuf::from(&self.claude_dir).join("history.jsonl")
+    }
+
+    /// Backups of files edited in each session: file-history/<session-id>/<backup-file-name>
+    pub fn claude_file_history_dir(&self) -> PathBuf {
+        PathBuf::from(&self.claude_dir).join("file-history")
+    }
+
+    pub fn stats_cache_file(&self) -> PathBuf {
+        PathBuf::from(&self.claude_dir).join("stats-cache.json")
+    }
+
+    pub fn codex_sessions_dir(&self) -> Option<PathBuf> {
+        self.codex_dir.as_ref().map(|dir| PathBuf::from(dir).join("sessions"))
+    }
+
+    /// Qualify a project id from this root so it is unique across roots
+    pub fn qualify_id(&self, id: &str) -> String {
+        if self.is_local() {
+            id.to_string()
+        } else {
+            format!("{}{}{}", self.id, ROOT_SEPARATOR, id)
+        }
+    }
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn get_roots_config_path() -> Result<PathBuf, String> {
+    let home_dir = dirs::home_dir().ok_or_else(|| "Could not determine home directory".to_string())?;
+    Ok(home_dir.join(".claude-dashboard").join("data-roots.json"))
+}
+
+fn env_dir(name: &str) -> Option<PathBuf> {
+    std::env::var_os(name).filter(|v| !v.is_empty()).map(PathBuf::from)
+}
+
+fn local_host() -> String {
+    whoami::fallible::hostname().unwrap_or_else(|_| "localhost".to_string())
+}
+
+fn read_configured_roots() -> Vec<DataRoot> {
+    let Ok(path) = get_roots_config_path() else { return Vec::new() };
+    let Ok(content) = std::fs::read_to_string(&path) else { return Vec::new() };
+
+    match serde_json::from_str::<DataRootsFile>(&content) {
+        Ok(file) => file.roots.into_iter().filter(|r| r.id != LOCAL_ROOT).collect(),
+        Err(e) => {
+            log::warn!("Ignoring invalid data roots in {}: {}", path.display(), e);
+            Vec::new()
+        }
+    }
+}
+
+async fn write_configured_roots(roots: Vec<DataRoot>) -> Result<(), String> {
+    let path = get_roots_config_path()?;
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+
+    let content = serde_json::to_string_pretty(&DataRootsFile { roots })
+        .map_err(|e| format!("Failed to serialize data roots: {}", e))?;
+    tokio::fs::write(&path, content)
+        .await
+        .map_err(|e| format!("Failed to write data roots: {}", e))
+}
+
+/// Lowercase slug of a root name, usable inside qualified ids
+pub(crate) fn slugify(name: &str) -> String {
+    let slug: String = name
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let slug = slug.split('-').filter(|s| !s.is_empty()).collect::<Vec<_>>().join("-");
+    if slug.is_empty() {
+        "root".to_string()
+    } else {
+        slug
+    }
+}
+
+/// Root and inner id of a qualified id, if its prefix names one of `roots`
+fn split_qualified_id<'a, 'r>(qualified: &'a str, roots: &'r [DataRoot]) -> Option<(&'r DataRoot, &'a str)> {
+    let (root_id, id) = qualified.split_once(ROOT_SEPARATOR)?;
+    roots.iter().find(|r| r.id == root_id).map(|root| (root, id))
+}
+
+/// `base`, or `base-2`, `base-3`, ... if it is taken by the local root or one of `roots`
+fn unique_root_id(base: &str, roots: &[DataRoot]) -> String {
+    let mut id = base.to_string();
+    let mut n = 2;
+    while id == LOCAL_ROOT || roots.iter().any(|r| r.id == id) {
+        id = format!("{}-{}", base, n);
+        n += 1;
+    }
+    id
+}
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+/// The data root of this machine: $CLAUDE_CONFIG_DIR (or ~/.claude) and $CODEX_HOME (or ~/.codex)
+pub fn local_root() -> Result<DataRoot, String> {
+    let home_dir = dirs::home_dir().ok_or_else(|| "Could not determine home directory".to_string())?;
+    let claude_dir = env_dir("CLAUDE_CONFIG_DIR").unwrap_or_else(|| home_dir.join(".claude"));
+    let codex_dir = env_dir("CODEX_HOME").unwrap_or_else(|| home_dir.join(".codex"));
+
+    Ok(DataRoot {
+        id: LOCAL_ROOT.to_string(),
+        name: "This machine".to_string(),
+        host: local_host(),
+        claude_dir: claude_dir.to_string_lossy().to_string(),
+        codex_dir: Some(codex_dir.to_string_lossy().to_string()),
+        read_only: false,
+    })
+}
+
+/// Every data root, local first
+pub fn data_roots() -> Vec<DataRoot> {
+    let mut roots: Vec<DataRoot> = local_root().into_iter().collect();
+    roots.extend(read_configured_roots());
+    roots
+}
+
+/// Split a qualified id into its root and the id within that root
+pub fn resolve_qualified_id(qualified: &str) -> Result<(DataRoot, String), String> {
+    let roots = data_roots();
+    match split_qualified_id(qualified, &roots) {
+        Some((root, id)) => Ok((root.clone(), id.to_string())),
+        None => Ok((local_root()?, qualified.to_string())),
+    }
+}
+
+/// Persist a new root, making its id unique among configured roots
+pub(crate) async fn register_data_root(mut root: DataRoot) -> Result<DataRoot, String> {
+    let mut roots = read_configured_roots();
+    root.id = unique_root_id(&root.id, &roots);
+
+    roots.push(root.clone());
+    write_configured_roots(roots).await?;
+
+    Ok(root)
+}
+
+/// Fail if the root holding this project must not be modified
+pub fn ensure_writable(project_id: &str) -> Result<(), String> {
+    let (root, _) = resolve_qualified_id(project_id)?;
+    if root.read_only {
+        return Err(format!("Data root {} is read-only", root.name));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// List all data roots, local first
+#[tauri::command]
+pub async fn get_data_roots() -> Result<Vec<DataRoot>, String> {
+    Ok(data_roots())
+}
+
+/// Register another Claude/Codex data folder (e.g. a synced copy from another machine)
+#[tauri::command]
+pub async fn add_data_root(
+    name: String,
+    claude_dir: String,
+    codex_dir: Option<String>,
+    host: Option<String>,
+) -> Result<DataRoot, String> {
+    if !PathBuf::from(&claude_dir).is_dir() {
+        return Err(format!("Directory not found: {}", claude_dir));
+    }
+
+    register_data_root(DataRoot {
+        id: slugify(&name),
+        host: host.filter(|h| !h.trim().is_empty()).unwrap_or_else(|| name.clone()),
+        name,
+        claude_dir,
+        codex_dir: codex_dir.filter(|d| !d.trim().is_empty()),
+        read_only: false,
+    })
+    .await
+}
+
+/// Forget a configured data root; the folder itself is left untouched
+#[tauri::command]
+pub async fn remove_data_root(id: String) -> Result<bool, String> {
+    if id == LOCAL_ROOT {
+        return Err("The local data root cannot be removed".to_string());
+    }
+
+    let mut roots = read_configured_roots();
+    let before = roots.len();
+    roots.retain(|r| r.id != id);
+    if roots.len() == before {
+        return Ok(false);
+    }
+
+    write_configured_roots(roots).await?;
+    Ok(true)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn root(id: &str) -> DataRoot {
+        DataRoot {
+            id: id.to_string(),
+            name: id.to_string(),
+            host: id.to_string(),
+            claude_dir: format!("/data/{}/.claude", id),
+            codex_dir: None,
+            read_only: false,
+        }
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Alice's MacBook Pro"), "alice-s-macbook-pro");
+        assert_eq!(slugify("  CI / build-01 "), "ci-build-01");
+        assert_eq!(slugify("---"), "root");
+    }
+
+    #[test]
+    fn test_qualified_id_round_trip() {
+        let roots = vec![root(LOCAL_ROOT), root("ci"), root("team-box")];
+
+        let qualified = roots[2].qualify_id("-home-me-app");
+        assert_eq!(qualified, "team-box:-home-me-app");
+        let (found, id) = split_qualified_id(&qualified, &roots).unwrap();
+        assert_eq!((found.id.as_str(), id), ("team-box", "-home-me-app"));
+
+        assert_eq!(roots[0].qualify_id("-home-me-app"), "-home-me-app");
+        assert!(split_qualified_id("-home-me-app", &roots).is_none());
+    }
+
+    #[test]
+    fn test_drive_paths_are_not_root_prefixes() {
+        let roots = vec![root(LOCAL_ROOT), root("ci")];
+        assert!(split_qualified_id("C:\\Users\\me\\app", &roots).is_none());
+        assert!(split_qualified_id("C--Users-me-app:1", &roots).is_none());
+
+        let (found, id) = split_qualified_id("ci:C--Users-me-app", &roots).unwrap();
+        assert_eq!((found.id.as_str(), id), ("ci", "C--Users-me-app"));
+    }
+
+    #[test]
+    fn test_unique_root_id() {
+        let roots = vec![root("ci"), root("ci-2")];
+        assert_eq!(unique_root_id("laptop", &roots), "laptop");
+        assert_eq!(unique_root_id("ci", &roots), "ci-3");
+        assert_eq!(unique_root_id(LOCAL_ROOT, &roots), "local-2");
+    }
+}
@@ -31,6 +31,7 @@ use crate::docs::{
     DocumentationWriter, DataCollector, WriterInput,
     read_claude_md,
 };
+use super::sources::{claude_project_dir, codex_session_files, find_source};
 
 // ============================================================================
 // Result Types
@@ -86,38 +87,21 @@ struct RawMessage {
     _extra: serde_json::Value,
 }
 
+/// What one session contributes to the documentation
+#[derive(Debug, Default)]
+pub struct SessionDocInput {
+    /// Working directory the session ran in
+    pub cwd: Option<PathBuf>,
+    /// User prompts, verbatim
+    pub user_messages: Vec<String>,
+    /// Files written or edited: (path, content, change type)
+    pub file_artifacts: Vec<(String, String, ChangeType)>,
+}
+
 // ============================================================================
 // Helper Functions
 // ============================================================================
 
-fn get_projects_dir() -> Result<PathBuf, String> {
-    let home_dir =
-        dirs::home_dir().ok_or_else(|| "Could not determine home directory".to_string())?;
-    Ok(home_dir.join(".claude").join("projects"))
-}
-
-fn get_codex_sessions_dir() -> Result<PathBuf, String> {
-    let home_dir =
-        dirs::home_dir().ok_or_else(|| "Could not determine home directory".to_string())?;
-    Ok(home_dir.join(".codex").join("sessions"))
-}
-
-fn list_codex_session_files(root: &Path) -> Vec<PathBuf> {
-    let mut files = Vec::new();
-    let Ok(entries) = std::fs::read_dir(root) else { return files };
-
-    for entry in entries.flatten() {
-        let path = entry.path();
-        if path.is_dir() {
-            files.extend(list_codex_session_files(&path));
-        } else if path.extension().map(|e| e == "jsonl").unwrap_or(false) {
-            files.push(path);
-        }
-    }
-
-    files
-}
-
 fn resolve_project_name(project_path: &Path) -> String {
     if let Some(name) = resolve_from_git_remote(project_path) {
         return name;
@@ -282,14 +266,24 @@ async fn read_session_entries(session_path: &Path) -> Result<Vec<RawLogEntry>, S
     Ok(entries)
 }
 
-async fn read_codex_user_messages(session_id: &str) -> Result<(Vec<String>, Option<PathBuf>), String> {
-    let codex_dir = get_codex_sessions_dir()?;
-    if !codex_dir.exists() {
-        return Ok((Vec::new(), None));
+/// Documentation input of a Claude Code session; a missing session contributes nothing
+pub(crate) async fn claude_session_doc_input(project_id: String, session_id: String) -> Result<SessionDocInput, String> {
+    let session_path = claude_project_dir(&project_id)?.join(format!("{}.jsonl", session_id));
+    if !session_path.exists() {
+        return Ok(SessionDocInput::default());
     }
 
-    let files = list_codex_session_files(&codex_dir);
-    for file in files {
+    let entries = read_session_entries(&session_path).await?;
+    Ok(SessionDocInput {
+        cwd: get_project_cwd(&entries),
+        user_messages: extract_user_messages(&entries),
+        file_artifacts: extract_file_artifacts(&entries),
+    })
+}
+
+/// Documentation input of a Codex session: its prompts and working directory
+pub(crate) async fn codex_session_doc_input(session_id: String) -> Result<SessionDocInput, String> {
+    for (_, file) in codex_session_files() {
         let content = std::fs::read_to_string(&file)
             .map_err(|e| format!("Failed to read session file: {}", e))?;
 
@@ -308,7 +302,7 @@ async fn read_codex_user_messages(session_id: &str) -> Result<(Vec<String>, Opti
             if entry_type == "session_meta" {
                 if let Some(payload) = value.get("payload") {
                     if let Some(id) = payload.get("id").and_then(|v| v.as_str()) {
-                        if id == session_id {
+                        if id == session_id.as_str() {
                             matches = true;
                         }
                     }
@@ -351,11 +345,34 @@ async fn read_codex_user_messages(session_id: &str) -> Result<(Vec<String>, Opti
         }
 
         if matches {
-            return Ok((messages, cwd));
+            return Ok(SessionDocInput {
+                cwd,
+                user_messages: messages,
+                file_artifacts: Vec::new(),
+            });
+        }
+    }
+
+    Ok(SessionDocInput::default())
+}
+
+/// Documentation input of each selected session, read through the source that recorded it
+async fn collect_session_inputs(
+    project_id: &str,
+    session_ids: &[String],
+    session_sources: Option<Vec<String>>,
+) -> Result<Vec<SessionDocInput>, String> {
+    let mut inputs = Vec::new();
+
+    for (idx, session_id) in session_ids.iter().enumerate() {
+        let source_id = session_sources.as_ref().and_then(|s| s.get(idx)).map(|s| s.as_str());
+        let source = find_source(source_id)?;
+        if let Ok(input) = source.session_doc_input(project_id.to_string(), session_id.clone()).await {
+            inputs.push(input);
         }
     }
 
-    Ok((Vec::new(), None))
+    Ok(inputs)
 }
 
 // ============================================================================
@@ -436,7 +453,7 @@ fn extract_file_artifacts(entries: &[RawLogEntry]) -> Vec<(String, String, Chang
                                 }
                             }
                         }
-                        "Edit" => {
+                        "Edit" | "MultiEdit" => {
                             if let Some(path) =
                                 input.and_then(|i| i.get("file_path")).and_then(|p| p.as_str())
                             {
@@ -488,8 +505,7 @@ pub async fn generate_documentation(
         resolved_project_path = Some(PathBuf::from(path));
         project_name = resolve_project_name_from_cwd(Path::new(path));
     } else {
-        let projects_dir = get_projects_dir()?;
-        let project_path = projects_dir.join(&project_id);
+        let project_path = claude_project_dir(&project_id)?;
         if !project_path.exists() {
             return Err(format!("Project not found: {}", project_id));
         }
@@ -503,44 +519,15 @@ pub async fn generate_documentation(
     let mut all_file_artifacts: Vec<(String, String, ChangeType)> = Vec::new();
     let mut project_cwd: Option<PathBuf> = None;
 
-    let sources = session_sources.unwrap_or_else(|| vec!["code".to_string(); session_ids.len()]);
-
-    for (idx, session_id) in session_ids.iter().enumerate() {
-        let source = sources.get(idx).map(|s| s.as_str()).unwrap_or("code");
-
-        if source == "codex" {
-            let (messages, cwd) = read_codex_user_messages(session_id).await?;
-            all_user_messages.extend(messages);
-            if project_cwd.is_none() {
-                project_cwd = cwd;
-            }
-            continue;
-        }
-
-        let projects_dir = get_projects_dir()?;
-        let project_path = projects_dir.join(&project_id);
-        let session_path = project_path.join(format!("{}.jsonl", session_id));
-        if !session_path.exists() {
-            continue;
-        }
-
-        let entries = match read_session_entries(&session_path).await {
-            Ok(data) => data,
-            Err(_) => continue,
-        };
-
+    for input in collect_session_inputs(&project_id, &session_ids, session_sources).await? {
         // Get project cwd (for reading CLAUDE.md)
         if project_cwd.is_none() {
-            project_cwd = get_project_cwd(&entries);
+            project_cwd = input.cwd;
         }
 
-        // Extract user messages (Stage 1 input)
-        let messages = extract_user_messages(&entries);
-        all_user_messages.extend(messages);
-
-        // Extract file artifacts (Stage 2 input)
-        let artifacts = extract_file_artifacts(&entries);
-        all_file_artifacts.extend(artifacts);
+        // User messages (Stage 1 input) and file artifacts (Stage 2 input)
+        all_user_messages.extend(input.user_messages);
+        all_file_artifacts.extend(input.file_artifacts);
     }
 
     // ========================================================================
@@ -681,8 +668,7 @@ pub async fn get_documentation_prompt(
         resolved_project_path = Some(PathBuf::from(path));
         project_name = resolve_project_name_from_cwd(Path::new(path));
     } else {
-        let projects_dir = get_projects_dir()?;
-        let project_path = projects_dir.join(&project_id);
+        let project_path = claude_project_dir(&project_id)?;
         if !project_path.exists() {
             return Err(format!("Project not found: {}", project_id));
         }
@@ -694,41 +680,12 @@ pub async fn get_documentation_prompt(
     let mut all_file_artifacts: Vec<(String, String, ChangeType)> = Vec::new();
     let mut project_cwd: Option<PathBuf> = None;
 
-    let sources = session_sources.unwrap_or_else(|| vec!["code".to_string(); session_ids.len()]);
-
-    for (idx, session_id) in session_ids.iter().enumerate() {
-        let source = sources.get(idx).map(|s| s.as_str()).unwrap_or("code");
-
-        if source == "codex" {
-            let (messages, cwd) = read_codex_user_messages(session_id).await?;
-            all_user_messages.extend(messages);
-            if project_cwd.is_none() {
-                project_cwd = cwd;
-            }
-            continue;
-        }
-
-        let projects_dir = get_projects_dir()?;
-        let project_path = projects_dir.join(&project_id);
-        let session_path = project_path.join(format!("{}.jsonl", session_id));
-        if !session_path.exists() {
-            continue;
-        }
-
-        let entries = match read_session_entries(&session_path).await {
-            Ok(data) => data,
-            Err(_) => continue,
-        };
-
+    for input in collect_session_inputs(&project_id, &session_ids, session_sources).await? {
         if project_cwd.is_none() {
-            project_cwd = get_project_cwd(&entries);
+            project_cwd = input.cwd;
         }
-
-        let messages = extract_user_messages(&entries);
-        all_user_messages.extend(messages);
-
-        let artifacts = extract_file_artifacts(&entries);
-        all_file_artifacts.extend(artifacts);
+        all_user_messages.extend(input.user_messages);
+        all_file_artifacts.extend(input.file_artifacts);
     }
 
     // Stage 1: Collect raw data (no interpretation)
@@ -0,0 +1,340 @@
+/**
+ * Edit survival analysis
+ *
+ * Measures how much of the code introduced by Write/Edit calls still exists in
+ * the working tree. A line introduced by a change survives when the current
+ * file still contains it; inside a git repository, `git blame` must also date
+ * the line no earlier than the change, so an identical line that predates the
+ * session is not credited to it. Survival is reported per session, per file,
+ * per model and per day.
+ */
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+use super::analytics_v2::local_date;
+use super::data_roots::resolve_qualified_id;
+use super::files::{project_file_changes, FileChange};
+use super::git_correlation::{added_lines, git, line_hash, parse_time_ms, repo_root, CLOCK_SKEW_MS};
+use super::transcript_index::with_index;
+use super::transcripts::find_project_cwd;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+const UNKNOWN_MODEL: &str = "unknown";
+
+/// Blame hash of lines not committed yet
+const UNCOMMITTED_BLAME: &str = "0000000000000000000000000000000000000000";
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SurvivalStats {
+    pub change_count: usize,
+    pub lines_introduced: usize,
+    pub lines_surviving: usize,
+    /// lines_surviving / lines_introduced * 100
+    pub survival_pct: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSurvival {
+    pub session_id: String,
+    pub first_change: String,
+    pub stats: SurvivalStats,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileSurvival {
+    pub file_path: String,
+    /// Whether the file still exists on disk
+    pub exists: bool,
+    /// Whether git blame dated the file's lines
+    pub blamed: bool,
+    pub stats: SurvivalStats,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelSurvival {
+    pub model: String,
+    pub stats: SurvivalStats,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailySurvival {
+    /// Local YYYY-MM-DD of the changes
+    pub date: String,
+    pub stats: SurvivalStats,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EditSurvival {
+    pub project_id: String,
+    pub repo_root: Option<String>,
+    /// Whether git blame dated the lines of at least one file
+    pub uses_blame: bool,
+    pub total: SurvivalStats,
+    /// Newest first
+    pub sessions: Vec<SessionSurvival>,
+    /// Most introduced lines first
+    pub files: Vec<FileSurvival>,
+    pub models: Vec<ModelSurvival>,
+    /// Oldest first
+    pub timeline: Vec<DailySurvival>,
+}
+
+/// Current contents of one file: line hash -> latest time (ms) any copy of it was
+/// committed, i64::MAX when uncommitted or when blame is unavailable
+type LineAges = HashMap<u64, i64>;
+
+/// Line ages of one file, and whether they came from git blame
+struct FileAges {
+    ages: LineAges,
+    blamed: bool,
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+impl SurvivalStats {
+    fn add(&mut self, introduced: usize, surviving: usize) {
+        self.change_count += 1;
+        self.lines_introduced += introduced;
+        self.lines_surviving += surviving;
+        self.survival_pct = if self.lines_introduced == 0 {
+            0.0
+        } else {
+            self.lines_surviving as f64 / self.lines_introduced as f64 * 100.0
+        };
+    }
+}
+
+/// Parse `git blame --line-porcelain` output into line ages
+pub fn parse_blame(output: &str) -> LineAges {
+    let mut ages = LineAges::new();
+    let mut commit = "";
+    let mut time_ms = 0;
+
+    for line in output.lines() {
+        if let Some(content) = line.strip_prefix('\t') {
+            let Some(hash) = line_hash(content) else { continue };
+            let age = if commit == UNCOMMITTED_BLAME { i64::MAX } else { time_ms };
+            let entry = ages.entry(hash).or_insert(age);
+            *entry = (*entry).max(age);
+        } else if let Some(time) = line.strip_prefix("committer-time ") {
+            time_ms = time.trim().parse::<i64>().unwrap_or(0) * 1000;
+        } else if line.len() > 40 && line.as_bytes()[40] == b' ' && line[..40].chars().all(|c| c.is_ascii_hexdigit()) {
+            commit = &line[..40];
+        }
+    }
+
+    ages
+}
+
+/// Line ages of a file on disk; None when the file no longer exists
+fn line_ages(file_path: &str, repo_root: Option<&Path>) -> Option<FileAges> {
+    let content = std::fs::read_to_string(file_path).ok()?;
+
+    let blamed = repo_root.and_then(|root| {
+        let relative = Path::new(file_path).strip_prefix(root).ok()?;
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        git(root, &["blame", "--line-porcelain", "--", &relative]).ok()
+    });
+
+    Some(match blamed {
+        Some(output) => FileAges { ages: parse_blame(&output), blamed: true },
+        None => FileAges { ages: content.lines().filter_map(line_hash).map(|h| (h, i64::MAX)).collect(), blamed: false },
+    })
+}
+
+/// Lines of `change` introduced and still surviving in the file
+pub fn survival_of(change: &FileChange, ages: Option<&LineAges>) -> (usize, usize) {
+    let introduced = added_lines(change);
+    let change_ms = parse_time_ms(&change.timestamp).unwrap_or(0);
+
+    let surviving = match ages {
+        Some(ages) => introduced
+            .iter()
+            .filter(|h| ages.get(h).map(|&age| age >= change_ms - CLOCK_SKEW_MS).unwrap_or(false))
+            .count(),
+        None => 0,
+    };
+
+    (introduced.len(), surviving)
+}
+
+/// Model of every assistant message of the project, by message uuid
+fn message_models(project_id: &str) -> Result<HashMap<String, String>, String> {
+    with_index(|index| {
+        index
+            .sessions
+            .values()
+            .filter(|s| s.project_id == project_id)
+            .flat_map(|s| s.entries.iter())
+            .filter_map(|e| Some((e.uuid.clone()?, e.model.clone()?)))
+            .collect()
+    })
+}
+
+fn analyze(project_id: &str) -> Result<EditSurvival, String> {
+    let (root, dir_name) = resolve_qualified_id(project_id)?;
+    if !root.is_local() {
+        return Err("Edit survival is only available for local projects".to_string());
+    }
+
+    let cwd = find_project_cwd(&root.claude_projects_dir().join(dir_name));
+    let repo_root = cwd.as_deref().and_then(repo_root);
+    let changes = project_file_changes(project_id)?;
+    let models = message_models(project_id)?;
+
+    let paths: HashSet<&str> = changes.iter().map(|c| c.file_path.as_str()).collect();
+    let ages: HashMap<&str, Option<FileAges>> = paths
+        .into_iter()
+        .map(|path| {
+            let in_repo = repo_root.as_deref().filter(|r| Path::new(path).starts_with(r));
+            (path, line_ages(path, in_repo))
+        })
+        .collect();
+
+    let mut total = SurvivalStats::default();
+    let mut sessions: HashMap<String, SessionSurvival> = HashMap::new();
+    let mut files: HashMap<String, SurvivalStats> = HashMap::new();
+    let mut by_model: HashMap<String, SurvivalStats> = HashMap::new();
+    let mut by_day: BTreeMap<String, SurvivalStats> = BTreeMap::new();
+
+    for change in &changes {
+        let (introduced, surviving) = survival_of(change, ages.get(change.file_path.as_str()).and_then(|a| a.as_ref()).map(|a| &a.ages));
+
+        total.add(introduced, surviving);
+        files.entry(change.file_path.clone()).or_default().add(introduced, surviving);
+
+        let session = sessions.entry(change.session_id.clone()).or_insert_with(|| SessionSurvival {
+            session_id: change.session_id.clone(),
+            first_change: change.timestamp.clone(),
+            stats: SurvivalStats::default(),
+        });
+        session.stats.add(introduced, surviving);
+        if change.timestamp < session.first_change {
+            session.first_change = change.timestamp.clone();
+        }
+
+        let model = models.get(&change.message_uuid).map(String::as_str).unwrap_or(UNKNOWN_MODEL);
+        by_model.entry(model.to_string()).or_default().add(introduced, surviving);
+
+        let day = local_date(&change.timestamp).unwrap_or_else(|| change.timestamp.chars().take(10).collect());
+        by_day.entry(day).or_default().add(introduced, surviving);
+    }
+
+    let mut sessions: Vec<SessionSurvival> = sessions.into_values().collect();
+    sessions.sort_by(|a, b| b.first_change.cmp(&a.first_change));
+
+    let mut files: Vec<FileSurvival> = files
+        .into_iter()
+        .map(|(file_path, stats)| {
+            let file_ages = ages.get(file_path.as_str()).and_then(|a| a.as_ref());
+            FileSurvival { exists: file_ages.is_some(), blamed: file_ages.map(|a| a.blamed).unwrap_or(false), file_path, stats }
+        })
+        .collect();
+    files.sort_by_key(|f| std::cmp::Reverse(f.stats.lines_introduced));
+
+    let mut models: Vec<ModelSurvival> = by_model.into_iter().map(|(model, stats)| ModelSurvival { model, stats }).collect();
+    models.sort_by_key(|m| std::cmp::Reverse(m.stats.lines_introduced));
+
+    Ok(EditSurvival {
+        project_id: project_id.to_string(),
+        uses_blame: files.iter().any(|f| f.blamed),
+        repo_root: repo_root.map(|r| r.to_string_lossy().to_string()),
+        total,
+        sessions,
+        files,
+        models,
+        timeline: by_day.into_iter().map(|(date, stats)| DailySurvival { date, stats }).collect(),
+    })
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// How much of the code written by sessions of a project still exists in the working tree
+#[tauri::command]
+pub async fn get_edit_survival(project_id: String) -> Result<EditSurvival, String> {
+    tokio::task::spawn_blocking(move || analyze(&project_id))
+        .await
+        .map_err(|e| format!("Edit survival task failed: {}", e))?
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(content: &str, timestamp: &str) -> FileChange {
+        FileChange {
+            hash: "h".to_string(),
+            session_id: "s".to_string(),
+            project_id: "p".to_string(),
+            file_path: "/repo/src/lib.rs".to_string(),
+            change_type: "write".to_string(),
+            timestamp: timestamp.to_string(),
+            content: content.to_string(),
+            old_content: None,
+            message_uuid: "m".to_string(),
+            replace_all: false,
+            tool: "Write".to_string(),
+            notebook_cell: None,
+        }
+    }
+
+    #[test]
+    fn test_survival_uses_blame_dates() {
+        let old = "a".repeat(40);
+        let new = "b".repeat(40);
+        let blame = format!(
+            "{old} 1 1 1\ncommitter-time 1700000000\n\tfn existing_helper() {{}}\n\
+             {new} 2 2 1\ncommitter-time 1762000000\n\tfn written_by_agent() {{}}\n\
+             {zero} 3 3 1\ncommitter-time 1762100000\n\tfn not_committed_yet() {{}}\n",
+            old = old,
+            new = new,
+            zero = UNCOMMITTED_BLAME
+        );
+        let ages = parse_blame(&blame);
+
+        // 2025-11-01, after the first commit and before the second
+        let change = write(
+            "fn existing_helper() {}\nfn written_by_agent() {}\nfn not_committed_yet() {}\nfn deleted_since() {}\n",
+            "2025-11-01T10:00:00Z",
+        );
+        assert_eq!(survival_of(&change, Some(&ages)), (4, 2));
+        assert_eq!(survival_of(&change, None), (4, 0));
+    }
+
+    #[test]
+    fn test_failed_blame_is_not_reported_as_blame() {
+        // A directory that is not a git repository: blame fails and every line counts as uncommitted
+        let dir = std::env::temp_dir().join(format!("edit-survival-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("lib.rs");
+        std::fs::write(&path, "fn written_by_agent() {}\n").unwrap();
+
+        let file_ages = line_ages(&path.to_string_lossy(), Some(&dir)).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(!file_ages.blamed);
+        assert_eq!(file_ages.ages.values().copied().collect::<Vec<_>>(), vec![i64::MAX]);
+    }
+}
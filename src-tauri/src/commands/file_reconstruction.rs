@@ -0,0 +1,580 @@
+/**
+ * Point-in-time file reconstruction
+ *
+ * Replays the Write/Edit changes a session made to one file to produce its
+ * full contents at any message. Replay needs a starting point, tried in order:
+ * - a backup from ~/.claude/file-history, recorded by `file-history-snapshot`
+ *   entries as the file's state at the start of a turn
+ * - the first Write of the file
+ * - the current file on disk, with the session's edits reverted back to the
+ *   state before the first edit (local data root only)
+ *
+ * Edits whose `old_string` is no longer present are skipped and reported as
+ * divergences; later backups and Writes bring the replay back in sync.
+ *
+ * Codex rollouts have no backups, so their apply_patch and shell changes start
+ * from the first added file or from the disk.
+ */
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+use super::data_roots::resolve_qualified_id;
+use super::files::{codex_session_file_changes, extract_file_changes_from_line, FileChange};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+pub const BASELINE_BACKUP: &str = "backup";
+pub const BASELINE_WRITE: &str = "write";
+pub const BASELINE_DISK: &str = "disk";
+pub const BASELINE_NONE: &str = "none";
+
+pub const DIVERGENCE_OLD_STRING_NOT_FOUND: &str = "oldStringNotFound";
+pub const DIVERGENCE_NO_BASELINE: &str = "noBaseline";
+pub const DIVERGENCE_BACKUP_MISMATCH: &str = "backupMismatch";
+pub const DIVERGENCE_UNSUPPORTED: &str = "unsupportedChange";
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// One step of a file's history within a session, in transcript order
+#[derive(Debug, Clone)]
+pub enum ReplayEvent {
+    Change(Box<FileChange>),
+    /// State of the file at the start of the turn of `message_uuid`; None when it did not exist
+    Backup {
+        message_uuid: String,
+        backup_file_name: Option<String>,
+        content: Option<String>,
+    },
+}
+
+/// Events of a file with their transcript positions, and the position of every message uuid
+type FileEvents = (Vec<(usize, ReplayEvent)>, HashMap<String, usize>);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayBaseline {
+    /// "backup", "write", "disk" or "none"
+    pub kind: String,
+    pub message_uuid: Option<String>,
+    pub backup_file_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayDivergence {
+    pub message_uuid: String,
+    /// Hash of the change that could not be applied, if any
+    pub hash: Option<String>,
+    /// "oldStringNotFound", "noBaseline", "backupMismatch" or "unsupportedChange"
+    pub reason: String,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Replay {
+    pub content: Option<String>,
+    pub baseline: ReplayBaseline,
+    pub applied_changes: usize,
+    pub divergences: Vec<ReplayDivergence>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileReconstruction {
+    pub project_id: String,
+    pub session_id: String,
+    pub file_path: String,
+    /// Message the contents are reconstructed at; None means the end of the session
+    pub at_message_uuid: Option<String>,
+    /// Whether the file existed at that point (false also when no baseline was found)
+    pub exists: bool,
+    pub content: Option<String>,
+    pub baseline: ReplayBaseline,
+    pub applied_changes: usize,
+    /// Write/Edit changes to the file up to that point
+    pub change_count: usize,
+    pub divergences: Vec<ReplayDivergence>,
+    /// Replay started from a backup or Write and applied every change cleanly.
+    /// A disk baseline is never exact: the file may hold later sessions' or manual edits.
+    pub exact: bool,
+}
+
+// ============================================================================
+// Replay
+// ============================================================================
+
+fn old_string(change: &FileChange) -> &str {
+    change.old_content.as_deref().unwrap_or("")
+}
+
+/// Apply an Edit (or append) to the current contents, or None if `old_string` is not there
+fn apply_edit(content: Option<&str>, change: &FileChange) -> Option<String> {
+    if change.change_type == "append" {
+        return Some(format!("{}{}", content.unwrap_or(""), change.content));
+    }
+    let old = old_string(change);
+    match content {
+        // Edit with an empty old_string creates the file
+        None | Some("") if old.is_empty() => Some(change.content.clone()),
+        Some(text) if !old.is_empty() && text.contains(old) => Some(if change.replace_all {
+            text.replace(old, &change.content)
+        } else {
+            text.replacen(old, &change.content, 1)
+        }),
+        _ => None,
+    }
+}
+
+/// Undo an Edit, or None if its `new_string` cannot be located
+fn revert_edit(text: &str, change: &FileChange) -> Option<String> {
+    if change.change_type == "append" {
+        return text.strip_suffix(change.content.as_str()).map(String::from);
+    }
+    let new = change.content.as_str();
+    let old = old_string(change);
+    if new.is_empty() || !text.contains(new) {
+        return None;
+    }
+    Some(if change.replace_all { text.replace(new, old) } else { text.replacen(new, old, 1) })
+}
+
+/// Contents before the first event (an Edit), found by reverting edits from the
+/// next backup, or from the file on disk when there is none. Writes, deletes and
+/// notebook edits in between make this impossible.
+fn reverse_baseline(events: &[ReplayEvent], disk: Option<&str>) -> Option<(String, ReplayBaseline)> {
+    let anchor = events.iter().position(|e| match e {
+        ReplayEvent::Backup { .. } => true,
+        ReplayEvent::Change(c) => !matches!(c.change_type.as_str(), "edit" | "append"),
+    });
+
+    let (mut text, baseline, edits) = match anchor.map(|i| (i, &events[i])) {
+        Some((i, ReplayEvent::Backup { message_uuid, backup_file_name, content: Some(content) })) => (
+            content.clone(),
+            ReplayBaseline {
+                kind: BASELINE_BACKUP.to_string(),
+                message_uuid: Some(message_uuid.clone()),
+                backup_file_name: backup_file_name.clone(),
+            },
+            &events[..i],
+        ),
+        Some(_) => return None,
+        None => (
+            disk?.to_string(),
+            ReplayBaseline { kind: BASELINE_DISK.to_string(), message_uuid: None, backup_file_name: None },
+            events,
+        ),
+    };
+
+    for event in edits.iter().rev() {
+        if let ReplayEvent::Change(change) = event {
+            text = revert_edit(&text, change)?;
+        }
+    }
+
+    Some((text, baseline))
+}
+
+impl Replay {
+    /// Whether the contents are known for certain: a backup or Write baseline and no divergences
+    pub fn is_exact(&self) -> bool {
+        self.divergences.is_empty() && self.baseline.kind != BASELINE_NONE && self.baseline.kind != BASELINE_DISK
+    }
+}
+
+/// Replay the first `end` events. `disk` is the current file, used only when
+/// the history starts with an Edit and no backup can anchor it.
+pub fn replay(events: &[ReplayEvent], end: usize, disk: Option<&str>) -> Replay {
+    let mut content: Option<String> = None;
+    let mut established = false;
+    let mut baseline = ReplayBaseline { kind: BASELINE_NONE.to_string(), message_uuid: None, backup_file_name: None };
+    let mut applied_changes = 0;
+    let mut divergences = Vec::new();
+
+    if let Some(ReplayEvent::Change(first)) = events.first() {
+        if matches!(first.change_type.as_str(), "edit" | "append") {
+            if let Some((text, base)) = reverse_baseline(events, disk) {
+                content = Some(text);
+                established = true;
+                baseline = base;
+            }
+        }
+    }
+
+    for event in &events[..end.min(events.len())] {
+        match event {
+            ReplayEvent::Backup { message_uuid, backup_file_name, content: backup } => {
+                if !established {
+                    baseline = ReplayBaseline {
+                        kind: BASELINE_BACKUP.to_string(),
+                        message_uuid: Some(message_uuid.clone()),
+                        backup_file_name: backup_file_name.clone(),
+                    };
+                } else if content != *backup {
+                    divergences.push(ReplayDivergence {
+                        message_uuid: message_uuid.clone(),
+                        hash: None,
+                        reason: DIVERGENCE_BACKUP_MISMATCH.to_string(),
+                        detail: "Replayed contents differ from the file-history backup; continuing from the backup".to_string(),
+                    });
+                }
+                content = backup.clone();
+                established = true;
+            }
+            ReplayEvent::Change(change) if change.change_type == "write" => {
+                if !established {
+                    baseline = ReplayBaseline {
+                        kind: BASELINE_WRITE.to_string(),
+                        message_uuid: Some(change.message_uuid.clone()),
+                        backup_file_name: None,
+                    };
+                }
+                content = Some(change.content.clone());
+                established = true;
+                applied_changes += 1;
+            }
+            ReplayEvent::Change(change) if change.change_type == "delete" => {
+                if !established {
+                    baseline = ReplayBaseline {
+                        kind: BASELINE_WRITE.to_string(),
+                        message_uuid: Some(change.message_uuid.clone()),
+                        backup_file_name: None,
+                    };
+                }
+                content = None;
+                established = true;
+                applied_changes += 1;
+            }
+            ReplayEvent::Change(change) if change.change_type == "notebook" => {
+                divergences.push(ReplayDivergence {
+                    message_uuid: change.message_uuid.clone(),
+                    hash: Some(change.hash.clone()),
+                    reason: DIVERGENCE_UNSUPPORTED.to_string(),
+                    detail: "Notebook cell edits are not replayed".to_string(),
+                });
+            }
+            ReplayEvent::Change(change) => {
+                if !established {
+                    divergences.push(ReplayDivergence {
+                        message_uuid: change.message_uuid.clone(),
+                        hash: Some(change.hash.clone()),
+                        reason: DIVERGENCE_NO_BASELINE.to_string(),
+                        detail: "Edit precedes any known contents of the file".to_string(),
+                    });
+                    continue;
+                }
+                match apply_edit(content.as_deref(), change) {
+                    Some(next) => {
+                        content = Some(next);
+                        applied_changes += 1;
+                    }
+                    None => divergences.push(ReplayDivergence {
+                        message_uuid: change.message_uuid.clone(),
+                        hash: Some(change.hash.clone()),
+                        reason: DIVERGENCE_OLD_STRING_NOT_FOUND.to_string(),
+                        detail: format!(
+                            "old_string not found: {}",
+                            old_string(change).lines().next().unwrap_or("").chars().take(80).collect::<String>()
+                        ),
+                    }),
+                }
+            }
+        }
+    }
+
+    Replay { content, baseline, applied_changes, divergences }
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Snapshot keys are absolute paths, or relative to the session's working directory
+fn path_matches(key: &str, file_path: &str) -> bool {
+    key == file_path || (!key.starts_with('/') && file_path.ends_with(&format!("/{}", key.trim_start_matches("./"))))
+}
+
+/// History of `file_path` in a session, in transcript order, plus the line of every message uuid
+async fn load_events(
+    project_id: &str,
+    session_id: &str,
+    file_path: &str,
+) -> Result<FileEvents, String> {
+    let (root, project_dir_name) = resolve_qualified_id(project_id)?;
+    let session_path = root.claude_projects_dir().join(project_dir_name).join(format!("{}.jsonl", session_id));
+    if !session_path.exists() {
+        return load_codex_events(project_id, session_id, file_path);
+    }
+
+    let file = tokio::fs::File::open(&session_path)
+        .await
+        .map_err(|e| format!("Failed to open session file: {}", e))?;
+    let mut lines = BufReader::new(file).lines();
+
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    let mut changes: Vec<(usize, ReplayEvent)> = Vec::new();
+    // (snapshot line, turn message id, backup file name)
+    let mut backups: Vec<(usize, String, Option<String>)> = Vec::new();
+    let mut line_no = 0;
+
+    while let Some(line) = lines.next_line().await.map_err(|e| format!("Error reading line: {}", e))? {
+        line_no += 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if line.contains("\"file-history-snapshot\"") {
+            let Ok(entry) = serde_json::from_str::<Value>(&line) else { continue };
+            let snapshot = entry.get("snapshot").unwrap_or(&Value::Null);
+            let message_id = snapshot
+                .get("messageId")
+                .or(entry.get("messageId"))
+                .and_then(|m| m.as_str())
+                .unwrap_or("")
+                .to_string();
+            if let Some(tracked) = snapshot.get("trackedFileBackups").and_then(|t| t.as_object()) {
+                for (key, backup) in tracked {
+                    if path_matches(key, file_path) {
+                        let name = backup.get("backupFileName").and_then(|n| n.as_str()).map(String::from);
+                        backups.push((line_no, message_id.clone(), name));
+                    }
+                }
+            }
+            continue;
+        }
+
+        if let Some(uuid) = serde_json::from_str::<Value>(&line)
+            .ok()
+            .and_then(|v| v.get("uuid").and_then(|u| u.as_str()).map(String::from))
+        {
+            positions.insert(uuid, line_no);
+        }
+
+        for change in extract_file_changes_from_line(&line, session_id, project_id) {
+            if change.file_path == file_path {
+                changes.push((line_no, ReplayEvent::Change(Box::new(change))));
+            }
+        }
+    }
+
+    // Snapshots are carried forward from turn to turn; keep each backup once,
+    // placed at the turn it belongs to
+    let backup_dir = root.claude_file_history_dir().join(session_id);
+    let mut seen: HashSet<(Option<String>, String)> = HashSet::new();
+    let mut events: Vec<(usize, ReplayEvent)> = Vec::new();
+    for (line, message_id, name) in backups {
+        let key = (name.clone(), if name.is_some() { String::new() } else { message_id.clone() });
+        if !seen.insert(key) {
+            continue;
+        }
+        let content = match &name {
+            Some(name) => match fs::read_to_string(backup_dir.join(name)) {
+                Ok(content) => Some(content),
+                Err(_) => continue,
+            },
+            None => None,
+        };
+        let position = positions.get(&message_id).copied().unwrap_or(line).min(line);
+        events.push((
+            position,
+            ReplayEvent::Backup { message_uuid: message_id, backup_file_name: name, content },
+        ));
+    }
+
+    // Backups come before the changes of their turn
+    events.extend(changes);
+    events.sort_by_key(|(position, event)| (*position, matches!(event, ReplayEvent::Change(_))));
+
+    Ok((events, positions))
+}
+
+/// History of `file_path` in a Codex session. Changes of one call share a
+/// position, and call ids stand in for message uuids.
+fn load_codex_events(
+    project_id: &str,
+    session_id: &str,
+    file_path: &str,
+) -> Result<FileEvents, String> {
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    let mut events: Vec<(usize, ReplayEvent)> = Vec::new();
+
+    for change in codex_session_file_changes(project_id, session_id)? {
+        let next = positions.len() + 1;
+        let position = *positions.entry(change.message_uuid.clone()).or_insert(next);
+        if change.file_path == file_path {
+            events.push((position, ReplayEvent::Change(Box::new(change))));
+        }
+    }
+
+    Ok((events, positions))
+}
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+/// Every event of one file within a session, with the current file on disk
+pub(crate) struct FileTimeline {
+    pub events: Vec<ReplayEvent>,
+    /// Transcript line of each event
+    positions: Vec<usize>,
+    /// Transcript line of every message uuid
+    messages: HashMap<String, usize>,
+    disk: Option<String>,
+}
+
+impl FileTimeline {
+    /// Replay of the first `end` events
+    pub fn at(&self, end: usize) -> Replay {
+        replay(&self.events, end, self.disk.as_deref())
+    }
+
+    /// Number of events up to and including message `message_uuid`
+    pub fn end_of_message(&self, message_uuid: &str) -> Result<usize, String> {
+        let target = *self
+            .messages
+            .get(message_uuid)
+            .ok_or_else(|| format!("Message not found in session: {}", message_uuid))?;
+        Ok(self.positions.iter().take_while(|position| **position <= target).count())
+    }
+
+    /// Index of the first (or last) event that is `change`
+    pub fn change_index(&self, change: &FileChange, last: bool) -> Option<usize> {
+        let is_change = |e: &ReplayEvent| {
+            matches!(e, ReplayEvent::Change(c) if c.hash == change.hash && c.message_uuid == change.message_uuid)
+        };
+        if last {
+            self.events.iter().rposition(is_change)
+        } else {
+            self.events.iter().position(is_change)
+        }
+    }
+}
+
+/// History of `file_path` within a session, ready to be replayed
+pub(crate) async fn file_timeline(project_id: &str, session_id: &str, file_path: &str) -> Result<FileTimeline, String> {
+    let (events, messages) = load_events(project_id, session_id, file_path).await?;
+
+    let (root, _) = resolve_qualified_id(project_id)?;
+    let disk = if root.is_local() { fs::read_to_string(file_path).ok() } else { None };
+
+    let (positions, events) = events.into_iter().unzip();
+    Ok(FileTimeline { events, positions, messages, disk })
+}
+
+/// Contents of `file_path` right after message `message_uuid` (or at the end of the session)
+pub(crate) async fn reconstruct_file(
+    project_id: &str,
+    session_id: &str,
+    file_path: &str,
+    message_uuid: Option<&str>,
+) -> Result<FileReconstruction, String> {
+    let timeline = file_timeline(project_id, session_id, file_path).await?;
+
+    let end = match message_uuid {
+        Some(uuid) => timeline.end_of_message(uuid)?,
+        None => timeline.events.len(),
+    };
+
+    let result = timeline.at(end);
+    let change_count = timeline.events[..end].iter().filter(|e| matches!(e, ReplayEvent::Change(_))).count();
+
+    Ok(FileReconstruction {
+        project_id: project_id.to_string(),
+        session_id: session_id.to_string(),
+        file_path: file_path.to_string(),
+        at_message_uuid: message_uuid.map(String::from),
+        exists: result.content.is_some(),
+        exact: result.is_exact(),
+        content: result.content,
+        baseline: result.baseline,
+        applied_changes: result.applied_changes,
+        change_count,
+        divergences: result.divergences,
+    })
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Reconstruct a file's full contents at a message of a session (default: end of session)
+#[tauri::command]
+pub async fn reconstruct_file_at(
+    project_id: String,
+    session_id: String,
+    file_path: String,
+    message_uuid: Option<String>,
+) -> Result<FileReconstruction, String> {
+    reconstruct_file(&project_id, &session_id, &file_path, message_uuid.as_deref()).await
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(uuid: &str, kind: &str, content: &str, old: Option<&str>) -> ReplayEvent {
+        ReplayEvent::Change(Box::new(FileChange {
+            hash: uuid.to_string(),
+            session_id: "s".to_string(),
+            project_id: "p".to_string(),
+            file_path: "/repo/a.txt".to_string(),
+            change_type: kind.to_string(),
+            timestamp: String::new(),
+            content: content.to_string(),
+            old_content: old.map(String::from),
+            message_uuid: uuid.to_string(),
+            replace_all: false,
+            tool: "Edit".to_string(),
+            notebook_cell: None,
+        }))
+    }
+
+    #[test]
+    fn test_replay_from_write_reports_missing_old_string() {
+        let events = vec![
+            change("m1", "write", "one\ntwo\n", None),
+            change("m2", "edit", "TWO", Some("two")),
+            change("m3", "edit", "x", Some("three")),
+        ];
+
+        let result = replay(&events, 2, None);
+        assert_eq!(result.content.as_deref(), Some("one\nTWO\n"));
+        assert_eq!(result.baseline.kind, BASELINE_WRITE);
+        assert!(result.divergences.is_empty());
+
+        let result = replay(&events, 3, None);
+        assert_eq!(result.content.as_deref(), Some("one\nTWO\n"));
+        assert_eq!(result.divergences.len(), 1);
+        assert_eq!(result.divergences[0].reason, DIVERGENCE_OLD_STRING_NOT_FOUND);
+    }
+
+    #[test]
+    fn test_replay_reverts_edits_from_disk() {
+        let events = vec![
+            change("m1", "edit", "beta", Some("alpha")),
+            change("m2", "edit", "gamma", Some("beta")),
+        ];
+
+        let result = replay(&events, 1, Some("gamma"));
+        assert_eq!(result.baseline.kind, BASELINE_DISK);
+        assert_eq!(result.content.as_deref(), Some("beta"));
+        assert!(result.divergences.is_empty());
+        assert!(!result.is_exact());
+
+        let result = replay(&events, 2, None);
+        assert_eq!(result.content, None);
+        assert_eq!(result.divergences.len(), 2);
+    }
+}
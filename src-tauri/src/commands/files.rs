@@ -1,7 +1,8 @@
 /**
  * Files service commands
  *
- * Extracts Write/Edit tool calls from session transcripts to track file modifications
+ * Extracts file-changing tool calls (Write, Edit, MultiEdit, NotebookEdit, shell
+ * redirections and Codex apply_patch) from session transcripts to track file modifications
  * Supports aggregation by file, session, and change lookup
  */
 
@@ -12,6 +13,24 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use tokio::io::{AsyncBufReadExt, BufReader};
 
+use super::shell_commands::{command_words, parse_command, split_commands, CommandWords};
+use super::sources::{claude_project_dir, codex_session_files};
+use super::transcript_index::{read_lines_at, with_index, IndexedSession};
+use super::transcripts::{
+    codex_session_identity, codex_tool_call_block, find_codex_session_file, get_file_modified_time, read_codex_session_data,
+    Message,
+};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Tools whose calls can change files
+const FILE_CHANGE_TOOLS: &[&str] = &["Write", "Edit", "MultiEdit", "NotebookEdit", "Bash"];
+
+/// Shell tools of Claude Code (`Bash`) and Codex
+pub(crate) const SHELL_TOOLS: &[&str] = &["Bash", "shell", "exec_command", "local_shell", "container.exec"];
+
 // ============================================================================
 // Type Definitions - Match TypeScript interfaces
 // ============================================================================
@@ -26,7 +45,7 @@ pub struct FileChange {
     #[serde(rename = "filePath")]
     pub file_path: String,
     #[serde(rename = "type")]
-    pub change_type: String, // "write" or "edit"
+    pub change_type: String, // "write", "edit", "append", "delete" or "notebook"
     pub timestamp: String,
     pub content: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -34,6 +53,26 @@ pub struct FileChange {
     pub old_content: Option<String>,
     #[serde(rename = "messageUuid")]
     pub message_uuid: String,
+    /// Edit replaced every occurrence of `old_content`
+    #[serde(rename = "replaceAll", default)]
+    pub replace_all: bool,
+    /// Tool that made the change (Write, Edit, MultiEdit, NotebookEdit, Bash, apply_patch)
+    #[serde(default)]
+    pub tool: String,
+    #[serde(rename = "notebookCell", default, skip_serializing_if = "Option::is_none")]
+    pub notebook_cell: Option<NotebookCellEdit>,
+}
+
+/// Cell targeted by a NotebookEdit; the change content is the cell's new source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotebookCellEdit {
+    #[serde(rename = "cellId", skip_serializing_if = "Option::is_none")]
+    pub cell_id: Option<String>,
+    #[serde(rename = "cellType", skip_serializing_if = "Option::is_none")]
+    pub cell_type: Option<String>,
+    /// "replace", "insert" or "delete"
+    #[serde(rename = "editMode")]
+    pub edit_mode: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -126,11 +165,6 @@ pub struct FileDiff {
 // Helper Functions
 // ============================================================================
 
-fn get_projects_dir() -> Result<PathBuf, String> {
-    let home_dir = dirs::home_dir().ok_or_else(|| "Could not determine home directory".to_string())?;
-    Ok(home_dir.join(".claude").join("projects"))
-}
-
 fn calculate_hash(content: &str) -> String {
     use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
@@ -148,71 +182,413 @@ fn get_filename(path: &str) -> String {
         .to_string()
 }
 
-/// Extract file changes from a message's content blocks
-fn extract_file_changes_from_message(
-    message_uuid: &str,
-    content: &Value,
-    timestamp: &str,
-    session_id: &str,
-    project_id: &str,
-) -> Vec<FileChange> {
+/// Where the changes of one message come from
+struct ChangeContext<'a> {
+    message_uuid: &'a str,
+    timestamp: &'a str,
+    session_id: &'a str,
+    project_id: &'a str,
+    /// Working directory for resolving relative paths
+    cwd: Option<&'a str>,
+}
+
+impl ChangeContext<'_> {
+    fn change(&self, tool: &str, file_path: &str, change_type: &str, content: &str, old_content: Option<&str>) -> FileChange {
+        FileChange {
+            hash: calculate_hash(content),
+            session_id: self.session_id.to_string(),
+            project_id: self.project_id.to_string(),
+            file_path: self.resolve(file_path),
+            change_type: change_type.to_string(),
+            timestamp: self.timestamp.to_string(),
+            content: content.to_string(),
+            old_content: old_content.map(String::from),
+            message_uuid: self.message_uuid.to_string(),
+            replace_all: false,
+            tool: tool.to_string(),
+            notebook_cell: None,
+        }
+    }
+
+    fn resolve(&self, path: &str) -> String {
+        match self.cwd {
+            Some(cwd) if !Path::new(path).is_absolute() => {
+                Path::new(cwd).join(path.trim_start_matches("./")).to_string_lossy().to_string()
+            }
+            _ => path.to_string(),
+        }
+    }
+}
+
+/// Command text of a shell tool call (Claude `Bash`, Codex `shell`/`exec_command`)
+pub(crate) fn shell_script(input: &Value) -> Option<String> {
+    match input.get("command").or(input.get("cmd"))? {
+        Value::String(s) => Some(s.clone()),
+        // ["bash", "-lc", "<script>"] or ["apply_patch", "<patch>"]
+        Value::Array(parts) => {
+            let parts: Vec<&str> = parts.iter().filter_map(|p| p.as_str()).collect();
+            match parts.as_slice() {
+                [program, patch] if program.ends_with("apply_patch") => Some(patch.to_string()),
+                [.., script] => Some(script.to_string()),
+                [] => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Patch text of a Codex `apply_patch` call, whose input is free-form
+fn patch_text(input: &Value) -> Option<String> {
+    match input {
+        Value::String(s) => Some(s.clone()),
+        _ => ["raw", "input", "patch"]
+            .iter()
+            .find_map(|k| input.get(*k).and_then(|v| v.as_str()))
+            .map(String::from),
+    }
+}
+
+/// Split a Codex patch envelope into per-file changes: added files become
+/// writes, deleted files deletes, and each hunk of an updated file an edit
+fn apply_patch_changes(patch: &str, ctx: &ChangeContext) -> Vec<FileChange> {
+    const TOOL: &str = "apply_patch";
+
+    let Some(start) = patch.find("*** Begin Patch") else { return Vec::new() };
+    let body = &patch[start..];
+    let body = body.find("*** End Patch").map(|end| &body[..end]).unwrap_or(body);
+
     let mut changes = Vec::new();
+    // (path, added lines) of an Add File section
+    let mut adding: Option<(String, Vec<&str>)> = None;
+    // (path, old lines, new lines) of the current hunk of an Update File section
+    let mut updating: Option<(String, Vec<&str>, Vec<&str>)> = None;
+
+    fn flush_hunk(updating: &mut Option<(String, Vec<&str>, Vec<&str>)>, ctx: &ChangeContext, changes: &mut Vec<FileChange>) {
+        if let Some((path, old, new)) = updating.as_mut() {
+            if !old.is_empty() || !new.is_empty() {
+                let old_text = old.join("\n");
+                changes.push(ctx.change(TOOL, path, "edit", &new.join("\n"), Some(&old_text)));
+            }
+            old.clear();
+            new.clear();
+        }
+    }
 
-    if let Value::Array(blocks) = content {
-        for block in blocks {
-            // Look for tool_use blocks that are Write or Edit operations
-            if block.get("type").and_then(|v| v.as_str()) == Some("tool_use") {
-                if let Some(tool_name) = block.get("name").and_then(|v| v.as_str()) {
-                    if tool_name == "Write" || tool_name == "Edit" {
-                        if let Some(input) = block.get("input") {
-                            let file_path = input.get("file_path").and_then(|v| v.as_str());
-
-                            if tool_name == "Write" {
-                                if let (Some(file_path_str), Some(content_str)) =
-                                    (file_path, input.get("content").and_then(|v| v.as_str()))
-                                {
-                                    let hash = calculate_hash(content_str);
-                                    changes.push(FileChange {
-                                        hash,
-                                        session_id: session_id.to_string(),
-                                        project_id: project_id.to_string(),
-                                        file_path: file_path_str.to_string(),
-                                        change_type: "write".to_string(),
-                                        timestamp: timestamp.to_string(),
-                                        content: content_str.to_string(),
-                                        old_content: None,
-                                        message_uuid: message_uuid.to_string(),
-                                    });
-                                }
-                            } else if tool_name == "Edit" {
-                                if let (Some(file_path_str), Some(new_str), Some(old_str)) =
-                                    (file_path, input.get("new_string").and_then(|v| v.as_str()),
-                                     input.get("old_string").and_then(|v| v.as_str()))
-                                {
-                                    let hash = calculate_hash(new_str);
-                                    changes.push(FileChange {
-                                        hash,
-                                        session_id: session_id.to_string(),
-                                        project_id: project_id.to_string(),
-                                        file_path: file_path_str.to_string(),
-                                        change_type: "edit".to_string(),
-                                        timestamp: timestamp.to_string(),
-                                        content: new_str.to_string(),
-                                        old_content: Some(old_str.to_string()),
-                                        message_uuid: message_uuid.to_string(),
-                                    });
-                                }
-                            }
-                        }
-                    }
+    for line in body.lines().skip(1) {
+        if let Some(header) = line.strip_prefix("*** ") {
+            flush_hunk(&mut updating, ctx, &mut changes);
+            if let Some((path, lines)) = adding.take() {
+                changes.push(ctx.change(TOOL, &path, "write", &format!("{}\n", lines.join("\n")), None));
+            }
+
+            if let Some(path) = header.strip_prefix("Add File: ") {
+                updating = None;
+                adding = Some((path.trim().to_string(), Vec::new()));
+            } else if let Some(path) = header.strip_prefix("Delete File: ") {
+                updating = None;
+                changes.push(ctx.change(TOOL, path.trim(), "delete", "", None));
+            } else if let Some(path) = header.strip_prefix("Update File: ") {
+                updating = Some((path.trim().to_string(), Vec::new(), Vec::new()));
+            } else if let Some(path) = header.strip_prefix("Move to: ") {
+                // Later hunks apply to the file under its new name
+                if let Some((old_path, _, _)) = updating.as_mut() {
+                    changes.push(ctx.change(TOOL, old_path, "delete", "", None));
+                    *old_path = path.trim().to_string();
                 }
             }
+            continue;
+        }
+
+        if let Some((_, lines)) = adding.as_mut() {
+            lines.push(line.strip_prefix('+').unwrap_or(line));
+        } else if line.starts_with("@@") {
+            flush_hunk(&mut updating, ctx, &mut changes);
+        } else if let Some((_, old, new)) = updating.as_mut() {
+            if let Some(removed) = line.strip_prefix('-') {
+                old.push(removed);
+            } else if let Some(added) = line.strip_prefix('+') {
+                new.push(added);
+            } else {
+                let context = line.strip_prefix(' ').unwrap_or(line);
+                old.push(context);
+                new.push(context);
+            }
+        }
+    }
+
+    flush_hunk(&mut updating, ctx, &mut changes);
+    if let Some((path, lines)) = adding.take() {
+        changes.push(ctx.change(TOOL, &path, "write", &format!("{}\n", lines.join("\n")), None));
+    }
+
+    changes
+}
+
+/// File written by an output redirection (`> file`, `>> file`) of a command, with whether it appends
+fn redirect_target(words: &CommandWords) -> Option<(String, bool)> {
+    words
+        .redirections
+        .iter()
+        .filter(|r| matches!(r.op.as_str(), ">" | ">>" | ">|") && r.fd.as_deref().map(|fd| fd == "1").unwrap_or(true))
+        .find(|r| !r.target.is_empty() && !r.target.starts_with("/dev/") && !r.target.contains(['$', '*', '`']))
+        .map(|r| (r.target.clone(), r.op == ">>"))
+}
+
+/// File argument of `tee`, with whether it appends
+fn tee_target(words: &CommandWords) -> Option<(String, bool)> {
+    let mut append = false;
+    for word in words.words.iter().skip_while(|w| w.rsplit('/').next() != Some("tee")).skip(1) {
+        match word.as_str() {
+            "-a" | "--append" => append = true,
+            w if w.starts_with('-') => {}
+            w => return Some((w.to_string(), append)),
+        }
+    }
+    None
+}
+
+/// Heredoc of a shell line: the command it feeds, its delimiter and whether tabs are stripped
+fn heredoc_command(line: &str) -> Option<(String, String, bool)> {
+    split_commands(line).into_iter().find_map(|segment| {
+        let words = command_words(&segment.text);
+        let heredoc = words.redirections.iter().find(|r| r.op == "<<" || r.op == "<<-")?;
+        Some((segment.text.clone(), heredoc.target.clone(), heredoc.op == "<<-"))
+    })
+}
+
+/// Files written by shell redirections whose content is spelled out in the
+/// command: `cat > file <<EOF`, `cat <<EOF >> file`, `tee file <<EOF` and
+/// `echo "text" > file`
+fn bash_redirect_changes(script: &str, ctx: &ChangeContext) -> Vec<FileChange> {
+    const TOOL: &str = "Bash";
+
+    let mut changes = Vec::new();
+    let lines: Vec<&str> = script.lines().collect();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        i += 1;
+
+        if let Some((command, delimiter, strip_tabs)) = heredoc_command(line) {
+            let mut body = Vec::new();
+            while i < lines.len() {
+                let candidate = if strip_tabs { lines[i].trim_start_matches('\t') } else { lines[i] };
+                i += 1;
+                if candidate.trim_end() == delimiter {
+                    break;
+                }
+                body.push(candidate);
+            }
+
+            let words = command_words(&command);
+            let target = match parse_command(&command).map(|c| c.program) {
+                Some(program) if program == "cat" => redirect_target(&words),
+                Some(program) if program == "tee" => tee_target(&words).or_else(|| redirect_target(&words)),
+                _ => None,
+            };
+            if let Some((path, append)) = target {
+                let content = format!("{}\n", body.join("\n"));
+                changes.push(ctx.change(TOOL, &path, if append { "append" } else { "write" }, &content, None));
+            }
+            continue;
+        }
+
+        for segment in split_commands(line) {
+            if parse_command(&segment.text).map(|c| c.program).as_deref() != Some("echo") {
+                continue;
+            }
+            let words = command_words(&segment.text);
+            let Some((path, append)) = redirect_target(&words) else { continue };
+            let text: Vec<&str> = words
+                .words
+                .iter()
+                .skip_while(|w| w.rsplit('/').next() != Some("echo"))
+                .skip(1)
+                .map(String::as_str)
+                .skip_while(|w| matches!(*w, "-e" | "-n" | "-en" | "-ne"))
+                .collect();
+            changes.push(ctx.change(TOOL, &path, if append { "append" } else { "write" }, &format!("{}\n", text.join(" ")), None));
         }
     }
 
     changes
 }
 
+/// Extract file changes from one tool call
+fn tool_use_changes(name: &str, input: &Value, ctx: &ChangeContext) -> Vec<FileChange> {
+    let str_field = |key: &str| input.get(key).and_then(|v| v.as_str());
+
+    match name {
+        "Write" => match (str_field("file_path"), str_field("content")) {
+            (Some(path), Some(content)) => vec![ctx.change(name, path, "write", content, None)],
+            _ => Vec::new(),
+        },
+        "Edit" => match (str_field("file_path"), str_field("new_string"), str_field("old_string")) {
+            (Some(path), Some(new), Some(old)) => {
+                let mut change = ctx.change(name, path, "edit", new, Some(old));
+                change.replace_all = input.get("replace_all").and_then(|v| v.as_bool()).unwrap_or(false);
+                vec![change]
+            }
+            _ => Vec::new(),
+        },
+        // One change per hunk, in the order they are applied
+        "MultiEdit" => {
+            let Some(path) = str_field("file_path") else { return Vec::new() };
+            input
+                .get("edits")
+                .and_then(|e| e.as_array())
+                .map(|edits| {
+                    edits
+                        .iter()
+                        .filter_map(|edit| {
+                            let new = edit.get("new_string")?.as_str()?;
+                            let old = edit.get("old_string")?.as_str()?;
+                            let mut change = ctx.change(name, path, "edit", new, Some(old));
+                            change.replace_all = edit.get("replace_all").and_then(|v| v.as_bool()).unwrap_or(false);
+                            Some(change)
+                        })
+                        .collect()
+                })
+                .unwrap_or_default()
+        }
+        "NotebookEdit" => {
+            let Some(path) = str_field("notebook_path") else { return Vec::new() };
+            let edit_mode = str_field("edit_mode").unwrap_or("replace");
+            let mut change = ctx.change(name, path, "notebook", str_field("new_source").unwrap_or(""), None);
+            change.notebook_cell = Some(NotebookCellEdit {
+                cell_id: str_field("cell_id").map(String::from),
+                cell_type: str_field("cell_type").map(String::from),
+                edit_mode: edit_mode.to_string(),
+            });
+            vec![change]
+        }
+        "apply_patch" => patch_text(input).map(|p| apply_patch_changes(&p, ctx)).unwrap_or_default(),
+        name if SHELL_TOOLS.contains(&name) => {
+            let Some(script) = shell_script(input) else { return Vec::new() };
+            let ctx = ChangeContext { cwd: str_field("workdir").or(ctx.cwd), ..*ctx };
+            if script.contains("*** Begin Patch") {
+                apply_patch_changes(&script, &ctx)
+            } else {
+                bash_redirect_changes(&script, &ctx)
+            }
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Extract file changes from a message's content
+fn extract_file_changes_from_message(content: &Value, ctx: &ChangeContext) -> Vec<FileChange> {
+    match content {
+        Value::Array(blocks) => extract_file_changes_from_blocks(blocks, ctx),
+        _ => Vec::new(),
+    }
+}
+
+/// Extract file changes from the tool calls among content blocks
+fn extract_file_changes_from_blocks(blocks: &[Value], ctx: &ChangeContext) -> Vec<FileChange> {
+    blocks
+        .iter()
+        .filter(|block| block.get("type").and_then(|v| v.as_str()) == Some("tool_use"))
+        .flat_map(|block| {
+            let name = block.get("name").and_then(|v| v.as_str()).unwrap_or("");
+            tool_use_changes(name, block.get("input").unwrap_or(&Value::Null), ctx)
+        })
+        .collect()
+}
+
+/// Extract file changes from a single JSONL log line
+pub(crate) fn extract_file_changes_from_line(line: &str, session_id: &str, project_id: &str) -> Vec<FileChange> {
+    let Ok(entry) = serde_json::from_str::<Value>(line) else {
+        return Vec::new();
+    };
+
+    if let (Some(uuid), Some(timestamp), Some(content)) = (
+        entry.get("uuid").and_then(|v| v.as_str()),
+        entry.get("timestamp").and_then(|v| v.as_str()),
+        entry.get("message").and_then(|v| v.get("content")),
+    ) {
+        let ctx = ChangeContext {
+            message_uuid: uuid,
+            timestamp,
+            session_id,
+            project_id,
+            cwd: entry.get("cwd").and_then(|v| v.as_str()),
+        };
+        extract_file_changes_from_message(content, &ctx)
+    } else {
+        Vec::new()
+    }
+}
+
+/// File changes in a Codex rollout: `apply_patch` calls and shell redirections
+fn extract_codex_file_changes(path: &Path, session_id: &str, project_id: &str, cwd: &str) -> Vec<FileChange> {
+    let Ok(file) = fs::File::open(path) else { return Vec::new() };
+    use std::io::BufRead;
+
+    let mut changes = Vec::new();
+    for line in std::io::BufReader::new(file).lines().map_while(Result::ok) {
+        if !line.contains("\"function_call\"") && !line.contains("\"custom_tool_call\"") {
+            continue;
+        }
+        let Ok(entry) = serde_json::from_str::<Value>(&line) else { continue };
+        let Some(payload) = entry.get("payload") else { continue };
+        let Some(block) = codex_tool_call_block(payload) else { continue };
+
+        let call_id = payload.get("call_id").and_then(|v| v.as_str()).unwrap_or("");
+        let ctx = ChangeContext {
+            message_uuid: call_id,
+            timestamp: entry.get("timestamp").and_then(|v| v.as_str()).unwrap_or(""),
+            session_id,
+            project_id,
+            cwd: Some(cwd),
+        };
+        changes.extend(extract_file_changes_from_blocks(&[block], &ctx));
+    }
+
+    changes
+}
+
+/// File changes of every Codex session, optionally of one project only
+fn codex_file_changes(project_filter: Option<&str>) -> Vec<FileChange> {
+    let mut changes = Vec::new();
+
+    for (root, path) in codex_session_files() {
+        let Some((local_project_id, session_id, cwd)) = codex_session_identity(&path) else { continue };
+        let project_id = root.qualify_id(&local_project_id);
+        if project_filter.map(|p| p != project_id).unwrap_or(false) {
+            continue;
+        }
+        changes.extend(extract_codex_file_changes(&path, &session_id, &project_id, &cwd));
+    }
+
+    changes
+}
+
+/// File changes made in one Codex session, in transcript order
+pub(crate) fn codex_session_file_changes(project_id: &str, session_id: &str) -> Result<Vec<FileChange>, String> {
+    let path = find_codex_session_file(project_id, session_id)?;
+    let (_, _, cwd) = codex_session_identity(&path).ok_or_else(|| "Session not found".to_string())?;
+    Ok(extract_codex_file_changes(&path, session_id, project_id, &cwd))
+}
+
+/// File changes made by already-loaded transcript messages
+pub(crate) fn file_changes_from_messages(messages: &[Message], session_id: &str, project_id: &str) -> Vec<FileChange> {
+    messages
+        .iter()
+        .flat_map(|m| {
+            let ctx = ChangeContext {
+                message_uuid: &m.uuid,
+                timestamp: &m.timestamp,
+                session_id,
+                project_id,
+                cwd: None,
+            };
+            extract_file_changes_from_blocks(&m.content, &ctx)
+        })
+        .collect()
+}
+
 /// Read session JSONL and extract all file changes
 async fn get_changes_from_session(
     session_path: &Path,
@@ -236,22 +612,7 @@ async fn get_changes_from_session(
             continue;
         }
 
-        if let Ok(entry) = serde_json::from_str::<serde_json::Value>(&line) {
-            if let (Some(uuid), Some(timestamp), Some(content)) = (
-                entry.get("uuid").and_then(|v| v.as_str()),
-                entry.get("timestamp").and_then(|v| v.as_str()),
-                entry.get("message").and_then(|v| v.get("content")),
-            ) {
-                let file_changes = extract_file_changes_from_message(
-                    uuid,
-                    content,
-                    timestamp,
-                    session_id,
-                    project_id,
-                );
-                changes.extend(file_changes);
-            }
-        }
+        changes.extend(extract_file_changes_from_line(&line, session_id, project_id));
     }
 
     // Sort by timestamp descending (newest first)
@@ -260,64 +621,148 @@ async fn get_changes_from_session(
     Ok(changes)
 }
 
-// ============================================================================
-// Tauri Commands
-// ============================================================================
+/// File changes made in one Claude Code or Codex session, newest first
+pub(crate) async fn session_file_changes(project_id: &str, session_id: &str) -> Result<Vec<FileChange>, String> {
+    let session_path = claude_project_dir(project_id)?.join(format!("{}.jsonl", session_id));
+    if session_path.exists() {
+        return get_changes_from_session(&session_path, session_id, project_id).await;
+    }
 
-/// Get all file changes across all sessions
-#[tauri::command]
-pub async fn get_all_file_changes() -> Result<Vec<FileChange>, String> {
-    let projects_dir = get_projects_dir()?;
+    let mut changes = codex_session_file_changes(project_id, session_id)?;
+    changes.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    Ok(changes)
+}
 
-    if !projects_dir.exists() {
-        return Ok(Vec::new());
+/// Summary of a Claude Code session, or the start of its first prompt
+fn claude_session_summary(session_path: &Path) -> String {
+    let mut summary = String::new();
+    let mut first_user_message = String::new();
+    if let Ok(file) = fs::File::open(session_path) {
+        use std::io::{BufRead, BufReader};
+        let reader = BufReader::new(file);
+        for line in reader.lines().flatten() {
+            if line.contains("\"type\":\"summary\"") {
+                if let Ok(val) = serde_json::from_str::<serde_json::Value>(&line) {
+                    if let Some(s) = val.get("summary").and_then(|v| v.as_str()) {
+                        summary = s.to_string();
+                        break;
+                    }
+                }
+            }
+            if summary.is_empty() && first_user_message.is_empty() && line.contains("\"type\":\"user\"") {
+                if let Ok(val) = serde_json::from_str::<serde_json::Value>(&line) {
+                    if let Some(message) = val.get("message") {
+                        if let Some(content) = message.get("content") {
+                            match content {
+                                Value::String(s) => {
+                                    first_user_message = s.chars().take(100).collect();
+                                }
+                                Value::Array(arr) => {
+                                    for block in arr {
+                                        if block.get("type").and_then(|v| v.as_str()) == Some("text") {
+                                            if let Some(text) = block.get("text").and_then(|v| v.as_str()) {
+                                                first_user_message = text.chars().take(100).collect();
+                                                break;
+                                            }
+                                        }
+                                    }
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    if summary.is_empty() {
+        summary = first_user_message;
     }
+    summary
+}
 
-    let mut all_changes = Vec::new();
+/// Summary of a Codex session
+async fn codex_session_summary(session_path: &Path) -> String {
+    read_codex_session_data(session_path)
+        .await
+        .map(|(_, _, summary, _)| summary)
+        .unwrap_or_default()
+}
 
-    // Iterate through projects
-    for project_entry in fs::read_dir(&projects_dir)
-        .map_err(|e| format!("Failed to read projects: {}", e))?
-    {
-        let project_entry = project_entry.map_err(|e| format!("Failed to read entry: {}", e))?;
-        let project_path = project_entry.path();
+/// Changes of one session grouped by file
+fn group_session_changes(
+    session_id: String,
+    project_id: String,
+    session_path: &Path,
+    summary: String,
+    changes: Vec<FileChange>,
+) -> SessionFileChanges {
+    let mut files_map: HashMap<String, Vec<FileChange>> = HashMap::new();
 
-        if !project_path.is_dir() {
-            continue;
-        }
+    for change in changes {
+        files_map
+            .entry(change.file_path.clone())
+            .or_insert_with(Vec::new)
+            .push(change);
+    }
 
-        let project_id = project_path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("unknown")
-            .to_string();
-
-        // Iterate through sessions
-        for session_entry in fs::read_dir(&project_path)
-            .map_err(|e| format!("Failed to read sessions: {}", e))?
-        {
-            let session_entry = session_entry.map_err(|e| format!("Failed to read entry: {}", e))?;
-            let session_path = session_entry.path();
-
-            if session_path.is_file()
-                && session_path
-                    .extension()
-                    .map(|ext| ext == "jsonl")
-                    .unwrap_or(false)
-            {
-                let session_id = session_path
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .map(|s| s.trim_end_matches(".jsonl").to_string())
-                    .unwrap_or_default();
-
-                match get_changes_from_session(&session_path, &session_id, &project_id).await {
-                    Ok(changes) => all_changes.extend(changes),
-                    Err(e) => {
-                        log::warn!("Failed to extract changes from session: {}", e);
-                    }
+    let mut files = Vec::new();
+    let mut total_changes = 0;
+
+    for (file_path, file_changes) in files_map {
+        total_changes += file_changes.len();
+        files.push(SessionFileInfo {
+            file_path: file_path.clone(),
+            display_name: get_filename(&file_path),
+            change_count: file_changes.len(),
+            changes: file_changes,
+        });
+    }
+
+    SessionFileChanges {
+        session_id,
+        project_name: project_id.clone(),
+        project_id,
+        timestamp: get_file_modified_time(session_path).to_string(),
+        summary,
+        files,
+        change_count: total_changes,
+    }
+}
+
+/// File changes of the indexed sessions accepted by `include`, newest first
+fn indexed_file_changes(include: impl Fn(&IndexedSession) -> bool) -> Result<Vec<FileChange>, String> {
+    // Only lines with Write/Edit tool calls need to be read from disk
+    let candidates: Vec<(String, String, String, Vec<u64>)> = with_index(|index| {
+        index
+            .sessions
+            .values()
+            .filter(|session| include(session))
+            .filter_map(|session| {
+                let offsets: Vec<u64> = session
+                    .entries
+                    .iter()
+                    .filter(|e| e.uuid.is_some() && e.uses_any_tool(FILE_CHANGE_TOOLS))
+                    .map(|e| e.offset)
+                    .collect();
+                if offsets.is_empty() {
+                    return None;
                 }
-            }
+                Some((
+                    session.file_path.clone(),
+                    session.session_id.clone(),
+                    session.project_id.clone(),
+                    offsets,
+                ))
+            })
+            .collect()
+    })?;
+
+    let mut all_changes = Vec::new();
+
+    for (file_path, session_id, project_id, offsets) in candidates {
+        for line in read_lines_at(Path::new(&file_path), &offsets) {
+            all_changes.extend(extract_file_changes_from_line(&line, &session_id, &project_id));
         }
     }
 
@@ -327,6 +772,27 @@ pub async fn get_all_file_changes() -> Result<Vec<FileChange>, String> {
     Ok(all_changes)
 }
 
+/// File changes made in every session of one project, newest first
+pub(crate) fn project_file_changes(project_id: &str) -> Result<Vec<FileChange>, String> {
+    let mut changes = indexed_file_changes(|session| session.project_id == project_id)?;
+    changes.extend(codex_file_changes(Some(project_id)));
+    changes.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    Ok(changes)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Get all file changes across all sessions
+#[tauri::command]
+pub async fn get_all_file_changes() -> Result<Vec<FileChange>, String> {
+    let mut all_changes = indexed_file_changes(|_| true)?;
+    all_changes.extend(codex_file_changes(None));
+    all_changes.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    Ok(all_changes)
+}
+
 /// Get file history stats
 #[tauri::command]
 pub async fn get_file_history_stats() -> Result<FileHistoryStats, String> {
@@ -410,144 +876,34 @@ pub async fn get_file_histories() -> Result<Vec<FileHistory>, String> {
 /// Get all sessions that have file changes
 #[tauri::command]
 pub async fn get_sessions_with_file_changes() -> Result<Vec<SessionFileChanges>, String> {
-    let projects_dir = get_projects_dir()?;
-
-    if !projects_dir.exists() {
-        return Ok(Vec::new());
+    let mut by_session: HashMap<(String, String), Vec<FileChange>> = HashMap::new();
+    for change in get_all_file_changes().await? {
+        by_session
+            .entry((change.project_id.clone(), change.session_id.clone()))
+            .or_default()
+            .push(change);
     }
 
-    let mut result = Vec::new();
-
-    // Iterate through projects
-    for project_entry in fs::read_dir(&projects_dir)
-        .map_err(|e| format!("Failed to read projects: {}", e))?
-    {
-        let project_entry = project_entry.map_err(|e| format!("Failed to read entry: {}", e))?;
-        let project_path = project_entry.path();
+    let codex_paths: HashMap<(String, String), PathBuf> = codex_session_files()
+        .into_iter()
+        .filter_map(|(root, path)| {
+            let (project_id, session_id, _) = codex_session_identity(&path)?;
+            Some(((root.qualify_id(&project_id), session_id), path))
+        })
+        .collect();
 
-        if !project_path.is_dir() {
+    let mut result = Vec::new();
+    for ((project_id, session_id), changes) in by_session {
+        let claude_path = claude_project_dir(&project_id)?.join(format!("{}.jsonl", session_id));
+        let (session_path, summary) = if claude_path.exists() {
+            let summary = claude_session_summary(&claude_path);
+            (claude_path, summary)
+        } else if let Some(path) = codex_paths.get(&(project_id.clone(), session_id.clone())) {
+            (path.clone(), codex_session_summary(path).await)
+        } else {
             continue;
-        }
-
-        let project_id = project_path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("unknown")
-            .to_string();
-
-        let project_name = project_id.clone();
-
-        // Iterate through sessions
-        for session_entry in fs::read_dir(&project_path)
-            .map_err(|e| format!("Failed to read sessions: {}", e))?
-        {
-            let session_entry = session_entry.map_err(|e| format!("Failed to read entry: {}", e))?;
-            let session_path = session_entry.path();
-
-            if session_path.is_file()
-                && session_path
-                    .extension()
-                    .map(|ext| ext == "jsonl")
-                    .unwrap_or(false)
-            {
-                let session_id = session_path
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .map(|s| s.trim_end_matches(".jsonl").to_string())
-                    .unwrap_or_default();
-
-                // Get summary if possible
-                let mut summary = String::new();
-                let mut first_user_message = String::new();
-                if let Ok(file) = fs::File::open(&session_path) {
-                    use std::io::{BufRead, BufReader};
-                    let reader = BufReader::new(file);
-                    for line in reader.lines().flatten() {
-                        if line.contains("\"type\":\"summary\"") {
-                            if let Ok(val) = serde_json::from_str::<serde_json::Value>(&line) {
-                                if let Some(s) = val.get("summary").and_then(|v| v.as_str()) {
-                                    summary = s.to_string();
-                                    break;
-                                }
-                            }
-                        }
-                        if summary.is_empty() && first_user_message.is_empty() && line.contains("\"type\":\"user\"") {
-                            if let Ok(val) = serde_json::from_str::<serde_json::Value>(&line) {
-                                if let Some(message) = val.get("message") {
-                                    if let Some(content) = message.get("content") {
-                                        match content {
-                                            Value::String(s) => {
-                                                first_user_message = s.chars().take(100).collect();
-                                            }
-                                            Value::Array(arr) => {
-                                                for block in arr {
-                                                    if block.get("type").and_then(|v| v.as_str()) == Some("text") {
-                                                        if let Some(text) = block.get("text").and_then(|v| v.as_str()) {
-                                                            first_user_message = text.chars().take(100).collect();
-                                                            break;
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                            _ => {}
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-                if summary.is_empty() {
-                    summary = first_user_message;
-                }
-
-                match get_changes_from_session(&session_path, &session_id, &project_id).await {
-                    Ok(changes) if !changes.is_empty() => {
-                        // Group changes by file
-                        let mut files_map: HashMap<String, Vec<FileChange>> = HashMap::new();
-
-                        for change in changes {
-                            files_map
-                                .entry(change.file_path.clone())
-                                .or_insert_with(Vec::new)
-                                .push(change);
-                        }
-
-                        let mut files = Vec::new();
-                        let mut total_changes = 0;
-
-                        for (file_path, file_changes) in files_map {
-                            total_changes += file_changes.len();
-                            files.push(SessionFileInfo {
-                                file_path: file_path.clone(),
-                                display_name: get_filename(&file_path),
-                                change_count: file_changes.len(),
-                                changes: file_changes,
-                            });
-                        }
-
-                        if !files.is_empty() {
-                            result.push(SessionFileChanges {
-                                session_id,
-                                project_id: project_id.clone(),
-                                project_name: project_name.clone(),
-                                timestamp: session_path
-                                    .metadata()
-                                    .ok()
-                                    .and_then(|m| m.modified().ok())
-                                    .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-                                    .map(|d| d.as_millis().to_string())
-                                    .unwrap_or_default(),
-                                summary,
-                                files,
-                                change_count: total_changes,
-                            });
-                        }
-                    }
-                    _ => {}
-                }
-            }
-        }
+        };
+        result.push(group_session_changes(session_id, project_id, &session_path, summary, changes));
     }
 
     // Sort by timestamp descending
@@ -562,100 +918,19 @@ pub async fn get_session_file_changes(
     project_id: String,
     session_id: String,
 ) -> Result<SessionFileChanges, String> {
-    let projects_dir = get_projects_dir()?;
-    let session_path = projects_dir
-        .join(&project_id)
-        .join(format!("{}.jsonl", session_id));
-
-    if !session_path.exists() {
-        return Err("Session not found".to_string());
-    }
-
-    let changes = get_changes_from_session(&session_path, &session_id, &project_id).await?;
-
-    // Get summary if possible
-    let mut summary = String::new();
-    let mut first_user_message = String::new();
-    if let Ok(file) = fs::File::open(&session_path) {
-        use std::io::{BufRead, BufReader};
-        let reader = BufReader::new(file);
-        for line in reader.lines().flatten() {
-            if line.contains("\"type\":\"summary\"") {
-                if let Ok(val) = serde_json::from_str::<serde_json::Value>(&line) {
-                    if let Some(s) = val.get("summary").and_then(|v| v.as_str()) {
-                        summary = s.to_string();
-                        break;
-                    }
-                }
-            }
-            if summary.is_empty() && first_user_message.is_empty() && line.contains("\"type\":\"user\"") {
-                if let Ok(val) = serde_json::from_str::<serde_json::Value>(&line) {
-                    if let Some(message) = val.get("message") {
-                        if let Some(content) = message.get("content") {
-                            match content {
-                                Value::String(s) => {
-                                    first_user_message = s.chars().take(100).collect();
-                                }
-                                Value::Array(arr) => {
-                                    for block in arr {
-                                        if block.get("type").and_then(|v| v.as_str()) == Some("text") {
-                                            if let Some(text) = block.get("text").and_then(|v| v.as_str()) {
-                                                first_user_message = text.chars().take(100).collect();
-                                                break;
-                                            }
-                                        }
-                                    }
-                                }
-                                _ => {}
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
-    if summary.is_empty() {
-        summary = first_user_message;
-    }
-
-    // Group by file
-    let mut files_map: HashMap<String, Vec<FileChange>> = HashMap::new();
-
-    for change in changes {
-        files_map
-            .entry(change.file_path.clone())
-            .or_insert_with(Vec::new)
-            .push(change);
-    }
-
-    let mut files = Vec::new();
-    let mut total_changes = 0;
-
-    for (file_path, file_changes) in files_map {
-        total_changes += file_changes.len();
-        files.push(SessionFileInfo {
-            file_path: file_path.clone(),
-            display_name: get_filename(&file_path),
-            change_count: file_changes.len(),
-            changes: file_changes,
-        });
-    }
-
-    Ok(SessionFileChanges {
-        session_id,
-        project_name: project_id.clone(),
-        project_id,
-        timestamp: session_path
-            .metadata()
-            .ok()
-            .and_then(|m: std::fs::Metadata| m.modified().ok())
-            .and_then(|t: std::time::SystemTime| t.duration_since(std::time::UNIX_EPOCH).ok())
-            .map(|d: std::time::Duration| d.as_millis().to_string())
-            .unwrap_or_default(),
-        summary,
-        files,
-        change_count: total_changes,
-    })
+    let claude_path = claude_project_dir(&project_id)?.join(format!("{}.jsonl", session_id));
+    let (session_path, summary) = if claude_path.exists() {
+        let summary = claude_session_summary(&claude_path);
+        (claude_path, summary)
+    } else {
+        let path = find_codex_session_file(&project_id, &session_id)?;
+        let summary = codex_session_summary(&path).await;
+        (path, summary)
+    };
+
+    let changes = session_file_changes(&project_id, &session_id).await?;
+
+    Ok(group_session_changes(session_id, project_id, &session_path, summary, changes))
 }
 
 /// Get a specific file change by hash
@@ -665,12 +940,7 @@ pub async fn get_file_change_by_hash(
     session_id: String,
     hash: String,
 ) -> Result<FileDiff, String> {
-    let projects_dir = get_projects_dir()?;
-    let session_path = projects_dir
-        .join(&project_id)
-        .join(format!("{}.jsonl", session_id));
-
-    let changes = get_changes_from_session(&session_path, &session_id, &project_id).await?;
+    let changes = session_file_changes(&project_id, &session_id).await?;
 
     let change = changes
         .into_iter()
@@ -689,3 +959,64 @@ pub async fn get_file_change_by_hash(
         previous_hash: None,
     })
 }
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn ctx() -> ChangeContext<'static> {
+        ChangeContext {
+            message_uuid: "m",
+            timestamp: "2025-11-01T10:00:00Z",
+            session_id: "s",
+            project_id: "p",
+            cwd: Some("/repo"),
+        }
+    }
+
+    #[test]
+    fn test_apply_patch_envelope() {
+        let patch = "*** Begin Patch\n*** Add File: src/new.rs\n+fn new() {}\n*** Update File: src/lib.rs\n@@ fn main\n fn main() {\n-    old();\n+    new();\n }\n*** Delete File: src/old.rs\n*** End Patch\n";
+        let changes = tool_use_changes("apply_patch", &json!({ "raw": patch }), &ctx());
+
+        let summary: Vec<(&str, &str)> = changes.iter().map(|c| (c.file_path.as_str(), c.change_type.as_str())).collect();
+        assert_eq!(summary, vec![("/repo/src/new.rs", "write"), ("/repo/src/lib.rs", "edit"), ("/repo/src/old.rs", "delete")]);
+        assert_eq!(changes[0].content, "fn new() {}\n");
+        assert_eq!(changes[1].old_content.as_deref(), Some("fn main() {\n    old();\n}"));
+        assert_eq!(changes[1].content, "fn main() {\n    new();\n}");
+    }
+
+    #[test]
+    fn test_multi_edit_and_bash_redirects() {
+        let input = json!({
+            "file_path": "/repo/a.rs",
+            "edits": [{ "old_string": "a", "new_string": "b" }, { "old_string": "c", "new_string": "d", "replace_all": true }]
+        });
+        let changes = tool_use_changes("MultiEdit", &input, &ctx());
+        assert_eq!(changes.len(), 2);
+        assert!(changes[1].replace_all);
+
+        let script = "mkdir -p docs && cat > docs/notes.md <<'EOF'\n# Notes\nline\nEOF\necho \"done\" >> log.txt 2>&1";
+        let changes = tool_use_changes("Bash", &json!({ "command": script }), &ctx());
+        let summary: Vec<(&str, &str, &str)> =
+            changes.iter().map(|c| (c.file_path.as_str(), c.change_type.as_str(), c.content.as_str())).collect();
+        assert_eq!(
+            summary,
+            vec![("/repo/docs/notes.md", "write", "# Notes\nline\n"), ("/repo/log.txt", "append", "done\n")]
+        );
+    }
+
+    #[test]
+    fn test_bash_redirects_respect_quoting() {
+        let script = "echo \"a -> b\" > notes.txt && echo 'x => y' >> out.txt; echo \"1 > 2\"\n./allocate.sh > run.log <<EOF\ninput\nEOF\nconcat <<EOF | tee copy.txt\nbody\nEOF";
+        let changes = tool_use_changes("Bash", &json!({ "command": script }), &ctx());
+        let summary: Vec<(&str, &str, &str)> =
+            changes.iter().map(|c| (c.file_path.as_str(), c.change_type.as_str(), c.content.as_str())).collect();
+        assert_eq!(summary, vec![("/repo/notes.txt", "write", "a -> b\n"), ("/repo/out.txt", "append", "x => y\n")]);
+    }
+}
@@ -0,0 +1,550 @@
+/**
+ * Git correlation
+ *
+ * Matches the Write/Edit changes made in a project's sessions against the
+ * commits of the project's local repository, to tell which agent edits landed.
+ * A change matches a commit when the commit touches the same file within a
+ * time window after the change and adds most of the lines the change added
+ * (compared by hash of the trimmed line). Each change ends up:
+ * - committed: found in a commit and not removed again by a later one
+ * - reverted: removed by a later commit, or never committed and gone from the working tree
+ * - uncommitted: not in any commit but still present in the working tree
+ */
+
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::data_roots::resolve_qualified_id;
+use super::files::{project_file_changes, FileChange};
+use super::transcripts::find_project_cwd;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+pub const STATUS_COMMITTED: &str = "committed";
+pub const STATUS_UNCOMMITTED: &str = "uncommitted";
+pub const STATUS_REVERTED: &str = "reverted";
+
+/// Commits this long after a change can still carry it
+const COMMIT_WINDOW_MS: i64 = 14 * 24 * 60 * 60 * 1000;
+
+/// Tolerated clock skew between transcript and commit timestamps
+pub(crate) const CLOCK_SKEW_MS: i64 = 10 * 60 * 1000;
+
+/// Share of a change's lines a commit must contain to match
+const MIN_MATCH_SCORE: f64 = 0.5;
+
+/// Lines shorter than this (braces, blank lines) say nothing about where a change landed
+const MIN_LINE_LENGTH: usize = 4;
+
+const MAX_COMMITS: usize = 2000;
+
+const RECORD_SEPARATOR: char = '\u{1e}';
+const FIELD_SEPARATOR: char = '\u{1f}';
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+#[derive(Debug, Clone, Default)]
+pub struct CommitFileDiff {
+    /// Path relative to the repository root
+    pub path: String,
+    pub added: HashSet<u64>,
+    pub removed: HashSet<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GitCommit {
+    pub hash: String,
+    pub author: String,
+    pub timestamp: String,
+    pub time_ms: i64,
+    pub subject: String,
+    pub files: Vec<CommitFileDiff>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeGitStatus {
+    pub hash: String,
+    pub message_uuid: String,
+    pub file_path: String,
+    pub timestamp: String,
+    /// "committed", "uncommitted" or "reverted"
+    pub status: String,
+    /// Commit the change landed in
+    pub commit: Option<String>,
+    /// Later commit that removed the change again
+    pub reverted_by: Option<String>,
+    /// Share of the change's lines found in `commit`
+    pub match_score: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionGitStatus {
+    pub session_id: String,
+    pub committed: usize,
+    pub uncommitted: usize,
+    pub reverted: usize,
+    /// Commits holding changes of this session, oldest first
+    pub commits: Vec<String>,
+    pub changes: Vec<ChangeGitStatus>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitSessionLink {
+    pub session_id: String,
+    pub change_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitLink {
+    pub hash: String,
+    pub short_hash: String,
+    pub author: String,
+    pub timestamp: String,
+    pub subject: String,
+    pub files: Vec<String>,
+    /// Sessions whose changes landed in this commit
+    pub sessions: Vec<CommitSessionLink>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitCorrelation {
+    pub project_id: String,
+    pub repo_root: String,
+    pub sessions: Vec<SessionGitStatus>,
+    /// Commits in the scanned window, newest first
+    pub commits: Vec<CommitLink>,
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+pub(crate) fn line_hash(line: &str) -> Option<u64> {
+    let trimmed = line.trim();
+    if trimmed.chars().count() < MIN_LINE_LENGTH {
+        return None;
+    }
+    let mut hasher = DefaultHasher::new();
+    trimmed.hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+pub(crate) fn line_hashes(text: &str) -> HashSet<u64> {
+    text.lines().filter_map(line_hash).collect()
+}
+
+pub(crate) fn parse_time_ms(ts: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc3339(ts).ok().map(|t| t.timestamp_millis())
+}
+
+/// Hashes of the lines a change introduced (not already in the text it replaced)
+pub(crate) fn added_lines(change: &FileChange) -> HashSet<u64> {
+    let new = line_hashes(&change.content);
+    let old = change.old_content.as_deref().map(line_hashes).unwrap_or_default();
+    new.difference(&old).copied().collect()
+}
+
+/// Lines that identify a change, and whether they were added (true) or removed (false)
+fn signature(change: &FileChange) -> (HashSet<u64>, bool) {
+    let added = added_lines(change);
+    if !added.is_empty() || change.change_type == "write" {
+        return (added, true);
+    }
+    let new = line_hashes(&change.content);
+    let old = change.old_content.as_deref().map(line_hashes).unwrap_or_default();
+    (old.difference(&new).copied().collect(), false)
+}
+
+fn score(signature: &HashSet<u64>, lines: &HashSet<u64>) -> f64 {
+    if signature.is_empty() {
+        return 0.0;
+    }
+    signature.intersection(lines).count() as f64 / signature.len() as f64
+}
+
+fn diff_path(raw: &str) -> Option<String> {
+    let raw = raw.trim().trim_matches('"');
+    if raw == "/dev/null" {
+        return None;
+    }
+    Some(raw.strip_prefix("b/").or_else(|| raw.strip_prefix("a/")).unwrap_or(raw).to_string())
+}
+
+/// Parse `git log -p --unified=0` output written with RECORD/FIELD separators
+pub fn parse_git_log(output: &str) -> Vec<GitCommit> {
+    let mut commits = Vec::new();
+
+    for record in output.split(RECORD_SEPARATOR).filter(|r| !r.trim().is_empty()) {
+        let mut lines = record.lines();
+        let header: Vec<&str> = lines.next().unwrap_or("").split(FIELD_SEPARATOR).collect();
+        if header.len() < 4 {
+            continue;
+        }
+
+        let mut files: Vec<CommitFileDiff> = Vec::new();
+        let mut in_header = false;
+        let mut old_path: Option<String> = None;
+
+        for line in lines {
+            if line.starts_with("diff --git ") {
+                in_header = true;
+                old_path = None;
+                files.push(CommitFileDiff::default());
+                continue;
+            }
+            let Some(file) = files.last_mut() else { continue };
+
+            if in_header {
+                if let Some(path) = line.strip_prefix("--- ") {
+                    old_path = diff_path(path);
+                } else if let Some(path) = line.strip_prefix("+++ ") {
+                    file.path = diff_path(path).or_else(|| old_path.clone()).unwrap_or_default();
+                } else if line.starts_with("@@") {
+                    in_header = false;
+                }
+                continue;
+            }
+
+            if let Some(added) = line.strip_prefix('+') {
+                file.added.extend(line_hash(added));
+            } else if let Some(removed) = line.strip_prefix('-') {
+                file.removed.extend(line_hash(removed));
+            }
+        }
+
+        files.retain(|f| !f.path.is_empty());
+        commits.push(GitCommit {
+            hash: header[0].trim().to_string(),
+            author: header[1].to_string(),
+            timestamp: header[2].to_string(),
+            time_ms: parse_time_ms(header[2]).unwrap_or(0),
+            subject: header[3].to_string(),
+            files,
+        });
+    }
+
+    commits
+}
+
+fn file_diff<'a>(commit: &'a GitCommit, relative_path: &str) -> Option<&'a CommitFileDiff> {
+    commit.files.iter().find(|f| f.path == relative_path)
+}
+
+pub(crate) fn git(repo: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(repo)
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("git {} failed: {}", args[0], String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Top-level directory of the repository containing `cwd`
+pub(crate) fn repo_root(cwd: &Path) -> Option<PathBuf> {
+    git(cwd, &["rev-parse", "--show-toplevel"]).ok().map(|root| PathBuf::from(root.trim()))
+}
+
+/// Status of one change given the commits (oldest first) and the working tree
+pub fn classify_change(
+    change: &FileChange,
+    relative_path: &str,
+    commits: &[GitCommit],
+    working_tree: Option<&HashSet<u64>>,
+) -> ChangeGitStatus {
+    let (lines, added) = signature(change);
+    let change_ms = parse_time_ms(&change.timestamp).unwrap_or(0);
+
+    let mut landed: Option<(&GitCommit, f64)> = None;
+    for commit in commits
+        .iter()
+        .filter(|c| c.time_ms >= change_ms - CLOCK_SKEW_MS && c.time_ms <= change_ms + COMMIT_WINDOW_MS)
+    {
+        let Some(diff) = file_diff(commit, relative_path) else { continue };
+        let s = if lines.is_empty() { 1.0 } else { score(&lines, if added { &diff.added } else { &diff.removed }) };
+        if s >= MIN_MATCH_SCORE {
+            landed = Some((commit, s));
+            break;
+        }
+    }
+
+    let mut status = ChangeGitStatus {
+        hash: change.hash.clone(),
+        message_uuid: change.message_uuid.clone(),
+        file_path: change.file_path.clone(),
+        timestamp: change.timestamp.clone(),
+        status: STATUS_UNCOMMITTED.to_string(),
+        commit: None,
+        reverted_by: None,
+        match_score: 0.0,
+    };
+
+    match landed {
+        Some((commit, s)) => {
+            status.commit = Some(commit.hash.clone());
+            status.match_score = s;
+            status.status = STATUS_COMMITTED.to_string();
+
+            // Added lines taken out again (or removed lines brought back) by a later commit
+            if !lines.is_empty() {
+                let undone = commits.iter().filter(|c| c.time_ms > commit.time_ms).find(|c| {
+                    file_diff(c, relative_path)
+                        .map(|d| score(&lines, if added { &d.removed } else { &d.added }) >= MIN_MATCH_SCORE)
+                        .unwrap_or(false)
+                });
+                if let Some(undone) = undone {
+                    status.status = STATUS_REVERTED.to_string();
+                    status.reverted_by = Some(undone.hash.clone());
+                }
+            }
+        }
+        None => {
+            let present = match working_tree {
+                Some(tree) if added => lines.is_empty() || score(&lines, tree) >= MIN_MATCH_SCORE,
+                Some(tree) => score(&lines, tree) < MIN_MATCH_SCORE,
+                // Deleted file: only a deletion can still be pending
+                None => !added,
+            };
+            if !present {
+                status.status = STATUS_REVERTED.to_string();
+            }
+        }
+    }
+
+    status
+}
+
+fn correlate(project_id: &str, session_filter: Option<&str>) -> Result<GitCorrelation, String> {
+    let (root, dir_name) = resolve_qualified_id(project_id)?;
+    if !root.is_local() {
+        return Err("Git correlation is only available for local projects".to_string());
+    }
+    let cwd = find_project_cwd(&root.claude_projects_dir().join(dir_name))
+        .ok_or_else(|| "Project working directory not found".to_string())?;
+    let repo_root = PathBuf::from(git(&cwd, &["rev-parse", "--show-toplevel"])?.trim());
+
+    let changes: Vec<FileChange> = project_file_changes(project_id)?
+        .into_iter()
+        .filter(|c| session_filter.map(|s| c.session_id == s).unwrap_or(true))
+        .filter(|c| Path::new(&c.file_path).starts_with(&repo_root))
+        .collect();
+
+    let since = changes
+        .iter()
+        .filter_map(|c| parse_time_ms(&c.timestamp))
+        .min()
+        .and_then(|ms| chrono::DateTime::from_timestamp_millis(ms - CLOCK_SKEW_MS))
+        .map(|t| t.to_rfc3339());
+
+    let commits = match &since {
+        Some(since) => {
+            let format = format!("--format={}%H{}%an{}%cI{}%s", RECORD_SEPARATOR, FIELD_SEPARATOR, FIELD_SEPARATOR, FIELD_SEPARATOR);
+            let max = format!("--max-count={}", MAX_COMMITS);
+            let since = format!("--since={}", since);
+            let log = git(
+                &repo_root,
+                &["log", "--all", "--no-merges", "--no-color", "--no-ext-diff", "-p", "--unified=0", &max, &since, &format],
+            )?;
+            parse_git_log(&log)
+        }
+        None => Vec::new(),
+    };
+    let mut oldest_first = commits.clone();
+    oldest_first.sort_by_key(|c| c.time_ms);
+
+    let mut working_trees: HashMap<String, Option<HashSet<u64>>> = HashMap::new();
+    let mut sessions: BTreeMap<String, Vec<ChangeGitStatus>> = BTreeMap::new();
+
+    for change in &changes {
+        let relative = Path::new(&change.file_path)
+            .strip_prefix(&repo_root)
+            .map(|p| p.to_string_lossy().replace('\\', "/"))
+            .unwrap_or_default();
+        let tree = working_trees
+            .entry(change.file_path.clone())
+            .or_insert_with(|| std::fs::read_to_string(&change.file_path).ok().map(|c| line_hashes(&c)));
+        let status = classify_change(change, &relative, &oldest_first, tree.as_ref());
+        sessions.entry(change.session_id.clone()).or_default().push(status);
+    }
+
+    let mut links: HashMap<String, HashMap<String, usize>> = HashMap::new();
+    let sessions: Vec<SessionGitStatus> = sessions
+        .into_iter()
+        .map(|(session_id, mut changes)| {
+            changes.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+            let count = |status: &str| changes.iter().filter(|c| c.status == status).count();
+
+            let mut session_commits: Vec<String> = Vec::new();
+            for commit in changes.iter().filter_map(|c| c.commit.as_ref()) {
+                *links.entry(commit.clone()).or_default().entry(session_id.clone()).or_insert(0) += 1;
+                if !session_commits.contains(commit) {
+                    session_commits.push(commit.clone());
+                }
+            }
+
+            SessionGitStatus {
+                committed: count(STATUS_COMMITTED),
+                uncommitted: count(STATUS_UNCOMMITTED),
+                reverted: count(STATUS_REVERTED),
+                commits: session_commits,
+                session_id,
+                changes,
+            }
+        })
+        .collect();
+
+    let commits = commits
+        .into_iter()
+        .map(|commit| {
+            let mut linked: Vec<CommitSessionLink> = links
+                .remove(&commit.hash)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(session_id, change_count)| CommitSessionLink { session_id, change_count })
+                .collect();
+            linked.sort_by_key(|l| std::cmp::Reverse(l.change_count));
+
+            CommitLink {
+                short_hash: commit.hash.chars().take(7).collect(),
+                hash: commit.hash,
+                author: commit.author,
+                timestamp: commit.timestamp,
+                subject: commit.subject,
+                files: commit.files.into_iter().map(|f| f.path).collect(),
+                sessions: linked,
+            }
+        })
+        .collect();
+
+    Ok(GitCorrelation {
+        project_id: project_id.to_string(),
+        repo_root: repo_root.to_string_lossy().to_string(),
+        sessions,
+        commits,
+    })
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Committed / uncommitted / reverted status of every session's changes, and the sessions behind each commit
+#[tauri::command]
+pub async fn get_project_git_correlation(project_id: String) -> Result<GitCorrelation, String> {
+    tokio::task::spawn_blocking(move || correlate(&project_id, None))
+        .await
+        .map_err(|e| format!("Git correlation task failed: {}", e))?
+}
+
+/// Committed / uncommitted / reverted status of one session's changes
+#[tauri::command]
+pub async fn get_session_git_status(project_id: String, session_id: String) -> Result<SessionGitStatus, String> {
+    let filter = session_id.clone();
+    let correlation = tokio::task::spawn_blocking(move || correlate(&project_id, Some(&filter)))
+        .await
+        .map_err(|e| format!("Git correlation task failed: {}", e))??;
+
+    Ok(correlation
+        .sessions
+        .into_iter()
+        .next()
+        .unwrap_or(SessionGitStatus {
+            session_id,
+            committed: 0,
+            uncommitted: 0,
+            reverted: 0,
+            commits: Vec::new(),
+            changes: Vec::new(),
+        }))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(old: &str, new: &str, timestamp: &str) -> FileChange {
+        FileChange {
+            hash: "h".to_string(),
+            session_id: "s".to_string(),
+            project_id: "p".to_string(),
+            file_path: "/repo/src/lib.rs".to_string(),
+            change_type: "edit".to_string(),
+            timestamp: timestamp.to_string(),
+            content: new.to_string(),
+            old_content: Some(old.to_string()),
+            message_uuid: "m".to_string(),
+            replace_all: false,
+            tool: "Edit".to_string(),
+            notebook_cell: None,
+        }
+    }
+
+    fn log() -> String {
+        let commit = |hash: &str, time: &str, subject: &str, patch: &str| {
+            format!("{r}{hash}{f}dev{f}{time}{f}{subject}\n\n{patch}", r = RECORD_SEPARATOR, f = FIELD_SEPARATOR)
+        };
+        [
+            commit(
+                "bbb",
+                "2025-11-03T10:00:00+00:00",
+                "Revert retry",
+                "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -2 +1,0 @@\n-    retry_with_backoff(request)?;\n",
+            ),
+            commit(
+                "aaa",
+                "2025-11-01T12:00:00+00:00",
+                "Add retry",
+                "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,0 +2 @@\n+    retry_with_backoff(request)?;\n",
+            ),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn test_parse_git_log() {
+        let commits = parse_git_log(&log());
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[1].hash, "aaa");
+        assert_eq!(commits[1].files[0].path, "src/lib.rs");
+        assert_eq!(commits[1].files[0].added.len(), 1);
+        assert_eq!(commits[0].files[0].removed.len(), 1);
+    }
+
+    #[test]
+    fn test_classify_committed_then_reverted() {
+        let mut commits = parse_git_log(&log());
+        commits.sort_by_key(|c| c.time_ms);
+        let change = edit("    send(request)?;", "    retry_with_backoff(request)?;", "2025-11-01T11:00:00Z");
+
+        let status = classify_change(&change, "src/lib.rs", &commits[..1], None);
+        assert_eq!(status.status, STATUS_COMMITTED);
+        assert_eq!(status.commit.as_deref(), Some("aaa"));
+
+        let status = classify_change(&change, "src/lib.rs", &commits, None);
+        assert_eq!(status.status, STATUS_REVERTED);
+        assert_eq!(status.reverted_by.as_deref(), Some("bbb"));
+
+        let late = edit("    send(request)?;", "    retry_with_backoff(request)?;", "2025-11-05T11:00:00Z");
+        let tree = line_hashes("fn main() {\n    retry_with_backoff(request)?;\n}\n");
+        assert_eq!(classify_change(&late, "src/lib.rs", &commits, Some(&tree)).status, STATUS_UNCOMMITTED);
+    }
+}
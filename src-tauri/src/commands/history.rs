@@ -1,7 +1,8 @@
 /**
  * History service commands
  *
- * Reads and processes command history from ~/.claude/history.jsonl
+ * Reads and processes command history from history.jsonl of every data root
+ * (~/.claude/history.jsonl locally)
  */
 
 use chrono::{DateTime, Local, Utc};
@@ -9,6 +10,9 @@ use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use tokio::io::{AsyncBufReadExt, BufReader};
 
+use super::data_roots::{data_roots, DataRoot};
+use super::sources::{codex_session_files, find_source, CLAUDE_CODE_SOURCE, CODEX_SOURCE};
+
 // ============================================================================
 // Type Definitions
 // ============================================================================
@@ -23,6 +27,11 @@ pub struct HistoryEntry {
     pub session_id: String,
     #[serde(default)]
     pub source: String, // "code" | "codex"
+    /// Data root and host the entry was read from
+    #[serde(default)]
+    pub root: String,
+    #[serde(default)]
+    pub host: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -86,11 +95,9 @@ fn format_timestamp(timestamp_ms: i64) -> (String, String) {
     }
 }
 
-async fn read_history_entries() -> Result<Vec<HistoryEntry>, String> {
-    let home_dir = dirs::home_dir()
-        .ok_or_else(|| "Could not determine home directory".to_string())?;
-
-    let history_path = home_dir.join(".claude").join("history.jsonl");
+/// Prompt history of one data root's history.jsonl
+async fn read_root_history_entries(root: &DataRoot) -> Result<Vec<HistoryEntry>, String> {
+    let history_path = root.claude_history_file();
 
     let file = tokio::fs::File::open(&history_path)
         .await
@@ -113,7 +120,9 @@ async fn read_history_entries() -> Result<Vec<HistoryEntry>, String> {
             Ok(entry) => {
                 if !entry.display.is_empty() && entry.timestamp > 0 {
                     let mut entry = entry;
-                    entry.source = "code".to_string();
+                    entry.source = CLAUDE_CODE_SOURCE.to_string();
+                    entry.root = root.id.clone();
+                    entry.host = root.host.clone();
                     entries.push(entry);
                 }
             }
@@ -123,32 +132,26 @@ async fn read_history_entries() -> Result<Vec<HistoryEntry>, String> {
         }
     }
 
-    // Sort by timestamp descending (newest first)
-    entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-
     Ok(entries)
 }
 
-fn get_codex_sessions_dir() -> Result<std::path::PathBuf, String> {
-    let home_dir = dirs::home_dir()
-        .ok_or_else(|| "Could not determine home directory".to_string())?;
-    Ok(home_dir.join(".codex").join("sessions"))
-}
-
-fn list_codex_session_files(root: &std::path::Path) -> Vec<std::path::PathBuf> {
-    let mut files = Vec::new();
-    let Ok(entries) = std::fs::read_dir(root) else { return files };
+/// Claude Code prompt history of every data root, newest first.
+/// Only a missing local history file is an error; other roots may lack one.
+pub(crate) async fn read_history_entries() -> Result<Vec<HistoryEntry>, String> {
+    let mut entries = Vec::new();
 
-    for entry in entries.flatten() {
-        let path = entry.path();
-        if path.is_dir() {
-            files.extend(list_codex_session_files(&path));
-        } else if path.extension().map(|e| e == "jsonl").unwrap_or(false) {
-            files.push(path);
+    for root in data_roots() {
+        match read_root_history_entries(&root).await {
+            Ok(root_entries) => entries.extend(root_entries),
+            Err(e) if root.is_local() => return Err(e),
+            Err(e) => log::warn!("Skipping history of data root {}: {}", root.id, e),
         }
     }
 
-    files
+    // Sort by timestamp descending (newest first)
+    entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+
+    Ok(entries)
 }
 
 fn parse_rfc3339_ms(ts: &str) -> Option<i64> {
@@ -159,16 +162,10 @@ fn parse_rfc3339_ms(ts: &str) -> Option<i64> {
     }
 }
 
-async fn read_codex_history_entries() -> Result<Vec<HistoryEntry>, String> {
-    let sessions_dir = get_codex_sessions_dir()?;
-    if !sessions_dir.exists() {
-        return Ok(Vec::new());
-    }
-
-    let files = list_codex_session_files(&sessions_dir);
+pub(crate) async fn read_codex_history_entries() -> Result<Vec<HistoryEntry>, String> {
     let mut entries: Vec<HistoryEntry> = Vec::new();
 
-    for file_path in files {
+    for (root, file_path) in codex_session_files() {
         let file = tokio::fs::File::open(&file_path)
             .await
             .map_err(|e| format!("Failed to open session file: {}", e))?;
@@ -249,7 +246,9 @@ async fn read_codex_history_entries() -> Result<Vec<HistoryEntry>, String> {
                 timestamp: timestamp_ms,
                 project: project_path.clone(),
                 session_id: session_id.clone(),
-                source: "codex".to_string(),
+                source: CODEX_SOURCE.to_string(),
+                root: root.id.clone(),
+                host: root.host.clone(),
             });
         }
     }
@@ -258,56 +257,37 @@ async fn read_codex_history_entries() -> Result<Vec<HistoryEntry>, String> {
     Ok(entries)
 }
 
+fn to_search_result(entry: HistoryEntry) -> SearchResult {
+    let (date, time) = format_timestamp(entry.timestamp);
+    let project_name = get_project_name(&entry.project);
+    SearchResult {
+        entry,
+        project_name,
+        formatted_date: date,
+        formatted_time: time,
+    }
+}
+
 // ============================================================================
 // Tauri Commands
 // ============================================================================
 
-/// Get all history entries with optional limit
+/// Get all history entries of a transcript source (default: Claude Code) with optional limit
 #[tauri::command]
-pub async fn get_history(limit: Option<usize>) -> Result<Vec<SearchResult>, String> {
-    let mut entries = read_history_entries().await?;
+pub async fn get_history(limit: Option<usize>, source: Option<String>) -> Result<Vec<SearchResult>, String> {
+    let mut entries = find_source(source.as_deref())?.history().await?;
 
     if let Some(l) = limit {
         entries.truncate(l);
     }
 
-    Ok(entries
-        .into_iter()
-        .map(|entry| {
-            let (date, time) = format_timestamp(entry.timestamp);
-            let project_name = get_project_name(&entry.project);
-            SearchResult {
-                entry,
-                project_name,
-                formatted_date: date,
-                formatted_time: time,
-            }
-        })
-        .collect())
+    Ok(entries.into_iter().map(to_search_result).collect())
 }
 
 /// Get all Codex history entries with optional limit
 #[tauri::command]
 pub async fn get_codex_history(limit: Option<usize>) -> Result<Vec<SearchResult>, String> {
-    let mut entries = read_codex_history_entries().await?;
-
-    if let Some(l) = limit {
-        entries.truncate(l);
-    }
-
-    Ok(entries
-        .into_iter()
-        .map(|entry| {
-            let (date, time) = format_timestamp(entry.timestamp);
-            let project_name = get_project_name(&entry.project);
-            SearchResult {
-                entry,
-                project_name,
-                formatted_date: date,
-                formatted_time: time,
-            }
-        })
-        .collect())
+    get_history(limit, Some(CODEX_SOURCE.to_string())).await
 }
 
 /// Search history entries
@@ -316,8 +296,9 @@ pub async fn search_history(
     query: String,
     project: Option<String>,
     limit: Option<usize>,
+    source: Option<String>,
 ) -> Result<Vec<SearchResult>, String> {
-    let entries = read_history_entries().await?;
+    let entries = find_source(source.as_deref())?.history().await?;
     let query_lower = query.to_lowercase();
 
     let mut filtered: Vec<HistoryEntry> = entries
@@ -336,19 +317,7 @@ pub async fn search_history(
         filtered.truncate(l);
     }
 
-    Ok(filtered
-        .into_iter()
-        .map(|entry| {
-            let (date, time) = format_timestamp(entry.timestamp);
-            let project_name = get_project_name(&entry.project);
-            SearchResult {
-                entry,
-                project_name,
-                formatted_date: date,
-                formatted_time: time,
-            }
-        })
-        .collect())
+    Ok(filtered.into_iter().map(to_search_result).collect())
 }
 
 /// Search Codex history entries
@@ -358,44 +327,13 @@ pub async fn search_codex_history(
     project: Option<String>,
     limit: Option<usize>,
 ) -> Result<Vec<SearchResult>, String> {
-    let entries = read_codex_history_entries().await?;
-    let query_lower = query.to_lowercase();
-
-    let mut filtered: Vec<HistoryEntry> = entries
-        .into_iter()
-        .filter(|entry| {
-            let matches_query = entry.display.to_lowercase().contains(&query_lower);
-            let matches_project = project
-                .as_ref()
-                .map(|p| entry.project.contains(p))
-                .unwrap_or(true);
-            matches_query && matches_project
-        })
-        .collect();
-
-    if let Some(l) = limit {
-        filtered.truncate(l);
-    }
-
-    Ok(filtered
-        .into_iter()
-        .map(|entry| {
-            let (date, time) = format_timestamp(entry.timestamp);
-            let project_name = get_project_name(&entry.project);
-            SearchResult {
-                entry,
-                project_name,
-                formatted_date: date,
-                formatted_time: time,
-            }
-        })
-        .collect())
+    search_history(query, project, limit, Some(CODEX_SOURCE.to_string())).await
 }
 
 /// Get history statistics
 #[tauri::command]
-pub async fn get_history_stats() -> Result<HistoryStats, String> {
-    let entries = read_history_entries().await?;
+pub async fn get_history_stats(source: Option<String>) -> Result<HistoryStats, String> {
+    let entries = find_source(source.as_deref())?.history().await?;
 
     if entries.is_empty() {
         return Ok(HistoryStats {
@@ -409,8 +347,8 @@ pub async fn get_history_stats() -> Result<HistoryStats, String> {
     let projects: HashSet<String> = entries.iter().map(|e| e.project.clone()).collect();
     let sessions: HashSet<String> = entries.iter().map(|e| e.session_id.clone()).collect();
 
-    let first_timestamp = entries.last().map(|e| e.timestamp).unwrap_or(0);
-    let last_timestamp = entries.first().map(|e| e.timestamp).unwrap_or(0);
+    let first_timestamp = entries.iter().map(|e| e.timestamp).min().unwrap_or(0);
+    let last_timestamp = entries.iter().map(|e| e.timestamp).max().unwrap_or(0);
 
     Ok(HistoryStats {
         total_prompts: entries.len(),
@@ -426,38 +364,14 @@ pub async fn get_history_stats() -> Result<HistoryStats, String> {
 /// Get Codex history statistics
 #[tauri::command]
 pub async fn get_codex_history_stats() -> Result<HistoryStats, String> {
-    let entries = read_codex_history_entries().await?;
-
-    if entries.is_empty() {
-        return Ok(HistoryStats {
-            total_prompts: 0,
-            unique_projects: 0,
-            unique_sessions: 0,
-            date_range: DateRange { first: 0, last: 0 },
-        });
-    }
-
-    let projects: HashSet<String> = entries.iter().map(|e| e.project.clone()).collect();
-    let sessions: HashSet<String> = entries.iter().map(|e| e.session_id.clone()).collect();
-
-    let first_timestamp = entries.iter().map(|e| e.timestamp).min().unwrap_or(0);
-    let last_timestamp = entries.iter().map(|e| e.timestamp).max().unwrap_or(0);
-
-    Ok(HistoryStats {
-        total_prompts: entries.len(),
-        unique_projects: projects.len(),
-        unique_sessions: sessions.len(),
-        date_range: DateRange {
-            first: first_timestamp,
-            last: last_timestamp,
-        },
-    })
+    get_history_stats(Some(CODEX_SOURCE.to_string())).await
 }
 
 /// Get unique projects from history
 #[tauri::command]
-pub async fn get_history_projects() -> Result<Vec<ProjectInfo>, String> {
-    let entries = read_history_entries().await?;
+pub async fn get_history_projects(source: Option<String>) -> Result<Vec<ProjectInfo>, String> {
+    let source = find_source(source.as_deref())?;
+    let entries = source.history().await?;
 
     let mut project_counts: HashMap<String, usize> = HashMap::new();
     for entry in entries {
@@ -470,7 +384,7 @@ pub async fn get_history_projects() -> Result<Vec<ProjectInfo>, String> {
             name: get_project_name(&path),
             path,
             count,
-            source: "code".to_string(),
+            source: source.id().to_string(),
         })
         .collect();
 
@@ -483,23 +397,5 @@ pub async fn get_history_projects() -> Result<Vec<ProjectInfo>, String> {
 /// Get unique Codex projects from history
 #[tauri::command]
 pub async fn get_codex_history_projects() -> Result<Vec<ProjectInfo>, String> {
-    let entries = read_codex_history_entries().await?;
-
-    let mut project_counts: HashMap<String, usize> = HashMap::new();
-    for entry in entries {
-        *project_counts.entry(entry.project.clone()).or_insert(0) += 1;
-    }
-
-    let mut projects: Vec<ProjectInfo> = project_counts
-        .into_iter()
-        .map(|(path, count)| ProjectInfo {
-            name: get_project_name(&path),
-            path,
-            count,
-            source: "codex".to_string(),
-        })
-        .collect();
-
-    projects.sort_by(|a, b| b.count.cmp(&a.count));
-    Ok(projects)
+    get_history_projects(Some(CODEX_SOURCE.to_string())).await
 }
@@ -0,0 +1,354 @@
+/**
+ * Live session tailing
+ *
+ * Watches active Claude Code session files and Codex rollout files and emits
+ * a `session-update` event whenever new lines are appended, carrying the newly
+ * parsed messages and the refreshed session stats.
+ *
+ * Each watched session gets its own polling thread. A caller starts one with
+ * `watch_session`, passing how many messages it already loaded so that lines
+ * appended since then are sent right away, and must stop it with
+ * `unwatch_session` once it no longer
+ * listens for updates; a thread also ends when its file goes away, and all of
+ * them stop when the app exits. A poll only compares the file size with what
+ * was already read, and reads nothing else unless the file grew.
+ */
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use super::sources::{find_source, SessionTailer, CODEX_SOURCE};
+use super::transcripts::{Message, SessionStats};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+const SESSION_UPDATE_EVENT: &str = "session-update";
+const POLL_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// Stop flags of running watchers, keyed by source, project and session
+static WATCHERS: Mutex<Option<HashMap<String, Arc<AtomicBool>>>> = Mutex::new(None);
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionUpdate {
+    #[serde(rename = "projectId")]
+    pub project_id: String,
+    #[serde(rename = "sessionId")]
+    pub session_id: String,
+    /// "code" or "codex"
+    pub source: String,
+    /// Newly appended messages, oldest first
+    pub messages: Vec<Message>,
+    pub stats: SessionStats,
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn watcher_key(source: &str, project_id: &str, session_id: &str) -> String {
+    format!("{}\n{}\n{}", source, project_id, session_id)
+}
+
+/// Register a stop flag for the session. Returns None if it is already watched.
+fn register_watcher(key: &str) -> Result<Option<Arc<AtomicBool>>, String> {
+    let mut guard = WATCHERS.lock().map_err(|_| "Watcher registry lock poisoned".to_string())?;
+    let watchers = guard.get_or_insert_with(HashMap::new);
+
+    if watchers.contains_key(key) {
+        return Ok(None);
+    }
+
+    let stop = Arc::new(AtomicBool::new(false));
+    watchers.insert(key.to_string(), stop.clone());
+    Ok(Some(stop))
+}
+
+fn unregister_watcher(key: &str) {
+    if let Ok(mut guard) = WATCHERS.lock() {
+        if let Some(watchers) = guard.as_mut() {
+            if let Some(stop) = watchers.remove(key) {
+                stop.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+/// Drop the registry entry of a finished thread, unless it was already replaced by a new watcher
+fn release_watcher(key: &str, stop: &Arc<AtomicBool>) {
+    if let Ok(mut guard) = WATCHERS.lock() {
+        if let Some(watchers) = guard.as_mut() {
+            if watchers.get(key).map(|s| Arc::ptr_eq(s, stop)).unwrap_or(false) {
+                watchers.remove(key);
+            }
+        }
+    }
+}
+
+/// Drop the first `known_messages` of a poll over the whole file; None if no message is left
+fn unseen_messages(
+    poll: Option<(Vec<Message>, SessionStats)>,
+    known_messages: usize,
+) -> Option<(Vec<Message>, SessionStats)> {
+    let (mut messages, stats) = poll?;
+    if messages.len() <= known_messages {
+        return None;
+    }
+    Some((messages.split_off(known_messages), stats))
+}
+
+/// Poll the session until stopped or the file goes away, emitting an event per change.
+/// The first poll reads the whole file; its first `known_messages` messages are already shown.
+fn spawn_tail_thread(
+    app: AppHandle,
+    project_id: String,
+    session_id: String,
+    source: String,
+    mut tail: Box<dyn SessionTailer>,
+    known_messages: usize,
+    stop: Arc<AtomicBool>,
+) {
+    std::thread::spawn(move || {
+        let key = watcher_key(&source, &project_id, &session_id);
+        let emit = |(messages, stats): (Vec<Message>, SessionStats)| {
+            let update = SessionUpdate {
+                project_id: project_id.clone(),
+                session_id: session_id.clone(),
+                source: source.clone(),
+                messages,
+                stats,
+            };
+            if let Err(e) = app.emit(SESSION_UPDATE_EVENT, update) {
+                log::warn!("Failed to emit session update: {}", e);
+            }
+        };
+
+        match tail.poll() {
+            Ok(poll) => {
+                if let Some(update) = unseen_messages(poll, known_messages) {
+                    emit(update);
+                }
+            }
+            Err(e) => {
+                log::warn!("Failed to read session {}: {}", session_id, e);
+                release_watcher(&key, &stop);
+                return;
+            }
+        }
+
+        while !stop.load(Ordering::Relaxed) {
+            std::thread::sleep(POLL_INTERVAL);
+
+            match tail.poll() {
+                Ok(Some(update)) => emit(update),
+                Ok(None) => {}
+                Err(e) => {
+                    log::info!("Stopped watching session {}: {}", session_id, e);
+                    break;
+                }
+            }
+        }
+
+        release_watcher(&key, &stop);
+    });
+}
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+/// Stop every running watcher; called when the app exits
+pub fn stop_all_watchers() {
+    if let Ok(mut guard) = WATCHERS.lock() {
+        if let Some(watchers) = guard.take() {
+            for stop in watchers.values() {
+                stop.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Start tailing a session of a transcript source (default: Claude Code); no-op if it is already being watched.
+/// `message_count` is the `totalMessages` of the transcript the caller loaded; messages past it are sent at once.
+#[tauri::command]
+pub async fn watch_session(
+    app: AppHandle,
+    project_id: String,
+    session_id: String,
+    message_count: usize,
+    source: Option<String>,
+) -> Result<(), String> {
+    let source = find_source(source.as_deref())?;
+    let tail = source.tail_session(&project_id, &session_id)?;
+
+    if let Some(stop) = register_watcher(&watcher_key(source.id(), &project_id, &session_id))? {
+        spawn_tail_thread(app, project_id, session_id, source.id().to_string(), tail, message_count, stop);
+    }
+
+    Ok(())
+}
+
+/// Start tailing a Codex rollout file; equivalent to `watch_session` with source "codex"
+#[tauri::command]
+pub async fn watch_codex_session(
+    app: AppHandle,
+    project_id: String,
+    session_id: String,
+    message_count: usize,
+) -> Result<(), String> {
+    watch_session(app, project_id, session_id, message_count, Some(CODEX_SOURCE.to_string())).await
+}
+
+/// Stop tailing a session started with `watch_session` or `watch_codex_session`
+#[tauri::command]
+pub async fn unwatch_session(project_id: String, session_id: String, source: Option<String>) -> Result<(), String> {
+    let source = find_source(source.as_deref())?;
+    unregister_watcher(&watcher_key(source.id(), &project_id, &session_id));
+    Ok(())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::transcripts::{ClaudeSessionTail, CodexSessionTail};
+    use std::fs;
+    use std::io::Write;
+    use std::path::PathBuf;
+
+    /// The registry is global; tests that use it run one at a time
+    static REGISTRY_LOCK: Mutex<()> = Mutex::new(());
+
+    fn temp_file(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("live-sessions-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn append(path: &PathBuf, text: &str) {
+        fs::OpenOptions::new().create(true).append(true).open(path).unwrap().write_all(text.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn test_watchers_are_keyed_by_source_project_and_session() {
+        let _guard = REGISTRY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let code = watcher_key("code", "p1", "s");
+        let other_project = watcher_key("code", "p2", "s");
+        let codex = watcher_key("codex", "p1", "s");
+
+        let stop = register_watcher(&code).unwrap().unwrap();
+        assert!(register_watcher(&code).unwrap().is_none());
+        let other = register_watcher(&other_project).unwrap().unwrap();
+        assert!(register_watcher(&codex).unwrap().is_some());
+
+        unregister_watcher(&code);
+        assert!(stop.load(Ordering::Relaxed));
+        assert!(!other.load(Ordering::Relaxed));
+
+        // A finished thread does not drop the watcher that replaced it
+        let replacement = register_watcher(&code).unwrap().unwrap();
+        release_watcher(&code, &stop);
+        assert!(register_watcher(&code).unwrap().is_none());
+        release_watcher(&code, &replacement);
+        unregister_watcher(&other_project);
+        unregister_watcher(&codex);
+    }
+
+    #[test]
+    fn test_stop_all_watchers() {
+        let _guard = REGISTRY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let first = register_watcher(&watcher_key("code", "stop-all", "a")).unwrap().unwrap();
+        let second = register_watcher(&watcher_key("codex", "stop-all", "b")).unwrap().unwrap();
+
+        stop_all_watchers();
+        assert!(first.load(Ordering::Relaxed) && second.load(Ordering::Relaxed));
+        assert!(register_watcher(&watcher_key("code", "stop-all", "a")).unwrap().is_some());
+        unregister_watcher(&watcher_key("code", "stop-all", "a"));
+    }
+
+    #[test]
+    fn test_codex_tail_reads_only_appended_lines() {
+        let path = temp_file("rollout.jsonl");
+        let message = |text: &str| {
+            format!(
+                "{{\"timestamp\":\"2025-06-01T10:00:00Z\",\"type\":\"response_item\",\"payload\":{{\"type\":\"message\",\"role\":\"user\",\"content\":[{{\"type\":\"input_text\",\"text\":\"{}\"}}]}}}}\n",
+                text
+            )
+        };
+        append(&path, &message("first"));
+
+        let mut tail = CodexSessionTail::new(path.clone());
+        let (messages, _) = tail.poll().unwrap().unwrap();
+        assert_eq!(messages.len(), 1);
+        assert!(tail.poll().unwrap().is_none());
+
+        // A partial line waits for its newline
+        let second = message("second");
+        append(&path, &second[..30]);
+        assert!(tail.poll().unwrap().is_none());
+        append(&path, &second[30..]);
+        let (messages, stats) = tail.poll().unwrap().unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].content[0]["text"], "second");
+        assert_eq!((stats.prompt_count, stats.message_count), (2, 2));
+    }
+
+    #[test]
+    fn test_first_poll_sends_messages_appended_after_the_snapshot() {
+        let path = temp_file("snapshot.jsonl");
+        let line = |uuid: &str| {
+            format!(
+                "{{\"type\":\"user\",\"uuid\":\"{}\",\"timestamp\":\"2025-06-01T10:00:00Z\",\"message\":{{\"role\":\"user\",\"content\":\"hello there\"}}}}\n",
+                uuid
+            )
+        };
+        // The frontend loaded "a"; "b" was appended before the watcher started
+        append(&path, &format!("{}{}", line("a"), line("b")));
+
+        let mut tail = ClaudeSessionTail::new(path.clone(), "p");
+        let (messages, stats) = unseen_messages(tail.poll().unwrap(), 1).unwrap();
+        assert_eq!(messages.iter().map(|m| m.uuid.as_str()).collect::<Vec<_>>(), vec!["b"]);
+        assert_eq!(stats.prompt_count, 2);
+
+        let mut tail = ClaudeSessionTail::new(path.clone(), "p");
+        assert!(unseen_messages(tail.poll().unwrap(), 2).is_none());
+    }
+
+    #[test]
+    fn test_claude_tail_restarts_when_truncated() {
+        let path = temp_file("session.jsonl");
+        let line = |uuid: &str| {
+            format!(
+                "{{\"type\":\"user\",\"uuid\":\"{}\",\"timestamp\":\"2025-06-01T10:00:00Z\",\"message\":{{\"role\":\"user\",\"content\":\"hello there\"}}}}\n",
+                uuid
+            )
+        };
+        append(&path, &format!("{}{}", line("a"), line("b")));
+
+        let mut tail = ClaudeSessionTail::new(path.clone(), "p");
+        assert_eq!(tail.poll().unwrap().unwrap().0.len(), 2);
+
+        fs::write(&path, line("c")).unwrap();
+        let (messages, stats) = tail.poll().unwrap().unwrap();
+        assert_eq!(messages.iter().map(|m| m.uuid.as_str()).collect::<Vec<_>>(), vec!["c"]);
+        assert_eq!(stats.prompt_count, 1);
+    }
+}
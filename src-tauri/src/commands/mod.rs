@@ -4,10 +4,30 @@
  */
 
 pub mod fs_utils;
+pub mod data_roots;
+pub mod sources;
+pub mod pricing;
+pub mod secrets;
 pub mod analytics;
 pub mod analytics_v2;
 pub mod history;
+pub mod search;
 pub mod transcripts;
+pub mod conversation_tree;
+pub mod conversations;
+pub mod session_compare;
+pub mod session_export;
+pub mod file_reconstruction;
+pub mod git_correlation;
+pub mod edit_survival;
+pub mod unified_diff;
+pub mod tool_reliability;
+pub mod shell_commands;
+pub mod session_health;
+pub mod budgets;
+pub mod spend_forecast;
+pub mod usage_blocks;
+pub mod context_window;
 pub mod plans;
 pub mod files;
 pub mod mcp;
@@ -19,16 +39,39 @@ pub mod agents;
 pub mod commands;
 pub mod plugins;
 pub mod work_unit_builder;
+pub mod transcript_index;
+pub mod live_sessions;
+pub mod archives;
 pub mod reports;
 pub mod auth;
 pub mod docs;
 
 // Re-export all commands
 pub use fs_utils::*;
+pub use data_roots::*;
+pub use sources::*;
+pub use pricing::*;
+pub use secrets::*;
 pub use analytics::*;
 pub use analytics_v2::*;
 pub use history::*;
+pub use search::*;
 pub use transcripts::*;
+pub use conversation_tree::*;
+pub use conversations::*;
+pub use session_compare::*;
+pub use session_export::*;
+pub use file_reconstruction::*;
+pub use git_correlation::*;
+pub use edit_survival::*;
+pub use unified_diff::*;
+pub use tool_reliability::*;
+pub use shell_commands::*;
+pub use session_health::*;
+pub use budgets::*;
+pub use spend_forecast::*;
+pub use usage_blocks::*;
+pub use context_window::*;
 pub use plans::*;
 pub use files::*;
 pub use mcp::*;
@@ -40,5 +83,8 @@ pub use agents::*;
 pub use commands::*;
 pub use plugins::*;
 pub use reports::*;
+pub use transcript_index::*;
+pub use live_sessions::*;
+pub use archives::*;
 pub use auth::*;
 pub use docs::*;
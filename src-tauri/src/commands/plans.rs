@@ -8,6 +8,8 @@
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+use super::data_roots::local_root;
+
 // ============================================================================
 // Type Definitions - Match TypeScript interfaces
 // ============================================================================
@@ -49,8 +51,7 @@ pub struct PlanSummary {
 // ============================================================================
 
 fn get_plans_dir() -> Result<PathBuf, String> {
-    let home_dir = dirs::home_dir().ok_or_else(|| "Could not determine home directory".to_string())?;
-    Ok(home_dir.join(".claude").join("plans"))
+    Ok(local_root()?.claude_plans_dir())
 }
 
 fn get_file_modified_time(path: &std::path::Path) -> i64 {
@@ -0,0 +1,443 @@
+/**
+ * Pricing engine
+ *
+ * Single source of every cost figure in the dashboard. Prices come from a
+ * versioned table bundled with the app (pricing.json next to this file) and
+ * can be overridden per model in ~/.claude-dashboard/pricing.json.
+ *
+ * Each table row matches model ids by substring (longest pattern wins), may be
+ * limited to an effective-date range, and may carry a long-context tier that
+ * applies to requests whose prompt exceeds a token threshold. Cache writes are
+ * priced separately for the 5-minute and 1-hour TTLs. All prices are USD per
+ * million tokens.
+ */
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use super::analytics::ModelUsage;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+const BUILTIN_PRICE_TABLE: &str = include_str!("pricing.json");
+
+/// Loaded built-in table and user overrides, populated lazily
+static PRICES: Mutex<Option<LoadedPrices>> = Mutex::new(None);
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceTable {
+    pub version: u32,
+    /// Date the table was last reviewed (YYYY-MM-DD)
+    #[serde(default)]
+    pub updated: String,
+    pub models: Vec<ModelPrice>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelPrice {
+    pub id: String,
+    /// "anthropic", "openai" or "custom"
+    #[serde(default = "default_provider")]
+    pub provider: String,
+    /// Substrings of model ids this row applies to
+    #[serde(rename = "match")]
+    pub patterns: Vec<String>,
+    /// First day the price applies (inclusive, YYYY-MM-DD)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_from: Option<String>,
+    /// Day the price stops applying (exclusive, YYYY-MM-DD)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_to: Option<String>,
+    pub input: f64,
+    pub output: f64,
+    #[serde(default)]
+    pub cache_read: f64,
+    /// Defaults to the input price when omitted
+    #[serde(rename = "cacheWrite5m", default, skip_serializing_if = "Option::is_none")]
+    pub cache_write_5m: Option<f64>,
+    /// Defaults to the 5-minute price when omitted
+    #[serde(rename = "cacheWrite1h", default, skip_serializing_if = "Option::is_none")]
+    pub cache_write_1h: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub long_context: Option<LongContextPrice>,
+}
+
+/// Prices for requests whose prompt is larger than `threshold_tokens`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LongContextPrice {
+    pub threshold_tokens: u64,
+    pub input: f64,
+    pub output: f64,
+    #[serde(default)]
+    pub cache_read: f64,
+    #[serde(rename = "cacheWrite5m", default, skip_serializing_if = "Option::is_none")]
+    pub cache_write_5m: Option<f64>,
+    #[serde(rename = "cacheWrite1h", default, skip_serializing_if = "Option::is_none")]
+    pub cache_write_1h: Option<f64>,
+}
+
+/// User override file: rows here take precedence over the built-in table
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PriceOverrides {
+    #[serde(default)]
+    pub models: Vec<ModelPrice>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceTableInfo {
+    pub builtin: PriceTable,
+    pub overrides: Vec<ModelPrice>,
+    pub overrides_path: String,
+}
+
+/// Token counts of one request (or a sum of requests) to be priced.
+/// `input` excludes cached tokens; cache writes are split by TTL.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokenCounts {
+    pub input: u64,
+    pub output: u64,
+    pub cache_read: u64,
+    pub cache_write_5m: u64,
+    pub cache_write_1h: u64,
+}
+
+impl TokenCounts {
+    /// Every token of the request, cached or not
+    pub fn total(&self) -> u64 {
+        self.input + self.output + self.cache_read + self.cache_write_5m + self.cache_write_1h
+    }
+}
+
+struct LoadedPrices {
+    builtin: Vec<ModelPrice>,
+    overrides: Vec<ModelPrice>,
+}
+
+struct Rates {
+    input: f64,
+    output: f64,
+    cache_read: f64,
+    cache_write_5m: f64,
+    cache_write_1h: f64,
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn default_provider() -> String {
+    "custom".to_string()
+}
+
+fn get_overrides_path() -> Result<PathBuf, String> {
+    let home_dir = dirs::home_dir().ok_or_else(|| "Could not determine home directory".to_string())?;
+    Ok(home_dir.join(".claude-dashboard").join("pricing.json"))
+}
+
+fn builtin_table() -> PriceTable {
+    serde_json::from_str(BUILTIN_PRICE_TABLE).expect("bundled pricing.json is valid")
+}
+
+fn read_overrides() -> Vec<ModelPrice> {
+    let Ok(path) = get_overrides_path() else { return Vec::new() };
+    let Ok(content) = std::fs::read_to_string(&path) else { return Vec::new() };
+
+    match serde_json::from_str::<PriceOverrides>(&content) {
+        Ok(overrides) => overrides.models,
+        Err(e) => {
+            log::warn!("Ignoring invalid price overrides in {}: {}", path.display(), e);
+            Vec::new()
+        }
+    }
+}
+
+fn in_effect(price: &ModelPrice, date: &str) -> bool {
+    let after_start = price.effective_from.as_deref().map(|from| date >= from).unwrap_or(true);
+    let before_end = price.effective_to.as_deref().map(|to| date < to).unwrap_or(true);
+    after_start && before_end
+}
+
+/// Length of the longest pattern of `price` contained in `model`
+fn match_length(price: &ModelPrice, model: &str) -> Option<usize> {
+    price
+        .patterns
+        .iter()
+        .filter(|p| !p.is_empty() && model.contains(&p.to_lowercase()))
+        .map(|p| p.len())
+        .max()
+}
+
+/// Pick the most specific rows for the model, then among them the row in effect on `date`.
+/// Without a date (or when none of them covers it) the open-ended current row wins.
+fn select_price<'a>(prices: &'a [ModelPrice], model: &str, date: Option<&str>) -> Option<&'a ModelPrice> {
+    let model = model.to_lowercase();
+    let candidates: Vec<(&ModelPrice, usize)> = prices
+        .iter()
+        .filter_map(|p| match_length(p, &model).map(|len| (p, len)))
+        .collect();
+
+    let longest = candidates.iter().map(|(_, len)| *len).max()?;
+    let tier: Vec<&ModelPrice> = candidates
+        .into_iter()
+        .filter(|(_, len)| *len == longest)
+        .map(|(p, _)| p)
+        .collect();
+
+    let dated: Vec<&ModelPrice> = match date {
+        Some(date) => tier.iter().copied().filter(|p| in_effect(p, date)).collect(),
+        None => tier.iter().copied().filter(|p| p.effective_to.is_none()).collect(),
+    };
+
+    let rows = if dated.is_empty() { tier } else { dated };
+    rows.into_iter().max_by(|a, b| a.effective_from.cmp(&b.effective_from))
+}
+
+fn rates_for(price: &ModelPrice, prompt_tokens: Option<u64>) -> Rates {
+    let long = price
+        .long_context
+        .as_ref()
+        .filter(|lc| prompt_tokens.map(|t| t > lc.threshold_tokens).unwrap_or(false));
+
+    match long {
+        Some(lc) => {
+            let cache_write_5m = lc.cache_write_5m.unwrap_or(lc.input);
+            Rates {
+                input: lc.input,
+                output: lc.output,
+                cache_read: lc.cache_read,
+                cache_write_5m,
+                cache_write_1h: lc.cache_write_1h.unwrap_or(cache_write_5m),
+            }
+        }
+        None => {
+            let cache_write_5m = price.cache_write_5m.unwrap_or(price.input);
+            Rates {
+                input: price.input,
+                output: price.output,
+                cache_read: price.cache_read,
+                cache_write_5m,
+                cache_write_1h: price.cache_write_1h.unwrap_or(cache_write_5m),
+            }
+        }
+    }
+}
+
+fn cost_with_rates(rates: &Rates, tokens: &TokenCounts) -> f64 {
+    (tokens.input as f64 * rates.input
+        + tokens.output as f64 * rates.output
+        + tokens.cache_read as f64 * rates.cache_read
+        + tokens.cache_write_5m as f64 * rates.cache_write_5m
+        + tokens.cache_write_1h as f64 * rates.cache_write_1h)
+        / 1_000_000.0
+}
+
+fn price_with(model: &str, timestamp: Option<&str>, tokens: &TokenCounts, per_request: bool) -> f64 {
+    let Ok(mut guard) = PRICES.lock() else { return 0.0 };
+    let prices = guard.get_or_insert_with(|| LoadedPrices {
+        builtin: builtin_table().models,
+        overrides: read_overrides(),
+    });
+
+    let date = timestamp.filter(|t| t.len() >= 10).map(|t| &t[..10]);
+    let Some(price) =
+        select_price(&prices.overrides, model, date).or_else(|| select_price(&prices.builtin, model, date))
+    else {
+        return 0.0;
+    };
+
+    let prompt_tokens = per_request.then_some(tokens.input + tokens.cache_read + tokens.cache_write_5m + tokens.cache_write_1h);
+    cost_with_rates(&rates_for(price, prompt_tokens), tokens)
+}
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+/// Cost in USD of a single request. `timestamp` (RFC 3339) selects dated prices;
+/// long-context rates apply when the request's prompt exceeds the tier threshold.
+/// Unknown models cost nothing.
+pub fn calculate_cost(model: &str, timestamp: Option<&str>, tokens: &TokenCounts) -> f64 {
+    price_with(model, timestamp, tokens, true)
+}
+
+/// Cost in USD of summed usage where individual requests are no longer known,
+/// priced at the current base rates
+pub fn calculate_aggregate_cost(model: &str, tokens: &TokenCounts) -> f64 {
+    price_with(model, None, tokens, false)
+}
+
+/// Cost in USD of a model's summed analytics usage; the whole cache write total
+/// is billed at the 5-minute rate
+pub fn model_usage_cost(model: &str, usage: &ModelUsage) -> f64 {
+    calculate_aggregate_cost(
+        model,
+        &TokenCounts {
+            input: usage.input_tokens.max(0) as u64,
+            output: usage.output_tokens.max(0) as u64,
+            cache_read: usage.cache_read_input_tokens.max(0) as u64,
+            cache_write_5m: usage.cache_creation_input_tokens.max(0) as u64,
+            cache_write_1h: 0,
+        },
+    )
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Get the bundled price table together with the user's overrides
+#[tauri::command]
+pub async fn get_price_table() -> Result<PriceTableInfo, String> {
+    let overrides_path = get_overrides_path()?;
+
+    Ok(PriceTableInfo {
+        builtin: builtin_table(),
+        overrides: read_overrides(),
+        overrides_path: overrides_path.to_string_lossy().to_string(),
+    })
+}
+
+/// Replace the user's price overrides; takes effect for subsequent cost calculations
+#[tauri::command]
+pub async fn save_price_overrides(overrides: Vec<ModelPrice>) -> Result<(), String> {
+    let path = get_overrides_path()?;
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+
+    let content = serde_json::to_string_pretty(&PriceOverrides { models: overrides })
+        .map_err(|e| format!("Failed to serialize price overrides: {}", e))?;
+    tokio::fs::write(&path, content)
+        .await
+        .map_err(|e| format!("Failed to write price overrides: {}", e))?;
+
+    if let Ok(mut guard) = PRICES.lock() {
+        *guard = None;
+    }
+
+    Ok(())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cost(model: &str, date: Option<&str>, tokens: &TokenCounts, per_request: bool) -> f64 {
+        let table = builtin_table();
+        let price = select_price(&table.models, model, date).expect("model is priced");
+        let prompt = per_request.then_some(tokens.input + tokens.cache_read + tokens.cache_write_5m + tokens.cache_write_1h);
+        cost_with_rates(&rates_for(price, prompt), tokens)
+    }
+
+    #[test]
+    fn test_most_specific_row_wins() {
+        let table = builtin_table();
+        let pick = |m: &str| select_price(&table.models, m, None).map(|p| p.id.clone());
+
+        assert_eq!(pick("claude-opus-4-5-20251101").as_deref(), Some("claude-opus-4-5"));
+        assert_eq!(pick("claude-opus-4-1-20250805").as_deref(), Some("claude-opus-4-1"));
+        assert_eq!(pick("claude-3-5-haiku-20241022").as_deref(), Some("claude-3-5-haiku"));
+        assert_eq!(pick("gpt-5-codex").as_deref(), Some("gpt-5"));
+        assert_eq!(pick("gpt-5.1-codex-mini").as_deref(), Some("gpt-5-mini"));
+        assert!(pick("llama-3").is_none());
+    }
+
+    #[test]
+    fn test_effective_dates() {
+        let prices = vec![
+            ModelPrice {
+                id: "old".to_string(),
+                provider: "custom".to_string(),
+                patterns: vec!["my-model".to_string()],
+                effective_from: None,
+                effective_to: Some("2025-06-01".to_string()),
+                input: 2.0,
+                output: 2.0,
+                cache_read: 0.0,
+                cache_write_5m: None,
+                cache_write_1h: None,
+                long_context: None,
+            },
+            ModelPrice {
+                id: "new".to_string(),
+                provider: "custom".to_string(),
+                patterns: vec!["my-model".to_string()],
+                effective_from: Some("2025-06-01".to_string()),
+                effective_to: None,
+                input: 1.0,
+                output: 1.0,
+                cache_read: 0.0,
+                cache_write_5m: None,
+                cache_write_1h: None,
+                long_context: None,
+            },
+        ];
+
+        assert_eq!(select_price(&prices, "my-model", Some("2025-05-31")).unwrap().id, "old");
+        assert_eq!(select_price(&prices, "my-model", Some("2025-06-01")).unwrap().id, "new");
+        assert_eq!(select_price(&prices, "my-model", None).unwrap().id, "new");
+    }
+
+    #[test]
+    fn test_specific_row_wins_over_dated_family_row() {
+        let family = ModelPrice {
+            id: "family".to_string(),
+            provider: "custom".to_string(),
+            patterns: vec!["my-model".to_string()],
+            effective_from: None,
+            effective_to: None,
+            input: 1.0,
+            output: 1.0,
+            cache_read: 0.0,
+            cache_write_5m: None,
+            cache_write_1h: None,
+            long_context: None,
+        };
+        // Only priced from a later date than the family row covers
+        let specific = ModelPrice {
+            id: "specific".to_string(),
+            patterns: vec!["my-model-pro".to_string()],
+            effective_from: Some("2025-06-01".to_string()),
+            ..family.clone()
+        };
+        let prices = vec![family, specific];
+
+        assert_eq!(select_price(&prices, "my-model-pro", Some("2025-05-01")).unwrap().id, "specific");
+        assert_eq!(select_price(&prices, "my-model-pro", Some("2025-07-01")).unwrap().id, "specific");
+        assert_eq!(select_price(&prices, "my-model-pro", None).unwrap().id, "specific");
+        assert_eq!(select_price(&prices, "my-model", Some("2025-07-01")).unwrap().id, "family");
+    }
+
+    #[test]
+    fn test_long_context_and_cache_ttl() {
+        let small = TokenCounts { input: 100_000, ..Default::default() };
+        assert!((cost("claude-sonnet-4-5", None, &small, true) - 0.3).abs() < 1e-9);
+
+        let large = TokenCounts { input: 250_000, output: 1_000, ..Default::default() };
+        let expected = 250_000.0 * 6.0 / 1e6 + 1_000.0 * 22.5 / 1e6;
+        assert!((cost("claude-sonnet-4-5", None, &large, true) - expected).abs() < 1e-9);
+        // Summed usage never triggers the tier
+        let expected = 250_000.0 * 3.0 / 1e6 + 1_000.0 * 15.0 / 1e6;
+        assert!((cost("claude-sonnet-4-5", None, &large, false) - expected).abs() < 1e-9);
+
+        let writes = TokenCounts { cache_write_5m: 1_000_000, cache_write_1h: 1_000_000, ..Default::default() };
+        assert!((cost("claude-opus-4-5", None, &writes, true) - 16.25).abs() < 1e-9);
+    }
+}
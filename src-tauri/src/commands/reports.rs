@@ -17,6 +17,11 @@ use std::process::Command;
 use tauri_plugin_dialog::DialogExt;
 use tokio::io::{AsyncBufReadExt, BufReader};
 
+use super::transcript_index::{read_lines_at, with_index};
+use super::secrets::redact_text;
+use super::shell_commands::split_commands;
+use super::sources::{claude_project_dir, claude_project_dirs};
+
 // use crate::commands::work_unit_builder::{SummaryEmitter, WorkUnitBuilder};
 
 // ============================================================================
@@ -92,12 +97,6 @@ struct RawMessage {
 // Helper Functions
 // ============================================================================
 
-fn get_projects_dir() -> Result<PathBuf, String> {
-    let home_dir =
-        dirs::home_dir().ok_or_else(|| "Could not determine home directory".to_string())?;
-    Ok(home_dir.join(".claude").join("projects"))
-}
-
 /// Resolve project display name from session data (cwd → git/package.json → basename)
 fn resolve_project_name(project_dir: &Path) -> String {
     if let Some(cwd) = find_project_cwd(project_dir) {
@@ -275,7 +274,7 @@ fn extract_work_items(
                                 });
                             }
                         }
-                        "Edit" => {
+                        "Edit" | "MultiEdit" => {
                             if let Some(path) = input.and_then(|i| i.get("file_path")).and_then(|p| p.as_str()) {
                                 items.push(WorkItem {
                                     project_id: project_id.to_string(),
@@ -289,8 +288,10 @@ fn extract_work_items(
                         }
                         "Bash" => {
                             if let Some(cmd) = input.and_then(|i| i.get("command")).and_then(|c| c.as_str()) {
-                                if let Some(item) = parse_bash_command(cmd, project_id, session_id, timestamp) {
-                                    items.push(item);
+                                for segment in split_commands(cmd) {
+                                    if let Some(item) = parse_bash_command(&segment.text, project_id, session_id, timestamp) {
+                                        items.push(item);
+                                    }
                                 }
                             }
                         }
@@ -499,6 +500,14 @@ fn group_work_items(items: Vec<WorkItem>) -> Vec<WorkGroup> {
 // Markdown Generation
 // ============================================================================
 
+/// Strip secrets (e.g. tokens inside commands) before groups reach the markdown or the AI formatter
+fn redact_work_groups(mut groups: Vec<WorkGroup>) -> Vec<WorkGroup> {
+    for group in &mut groups {
+        group.subject = redact_text(&group.subject);
+    }
+    groups
+}
+
 fn generate_markdown(title: &str, date_range: &str, groups: &[WorkGroup], summary: &str) -> String {
     let now = Utc::now().format("%Y-%m-%d %H:%M UTC").to_string();
 
@@ -811,11 +820,6 @@ pub async fn generate_report(
     use_ai: Option<bool>,
 ) -> Result<ReportData, String> {
     let use_ai_formatting = use_ai.unwrap_or(false);
-    let projects_dir = get_projects_dir()?;
-
-    if !projects_dir.exists() {
-        return Err("Projects directory not found".to_string());
-    }
 
     // Parse date range
     let start_ms = DateTime::parse_from_rfc3339(&format!("{}T00:00:00Z", start_date))
@@ -829,9 +833,9 @@ pub async fn generate_report(
     // Collect projects to scan
     let project_dirs: Vec<(PathBuf, String, String)> = if let Some(ref pid) = project_id {
         if pid.is_empty() {
-            collect_all_projects(&projects_dir)?
+            collect_all_projects()
         } else {
-            let path = projects_dir.join(pid);
+            let path = claude_project_dir(pid)?;
             if !path.exists() {
                 return Err(format!("Project not found: {}", pid));
             }
@@ -839,59 +843,72 @@ pub async fn generate_report(
             vec![(path, pid.clone(), name)]
         }
     } else {
-        collect_all_projects(&projects_dir)?
+        collect_all_projects()
     };
 
     let mut all_work_items: Vec<WorkItem> = Vec::new();
     let mut session_ids: HashSet<String> = HashSet::new();
     let mut session_summaries: Vec<String> = Vec::new();
 
-    // Extract work items from each project
-    for (project_path, project_id, _) in &project_dirs {
-        let dir_entries = match fs::read_dir(project_path) {
-            Ok(entries) => entries,
-            Err(_) => continue,
-        };
-
-        for entry in dir_entries.flatten() {
-            let path = entry.path();
-
-            if !path.is_file() || path.extension().map(|ext| ext != "jsonl").unwrap_or(true) {
-                continue;
-            }
-
-            let session_id = path
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("unknown")
-                .to_string();
-
-            let (entries, session_summary) = match read_session_entries(&path).await {
-                Ok(data) if !data.0.is_empty() => data,
-                _ => continue,
-            };
-
-            // Extract work items from this session
-            let items = extract_work_items(&entries, project_id, &session_id);
-
-            // Filter by date range
-            let mut matched = false;
-            for item in items {
-                if item.timestamp >= start_ms && item.timestamp <= end_ms {
-                    session_ids.insert(item.session_id.clone());
-                    all_work_items.push(item);
-                    matched = true;
+    // Pick tool-using assistant lines in range from the transcript index,
+    // then read only those lines from disk
+    let project_ids: HashSet<&str> = project_dirs.iter().map(|(_, id, _)| id.as_str()).collect();
+    let candidates: Vec<(String, String, String, String, Vec<u64>)> = with_index(|index| {
+        index
+            .sessions
+            .values()
+            .filter(|session| project_ids.contains(session.project_id.as_str()))
+            .filter_map(|session| {
+                let offsets: Vec<u64> = session
+                    .entries
+                    .iter()
+                    .filter(|e| e.entry_type == "assistant" && !e.tool_uses.is_empty())
+                    .filter(|e| {
+                        let ts = parse_timestamp(&e.timestamp);
+                        ts >= start_ms && ts <= end_ms
+                    })
+                    .map(|e| e.offset)
+                    .collect();
+                if offsets.is_empty() {
+                    return None;
                 }
+                Some((
+                    session.file_path.clone(),
+                    session.project_id.clone(),
+                    session.session_id.clone(),
+                    session.summaries.last().map(|s| s.summary.clone()).unwrap_or_default(),
+                    offsets,
+                ))
+            })
+            .collect()
+    })?;
+
+    for (file_path, project_id, session_id, session_summary, offsets) in candidates {
+        let entries: Vec<RawLogEntry> = read_lines_at(Path::new(&file_path), &offsets)
+            .iter()
+            .filter_map(|line| serde_json::from_str::<RawLogEntry>(line).ok())
+            .collect();
+
+        // Extract work items from this session
+        let items = extract_work_items(&entries, &project_id, &session_id);
+
+        // Filter by date range
+        let mut matched = false;
+        for item in items {
+            if item.timestamp >= start_ms && item.timestamp <= end_ms {
+                session_ids.insert(item.session_id.clone());
+                all_work_items.push(item);
+                matched = true;
             }
+        }
 
-            if matched && !session_summary.is_empty() {
-                session_summaries.push(session_summary);
-            }
+        if matched && !session_summary.is_empty() {
+            session_summaries.push(redact_text(&session_summary));
         }
     }
 
     // Group and deduplicate work items
-    let work_groups = group_work_items(all_work_items);
+    let work_groups = redact_work_groups(group_work_items(all_work_items));
 
     // Count totals
     let total_files = work_groups
@@ -950,27 +967,14 @@ pub async fn generate_report(
     })
 }
 
-fn collect_all_projects(projects_dir: &PathBuf) -> Result<Vec<(PathBuf, String, String)>, String> {
-    let mut result = Vec::new();
-
-    for entry in
-        fs::read_dir(projects_dir).map_err(|e| format!("Failed to read projects dir: {}", e))?
-    {
-        let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
-        let path = entry.path();
-
-        if path.is_dir() {
-            let project_id = path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("unknown")
-                .to_string();
+fn collect_all_projects() -> Vec<(PathBuf, String, String)> {
+    claude_project_dirs()
+        .into_iter()
+        .map(|(_, project_id, path)| {
             let project_name = resolve_project_name(&path);
-            result.push((path, project_id, project_name));
-        }
-    }
-
-    Ok(result)
+            (path, project_id, project_name)
+        })
+        .collect()
 }
 
 /// Save report to file with native file dialog
@@ -1018,8 +1022,7 @@ pub async fn generate_project_documentation(
     use_ai: Option<bool>,
 ) -> Result<ReportData, String> {
     let use_ai_formatting = use_ai.unwrap_or(false);
-    let projects_dir = get_projects_dir()?;
-    let project_path = projects_dir.join(&project_id);
+    let project_path = claude_project_dir(&project_id)?;
 
     if !project_path.exists() {
         return Err(format!("Project not found: {}", project_id));
@@ -1043,11 +1046,11 @@ pub async fn generate_project_documentation(
         all_work_items.extend(items);
 
         if !session_summary.is_empty() {
-            session_summaries.push(session_summary);
+            session_summaries.push(redact_text(&session_summary));
         }
     }
 
-    let work_groups = group_work_items(all_work_items);
+    let work_groups = redact_work_groups(group_work_items(all_work_items));
     let local_summary = generate_work_summary(&work_groups, &session_summaries);
 
     let summary = if use_ai_formatting {
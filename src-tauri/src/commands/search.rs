@@ -0,0 +1,784 @@
+/**
+ * Full-text transcript search
+ *
+ * Searches every message body, thinking block, tool input and tool result of
+ * every session in every transcript source, unlike `search_history` which only
+ * matches prompt text in history.jsonl. Each content block is one document;
+ * hits are ranked with BM25 and come with a highlighted snippet plus enough
+ * location data (source, project, session, page, message uuid) to deep-link
+ * into the session page.
+ *
+ * The index keeps, per session file, the postings of each term and where each
+ * document lives in the file (line offset and content block), but no text:
+ * snippets are read back from the JSONL line of each hit on demand. It is
+ * stored in ~/.claude-dashboard/search-index/, one JSON file per session file
+ * next to the transcript index, loaded on the first search and refreshed
+ * before each query from the session files' mtimes (Claude Code files as
+ * tracked by the transcript index, Codex rollouts from a directory listing):
+ * only files that are new or changed since they were indexed are read.
+ */
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::hash::Hasher;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::sources::{codex_session_files, find_source, registered_sources, TranscriptSource, CLAUDE_CODE_SOURCE, CODEX_SOURCE};
+use super::transcript_index::{read_lines_at, with_index};
+use super::transcripts::{
+    claude_line_message, codex_line_message, codex_session_identity, get_file_modified_time, message_page,
+    read_codex_message_lines, Message,
+};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+const SEARCH_INDEX_VERSION: u32 = 1;
+
+/// BM25 term-frequency saturation
+const BM25_K1: f64 = 1.2;
+/// BM25 document-length normalisation
+const BM25_B: f64 = 0.75;
+
+/// Text indexed per document; long tool results are truncated for indexing
+const MAX_DOC_CHARS: usize = 20_000;
+
+const MAX_TOKEN_LEN: usize = 64;
+
+/// Characters of context shown before the first hit in a snippet
+const SNIPPET_BEFORE: usize = 80;
+const SNIPPET_LENGTH: usize = 240;
+
+const DEFAULT_LIMIT: usize = 50;
+
+/// In-process copy of the index, loaded lazily on first use
+static SEARCH_INDEX: Mutex<Option<SearchIndex>> = Mutex::new(None);
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchFilters {
+    /// Limit to one transcript source; all sources when omitted
+    pub source: Option<String>,
+    pub project_id: Option<String>,
+    /// Inclusive YYYY-MM-DD bounds on the message timestamp
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    /// Substring of the model id
+    pub model: Option<String>,
+    pub tool_name: Option<String>,
+    /// "user" or "assistant"
+    pub role: Option<String>,
+    /// "text", "thinking", "toolInput" or "toolResult"
+    pub kind: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnippetPart {
+    pub text: String,
+    pub highlight: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub source: String,
+    pub project_id: String,
+    pub session_id: String,
+    pub session_summary: String,
+    pub message_uuid: String,
+    /// Session page holding the message
+    pub page: usize,
+    pub role: String,
+    pub kind: String,
+    pub tool_name: Option<String>,
+    pub model: Option<String>,
+    pub timestamp: String,
+    pub score: f64,
+    pub snippet: Vec<SnippetPart>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResponse {
+    pub hits: Vec<SearchHit>,
+    pub total_hits: usize,
+    pub sessions_indexed: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchDoc {
+    message_uuid: String,
+    message_index: usize,
+    /// Byte offset of the session file line the message was read from
+    offset: u64,
+    /// Content block of that message holding the text
+    block: usize,
+    role: String,
+    kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    model: Option<String>,
+    timestamp: String,
+    /// Token count of the text
+    len: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionDocs {
+    source: String,
+    project_id: String,
+    session_id: String,
+    summary: String,
+    file_path: String,
+    last_modified: i64,
+    docs: Vec<SearchDoc>,
+    /// Term -> (document index, term frequency) of every document holding it, by document index
+    postings: HashMap<String, Vec<(u32, u32)>>,
+}
+
+/// On-disk record of one session file
+#[derive(Serialize, Deserialize)]
+struct SearchIndexFile<T> {
+    version: u32,
+    session: T,
+}
+
+#[derive(Default)]
+struct SearchIndex {
+    sessions: HashMap<String, SessionDocs>,
+    doc_freq: HashMap<String, u32>,
+    doc_count: usize,
+    total_len: u64,
+}
+
+impl SearchIndex {
+    fn remove(&mut self, key: &str) -> Option<SessionDocs> {
+        let old = self.sessions.remove(key)?;
+        for (term, postings) in &old.postings {
+            if let Some(df) = self.doc_freq.get_mut(term) {
+                *df -= postings.len() as u32;
+                if *df == 0 {
+                    self.doc_freq.remove(term);
+                }
+            }
+        }
+        self.doc_count -= old.docs.len();
+        self.total_len -= old.docs.iter().map(|d| d.len as u64).sum::<u64>();
+        Some(old)
+    }
+
+    fn insert(&mut self, key: String, session: SessionDocs) {
+        self.remove(&key);
+        for (term, postings) in &session.postings {
+            *self.doc_freq.entry(term.clone()).or_insert(0) += postings.len() as u32;
+        }
+        self.doc_count += session.docs.len();
+        self.total_len += session.docs.iter().map(|d| d.len as u64).sum::<u64>();
+        self.sessions.insert(key, session);
+    }
+}
+
+impl SessionDocs {
+    /// Index every content block of the messages, given in display order with the offset of their line.
+    /// Messages without a known line are left out.
+    fn new(
+        source: &str,
+        project_id: String,
+        session_id: String,
+        summary: String,
+        file_path: String,
+        last_modified: i64,
+        messages: &[(Option<u64>, Message)],
+    ) -> Self {
+        let mut tool_names: HashMap<String, String> = HashMap::new();
+        let mut docs = Vec::new();
+        let mut postings: HashMap<String, Vec<(u32, u32)>> = HashMap::new();
+
+        for (index, (offset, message)) in messages.iter().enumerate() {
+            for (block_index, block) in message.content.iter().enumerate() {
+                if block.get("type").and_then(|t| t.as_str()) == Some("tool_use") {
+                    if let (Some(id), Some(name)) = (
+                        block.get("id").and_then(|i| i.as_str()),
+                        block.get("name").and_then(|n| n.as_str()),
+                    ) {
+                        tool_names.insert(id.to_string(), name.to_string());
+                    }
+                }
+
+                let Some(offset) = offset else { continue };
+                let Some((kind, text)) = block_text(block) else { continue };
+                let tokens = tokenize(&text);
+                if tokens.is_empty() {
+                    continue;
+                }
+
+                let tool_name = match kind {
+                    "toolInput" => block.get("name").and_then(|n| n.as_str()).map(String::from),
+                    "toolResult" => block
+                        .get("tool_use_id")
+                        .and_then(|i| i.as_str())
+                        .and_then(|id| tool_names.get(id).cloned()),
+                    _ => None,
+                };
+
+                let mut terms: HashMap<String, u32> = HashMap::new();
+                for token in &tokens {
+                    *terms.entry(token.clone()).or_insert(0) += 1;
+                }
+                let doc = docs.len() as u32;
+                for (term, tf) in terms {
+                    postings.entry(term).or_default().push((doc, tf));
+                }
+
+                docs.push(SearchDoc {
+                    message_uuid: message.uuid.clone(),
+                    message_index: index,
+                    offset: *offset,
+                    block: block_index,
+                    role: message.msg_type.clone(),
+                    kind: kind.to_string(),
+                    tool_name,
+                    model: message.model.clone(),
+                    timestamp: message.timestamp.clone(),
+                    len: tokens.len() as u32,
+                });
+            }
+        }
+
+        SessionDocs {
+            source: source.to_string(),
+            project_id,
+            session_id,
+            summary,
+            file_path,
+            last_modified,
+            docs,
+            postings,
+        }
+    }
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn session_key(source: &str, project_id: &str, session_id: &str) -> String {
+    format!("{}\n{}\n{}", source, project_id, session_id)
+}
+
+fn get_search_index_dir() -> Result<PathBuf, String> {
+    let home_dir = dirs::home_dir().ok_or_else(|| "Could not determine home directory".to_string())?;
+    Ok(home_dir.join(".claude-dashboard").join("search-index"))
+}
+
+/// Record file of a session, named after the hash of its session file path
+fn session_record_path(dir: &Path, file_path: &str) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    hasher.write(file_path.as_bytes());
+    dir.join(format!("{:016x}.json", hasher.finish()))
+}
+
+/// Load the index from disk. Unreadable or outdated records are dropped and rebuilt.
+fn read_search_index_dir(dir: &Path) -> SearchIndex {
+    let mut index = SearchIndex::default();
+
+    let Ok(entries) = fs::read_dir(dir) else { return index };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().map(|ext| ext != "json").unwrap_or(true) {
+            continue;
+        }
+        let record = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<SearchIndexFile<SessionDocs>>(&content).ok());
+        match record {
+            Some(record) if record.version == SEARCH_INDEX_VERSION => {
+                let session = record.session;
+                let key = session_key(&session.source, &session.project_id, &session.session_id);
+                index.insert(key, session);
+            }
+            _ => {
+                log::info!("Dropping outdated search index record {}", path.display());
+                let _ = fs::remove_file(&path);
+            }
+        }
+    }
+
+    index
+}
+
+/// Write one session's record atomically (temp file + rename)
+fn write_session_record(dir: &Path, session: &SessionDocs) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create search index directory: {}", e))?;
+
+    let record = SearchIndexFile { version: SEARCH_INDEX_VERSION, session };
+    let content = serde_json::to_string(&record).map_err(|e| format!("Failed to serialize search index: {}", e))?;
+    let path = session_record_path(dir, &session.file_path);
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, content).map_err(|e| format!("Failed to write search index: {}", e))?;
+    fs::rename(&tmp_path, &path).map_err(|e| format!("Failed to replace search index: {}", e))?;
+
+    Ok(())
+}
+
+/// Lowercased word tokens with their byte ranges in `text`
+fn tokens_with_offsets(text: &str) -> Vec<(usize, usize, String)> {
+    let mut tokens = Vec::new();
+    let mut start: Option<usize> = None;
+
+    for (i, c) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+        let is_word = c.is_alphanumeric() || c == '_';
+        match (is_word, start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                let token = text[s..i].to_lowercase();
+                if token.chars().count() >= 2 && token.len() <= MAX_TOKEN_LEN {
+                    tokens.push((s, i, token));
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    tokens
+}
+
+fn tokenize(text: &str) -> Vec<String> {
+    tokens_with_offsets(text).into_iter().map(|(_, _, t)| t).collect()
+}
+
+fn truncate_chars(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((i, _)) => text[..i].to_string(),
+        None => text.to_string(),
+    }
+}
+
+/// String leaves of a JSON value joined by newlines, skipping inline image data
+fn value_text(value: &Value) -> String {
+    fn walk(value: &Value, out: &mut Vec<String>) {
+        match value {
+            Value::String(s) => out.push(s.clone()),
+            Value::Array(items) => items.iter().for_each(|item| walk(item, out)),
+            Value::Object(map) if value.get("type").and_then(|t| t.as_str()) != Some("image") => {
+                map.values().for_each(|v| walk(v, out))
+            }
+            _ => {}
+        }
+    }
+
+    let mut parts = Vec::new();
+    walk(value, &mut parts);
+    parts.join("\n")
+}
+
+/// Document kind and (truncated) text of a content block, or None for blocks that are not searched
+fn block_text(block: &Value) -> Option<(&'static str, String)> {
+    let (kind, text) = match block.get("type").and_then(|t| t.as_str()).unwrap_or("") {
+        "text" => ("text", block.get("text").and_then(|t| t.as_str()).unwrap_or("").to_string()),
+        "thinking" => ("thinking", block.get("thinking").and_then(|t| t.as_str()).unwrap_or("").to_string()),
+        "tool_use" => ("toolInput", block.get("input").map(value_text).unwrap_or_default()),
+        "tool_result" => ("toolResult", block.get("content").map(value_text).unwrap_or_default()),
+        _ => return None,
+    };
+    Some((kind, truncate_chars(&text, MAX_DOC_CHARS)))
+}
+
+/// Text of an indexed document, read back from its line of the session file
+fn read_doc_text(source: &str, file_path: &str, offset: u64, block: usize) -> Option<String> {
+    let line = read_lines_at(Path::new(file_path), &[offset]).pop()?;
+    let message = match source {
+        CLAUDE_CODE_SOURCE => claude_line_message(&line),
+        CODEX_SOURCE => codex_line_message(&line),
+        _ => None,
+    }?;
+    block_text(message.content.get(block)?).map(|(_, text)| text)
+}
+
+/// Re-read session files that are new or changed since they were indexed and drop deleted ones
+async fn refresh_search_index(sources: &[Box<dyn TranscriptSource>]) -> Result<(), String> {
+    let index_dir = get_search_index_dir()?;
+
+    // Indexed sessions by file path
+    let known: HashMap<String, (String, i64)> = {
+        let mut guard = SEARCH_INDEX.lock().map_err(|e| format!("Failed to lock search index: {}", e))?;
+        let index = guard.get_or_insert_with(|| read_search_index_dir(&index_dir));
+        index.sessions.iter().map(|(k, s)| (s.file_path.clone(), (k.clone(), s.last_modified))).collect()
+    };
+    let unchanged = |path: &str, last_modified: i64| known.get(path).filter(|(_, m)| *m == last_modified).map(|(k, _)| k.clone());
+
+    let mut listed: HashSet<String> = HashSet::new();
+    let mut updates: Vec<(String, SessionDocs)> = Vec::new();
+
+    for source in sources {
+        match source.id() {
+            CLAUDE_CODE_SOURCE => {
+                // Changed files, with the line offset of each message uuid from the transcript index
+                type ChangedFile = (String, String, String, i64, HashMap<String, u64>);
+                let mut changed: Vec<ChangedFile> = Vec::new();
+                with_index(|index| {
+                    for s in index.sessions.values() {
+                        if let Some(key) = unchanged(&s.file_path, s.modified_ms) {
+                            listed.insert(key);
+                            continue;
+                        }
+                        let mut offsets: HashMap<String, u64> = HashMap::new();
+                        for entry in &s.entries {
+                            if let Some(uuid) = &entry.uuid {
+                                offsets.entry(uuid.clone()).or_insert(entry.offset);
+                            }
+                        }
+                        changed.push((s.file_path.clone(), s.project_id.clone(), s.session_id.clone(), s.modified_ms, offsets));
+                    }
+                })?;
+
+                for (file_path, project_id, session_id, last_modified, offsets) in changed {
+                    let key = session_key(CLAUDE_CODE_SOURCE, &project_id, &session_id);
+                    listed.insert(key.clone());
+                    match source.session_transcript(project_id.clone(), session_id.clone()).await {
+                        Ok(transcript) => {
+                            let messages: Vec<(Option<u64>, Message)> = transcript
+                                .messages
+                                .into_iter()
+                                .map(|m| (offsets.get(&m.uuid).copied(), m))
+                                .collect();
+                            let session = SessionDocs::new(
+                                CLAUDE_CODE_SOURCE,
+                                project_id,
+                                session_id,
+                                transcript.session.summary,
+                                file_path,
+                                last_modified,
+                                &messages,
+                            );
+                            updates.push((key, session));
+                        }
+                        Err(e) => log::warn!("Skipping session {} in search index: {}", session_id, e),
+                    }
+                }
+            }
+            CODEX_SOURCE => {
+                for (root, path) in codex_session_files() {
+                    let file_path = path.to_string_lossy().to_string();
+                    let last_modified = get_file_modified_time(&path);
+                    if let Some(key) = unchanged(&file_path, last_modified) {
+                        listed.insert(key);
+                        continue;
+                    }
+                    let Some((local_project_id, session_id, _)) = codex_session_identity(&path) else { continue };
+                    let project_id = root.qualify_id(&local_project_id);
+                    let key = session_key(CODEX_SOURCE, &project_id, &session_id);
+                    listed.insert(key.clone());
+                    match read_codex_message_lines(&path) {
+                        Ok((messages, summary)) => {
+                            let messages: Vec<(Option<u64>, Message)> =
+                                messages.into_iter().map(|(offset, m)| (Some(offset), m)).collect();
+                            let session = SessionDocs::new(
+                                CODEX_SOURCE,
+                                project_id,
+                                session_id,
+                                summary,
+                                file_path,
+                                last_modified,
+                                &messages,
+                            );
+                            updates.push((key, session));
+                        }
+                        Err(e) => log::warn!("Skipping session {} in search index: {}", session_id, e),
+                    }
+                }
+            }
+            other => log::warn!("Transcript source {} is not searchable", other),
+        }
+    }
+
+    let source_ids: HashSet<&str> = sources.iter().map(|s| s.id()).collect();
+    let mut guard = SEARCH_INDEX.lock().map_err(|e| format!("Failed to lock search index: {}", e))?;
+    let index = guard.get_or_insert_with(SearchIndex::default);
+
+    let removed: Vec<String> = index
+        .sessions
+        .iter()
+        .filter(|(key, s)| source_ids.contains(s.source.as_str()) && !listed.contains(*key))
+        .map(|(key, _)| key.clone())
+        .collect();
+    for key in removed {
+        if let Some(old) = index.remove(&key) {
+            let _ = fs::remove_file(session_record_path(&index_dir, &old.file_path));
+        }
+    }
+    for (key, session) in updates {
+        if let Err(e) = write_session_record(&index_dir, &session) {
+            log::warn!("{}", e);
+        }
+        index.insert(key, session);
+    }
+
+    Ok(())
+}
+
+fn matches_filters(session: &SessionDocs, doc: &SearchDoc, filters: &SearchFilters) -> bool {
+    let date = doc.timestamp.get(..10).unwrap_or("");
+    filters.project_id.as_ref().map_or(true, |p| &session.project_id == p)
+        && filters.start_date.as_deref().map_or(true, |d| date >= d)
+        && filters.end_date.as_deref().map_or(true, |d| date <= d)
+        && filters.model.as_ref().map_or(true, |m| {
+            doc.model.as_ref().is_some_and(|model| model.to_lowercase().contains(&m.to_lowercase()))
+        })
+        && filters.tool_name.as_ref().map_or(true, |t| {
+            doc.tool_name.as_ref().is_some_and(|name| name.eq_ignore_ascii_case(t))
+        })
+        && filters.role.as_ref().map_or(true, |r| &doc.role == r)
+        && filters.kind.as_ref().map_or(true, |k| &doc.kind == k)
+}
+
+/// BM25 score of document `doc` of a session, term frequencies read from the session's postings
+fn bm25(index: &SearchIndex, session: &SessionDocs, doc: u32, terms: &[String]) -> f64 {
+    let n = index.doc_count as f64;
+    let avg_len = if index.doc_count > 0 { index.total_len as f64 / n } else { 1.0 };
+    let len = session.docs[doc as usize].len as f64;
+
+    terms
+        .iter()
+        .filter_map(|term| {
+            let postings = session.postings.get(term)?;
+            let position = postings.binary_search_by_key(&doc, |(d, _)| *d).ok()?;
+            let tf = postings[position].1 as f64;
+            let df = *index.doc_freq.get(term).unwrap_or(&0) as f64;
+            let idf = (1.0 + (n - df + 0.5) / (df + 0.5)).ln();
+            let norm = BM25_K1 * (1.0 - BM25_B + BM25_B * len / avg_len);
+            Some(idf * tf * (BM25_K1 + 1.0) / (tf + norm))
+        })
+        .sum()
+}
+
+/// Window of text around the first hit, split into plain and highlighted parts
+fn build_snippet(text: &str, terms: &HashSet<String>) -> Vec<SnippetPart> {
+    let hits: Vec<(usize, usize)> = tokens_with_offsets(text)
+        .into_iter()
+        .filter(|(_, _, t)| terms.contains(t))
+        .map(|(s, e, _)| (s, e))
+        .collect();
+
+    let first = hits.first().map(|(s, _)| *s).unwrap_or(0);
+    let window_start = text[..first]
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_BEFORE.saturating_sub(1))
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+    let window_end = text[window_start..]
+        .char_indices()
+        .nth(SNIPPET_LENGTH)
+        .map(|(i, _)| window_start + i)
+        .unwrap_or(text.len());
+
+    let mut parts = Vec::new();
+    let mut push = |s: &str, highlight: bool| {
+        if !s.is_empty() {
+            parts.push(SnippetPart { text: s.to_string(), highlight });
+        }
+    };
+
+    if window_start > 0 {
+        push("…", false);
+    }
+    let mut pos = window_start;
+    for (s, e) in hits.into_iter().filter(|(s, e)| *s >= window_start && *e <= window_end) {
+        push(&text[pos..s], false);
+        push(&text[s..e], true);
+        pos = e;
+    }
+    push(&text[pos..window_end], false);
+    if window_end < text.len() {
+        push("…", false);
+    }
+
+    parts
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Ranked full-text search over the content of every session
+#[tauri::command]
+pub async fn search_transcripts(
+    query: String,
+    filters: Option<SearchFilters>,
+    limit: Option<usize>,
+) -> Result<SearchResponse, String> {
+    let filters = filters.unwrap_or_default();
+    let sources = match filters.source.as_deref() {
+        Some(id) => vec![find_source(Some(id))?],
+        None => registered_sources(),
+    };
+
+    refresh_search_index(&sources).await?;
+
+    let mut terms = tokenize(&query);
+    terms.sort();
+    terms.dedup();
+    if terms.is_empty() {
+        return Ok(SearchResponse { hits: Vec::new(), total_hits: 0, sessions_indexed: 0 });
+    }
+
+    // Hits with where their text lives, collected under the lock; snippets are read after releasing it
+    let (located, total_hits, sessions_indexed) = {
+        let guard = SEARCH_INDEX.lock().map_err(|e| format!("Failed to lock search index: {}", e))?;
+        let Some(index) = guard.as_ref() else {
+            return Ok(SearchResponse { hits: Vec::new(), total_hits: 0, sessions_indexed: 0 });
+        };
+
+        let mut scored: Vec<(f64, &SessionDocs, &SearchDoc)> = Vec::new();
+        let mut sessions_indexed = 0;
+        for session in index.sessions.values() {
+            if filters.source.as_ref().is_some_and(|s| &session.source != s) {
+                continue;
+            }
+            sessions_indexed += 1;
+
+            let candidates: HashSet<u32> = terms
+                .iter()
+                .filter_map(|term| session.postings.get(term))
+                .flat_map(|postings| postings.iter().map(|(doc, _)| *doc))
+                .collect();
+            for doc in candidates {
+                let search_doc = &session.docs[doc as usize];
+                if !matches_filters(session, search_doc, &filters) {
+                    continue;
+                }
+                let score = bm25(index, session, doc, &terms);
+                if score > 0.0 {
+                    scored.push((score, session, search_doc));
+                }
+            }
+        }
+
+        scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+        let total_hits = scored.len();
+
+        let located: Vec<(SearchHit, String, u64, usize)> = scored
+            .into_iter()
+            .take(limit.unwrap_or(DEFAULT_LIMIT))
+            .map(|(score, session, doc)| {
+                let hit = SearchHit {
+                    source: session.source.clone(),
+                    project_id: session.project_id.clone(),
+                    session_id: session.session_id.clone(),
+                    session_summary: session.summary.clone(),
+                    message_uuid: doc.message_uuid.clone(),
+                    page: message_page(doc.message_index),
+                    role: doc.role.clone(),
+                    kind: doc.kind.clone(),
+                    tool_name: doc.tool_name.clone(),
+                    model: doc.model.clone(),
+                    timestamp: doc.timestamp.clone(),
+                    score,
+                    snippet: Vec::new(),
+                };
+                (hit, session.file_path.clone(), doc.offset, doc.block)
+            })
+            .collect();
+
+        (located, total_hits, sessions_indexed)
+    };
+
+    let term_set: HashSet<String> = terms.into_iter().collect();
+    let hits = located
+        .into_iter()
+        .map(|(mut hit, file_path, offset, block)| {
+            if let Some(text) = read_doc_text(&hit.source, &file_path, offset, block) {
+                hit.snippet = build_snippet(&text, &term_set);
+            }
+            hit
+        })
+        .collect();
+
+    Ok(SearchResponse { hits, total_hits, sessions_indexed })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(uuid: &str, text: &str) -> Message {
+        Message {
+            uuid: uuid.to_string(),
+            msg_type: "user".to_string(),
+            timestamp: "2025-11-01T10:00:00Z".to_string(),
+            content: vec![serde_json::json!({ "type": "text", "text": text })],
+            model: None,
+            usage: None,
+        }
+    }
+
+    fn session(messages: &[Message]) -> SessionDocs {
+        let messages: Vec<(Option<u64>, Message)> =
+            messages.iter().enumerate().map(|(i, m)| (Some(i as u64 * 100), m.clone())).collect();
+        SessionDocs::new("code", "p".to_string(), "s".to_string(), String::new(), "s.jsonl".to_string(), 0, &messages)
+    }
+
+    #[test]
+    fn test_bm25_prefers_denser_match() {
+        let mut index = SearchIndex::default();
+        let messages = vec![
+            message("a", "the migration failed because the migration lock was held"),
+            message("b", "we talked about the weather and lunch and a migration plan for next quarter"),
+            message("c", "unrelated text about nothing"),
+        ];
+        index.insert("k".to_string(), session(&messages));
+
+        let terms = vec!["migration".to_string()];
+        let session = &index.sessions["k"];
+        assert_eq!(session.docs[1].offset, 100);
+        assert!(bm25(&index, session, 0, &terms) > bm25(&index, session, 1, &terms));
+        assert_eq!(bm25(&index, session, 2, &terms), 0.0);
+
+        index.remove("k");
+        assert_eq!(index.doc_count, 0);
+        assert!(index.doc_freq.is_empty());
+    }
+
+    #[test]
+    fn test_records_round_trip_through_disk() {
+        let dir = std::env::temp_dir().join(format!("search-index-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let docs = session(&[message("a", "flaky migration test")]);
+        write_session_record(&dir, &docs).unwrap();
+        let index = read_search_index_dir(&dir);
+        let key = session_key("code", "p", "s");
+        assert_eq!(index.doc_count, 1);
+        assert_eq!(index.sessions[&key].postings["migration"], vec![(0, 1)]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_snippet_highlights_terms() {
+        let terms: HashSet<String> = ["lock".to_string()].into_iter().collect();
+        let parts = build_snippet("Waiting on the Lock file", &terms);
+        let highlighted: Vec<&str> = parts.iter().filter(|p| p.highlight).map(|p| p.text.as_str()).collect();
+        assert_eq!(highlighted, vec!["Lock"]);
+        let joined: String = parts.iter().map(|p| p.text.as_str()).collect();
+        assert_eq!(joined, "Waiting on the Lock file");
+    }
+}
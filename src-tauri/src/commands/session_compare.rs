@@ -0,0 +1,420 @@
+/**
+ * Session comparison
+ *
+ * Compares two sessions that worked on the same task (different models,
+ * prompts or CLAUDE.md versions). Prompts are aligned one-to-one by word
+ * overlap, so a prompt that exists only in one run shows up as a gap rather
+ * than shifting every later pair. Alongside the aligned turns the comparison
+ * reports tool usage deltas, cost/token/duration differences, the overlap of
+ * files touched and both sessions' health side by side.
+ */
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use super::files::file_changes_from_messages;
+use super::pricing::TokenCounts;
+use super::sources::find_source;
+use super::transcripts::{message_cost, Message, SessionHealth, SessionStats};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Prompts less similar than this are never paired
+const MIN_PROMPT_SIMILARITY: f64 = 0.1;
+
+const PROMPT_PREVIEW_LENGTH: usize = 300;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompareTarget {
+    pub project_id: String,
+    pub session_id: String,
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComparedSession {
+    pub project_id: String,
+    pub session_id: String,
+    pub source: String,
+    pub summary: String,
+    /// Model that produced most assistant messages
+    pub model: Option<String>,
+    pub stats: Option<SessionStats>,
+}
+
+/// One prompt and the work done until the next prompt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptTurn {
+    /// Position of the prompt within its session (0-based)
+    pub index: usize,
+    pub message_uuid: String,
+    pub text: String,
+    pub timestamp: String,
+    pub tool_calls: usize,
+    pub tokens: u64,
+    pub cost_usd: f64,
+    pub duration_ms: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptPair {
+    pub left: Option<PromptTurn>,
+    pub right: Option<PromptTurn>,
+    /// Word overlap of the two prompts (0-1); 0 when one side is missing
+    pub similarity: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolDelta {
+    pub name: String,
+    pub left: usize,
+    pub right: usize,
+    pub delta: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricDelta {
+    pub left: f64,
+    pub right: f64,
+    /// right - left
+    pub delta: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsComparison {
+    pub cost_usd: MetricDelta,
+    pub input_tokens: MetricDelta,
+    pub output_tokens: MetricDelta,
+    pub cache_read_tokens: MetricDelta,
+    pub duration_ms: MetricDelta,
+    pub prompts: MetricDelta,
+    pub messages: MetricDelta,
+    pub tool_calls: MetricDelta,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilesOverlap {
+    pub both: Vec<String>,
+    pub left_only: Vec<String>,
+    pub right_only: Vec<String>,
+    /// |both| / |either|
+    pub jaccard: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthComparison {
+    pub left: Option<SessionHealth>,
+    pub right: Option<SessionHealth>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionComparison {
+    pub left: ComparedSession,
+    pub right: ComparedSession,
+    pub prompts: Vec<PromptPair>,
+    pub tools: Vec<ToolDelta>,
+    pub metrics: MetricsComparison,
+    pub files: FilesOverlap,
+    pub health: HealthComparison,
+}
+
+struct LoadedSession {
+    info: ComparedSession,
+    turns: Vec<PromptTurn>,
+    tokens: TokenCounts,
+    files: BTreeSet<String>,
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn metric(left: f64, right: f64) -> MetricDelta {
+    MetricDelta { left, right, delta: right - left }
+}
+
+fn words(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= 3)
+        .map(|w| w.to_lowercase())
+        .collect()
+}
+
+fn similarity(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+/// Text of a user prompt, or None for tool results and command/system output
+pub(crate) fn prompt_text(message: &Message) -> Option<String> {
+    if message.msg_type != "user" {
+        return None;
+    }
+    let mut text = None;
+    for block in &message.content {
+        match block.get("type").and_then(|t| t.as_str()) {
+            Some("tool_result") => return None,
+            Some("text") if text.is_none() => text = block.get("text").and_then(|t| t.as_str()),
+            _ => {}
+        }
+    }
+    let text = text?.trim();
+    if text.is_empty() || (text.starts_with('<') && text.ends_with('>')) {
+        return None;
+    }
+    Some(text.to_string())
+}
+
+fn timestamp_ms(ts: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc3339(ts).ok().map(|t| t.timestamp_millis())
+}
+
+/// Split messages (oldest first) into prompt turns
+fn prompt_turns(messages: &[Message]) -> Vec<PromptTurn> {
+    let mut turns: Vec<PromptTurn> = Vec::new();
+    let mut last_ts: Option<i64> = None;
+
+    for message in messages {
+        if let Some(text) = prompt_text(message) {
+            if let (Some(turn), Some(end)) = (turns.last_mut(), last_ts) {
+                turn.duration_ms = timestamp_ms(&turn.timestamp).map(|start| end - start).unwrap_or(0);
+            }
+            turns.push(PromptTurn {
+                index: turns.len(),
+                message_uuid: message.uuid.clone(),
+                text: text.chars().take(PROMPT_PREVIEW_LENGTH).collect(),
+                timestamp: message.timestamp.clone(),
+                tool_calls: 0,
+                tokens: 0,
+                cost_usd: 0.0,
+                duration_ms: 0,
+            });
+        } else if let Some(turn) = turns.last_mut() {
+            turn.tool_calls += message
+                .content
+                .iter()
+                .filter(|b| b.get("type").and_then(|t| t.as_str()) == Some("tool_use"))
+                .count();
+            turn.tokens += message.usage.as_ref().map(|u| u.total_tokens as u64).unwrap_or(0);
+            turn.cost_usd += message_cost(message);
+        }
+        last_ts = timestamp_ms(&message.timestamp).or(last_ts);
+    }
+
+    if let (Some(turn), Some(end)) = (turns.last_mut(), last_ts) {
+        turn.duration_ms = timestamp_ms(&turn.timestamp).map(|start| end - start).unwrap_or(0);
+    }
+
+    turns
+}
+
+/// Pair prompts in order, maximising total similarity; unmatched prompts become gaps
+fn align_prompts(left: &[PromptTurn], right: &[PromptTurn]) -> Vec<PromptPair> {
+    let left_words: Vec<HashSet<String>> = left.iter().map(|t| words(&t.text)).collect();
+    let right_words: Vec<HashSet<String>> = right.iter().map(|t| words(&t.text)).collect();
+    let (n, m) = (left.len(), right.len());
+
+    // best[i][j]: best score aligning left[i..] with right[j..]
+    let mut best = vec![vec![0.0f64; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            let mut score = best[i + 1][j].max(best[i][j + 1]);
+            let sim = similarity(&left_words[i], &right_words[j]);
+            if sim >= MIN_PROMPT_SIMILARITY {
+                score = score.max(best[i + 1][j + 1] + sim);
+            }
+            best[i][j] = score;
+        }
+    }
+
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m {
+            let sim = similarity(&left_words[i], &right_words[j]);
+            if sim >= MIN_PROMPT_SIMILARITY && (best[i][j] - (best[i + 1][j + 1] + sim)).abs() < 1e-9 {
+                pairs.push(PromptPair { left: Some(left[i].clone()), right: Some(right[j].clone()), similarity: sim });
+                i += 1;
+                j += 1;
+                continue;
+            }
+        }
+        if j >= m || (i < n && (best[i][j] - best[i + 1][j]).abs() < 1e-9) {
+            pairs.push(PromptPair { left: Some(left[i].clone()), right: None, similarity: 0.0 });
+            i += 1;
+        } else {
+            pairs.push(PromptPair { left: None, right: Some(right[j].clone()), similarity: 0.0 });
+            j += 1;
+        }
+    }
+
+    pairs
+}
+
+async fn load_session(target: CompareTarget) -> Result<LoadedSession, String> {
+    let source = find_source(target.source.as_deref())?;
+    let transcript = source
+        .session_transcript(target.project_id.clone(), target.session_id.clone())
+        .await?;
+
+    let mut messages = transcript.messages;
+    messages.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+
+    let mut tokens = TokenCounts::default();
+    let mut models: HashMap<String, usize> = HashMap::new();
+    for message in &messages {
+        if let Some(usage) = &message.usage {
+            let counts = usage.token_counts();
+            tokens.input += counts.input;
+            tokens.output += counts.output;
+            tokens.cache_read += counts.cache_read;
+            tokens.cache_write_5m += counts.cache_write_5m;
+            tokens.cache_write_1h += counts.cache_write_1h;
+        }
+        if let Some(model) = message.model.as_ref().filter(|_| message.msg_type == "assistant") {
+            *models.entry(model.clone()).or_insert(0) += 1;
+        }
+    }
+
+    let files = file_changes_from_messages(&messages, &target.session_id, &target.project_id)
+        .into_iter()
+        .map(|c| c.file_path)
+        .collect();
+
+    Ok(LoadedSession {
+        info: ComparedSession {
+            project_id: target.project_id,
+            session_id: target.session_id,
+            source: source.id().to_string(),
+            summary: transcript.session.summary,
+            model: models.into_iter().max_by_key(|(_, n)| *n).map(|(m, _)| m),
+            stats: transcript.session.stats,
+        },
+        turns: prompt_turns(&messages),
+        tokens,
+        files,
+    })
+}
+
+fn tool_deltas(left: Option<&SessionStats>, right: Option<&SessionStats>) -> Vec<ToolDelta> {
+    let empty = HashMap::new();
+    let left = left.and_then(|s| s.tool_breakdown.as_ref()).unwrap_or(&empty);
+    let right = right.and_then(|s| s.tool_breakdown.as_ref()).unwrap_or(&empty);
+
+    let names: BTreeSet<&String> = left.keys().chain(right.keys()).collect();
+    let mut deltas: Vec<ToolDelta> = names
+        .into_iter()
+        .map(|name| {
+            let l = left.get(name).copied().unwrap_or(0);
+            let r = right.get(name).copied().unwrap_or(0);
+            ToolDelta { name: name.clone(), left: l, right: r, delta: r as i64 - l as i64 }
+        })
+        .collect();
+    deltas.sort_by_key(|d| std::cmp::Reverse(d.delta.abs()));
+    deltas
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Compare two sessions: aligned prompts, tool/cost/token/duration deltas, file overlap and health
+#[tauri::command]
+pub async fn compare_sessions(left: CompareTarget, right: CompareTarget) -> Result<SessionComparison, String> {
+    let left = load_session(left).await?;
+    let right = load_session(right).await?;
+
+    let stat = |s: &LoadedSession, f: fn(&SessionStats) -> f64| s.info.stats.as_ref().map(f).unwrap_or(0.0);
+
+    let metrics = MetricsComparison {
+        cost_usd: metric(stat(&left, |s| s.total_cost_usd), stat(&right, |s| s.total_cost_usd)),
+        input_tokens: metric(left.tokens.input as f64, right.tokens.input as f64),
+        output_tokens: metric(left.tokens.output as f64, right.tokens.output as f64),
+        cache_read_tokens: metric(left.tokens.cache_read as f64, right.tokens.cache_read as f64),
+        duration_ms: metric(stat(&left, |s| s.duration_ms as f64), stat(&right, |s| s.duration_ms as f64)),
+        prompts: metric(left.turns.len() as f64, right.turns.len() as f64),
+        messages: metric(stat(&left, |s| s.message_count as f64), stat(&right, |s| s.message_count as f64)),
+        tool_calls: metric(stat(&left, |s| s.tool_call_count as f64), stat(&right, |s| s.tool_call_count as f64)),
+    };
+
+    let both: Vec<String> = left.files.intersection(&right.files).cloned().collect();
+    let either = left.files.union(&right.files).count();
+    let files = FilesOverlap {
+        left_only: left.files.difference(&right.files).cloned().collect(),
+        right_only: right.files.difference(&left.files).cloned().collect(),
+        jaccard: if either == 0 { 0.0 } else { both.len() as f64 / either as f64 },
+        both,
+    };
+
+    Ok(SessionComparison {
+        prompts: align_prompts(&left.turns, &right.turns),
+        tools: tool_deltas(left.info.stats.as_ref(), right.info.stats.as_ref()),
+        metrics,
+        files,
+        health: HealthComparison {
+            left: left.info.stats.as_ref().and_then(|s| s.health.clone()),
+            right: right.info.stats.as_ref().and_then(|s| s.health.clone()),
+        },
+        left: left.info,
+        right: right.info,
+    })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(index: usize, text: &str) -> PromptTurn {
+        PromptTurn {
+            index,
+            message_uuid: format!("m{}", index),
+            text: text.to_string(),
+            timestamp: String::new(),
+            tool_calls: 0,
+            tokens: 0,
+            cost_usd: 0.0,
+            duration_ms: 0,
+        }
+    }
+
+    #[test]
+    fn test_align_prompts_with_gap() {
+        let left = vec![
+            turn(0, "add a login form with email and password"),
+            turn(1, "write tests for the login form"),
+        ];
+        let right = vec![
+            turn(0, "add a login form with email and password fields"),
+            turn(1, "use tailwind for styling"),
+            turn(2, "write unit tests for the login form"),
+        ];
+
+        let pairs = align_prompts(&left, &right);
+        let shape: Vec<(Option<usize>, Option<usize>)> = pairs
+            .iter()
+            .map(|p| (p.left.as_ref().map(|t| t.index), p.right.as_ref().map(|t| t.index)))
+            .collect();
+        assert_eq!(shape, vec![(Some(0), Some(0)), (None, Some(1)), (Some(1), Some(2))]);
+    }
+}
@@ -0,0 +1,627 @@
+/**
+ * Session export
+ *
+ * Renders one session as Markdown, a self-contained HTML page or normalized
+ * JSON, for pasting into pull requests and postmortems. Messages are first
+ * normalized into `ExportMessage`s (chronological, redacted, with tool results
+ * labelled by tool name and subagent runs optionally inlined under the tool
+ * call that started them), then rendered to the requested format.
+ */
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+
+use super::secrets::redact_text;
+use super::sources::{find_source, CLAUDE_CODE_SOURCE};
+use super::transcripts::{load_claude_subagent_messages, Message, Session};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+pub const FORMAT_MARKDOWN: &str = "markdown";
+pub const FORMAT_HTML: &str = "html";
+pub const FORMAT_JSON: &str = "json";
+
+const HTML_STYLE: &str = r#"
+body { font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", sans-serif; max-width: 960px; margin: 2rem auto; padding: 0 1rem; color: #1f2328; line-height: 1.5; }
+header { border-bottom: 1px solid #d0d7de; margin-bottom: 1.5rem; }
+header dl { display: grid; grid-template-columns: max-content 1fr; gap: 0.25rem 1rem; font-size: 0.9rem; }
+header dt { color: #656d76; }
+.message { border: 1px solid #d0d7de; border-radius: 6px; margin: 1rem 0; padding: 0.75rem 1rem; }
+.message.user { background: #f6f8fa; }
+.message h2 { font-size: 0.95rem; margin: 0 0 0.5rem; }
+.message h2 .meta { color: #656d76; font-weight: normal; }
+.text { white-space: pre-wrap; word-wrap: break-word; }
+.thinking { color: #656d76; font-style: italic; white-space: pre-wrap; }
+pre { background: #f6f8fa; border-radius: 6px; padding: 0.75rem; overflow-x: auto; font-size: 0.85rem; }
+.tool { font-weight: 600; }
+.error { color: #cf222e; }
+.subagent { border-left: 3px solid #8250df; padding-left: 1rem; margin: 0.5rem 0; }
+img { max-width: 100%; }
+"#;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportOptions {
+    /// Fold tool results into <details> blocks (default: true)
+    pub collapse_tool_results: Option<bool>,
+    /// Include thinking blocks (default: false)
+    pub include_thinking: Option<bool>,
+    /// Embed images as data URIs instead of placeholders (default: false)
+    pub embed_images: Option<bool>,
+    /// Inline subagent transcripts under the tool call that started them (default: false)
+    pub inline_subagents: Option<bool>,
+    /// Redact detected secrets (default: true)
+    pub redact: Option<bool>,
+}
+
+struct Settings {
+    collapse_tool_results: bool,
+    include_thinking: bool,
+    embed_images: bool,
+    inline_subagents: bool,
+    redact: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ExportBlock {
+    Text {
+        text: String,
+    },
+    Thinking {
+        text: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    ToolUse {
+        id: String,
+        name: String,
+        /// Pretty-printed JSON input
+        input: String,
+        subagent: Option<Vec<ExportMessage>>,
+    },
+    #[serde(rename_all = "camelCase")]
+    ToolResult {
+        tool_use_id: String,
+        tool_name: Option<String>,
+        content: String,
+        is_error: bool,
+    },
+    #[serde(rename_all = "camelCase")]
+    Image {
+        media_type: String,
+        /// Base64 data, only when images are embedded
+        data: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportMessage {
+    pub uuid: String,
+    pub role: String,
+    pub timestamp: String,
+    pub model: Option<String>,
+    pub blocks: Vec<ExportBlock>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportDocument {
+    pub project_id: String,
+    pub session_id: String,
+    pub source: String,
+    pub summary: String,
+    pub models: Vec<String>,
+    pub start_timestamp: Option<String>,
+    pub end_timestamp: Option<String>,
+    pub total_cost_usd: f64,
+    pub messages: Vec<ExportMessage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionExport {
+    pub format: String,
+    pub file_name: String,
+    pub mime_type: String,
+    pub content: String,
+    /// Set when the export was written to disk
+    pub output_path: Option<String>,
+}
+
+// ============================================================================
+// Normalization
+// ============================================================================
+
+impl Settings {
+    fn from_options(options: ExportOptions) -> Self {
+        Self {
+            collapse_tool_results: options.collapse_tool_results.unwrap_or(true),
+            include_thinking: options.include_thinking.unwrap_or(false),
+            embed_images: options.embed_images.unwrap_or(false),
+            inline_subagents: options.inline_subagents.unwrap_or(false),
+            redact: options.redact.unwrap_or(true),
+        }
+    }
+
+    fn clean(&self, text: &str) -> String {
+        if self.redact {
+            redact_text(text)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+fn image_block(block: &Value, settings: &Settings) -> ExportBlock {
+    let source = block.get("source");
+    ExportBlock::Image {
+        media_type: source
+            .and_then(|s| s.get("media_type"))
+            .and_then(|m| m.as_str())
+            .unwrap_or("image")
+            .to_string(),
+        data: source
+            .filter(|_| settings.embed_images)
+            .filter(|s| s.get("type").and_then(|t| t.as_str()) == Some("base64"))
+            .and_then(|s| s.get("data"))
+            .and_then(|d| d.as_str())
+            .map(String::from),
+    }
+}
+
+/// Text of a tool result, plus any images it carries
+fn tool_result_content(content: Option<&Value>, settings: &Settings) -> (String, Vec<ExportBlock>) {
+    match content {
+        Some(Value::String(s)) => (s.clone(), Vec::new()),
+        Some(Value::Array(parts)) => {
+            let mut texts = Vec::new();
+            let mut images = Vec::new();
+            for part in parts {
+                match part.get("type").and_then(|t| t.as_str()) {
+                    Some("text") => texts.push(part.get("text").and_then(|t| t.as_str()).unwrap_or("").to_string()),
+                    Some("image") => images.push(image_block(part, settings)),
+                    _ => {}
+                }
+            }
+            (texts.join("\n"), images)
+        }
+        Some(Value::Null) | None => (String::new(), Vec::new()),
+        Some(other) => (other.to_string(), Vec::new()),
+    }
+}
+
+/// Sorts messages chronologically and normalizes their content blocks
+fn normalize_messages(mut messages: Vec<Message>, settings: &Settings) -> Vec<ExportMessage> {
+    messages.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+
+    let mut tool_names: HashMap<String, String> = HashMap::new();
+    let mut result = Vec::new();
+
+    for message in messages {
+        if message.msg_type != "user" && message.msg_type != "assistant" {
+            continue;
+        }
+
+        let mut blocks = Vec::new();
+        for block in &message.content {
+            match block.get("type").and_then(|t| t.as_str()) {
+                Some("text") => {
+                    let text = block.get("text").and_then(|t| t.as_str()).unwrap_or("");
+                    if !text.trim().is_empty() {
+                        blocks.push(ExportBlock::Text { text: settings.clean(text) });
+                    }
+                }
+                Some("thinking") if settings.include_thinking => {
+                    let text = block.get("thinking").or(block.get("text")).and_then(|t| t.as_str()).unwrap_or("");
+                    if !text.trim().is_empty() {
+                        blocks.push(ExportBlock::Thinking { text: settings.clean(text) });
+                    }
+                }
+                Some("tool_use") => {
+                    let id = block.get("id").and_then(|i| i.as_str()).unwrap_or("").to_string();
+                    let name = block.get("name").and_then(|n| n.as_str()).unwrap_or("tool").to_string();
+                    let input = block
+                        .get("input")
+                        .map(|i| serde_json::to_string_pretty(i).unwrap_or_default())
+                        .unwrap_or_default();
+                    tool_names.insert(id.clone(), name.clone());
+                    blocks.push(ExportBlock::ToolUse { id, name, input: settings.clean(&input), subagent: None });
+                }
+                Some("tool_result") => {
+                    let tool_use_id = block.get("tool_use_id").and_then(|i| i.as_str()).unwrap_or("").to_string();
+                    let (content, images) = tool_result_content(block.get("content"), settings);
+                    blocks.push(ExportBlock::ToolResult {
+                        tool_name: tool_names.get(&tool_use_id).cloned(),
+                        tool_use_id,
+                        content: settings.clean(&content),
+                        is_error: block.get("is_error").and_then(|e| e.as_bool()).unwrap_or(false),
+                    });
+                    blocks.extend(images);
+                }
+                Some("image") => blocks.push(image_block(block, settings)),
+                _ => {}
+            }
+        }
+
+        if !blocks.is_empty() {
+            result.push(ExportMessage {
+                uuid: message.uuid,
+                role: message.msg_type,
+                timestamp: message.timestamp,
+                model: message.model,
+                blocks,
+            });
+        }
+    }
+
+    result
+}
+
+/// Agent id recorded on each tool_use block that started a subagent
+fn subagent_ids(messages: &[Message]) -> HashMap<String, String> {
+    messages
+        .iter()
+        .flat_map(|m| m.content.iter())
+        .filter(|b| b.get("type").and_then(|t| t.as_str()) == Some("tool_use"))
+        .filter_map(|b| {
+            let id = b.get("id").and_then(|i| i.as_str())?;
+            let agent_id = b.get("agentId").and_then(|a| a.as_str())?;
+            Some((id.to_string(), agent_id.to_string()))
+        })
+        .collect()
+}
+
+async fn inline_subagents(
+    exported: &mut [ExportMessage],
+    agents: HashMap<String, String>,
+    project_id: &str,
+    session_id: &str,
+    settings: &Settings,
+) {
+    for message in exported.iter_mut() {
+        for block in message.blocks.iter_mut() {
+            let ExportBlock::ToolUse { id, subagent, .. } = block else { continue };
+            let Some(agent_id) = agents.get(id.as_str()) else { continue };
+            match load_claude_subagent_messages(project_id.to_string(), session_id.to_string(), agent_id.clone()).await {
+                Ok(messages) => *subagent = Some(normalize_messages(messages, settings)),
+                Err(e) => log::warn!("Skipping subagent {}: {}", agent_id, e),
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Renderers
+// ============================================================================
+
+fn role_label(role: &str) -> &'static str {
+    if role == "user" {
+        "User"
+    } else {
+        "Assistant"
+    }
+}
+
+/// Code fence longer than any backtick run inside `content`
+fn fence_for(content: &str) -> String {
+    let mut longest = 0;
+    let mut run = 0;
+    for c in content.chars() {
+        run = if c == '`' { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+    "`".repeat((longest + 1).max(3))
+}
+
+fn markdown_code(content: &str, lang: &str) -> String {
+    let fence = fence_for(content);
+    format!("{}{}\n{}\n{}\n", fence, lang, content.trim_end(), fence)
+}
+
+fn render_markdown_messages(messages: &[ExportMessage], level: usize, settings: &Settings, out: &mut String) {
+    let heading = "#".repeat(level);
+
+    for message in messages {
+        let model = message.model.as_deref().map(|m| format!(" ({})", m)).unwrap_or_default();
+        out.push_str(&format!("{} {}{} · {}\n\n", heading, role_label(&message.role), model, message.timestamp));
+
+        for block in &message.blocks {
+            match block {
+                ExportBlock::Text { text } => out.push_str(&format!("{}\n\n", text.trim_end())),
+                ExportBlock::Thinking { text } => {
+                    out.push_str(&format!("<details>\n<summary>Thinking</summary>\n\n{}\n\n</details>\n\n", text.trim_end()));
+                }
+                ExportBlock::ToolUse { name, input, subagent, .. } => {
+                    out.push_str(&format!("**Tool: {}**\n\n{}\n", name, markdown_code(input, "json")));
+                    if let Some(sub) = subagent {
+                        out.push_str("<details>\n<summary>Subagent transcript</summary>\n\n");
+                        render_markdown_messages(sub, (level + 1).min(6), settings, out);
+                        out.push_str("</details>\n\n");
+                    }
+                }
+                ExportBlock::ToolResult { tool_name, content, is_error, .. } => {
+                    let label = format!(
+                        "{}{}",
+                        if *is_error { "Error" } else { "Result" },
+                        tool_name.as_deref().map(|n| format!(": {}", n)).unwrap_or_default()
+                    );
+                    if settings.collapse_tool_results {
+                        out.push_str(&format!("<details>\n<summary>{}</summary>\n\n{}\n</details>\n\n", label, markdown_code(content, "")));
+                    } else {
+                        out.push_str(&format!("**{}**\n\n{}\n", label, markdown_code(content, "")));
+                    }
+                }
+                ExportBlock::Image { media_type, data } => match data {
+                    Some(data) => out.push_str(&format!("![image](data:{};base64,{})\n\n", media_type, data)),
+                    None => out.push_str(&format!("_[image: {}]_\n\n", media_type)),
+                },
+            }
+        }
+    }
+}
+
+fn render_markdown(doc: &ExportDocument, settings: &Settings) -> String {
+    let mut out = format!("# {}\n\n", if doc.summary.is_empty() { &doc.session_id } else { &doc.summary });
+    out.push_str(&format!("- Session: `{}`\n", doc.session_id));
+    out.push_str(&format!("- Source: {}\n", doc.source));
+    if !doc.models.is_empty() {
+        out.push_str(&format!("- Models: {}\n", doc.models.join(", ")));
+    }
+    if let (Some(start), Some(end)) = (&doc.start_timestamp, &doc.end_timestamp) {
+        out.push_str(&format!("- Time: {} → {}\n", start, end));
+    }
+    out.push_str(&format!("- Cost: ${:.2}\n\n---\n\n", doc.total_cost_usd));
+
+    render_markdown_messages(&doc.messages, 2, settings, &mut out);
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn render_html_messages(messages: &[ExportMessage], settings: &Settings, out: &mut String) {
+    for message in messages {
+        let model = message.model.as_deref().map(|m| format!(" · {}", escape_html(m))).unwrap_or_default();
+        out.push_str(&format!(
+            "<section class=\"message {}\">\n<h2>{} <span class=\"meta\">{}{}</span></h2>\n",
+            escape_html(&message.role),
+            role_label(&message.role),
+            escape_html(&message.timestamp),
+            model
+        ));
+
+        for block in &message.blocks {
+            match block {
+                ExportBlock::Text { text } => out.push_str(&format!("<div class=\"text\">{}</div>\n", escape_html(text.trim_end()))),
+                ExportBlock::Thinking { text } => {
+                    out.push_str(&format!(
+                        "<details><summary>Thinking</summary><div class=\"thinking\">{}</div></details>\n",
+                        escape_html(text.trim_end())
+                    ));
+                }
+                ExportBlock::ToolUse { name, input, subagent, .. } => {
+                    out.push_str(&format!(
+                        "<div class=\"tool\">Tool: {}</div>\n<pre><code>{}</code></pre>\n",
+                        escape_html(name),
+                        escape_html(input)
+                    ));
+                    if let Some(sub) = subagent {
+                        out.push_str("<details><summary>Subagent transcript</summary><div class=\"subagent\">\n");
+                        render_html_messages(sub, settings, out);
+                        out.push_str("</div></details>\n");
+                    }
+                }
+                ExportBlock::ToolResult { tool_name, content, is_error, .. } => {
+                    let label = format!(
+                        "{}{}",
+                        if *is_error { "Error" } else { "Result" },
+                        tool_name.as_deref().map(|n| format!(": {}", escape_html(n))).unwrap_or_default()
+                    );
+                    let class = if *is_error { " class=\"error\"" } else { "" };
+                    let body = format!("<pre{}><code>{}</code></pre>", class, escape_html(content.trim_end()));
+                    if settings.collapse_tool_results {
+                        out.push_str(&format!("<details><summary>{}</summary>{}</details>\n", label, body));
+                    } else {
+                        out.push_str(&format!("<div class=\"tool\">{}</div>\n{}\n", label, body));
+                    }
+                }
+                ExportBlock::Image { media_type, data } => match data {
+                    Some(data) => out.push_str(&format!(
+                        "<img src=\"data:{};base64,{}\" alt=\"image\">\n",
+                        escape_html(media_type),
+                        escape_html(data)
+                    )),
+                    None => out.push_str(&format!("<p><em>[image: {}]</em></p>\n", escape_html(media_type))),
+                },
+            }
+        }
+
+        out.push_str("</section>\n");
+    }
+}
+
+fn render_html(doc: &ExportDocument, settings: &Settings) -> String {
+    let title = escape_html(if doc.summary.is_empty() { &doc.session_id } else { &doc.summary });
+
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n",
+        title, HTML_STYLE
+    );
+    out.push_str(&format!("<header>\n<h1>{}</h1>\n<dl>\n", title));
+    out.push_str(&format!("<dt>Session</dt><dd><code>{}</code></dd>\n", escape_html(&doc.session_id)));
+    out.push_str(&format!("<dt>Source</dt><dd>{}</dd>\n", escape_html(&doc.source)));
+    if !doc.models.is_empty() {
+        out.push_str(&format!("<dt>Models</dt><dd>{}</dd>\n", escape_html(&doc.models.join(", "))));
+    }
+    if let (Some(start), Some(end)) = (&doc.start_timestamp, &doc.end_timestamp) {
+        out.push_str(&format!("<dt>Time</dt><dd>{} → {}</dd>\n", escape_html(start), escape_html(end)));
+    }
+    out.push_str(&format!("<dt>Cost</dt><dd>${:.2}</dd>\n</dl>\n</header>\n<main>\n", doc.total_cost_usd));
+
+    render_html_messages(&doc.messages, settings, &mut out);
+    out.push_str("</main>\n</body>\n</html>\n");
+    out
+}
+
+fn file_stem(session: &Session) -> String {
+    let base: String = session
+        .summary
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    let base: Vec<&str> = base.split('-').filter(|s| !s.is_empty()).take(8).collect();
+    if base.is_empty() {
+        format!("session-{}", session.id)
+    } else {
+        base.join("-")
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Export a session as Markdown, HTML or JSON; writes to `output_path` when given
+#[tauri::command]
+pub async fn export_session(
+    project_id: String,
+    session_id: String,
+    source: Option<String>,
+    format: String,
+    options: Option<ExportOptions>,
+    output_path: Option<String>,
+) -> Result<SessionExport, String> {
+    let (extension, mime_type) = match format.as_str() {
+        FORMAT_MARKDOWN => ("md", "text/markdown"),
+        FORMAT_HTML => ("html", "text/html"),
+        FORMAT_JSON => ("json", "application/json"),
+        other => return Err(format!("Unsupported export format: {}", other)),
+    };
+    let settings = Settings::from_options(options.unwrap_or_default());
+
+    let source = find_source(source.as_deref())?;
+    let transcript = source.session_transcript(project_id.clone(), session_id.clone()).await?;
+    let session = transcript.session;
+
+    let agents = subagent_ids(&transcript.messages);
+    let mut messages = normalize_messages(transcript.messages, &settings);
+    if settings.inline_subagents && source.id() == CLAUDE_CODE_SOURCE {
+        inline_subagents(&mut messages, agents, &project_id, &session_id, &settings).await;
+    }
+
+    let mut models: Vec<String> = messages.iter().filter_map(|m| m.model.clone()).collect();
+    models.sort();
+    models.dedup();
+
+    let stats = session.stats.as_ref();
+    let doc = ExportDocument {
+        project_id,
+        session_id,
+        source: source.id().to_string(),
+        summary: settings.clean(&session.summary),
+        models,
+        start_timestamp: stats.and_then(|s| s.start_timestamp.clone()).or_else(|| messages.first().map(|m| m.timestamp.clone())),
+        end_timestamp: stats.and_then(|s| s.end_timestamp.clone()).or_else(|| messages.last().map(|m| m.timestamp.clone())),
+        total_cost_usd: stats.map(|s| s.total_cost_usd).unwrap_or(0.0),
+        messages,
+    };
+
+    let content = match format.as_str() {
+        FORMAT_MARKDOWN => render_markdown(&doc, &settings),
+        FORMAT_HTML => render_html(&doc, &settings),
+        _ => serde_json::to_string_pretty(&doc).map_err(|e| format!("Failed to serialize export: {}", e))?,
+    };
+
+    if let Some(path) = &output_path {
+        if let Some(parent) = std::path::Path::new(path).parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create directories: {}", e))?;
+        }
+        fs::write(path, &content).map_err(|e| format!("Failed to write export: {}", e))?;
+    }
+
+    Ok(SessionExport {
+        format,
+        file_name: format!("{}.{}", file_stem(&session), extension),
+        mime_type: mime_type.to_string(),
+        content,
+        output_path,
+    })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn settings() -> Settings {
+        Settings::from_options(ExportOptions { redact: Some(false), ..Default::default() })
+    }
+
+    #[test]
+    fn test_normalize_labels_results_and_skips_thinking() {
+        let messages = vec![
+            Message {
+                uuid: "b".to_string(),
+                msg_type: "user".to_string(),
+                timestamp: "2025-11-01T10:00:02Z".to_string(),
+                content: vec![json!({ "type": "tool_result", "tool_use_id": "t1", "content": [{ "type": "text", "text": "ok" }] })],
+                model: None,
+                usage: None,
+            },
+            Message {
+                uuid: "a".to_string(),
+                msg_type: "assistant".to_string(),
+                timestamp: "2025-11-01T10:00:01Z".to_string(),
+                content: vec![
+                    json!({ "type": "thinking", "thinking": "hmm" }),
+                    json!({ "type": "tool_use", "id": "t1", "name": "Bash", "input": { "command": "ls" } }),
+                ],
+                model: Some("claude-sonnet-4-5".to_string()),
+                usage: None,
+            },
+        ];
+
+        let exported = normalize_messages(messages, &settings());
+        assert_eq!(exported.len(), 2);
+        assert_eq!(exported[0].uuid, "a");
+        assert_eq!(exported[0].blocks.len(), 1);
+        match &exported[1].blocks[0] {
+            ExportBlock::ToolResult { tool_name, content, .. } => {
+                assert_eq!(tool_name.as_deref(), Some("Bash"));
+                assert_eq!(content, "ok");
+            }
+            other => panic!("unexpected block: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_fence_is_longer_than_content_backticks() {
+        assert_eq!(fence_for("plain"), "```");
+        assert_eq!(fence_for("has ```` inside"), "`````");
+    }
+}
@@ -0,0 +1,719 @@
+/**
+ * Windowed session health
+ *
+ * Whole-session averages hide trouble: twenty minutes of looping inside a
+ * four-hour run still averages out as healthy. Health is therefore also
+ * computed over sliding windows (30 minutes every 5 by default), giving a time
+ * series of rates, statuses and verdicts per session.
+ *
+ * Loop signatures are detected directly from tool calls:
+ * - repeatedToolCall: the same tool with identical input, repeated within a window
+ * - repeatedEditFailure: the same Edit failing again because old_string is not found
+ * - repeatedToolError: the same tool failing with the same error within a window
+ *
+ * Thresholds live in ~/.claude-dashboard/health-thresholds.json as defaults
+ * plus per-project overrides.
+ */
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use super::session_compare::prompt_text;
+use super::sources::find_source;
+use super::tool_reliability::{codex_output_error, error_signature};
+use super::transcripts::{message_cost, Message};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+pub const STATUS_HEALTHY: &str = "healthy";
+pub const STATUS_STALLED: &str = "stalled";
+pub const STATUS_FRANTIC: &str = "frantic";
+pub const STATUS_LOOPING: &str = "looping";
+pub const STATUS_EXPLODING: &str = "exploding";
+pub const STATUS_HEAVY: &str = "heavy";
+pub const STATUS_EXPENSIVE: &str = "expensive";
+
+pub const VERDICT_CONTINUE: &str = "continue";
+pub const VERDICT_CONSTRAIN: &str = "constrain";
+pub const VERDICT_RESTART: &str = "restart";
+
+pub const LOOP_REPEATED_CALL: &str = "repeatedToolCall";
+pub const LOOP_EDIT_FAILURE: &str = "repeatedEditFailure";
+pub const LOOP_REPEATED_ERROR: &str = "repeatedToolError";
+
+/// Heavy or expensive sessions with fewer prompts per hour than this should restart
+const RESTART_BELOW_PROMPTS_PER_HOUR: f64 = 5.0;
+
+const MAX_DETAIL: usize = 120;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HealthThresholds {
+    pub window_minutes: u32,
+    pub step_minutes: u32,
+    pub max_prompts_per_hour: f64,
+    /// Below this over a full window (or over an hour for whole sessions) the session is stalled
+    pub min_prompts_per_hour: f64,
+    pub max_tool_calls_per_prompt: f64,
+    pub max_assistant_messages_per_prompt: f64,
+    pub max_tokens_per_minute: f64,
+    pub max_cost_per_minute: f64,
+    /// Identical tool calls (or identical errors) within one window that count as a loop
+    pub repeated_call_threshold: usize,
+    /// Failures of the same Edit that count as a loop
+    pub edit_failure_threshold: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct HealthConfigFile {
+    #[serde(default)]
+    defaults: Option<HealthThresholds>,
+    #[serde(default)]
+    projects: HashMap<String, HealthThresholds>,
+}
+
+/// Rates a status is derived from
+#[derive(Debug, Clone, Default)]
+pub struct HealthMetrics {
+    pub prompts_per_hour: f64,
+    pub tool_calls_per_prompt: f64,
+    pub assistant_messages_per_prompt: f64,
+    pub tokens_per_minute: f64,
+    pub cost_per_minute: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthWindow {
+    pub start: String,
+    pub end: String,
+    pub prompts: usize,
+    pub tool_calls: usize,
+    pub tool_errors: usize,
+    pub assistant_messages: usize,
+    pub tokens: u64,
+    pub cost_usd: f64,
+    /// Most repetitions of one identical tool call in the window
+    pub max_identical_calls: usize,
+    pub prompts_per_hour: f64,
+    /// Tool calls per prompt, counting at least one prompt
+    pub tool_calls_per_prompt: f64,
+    pub assistant_messages_per_prompt: f64,
+    pub tokens_per_minute: f64,
+    pub cost_per_minute: f64,
+    pub status: String,
+    pub verdict: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoopSignature {
+    /// "repeatedToolCall", "repeatedEditFailure" or "repeatedToolError"
+    pub kind: String,
+    pub tool: String,
+    /// Input preview, file path or error message
+    pub detail: String,
+    pub count: usize,
+    /// Most occurrences within one window
+    pub max_in_window: usize,
+    pub first_seen: String,
+    pub last_seen: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionHealthTimeline {
+    pub project_id: String,
+    pub session_id: String,
+    pub thresholds: HealthThresholds,
+    /// Status and verdict of the worst window
+    pub status: String,
+    pub verdict: String,
+    pub worst_window: Option<usize>,
+    pub unhealthy_windows: usize,
+    /// Oldest first
+    pub windows: Vec<HealthWindow>,
+    /// Most occurrences first
+    pub loops: Vec<LoopSignature>,
+}
+
+/// What one message contributes to a window
+struct MessageEvent {
+    ms: i64,
+    prompt: bool,
+    assistant: bool,
+    tokens: u64,
+    cost_usd: f64,
+    /// Indices into the session's tool calls
+    calls: Vec<usize>,
+    errors: usize,
+}
+
+struct ToolCallEvent {
+    ms: i64,
+    timestamp: String,
+    tool: String,
+    /// Tool name plus serialized input
+    key: String,
+    input: Value,
+}
+
+impl Default for HealthThresholds {
+    fn default() -> Self {
+        Self {
+            window_minutes: 30,
+            step_minutes: 5,
+            max_prompts_per_hour: 20.0,
+            min_prompts_per_hour: 2.0,
+            max_tool_calls_per_prompt: 8.0,
+            max_assistant_messages_per_prompt: 5.0,
+            max_tokens_per_minute: 50_000.0,
+            max_cost_per_minute: 0.50,
+            repeated_call_threshold: 3,
+            edit_failure_threshold: 2,
+        }
+    }
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn get_config_path() -> Result<PathBuf, String> {
+    let home_dir = dirs::home_dir().ok_or_else(|| "Could not determine home directory".to_string())?;
+    Ok(home_dir.join(".claude-dashboard").join("health-thresholds.json"))
+}
+
+fn read_config() -> HealthConfigFile {
+    let Ok(path) = get_config_path() else { return HealthConfigFile::default() };
+    let Ok(content) = std::fs::read_to_string(&path) else { return HealthConfigFile::default() };
+
+    match serde_json::from_str::<HealthConfigFile>(&content) {
+        Ok(config) => config,
+        Err(e) => {
+            log::warn!("Ignoring invalid health thresholds in {}: {}", path.display(), e);
+            HealthConfigFile::default()
+        }
+    }
+}
+
+async fn write_config(config: &HealthConfigFile) -> Result<(), String> {
+    let path = get_config_path()?;
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+
+    let content =
+        serde_json::to_string_pretty(config).map_err(|e| format!("Failed to serialize health thresholds: {}", e))?;
+    tokio::fs::write(&path, content)
+        .await
+        .map_err(|e| format!("Failed to write health thresholds: {}", e))
+}
+
+fn timestamp_ms(ts: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc3339(ts).ok().map(|t| t.timestamp_millis())
+}
+
+fn ms_to_rfc3339(ms: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(ms).map(|t| t.to_rfc3339()).unwrap_or_default()
+}
+
+fn preview(text: &str) -> String {
+    let line = text.lines().next().unwrap_or("");
+    line.chars().take(MAX_DETAIL).collect()
+}
+
+fn verdict_rank(verdict: &str) -> u8 {
+    match verdict {
+        VERDICT_RESTART => 2,
+        VERDICT_CONSTRAIN => 1,
+        _ => 0,
+    }
+}
+
+/// Whether a tool_result block reports a failure; Codex outputs carry no flag
+fn result_failed(block: &Value) -> bool {
+    match block.get("is_error").and_then(|v| v.as_bool()) {
+        Some(is_error) => is_error,
+        None => block.get("content").and_then(|c| c.as_str()).and_then(codex_output_error).is_some(),
+    }
+}
+
+fn result_text(block: &Value) -> String {
+    match block.get("content") {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Array(parts)) => parts
+            .iter()
+            .filter_map(|p| p.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<&str>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// An Edit whose old_string was not in the file
+fn is_edit_miss(tool: &str, error: &str) -> bool {
+    let error = error.to_lowercase();
+    matches!(tool, "Edit" | "MultiEdit") && (error.contains("string to replace not found") || error.contains("old_string not found"))
+}
+
+/// Most timestamps (sorted) within any span of `window_ms`
+fn max_in_window(sorted_ms: &[i64], window_ms: i64) -> usize {
+    let mut best = 0;
+    let mut start = 0;
+    for end in 0..sorted_ms.len() {
+        while sorted_ms[end] - sorted_ms[start] > window_ms {
+            start += 1;
+        }
+        best = best.max(end - start + 1);
+    }
+    best
+}
+
+/// Status and verdict for a set of rates. `can_stall` says whether the span is
+/// long enough for a low prompt rate to mean the session stalled; `looping`
+/// reports a loop signature found in the span.
+pub fn classify(metrics: &HealthMetrics, thresholds: &HealthThresholds, can_stall: bool, looping: bool) -> (String, String) {
+    let mut status = STATUS_HEALTHY;
+    let mut verdict = VERDICT_CONTINUE;
+
+    if metrics.prompts_per_hour > thresholds.max_prompts_per_hour {
+        status = STATUS_FRANTIC;
+        verdict = VERDICT_CONSTRAIN;
+    } else if metrics.prompts_per_hour < thresholds.min_prompts_per_hour && can_stall {
+        status = STATUS_STALLED;
+    }
+
+    if metrics.tool_calls_per_prompt > thresholds.max_tool_calls_per_prompt || looping {
+        status = STATUS_LOOPING;
+        verdict = VERDICT_CONSTRAIN;
+    }
+
+    if metrics.assistant_messages_per_prompt > thresholds.max_assistant_messages_per_prompt {
+        status = STATUS_EXPLODING;
+        verdict = VERDICT_RESTART;
+    }
+
+    if metrics.tokens_per_minute > thresholds.max_tokens_per_minute {
+        status = STATUS_HEAVY;
+        if metrics.prompts_per_hour < RESTART_BELOW_PROMPTS_PER_HOUR {
+            verdict = VERDICT_RESTART;
+        }
+    } else if metrics.cost_per_minute > thresholds.max_cost_per_minute {
+        status = STATUS_EXPENSIVE;
+        if metrics.prompts_per_hour < RESTART_BELOW_PROMPTS_PER_HOUR {
+            verdict = VERDICT_RESTART;
+        }
+    }
+
+    (status.to_string(), verdict.to_string())
+}
+
+/// Per-message events and tool calls of a session, oldest first
+fn session_events(messages: &[Message]) -> (Vec<MessageEvent>, Vec<ToolCallEvent>, Vec<(usize, String)>) {
+    let mut events = Vec::new();
+    let mut calls: Vec<ToolCallEvent> = Vec::new();
+    let mut call_ids: HashMap<String, usize> = HashMap::new();
+    // (call index, error text) of failed calls
+    let mut failures: Vec<(usize, String)> = Vec::new();
+
+    for message in messages {
+        let Some(ms) = timestamp_ms(&message.timestamp) else { continue };
+        let mut event = MessageEvent {
+            ms,
+            prompt: prompt_text(message).is_some(),
+            assistant: message.msg_type == "assistant",
+            tokens: message.usage.as_ref().map(|u| u.total_tokens as u64).unwrap_or(0),
+            cost_usd: message_cost(message),
+            calls: Vec::new(),
+            errors: 0,
+        };
+
+        for block in &message.content {
+            match block.get("type").and_then(|t| t.as_str()) {
+                Some("tool_use") => {
+                    let tool = block.get("name").and_then(|v| v.as_str()).unwrap_or("tool").to_string();
+                    let input = block.get("input").cloned().unwrap_or(Value::Null);
+                    if let Some(id) = block.get("id").and_then(|v| v.as_str()) {
+                        call_ids.insert(id.to_string(), calls.len());
+                    }
+                    event.calls.push(calls.len());
+                    calls.push(ToolCallEvent {
+                        ms,
+                        timestamp: message.timestamp.clone(),
+                        key: format!("{}\u{1f}{}", tool, input),
+                        tool,
+                        input,
+                    });
+                }
+                Some("tool_result") if result_failed(block) => {
+                    event.errors += 1;
+                    let call = block.get("tool_use_id").and_then(|v| v.as_str()).and_then(|id| call_ids.get(id));
+                    if let Some(&call) = call {
+                        failures.push((call, result_text(block)));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        events.push(event);
+    }
+
+    (events, calls, failures)
+}
+
+fn window(
+    events: &[MessageEvent],
+    calls: &[ToolCallEvent],
+    start_ms: i64,
+    end_ms: i64,
+    thresholds: &HealthThresholds,
+) -> HealthWindow {
+    let in_window: Vec<&MessageEvent> = events.iter().filter(|e| e.ms >= start_ms && e.ms < end_ms).collect();
+
+    let prompts = in_window.iter().filter(|e| e.prompt).count();
+    let assistant_messages = in_window.iter().filter(|e| e.assistant).count();
+    let tool_calls: usize = in_window.iter().map(|e| e.calls.len()).sum();
+    let tool_errors: usize = in_window.iter().map(|e| e.errors).sum();
+    let tokens: u64 = in_window.iter().map(|e| e.tokens).sum();
+    let cost_usd: f64 = in_window.iter().map(|e| e.cost_usd).sum();
+
+    let mut identical: HashMap<&str, usize> = HashMap::new();
+    for call in in_window.iter().flat_map(|e| &e.calls) {
+        *identical.entry(calls[*call].key.as_str()).or_insert(0) += 1;
+    }
+    let max_identical_calls = identical.values().copied().max().unwrap_or(0);
+
+    // At least a minute, so a single burst does not read as an infinite rate
+    let minutes = ((end_ms - start_ms) as f64 / 60_000.0).max(1.0);
+    let per_prompt = prompts.max(1) as f64;
+    let metrics = HealthMetrics {
+        prompts_per_hour: prompts as f64 / minutes * 60.0,
+        tool_calls_per_prompt: tool_calls as f64 / per_prompt,
+        assistant_messages_per_prompt: assistant_messages as f64 / per_prompt,
+        tokens_per_minute: tokens as f64 / minutes,
+        cost_per_minute: cost_usd / minutes,
+    };
+
+    let full_window = end_ms - start_ms >= thresholds.window_minutes as i64 * 60_000;
+    let (status, verdict) = classify(
+        &metrics,
+        thresholds,
+        full_window,
+        max_identical_calls >= thresholds.repeated_call_threshold,
+    );
+
+    HealthWindow {
+        start: ms_to_rfc3339(start_ms),
+        end: ms_to_rfc3339(end_ms),
+        prompts,
+        tool_calls,
+        tool_errors,
+        assistant_messages,
+        tokens,
+        cost_usd,
+        max_identical_calls,
+        prompts_per_hour: metrics.prompts_per_hour,
+        tool_calls_per_prompt: metrics.tool_calls_per_prompt,
+        assistant_messages_per_prompt: metrics.assistant_messages_per_prompt,
+        tokens_per_minute: metrics.tokens_per_minute,
+        cost_per_minute: metrics.cost_per_minute,
+        status,
+        verdict,
+    }
+}
+
+/// Sliding windows over the session's span; a session shorter than one window gets one
+pub fn health_windows(messages: &[Message], thresholds: &HealthThresholds) -> Vec<HealthWindow> {
+    let (events, calls, _) = session_events(messages);
+    let (Some(first), Some(last)) = (events.iter().map(|e| e.ms).min(), events.iter().map(|e| e.ms).max()) else {
+        return Vec::new();
+    };
+
+    let window_ms = thresholds.window_minutes.max(1) as i64 * 60_000;
+    let step_ms = thresholds.step_minutes.max(1) as i64 * 60_000;
+
+    let mut windows = Vec::new();
+    let mut start = first;
+    loop {
+        // The last message must fall inside the final window
+        let end = (start + window_ms).min(last + 1);
+        windows.push(window(&events, &calls, start, end, thresholds));
+        if end > last {
+            break;
+        }
+        start += step_ms;
+    }
+    windows
+}
+
+fn loop_signature(kind: &str, tool: &str, detail: String, occurrences: &[&ToolCallEvent], window_ms: i64) -> LoopSignature {
+    let mut ms: Vec<i64> = occurrences.iter().map(|c| c.ms).collect();
+    ms.sort_unstable();
+    LoopSignature {
+        kind: kind.to_string(),
+        tool: tool.to_string(),
+        detail,
+        count: occurrences.len(),
+        max_in_window: max_in_window(&ms, window_ms),
+        first_seen: occurrences.iter().map(|c| c.timestamp.clone()).min().unwrap_or_default(),
+        last_seen: occurrences.iter().map(|c| c.timestamp.clone()).max().unwrap_or_default(),
+    }
+}
+
+/// Repeated identical calls, repeated Edit misses and repeated identical errors
+pub fn detect_loops(messages: &[Message], thresholds: &HealthThresholds) -> Vec<LoopSignature> {
+    let (_, calls, failures) = session_events(messages);
+    let window_ms = thresholds.window_minutes.max(1) as i64 * 60_000;
+    let mut loops = Vec::new();
+
+    let mut identical: HashMap<&str, Vec<&ToolCallEvent>> = HashMap::new();
+    for call in &calls {
+        identical.entry(call.key.as_str()).or_default().push(call);
+    }
+    for occurrences in identical.values() {
+        let call = occurrences[0];
+        let signature = loop_signature(LOOP_REPEATED_CALL, &call.tool, preview(&call.input.to_string()), occurrences, window_ms);
+        if signature.max_in_window >= thresholds.repeated_call_threshold {
+            loops.push(signature);
+        }
+    }
+
+    let mut edit_misses: HashMap<(String, String), Vec<&ToolCallEvent>> = HashMap::new();
+    let mut errors: HashMap<(&str, String), Vec<&ToolCallEvent>> = HashMap::new();
+    for (index, error) in &failures {
+        let call = &calls[*index];
+        if is_edit_miss(&call.tool, error) {
+            let field = |key: &str| call.input.get(key).and_then(|v| v.as_str()).unwrap_or("").to_string();
+            edit_misses.entry((field("file_path"), field("old_string"))).or_default().push(call);
+        } else {
+            errors.entry((call.tool.as_str(), error_signature(error))).or_default().push(call);
+        }
+    }
+    for ((file_path, _), occurrences) in edit_misses {
+        if occurrences.len() >= thresholds.edit_failure_threshold {
+            let tool = occurrences[0].tool.clone();
+            loops.push(loop_signature(LOOP_EDIT_FAILURE, &tool, file_path, &occurrences, window_ms));
+        }
+    }
+    for ((tool, message), occurrences) in errors {
+        let signature = loop_signature(LOOP_REPEATED_ERROR, tool, message, &occurrences, window_ms);
+        if signature.max_in_window >= thresholds.repeated_call_threshold {
+            loops.push(signature);
+        }
+    }
+
+    loops.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.first_seen.cmp(&b.first_seen)));
+    loops
+}
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+/// Thresholds in effect for a project: its override, else the configured defaults
+pub fn health_thresholds(project_id: Option<&str>) -> HealthThresholds {
+    let mut config = read_config();
+    project_id
+        .and_then(|id| config.projects.remove(id))
+        .or(config.defaults)
+        .unwrap_or_default()
+}
+
+/// Window with the most severe verdict, the earliest of equals; None when every window is healthy
+pub fn worst_window(windows: &[HealthWindow]) -> Option<usize> {
+    windows
+        .iter()
+        .enumerate()
+        .filter(|(_, w)| w.status != STATUS_HEALTHY)
+        .max_by(|(i, a), (j, b)| verdict_rank(&a.verdict).cmp(&verdict_rank(&b.verdict)).then_with(|| j.cmp(i)))
+        .map(|(i, _)| i)
+}
+
+/// Status and verdict of a whole session. `overall` is the classification of
+/// the whole-session rates, which averages away a bad stretch; a worse window
+/// or a loop signature anywhere in the session takes precedence.
+pub fn session_status(overall: (String, String), messages: &[Message], thresholds: &HealthThresholds) -> (String, String) {
+    let windows = health_windows(messages, thresholds);
+    let mut worst = overall;
+
+    if let Some(i) = worst_window(&windows) {
+        let window = &windows[i];
+        if worst.0 == STATUS_HEALTHY || verdict_rank(&window.verdict) > verdict_rank(&worst.1) {
+            worst = (window.status.clone(), window.verdict.clone());
+        }
+    }
+    if verdict_rank(&worst.1) < verdict_rank(VERDICT_CONSTRAIN) && !detect_loops(messages, thresholds).is_empty() {
+        worst = (STATUS_LOOPING.to_string(), VERDICT_CONSTRAIN.to_string());
+    }
+
+    worst
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Health thresholds in effect for a project, or the defaults when no project is given
+#[tauri::command]
+pub async fn get_health_thresholds(project_id: Option<String>) -> Result<HealthThresholds, String> {
+    Ok(health_thresholds(project_id.as_deref()))
+}
+
+/// Set the default thresholds, or a project's override; `None` thresholds reset to the built-in defaults
+#[tauri::command]
+pub async fn set_health_thresholds(
+    project_id: Option<String>,
+    thresholds: Option<HealthThresholds>,
+) -> Result<HealthThresholds, String> {
+    let mut config = read_config();
+    match (&project_id, thresholds) {
+        (Some(id), Some(thresholds)) => {
+            config.projects.insert(id.clone(), thresholds);
+        }
+        (Some(id), None) => {
+            config.projects.remove(id);
+        }
+        (None, thresholds) => config.defaults = thresholds,
+    }
+    write_config(&config).await?;
+    Ok(health_thresholds(project_id.as_deref()))
+}
+
+/// Health of a session over sliding windows, with the loop signatures found in it
+#[tauri::command]
+pub async fn get_session_health_timeline(
+    project_id: String,
+    session_id: String,
+    source: Option<String>,
+) -> Result<SessionHealthTimeline, String> {
+    let source = find_source(source.as_deref())?;
+    let transcript = source.session_transcript(project_id.clone(), session_id.clone()).await?;
+    let thresholds = health_thresholds(Some(&project_id));
+
+    let windows = health_windows(&transcript.messages, &thresholds);
+    let loops = detect_loops(&transcript.messages, &thresholds);
+
+    let worst_window = worst_window(&windows);
+    let (status, verdict) = match worst_window {
+        Some(i) => (windows[i].status.clone(), windows[i].verdict.clone()),
+        None => (STATUS_HEALTHY.to_string(), VERDICT_CONTINUE.to_string()),
+    };
+
+    Ok(SessionHealthTimeline {
+        project_id,
+        session_id,
+        thresholds,
+        status,
+        verdict,
+        worst_window,
+        unhealthy_windows: windows.iter().filter(|w| w.status != STATUS_HEALTHY).count(),
+        windows,
+        loops,
+    })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn message(msg_type: &str, minute: i64, content: Vec<Value>) -> Message {
+        Message {
+            uuid: format!("m{}", minute),
+            msg_type: msg_type.to_string(),
+            timestamp: ms_to_rfc3339(1_750_000_000_000 + minute * 60_000),
+            content,
+            model: None,
+            usage: None,
+        }
+    }
+
+    fn prompt(minute: i64) -> Message {
+        message("user", minute, vec![json!({"type": "text", "text": "next step please"})])
+    }
+
+    fn call(minute: i64, id: &str, name: &str, input: Value) -> Message {
+        message("assistant", minute, vec![json!({"type": "tool_use", "id": id, "name": name, "input": input})])
+    }
+
+    fn failure(minute: i64, id: &str, text: &str) -> Message {
+        message("user", minute, vec![json!({"type": "tool_result", "tool_use_id": id, "is_error": true, "content": text})])
+    }
+
+    /// Four hours with a prompt every 20 minutes, and a 20-minute burst of identical test runs
+    fn looping_session() -> Vec<Message> {
+        let mut messages: Vec<Message> = (0..12).map(|i| prompt(i * 20)).collect();
+        for i in 0..10 {
+            messages.push(call(100 + i * 2, &format!("t{}", i), "Bash", json!({"command": "cargo test"})));
+        }
+        messages.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        messages
+    }
+
+    #[test]
+    fn test_loop_inside_long_session_is_flagged() {
+        let messages = looping_session();
+        let thresholds = HealthThresholds::default();
+
+        let windows = health_windows(&messages, &thresholds);
+        assert!(windows.iter().any(|w| w.status == STATUS_LOOPING));
+        assert_eq!(windows[0].status, STATUS_HEALTHY);
+        assert_eq!(windows.last().map(|w| w.status.as_str()), Some(STATUS_HEALTHY));
+
+        let loops = detect_loops(&messages, &thresholds);
+        assert_eq!(loops[0].kind, LOOP_REPEATED_CALL);
+        assert_eq!((loops[0].count, loops[0].max_in_window), (10, 10));
+    }
+
+    #[test]
+    fn test_session_status_of_looping_session() {
+        let messages = looping_session();
+        let thresholds = HealthThresholds::default();
+
+        // Averaged over four hours the session looks healthy
+        let healthy = (STATUS_HEALTHY.to_string(), VERDICT_CONTINUE.to_string());
+        let (status, verdict) = session_status(healthy.clone(), &messages, &thresholds);
+        assert_ne!(status, STATUS_HEALTHY);
+        assert_ne!(verdict, VERDICT_CONTINUE);
+
+        // A worse whole-session verdict is kept
+        let restart = (STATUS_EXPLODING.to_string(), VERDICT_RESTART.to_string());
+        assert_eq!(session_status(restart.clone(), &messages, &thresholds), restart);
+
+        let calm: Vec<Message> = (0..12).map(|i| prompt(i * 20)).collect();
+        assert_eq!(session_status(healthy.clone(), &calm, &thresholds), healthy);
+    }
+
+    #[test]
+    fn test_repeated_edit_failure() {
+        let edit = json!({"file_path": "/p/src/lib.rs", "old_string": "fn old()", "new_string": "fn new()"});
+        let miss = "<tool_use_error>String to replace not found in file.\nString: fn old()</tool_use_error>";
+        let messages = vec![
+            prompt(0),
+            call(1, "e1", "Edit", edit.clone()),
+            failure(1, "e1", miss),
+            call(2, "e2", "Edit", edit),
+            failure(2, "e2", miss),
+        ];
+
+        let loops = detect_loops(&messages, &HealthThresholds::default());
+        assert_eq!(loops.len(), 1);
+        assert_eq!((loops[0].kind.as_str(), loops[0].detail.as_str(), loops[0].count), (LOOP_EDIT_FAILURE, "/p/src/lib.rs", 2));
+    }
+}
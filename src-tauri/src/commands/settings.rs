@@ -10,6 +10,8 @@ use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::path::PathBuf;
 
+use super::data_roots::local_root;
+
 // ============================================================================
 // Type Definitions
 // ============================================================================
@@ -78,8 +80,7 @@ pub struct SettingsDashboardData {
 // ============================================================================
 
 fn get_global_settings_path() -> Result<PathBuf, String> {
-    let home_dir = dirs::home_dir().ok_or_else(|| "Could not determine home directory".to_string())?;
-    Ok(home_dir.join(".claude").join("settings.json"))
+    Ok(PathBuf::from(local_root()?.claude_dir).join("settings.json"))
 }
 
 fn get_project_settings_path(project_path: &str) -> PathBuf {
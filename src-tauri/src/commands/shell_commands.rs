@@ -0,0 +1,1014 @@
+/**
+ * Shell command analytics
+ *
+ * Parses every shell tool call (Claude Code `Bash`, Codex `shell` and
+ * `exec_command`) into the individual commands of its script: pipelines,
+ * `&&`/`||`/`;` chains and lines are split, heredoc bodies and comments
+ * skipped, and env assignments and wrappers (`sudo`, `env`, `time`, `npx`...)
+ * stripped. Each command is normalized to a key ("git commit", "npm run build")
+ * and a family (vcs, build, test...).
+ *
+ * Exit status and duration belong to the whole call; they are attributed to its
+ * primary command, the last one that is neither navigation (`cd`, `export`) nor
+ * fed by a pipe.
+ */
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use super::files::{shell_script, SHELL_TOOLS};
+use super::sources::codex_session_files;
+use super::tool_reliability::{codex_output_error, codex_rollout_tool_calls, error_signature, exit_code, latency_ms, percent};
+use super::transcript_index::{read_lines_at, sessions_oldest_first, with_index};
+use super::transcripts::{codex_session_identity, codex_tool_call_block};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+pub const FAMILY_VCS: &str = "vcs";
+pub const FAMILY_PACKAGE: &str = "package";
+pub const FAMILY_BUILD: &str = "build";
+pub const FAMILY_TEST: &str = "test";
+pub const FAMILY_LINT: &str = "lint";
+pub const FAMILY_RUN: &str = "run";
+pub const FAMILY_SEARCH: &str = "search";
+pub const FAMILY_FILESYSTEM: &str = "filesystem";
+pub const FAMILY_TEXT: &str = "text";
+pub const FAMILY_SHELL: &str = "shell";
+pub const FAMILY_NETWORK: &str = "network";
+pub const FAMILY_CONTAINER: &str = "container";
+pub const FAMILY_SYSTEM: &str = "system";
+pub const FAMILY_OTHER: &str = "other";
+
+/// Programs whose first argument selects what they do
+const SUBCOMMAND_PROGRAMS: &[&str] = &[
+    "git", "gh", "cargo", "npm", "pnpm", "yarn", "bun", "go", "docker", "docker-compose", "podman", "kubectl",
+    "helm", "pip", "pip3", "uv", "poetry", "make", "brew", "apt", "apt-get", "rustup", "dotnet", "terraform",
+    "deno", "gradle", "gradlew", "mvn", "composer", "bundle", "rails", "mix", "swift", "flutter", "pod",
+];
+
+/// Prefixes that run the command after them
+const WRAPPERS: &[&str] = &["sudo", "env", "time", "nohup", "exec", "command", "builtin", "nice", "timeout", "stdbuf"];
+
+/// Runners whose first argument is the actual tool
+const RUNNERS: &[&str] = &["npx", "bunx", "pnpx", "uvx"];
+
+const MAX_COMMANDS: usize = 100;
+const MAX_FAILURES: usize = 50;
+const MAX_EXAMPLE: usize = 200;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// One simple command of a script
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandSegment {
+    pub text: String,
+    /// Its input comes from a pipe
+    pub piped: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedCommand {
+    /// Program name without its directory
+    pub program: String,
+    pub subcommand: Option<String>,
+    /// Normalized form used for grouping, e.g. "cargo test"
+    pub key: String,
+    pub family: &'static str,
+}
+
+/// A redirection of a simple command, e.g. `2>&1` or `>> log.txt`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Redirection {
+    /// File descriptor or `&` before the operator
+    pub fd: Option<String>,
+    /// `>`, `>>`, `>|`, `>&`, `<`, `<<`, `<<-`, `<<<`, ...
+    pub op: String,
+    /// Target file, descriptor or heredoc delimiter
+    pub target: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CommandWords {
+    pub words: Vec<String>,
+    pub redirections: Vec<Redirection>,
+}
+
+/// One shell tool call with its outcome
+#[derive(Debug, Clone)]
+pub struct ShellInvocation {
+    pub project_id: String,
+    pub timestamp: String,
+    pub script: String,
+    pub answered: bool,
+    pub failed: bool,
+    pub exit_code: Option<i64>,
+    pub error: Option<String>,
+    pub duration_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExitCodeCount {
+    pub code: i64,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandStats {
+    pub command: String,
+    pub family: String,
+    /// Occurrences across all calls
+    pub runs: usize,
+    /// Calls this command was the primary command of, with a result
+    pub attributed_runs: usize,
+    pub failures: usize,
+    /// failures / attributed_runs * 100
+    pub failure_rate: f64,
+    pub total_duration_ms: u64,
+    pub mean_duration_ms: f64,
+    /// Non-zero exit codes, most frequent first
+    pub exit_codes: Vec<ExitCodeCount>,
+    pub example: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FamilyStats {
+    pub family: String,
+    pub runs: usize,
+    pub failures: usize,
+    pub failure_rate: f64,
+    pub total_duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectShellStats {
+    pub project_id: String,
+    pub invocations: usize,
+    pub failures: usize,
+    pub failure_rate: f64,
+    /// Time spent waiting on shell calls
+    pub total_duration_ms: u64,
+    /// Most runs first
+    pub families: Vec<FamilyStats>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandFailure {
+    pub command: String,
+    /// First line of the error, with numbers replaced by N
+    pub message: String,
+    pub count: usize,
+    pub last_seen: String,
+    pub example: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShellAnalytics {
+    pub total_invocations: usize,
+    pub total_commands: usize,
+    pub failed_invocations: usize,
+    pub failure_rate: f64,
+    pub total_duration_ms: u64,
+    /// Most runs first
+    pub commands: Vec<CommandStats>,
+    pub families: Vec<FamilyStats>,
+    /// Most time spent first
+    pub projects: Vec<ProjectShellStats>,
+    /// Most frequent first
+    pub failures: Vec<CommandFailure>,
+}
+
+#[derive(Default)]
+struct CommandAccumulator {
+    family: &'static str,
+    runs: usize,
+    attributed_runs: usize,
+    failures: usize,
+    total_duration_ms: u64,
+    exit_codes: HashMap<i64, usize>,
+    example: String,
+}
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+/// Delimiter of a heredoc starting at `chars[i]` ("<<EOF", "<<-'EOF'"), and the index after it
+fn heredoc_at(chars: &[char], i: usize) -> Option<(String, bool, usize)> {
+    if chars.get(i) != Some(&'<') || chars.get(i + 1) != Some(&'<') || chars.get(i + 2) == Some(&'<') {
+        return None;
+    }
+    let mut j = i + 2;
+    let strip_tabs = chars.get(j) == Some(&'-');
+    if strip_tabs {
+        j += 1;
+    }
+    while chars.get(j).map(|c| *c == ' ').unwrap_or(false) {
+        j += 1;
+    }
+    let mut delimiter = String::new();
+    while let Some(&c) = chars.get(j) {
+        if c.is_whitespace() || matches!(c, ';' | '&' | '|' | '<' | '>' | ')') {
+            break;
+        }
+        if c != '\'' && c != '"' && c != '\\' {
+            delimiter.push(c);
+        }
+        j += 1;
+    }
+    (!delimiter.is_empty()).then_some((delimiter, strip_tabs, j))
+}
+
+/// Split a script into simple commands at `&&`, `||`, `;`, `&`, `|` and newlines,
+/// outside quotes and command substitutions
+pub fn split_commands(script: &str) -> Vec<CommandSegment> {
+    let chars: Vec<char> = script.chars().collect();
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut piped = false;
+    let mut quote: Option<char> = None;
+    let mut depth = 0usize;
+    let mut heredocs: Vec<(String, bool)> = Vec::new();
+
+    let mut flush = |current: &mut String, piped: bool| {
+        let text = current.trim();
+        if !text.is_empty() {
+            segments.push(CommandSegment { text: text.to_string(), piped });
+        }
+        current.clear();
+    };
+
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+
+        if let Some(q) = quote {
+            current.push(c);
+            if c == '\\' && q == '"' {
+                if let Some(n) = next {
+                    current.push(n);
+                    i += 1;
+                }
+            } else if c == q {
+                quote = None;
+            }
+            i += 1;
+            continue;
+        }
+
+        if depth == 0 {
+            if let Some((delimiter, strip_tabs, end)) = heredoc_at(&chars, i) {
+                current.extend(&chars[i..end]);
+                heredocs.push((delimiter, strip_tabs));
+                i = end;
+                continue;
+            }
+        }
+
+        match c {
+            '\\' => {
+                // A line continuation joins the lines
+                if next != Some('\n') {
+                    current.push(c);
+                    current.extend(next);
+                }
+                i += 2;
+                continue;
+            }
+            '\'' | '"' | '`' => {
+                quote = Some(c);
+                current.push(c);
+            }
+            '$' if next == Some('(') => {
+                depth += 1;
+                current.push_str("$(");
+                i += 2;
+                continue;
+            }
+            '(' if depth > 0 => {
+                depth += 1;
+                current.push(c);
+            }
+            ')' if depth > 0 => {
+                depth -= 1;
+                current.push(c);
+            }
+            '#' if depth == 0 && current.chars().last().map(char::is_whitespace).unwrap_or(true) => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            '\n' if depth == 0 => {
+                flush(&mut current, piped);
+                piped = false;
+                // Heredoc bodies start on the next line and are not commands
+                i += 1;
+                for (delimiter, strip_tabs) in heredocs.drain(..) {
+                    while i < chars.len() {
+                        let end = chars[i..].iter().position(|c| *c == '\n').map(|p| i + p).unwrap_or(chars.len());
+                        let line: String = chars[i..end].iter().collect();
+                        i = end + 1;
+                        let line = if strip_tabs { line.trim_start_matches('\t') } else { line.as_str() };
+                        if line.trim_end() == delimiter {
+                            break;
+                        }
+                    }
+                }
+                continue;
+            }
+            ';' if depth == 0 => {
+                flush(&mut current, piped);
+                piped = false;
+            }
+            // `&>` and `>&` are redirections
+            '&' if depth == 0 && next != Some('>') && !current.ends_with('>') => {
+                if next == Some('&') {
+                    i += 1;
+                }
+                flush(&mut current, piped);
+                piped = false;
+            }
+            '|' if depth == 0 => {
+                flush(&mut current, piped);
+                if next == Some('|') {
+                    i += 1;
+                    piped = false;
+                } else {
+                    if next == Some('&') {
+                        i += 1;
+                    }
+                    piped = true;
+                }
+            }
+            _ => current.push(c),
+        }
+        i += 1;
+    }
+    flush(&mut current, piped);
+
+    segments
+}
+
+/// Words and redirections of a simple command with quotes removed
+pub fn command_words(segment: &str) -> CommandWords {
+    let mut result = CommandWords::default();
+    let mut word = String::new();
+    let mut in_word = false;
+    // Whether part of the word was quoted, so `"2">` is not a descriptor
+    let mut quoted = false;
+    let mut pending: Option<(Option<String>, String)> = None;
+    let mut quote: Option<char> = None;
+    let mut depth = 0usize;
+    let chars: Vec<char> = segment.chars().collect();
+
+    let finish = |word: &mut String, pending: &mut Option<(Option<String>, String)>, result: &mut CommandWords| {
+        let word = std::mem::take(word);
+        match pending.take() {
+            Some((fd, op)) => result.redirections.push(Redirection { fd, op, target: word }),
+            None => result.words.push(word),
+        }
+    };
+
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') => {
+                word.extend(next);
+                i += 1;
+            }
+            (Some(_), c) => word.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_word = true;
+                quoted = true;
+            }
+            (None, '\\') => {
+                word.extend(next);
+                in_word = true;
+                i += 1;
+            }
+            (None, '$') if next == Some('(') => {
+                depth += 1;
+                word.push_str("$(");
+                in_word = true;
+                i += 1;
+            }
+            (None, '(') if depth > 0 => {
+                depth += 1;
+                word.push(c);
+            }
+            (None, ')') if depth > 0 => {
+                depth -= 1;
+                word.push(c);
+            }
+            (None, c) if depth > 0 => word.push(c),
+            // `&>` redirects both outputs; other `&` were split off already
+            (None, '>' | '<' | '&') if c != '&' || next == Some('>') => {
+                // A number right before the operator is its descriptor
+                let fd = if in_word && !quoted && !word.is_empty() && word.chars().all(|c| c.is_ascii_digit()) {
+                    Some(std::mem::take(&mut word))
+                } else {
+                    if in_word {
+                        finish(&mut word, &mut pending, &mut result);
+                    }
+                    None
+                };
+                let (fd, mut op) = if c == '&' {
+                    i += 1;
+                    (Some("&".to_string()), ">".to_string())
+                } else {
+                    (fd, c.to_string())
+                };
+                while op.len() < 3 && chars.get(i + 1).map(|n| *n == '>' || *n == '<').unwrap_or(false) {
+                    i += 1;
+                    op.push(chars[i]);
+                }
+                if matches!(chars.get(i + 1), Some('&' | '|')) || (op == "<<" && chars.get(i + 1) == Some(&'-')) {
+                    i += 1;
+                    op.push(chars[i]);
+                }
+                pending = Some((fd, op));
+                in_word = false;
+                quoted = false;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    finish(&mut word, &mut pending, &mut result);
+                    in_word = false;
+                    quoted = false;
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+        i += 1;
+    }
+    if in_word {
+        finish(&mut word, &mut pending, &mut result);
+    }
+    result
+}
+
+fn is_assignment(word: &str) -> bool {
+    match word.split_once('=') {
+        Some((name, _)) => {
+            !name.is_empty()
+                && !name.starts_with(|c: char| c.is_ascii_digit())
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        None => false,
+    }
+}
+
+/// Family of a command from its program and subcommand
+pub fn command_family(program: &str, subcommand: Option<&str>) -> &'static str {
+    let sub = subcommand.unwrap_or("");
+    match program {
+        "git" | "gh" | "hg" | "svn" => FAMILY_VCS,
+        "cargo" => match sub {
+            "test" | "nextest" | "bench" => FAMILY_TEST,
+            "clippy" | "fmt" => FAMILY_LINT,
+            "add" | "install" | "update" | "remove" | "fetch" => FAMILY_PACKAGE,
+            "run" => FAMILY_RUN,
+            _ => FAMILY_BUILD,
+        },
+        "npm" | "pnpm" | "yarn" | "bun" => match sub {
+            "install" | "i" | "add" | "ci" | "remove" | "rm" | "uninstall" | "update" | "upgrade" => FAMILY_PACKAGE,
+            "test" | "t" => FAMILY_TEST,
+            s if s.contains("test") => FAMILY_TEST,
+            s if s.contains("lint") || s.contains("format") || s.contains("typecheck") || s.contains("check") => {
+                FAMILY_LINT
+            }
+            s if s.contains("build") || s.contains("compile") => FAMILY_BUILD,
+            _ => FAMILY_RUN,
+        },
+        "go" => match sub {
+            "test" => FAMILY_TEST,
+            "vet" | "fmt" => FAMILY_LINT,
+            "get" | "mod" | "install" => FAMILY_PACKAGE,
+            "run" => FAMILY_RUN,
+            _ => FAMILY_BUILD,
+        },
+        "pip" | "pip3" | "poetry" | "uv" | "brew" | "apt" | "apt-get" | "composer" | "bundle" | "pod" | "rustup" => {
+            match sub {
+                "run" => FAMILY_RUN,
+                _ => FAMILY_PACKAGE,
+            }
+        }
+        "pytest" | "jest" | "vitest" | "mocha" | "rspec" | "phpunit" | "playwright" | "cypress" | "tox" | "nextest" => {
+            FAMILY_TEST
+        }
+        "make" | "cmake" | "ninja" | "tsc" | "gradle" | "gradlew" | "mvn" | "webpack" | "vite" | "esbuild" | "rollup"
+        | "dotnet" | "swift" | "flutter" | "xcodebuild" | "bazel" => match sub {
+            "test" | "check" => FAMILY_TEST,
+            _ => FAMILY_BUILD,
+        },
+        "eslint" | "prettier" | "ruff" | "black" | "isort" | "flake8" | "mypy" | "pyright" | "pylint" | "rustfmt"
+        | "clippy-driver" | "biome" | "stylelint" | "shellcheck" | "gofmt" | "golangci-lint" => FAMILY_LINT,
+        "grep" | "rg" | "egrep" | "fgrep" | "find" | "fd" | "ag" | "ack" | "locate" => FAMILY_SEARCH,
+        "ls" | "mkdir" | "rm" | "rmdir" | "cp" | "mv" | "touch" | "chmod" | "chown" | "tree" | "du" | "df" | "ln"
+        | "stat" | "file" | "realpath" | "basename" | "dirname" | "tar" | "zip" | "unzip" => FAMILY_FILESYSTEM,
+        "cat" | "head" | "tail" | "less" | "more" | "wc" | "sed" | "awk" | "sort" | "uniq" | "cut" | "tr" | "jq"
+        | "yq" | "diff" | "echo" | "printf" | "tee" | "xargs" | "column" | "nl" => FAMILY_TEXT,
+        "cd" | "pushd" | "popd" | "pwd" | "export" | "source" | "." | "set" | "unset" | "alias" | "sleep" | "true"
+        | "false" | "exit" | "wait" | "read" | "test" | "[" | "[[" | "if" | "then" | "else" | "fi" | "for" | "do"
+        | "done" | "while" => FAMILY_SHELL,
+        "curl" | "wget" | "ssh" | "scp" | "rsync" | "ping" | "nc" | "dig" | "http" => FAMILY_NETWORK,
+        "docker" | "docker-compose" | "podman" | "kubectl" | "helm" | "terraform" => FAMILY_CONTAINER,
+        "node" | "python" | "python3" | "ruby" | "deno" | "java" | "php" | "perl" | "bash" | "sh" | "zsh" | "rails"
+        | "mix" | "elixir" | "lua" => FAMILY_RUN,
+        "ps" | "kill" | "pkill" | "killall" | "lsof" | "top" | "htop" | "which" | "whoami" | "uname" | "date"
+        | "hostname" | "open" | "xdg-open" | "printenv" | "id" | "type" => FAMILY_SYSTEM,
+        _ => FAMILY_OTHER,
+    }
+}
+
+/// Program, subcommand, key and family of a simple command
+pub fn parse_command(segment: &str) -> Option<ParsedCommand> {
+    let words = command_words(segment).words;
+    let mut rest = words.iter().map(String::as_str).filter(|w| !w.is_empty()).peekable();
+
+    // Env assignments, grouping and wrappers before the program
+    let program = loop {
+        let word = rest.next()?;
+        let word = word.trim_start_matches(['(', '{']);
+        if word.is_empty() || word == "!" || is_assignment(word) {
+            continue;
+        }
+        if WRAPPERS.contains(&word) {
+            // Wrapper flags, and the duration of `timeout`
+            while let Some(next) = rest.peek() {
+                if next.starts_with('-') || is_assignment(next) || (word == "timeout" && next.starts_with(|c: char| c.is_ascii_digit())) {
+                    rest.next();
+                } else {
+                    break;
+                }
+            }
+            continue;
+        }
+        break word;
+    };
+
+    let name = program.rsplit('/').next().unwrap_or(program);
+    let name = name.trim_end_matches(['}', ')', ';']);
+    if name.is_empty() {
+        return None;
+    }
+
+    let mut program = name.to_string();
+    let mut subcommand: Option<String> = None;
+    let args: Vec<&str> = rest.collect();
+
+    // `npx jest` is jest
+    if RUNNERS.contains(&name) {
+        let tool = args.iter().find(|a| !a.starts_with('-'))?;
+        let parsed = parse_command(tool)?;
+        return Some(ParsedCommand { key: format!("{} {}", name, parsed.program), ..parsed });
+    }
+
+    if matches!(name, "python" | "python3") {
+        if let Some(i) = args.iter().position(|a| *a == "-m") {
+            if let Some(module) = args.get(i + 1) {
+                let family = match command_family(module, None) {
+                    FAMILY_OTHER => FAMILY_RUN,
+                    family => family,
+                };
+                return Some(ParsedCommand {
+                    key: format!("{} -m {}", name, module),
+                    program,
+                    subcommand: Some(module.to_string()),
+                    family,
+                });
+            }
+        }
+    }
+
+    if SUBCOMMAND_PROGRAMS.contains(&name) {
+        program = program.trim_start_matches("./").to_string();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if name == "git" && matches!(*arg, "-C" | "-c") {
+                iter.next();
+                continue;
+            }
+            if arg.starts_with('-') || arg.starts_with('+') || (name == "make" && arg.contains('=')) {
+                continue;
+            }
+            let mut sub = arg.to_string();
+            // `npm run build` groups by script
+            if matches!(name, "npm" | "pnpm" | "yarn" | "bun") && matches!(*arg, "run" | "run-script") {
+                if let Some(script) = iter.find(|a| !a.starts_with('-')) {
+                    sub = format!("run {}", script);
+                }
+            }
+            subcommand = Some(sub);
+            break;
+        }
+    }
+
+    let family_sub = subcommand.as_deref().map(|s| s.strip_prefix("run ").unwrap_or(s));
+    let family = command_family(&program, family_sub);
+    let key = match &subcommand {
+        Some(sub) => format!("{} {}", program, sub),
+        None => program.clone(),
+    };
+
+    Some(ParsedCommand { program, subcommand, key, family })
+}
+
+/// Parsed commands of a script and the index of its primary command
+pub fn parse_script(script: &str) -> (Vec<ParsedCommand>, Option<usize>) {
+    let mut commands = Vec::new();
+    let mut primary = None;
+    for segment in split_commands(script) {
+        let Some(parsed) = parse_command(&segment.text) else { continue };
+        if !segment.piped && parsed.family != FAMILY_SHELL {
+            primary = Some(commands.len());
+        }
+        commands.push(parsed);
+    }
+    let primary = primary.or_else(|| commands.len().checked_sub(1));
+    (commands, primary)
+}
+
+// ============================================================================
+// Aggregation
+// ============================================================================
+
+fn example(text: &str) -> String {
+    text.chars().take(MAX_EXAMPLE).collect()
+}
+
+impl FamilyStats {
+    fn add(&mut self, failed: bool, duration_ms: Option<u64>) {
+        if failed {
+            self.failures += 1;
+        }
+        self.total_duration_ms += duration_ms.unwrap_or(0);
+    }
+
+    fn sorted(families: HashMap<&'static str, FamilyStats>) -> Vec<FamilyStats> {
+        let mut families: Vec<FamilyStats> = families
+            .into_iter()
+            .map(|(family, mut stats)| {
+                stats.family = family.to_string();
+                stats.failure_rate = percent(stats.failures, stats.runs);
+                stats
+            })
+            .collect();
+        families.sort_by(|a, b| b.runs.cmp(&a.runs).then_with(|| a.family.cmp(&b.family)));
+        families
+    }
+}
+
+pub fn summarize(invocations: &[ShellInvocation]) -> ShellAnalytics {
+    let mut commands: HashMap<String, CommandAccumulator> = HashMap::new();
+    let mut families: HashMap<&'static str, FamilyStats> = HashMap::new();
+    let mut projects: HashMap<&str, (ProjectShellStats, HashMap<&'static str, FamilyStats>)> = HashMap::new();
+    let mut failures: HashMap<(String, String), CommandFailure> = HashMap::new();
+    let mut total_commands = 0;
+
+    for invocation in invocations {
+        let (parsed, primary) = parse_script(&invocation.script);
+        total_commands += parsed.len();
+
+        let (project, project_families) = projects.entry(invocation.project_id.as_str()).or_insert_with(|| {
+            (
+                ProjectShellStats {
+                    project_id: invocation.project_id.clone(),
+                    invocations: 0,
+                    failures: 0,
+                    failure_rate: 0.0,
+                    total_duration_ms: 0,
+                    families: Vec::new(),
+                },
+                HashMap::new(),
+            )
+        });
+        project.invocations += 1;
+        project.total_duration_ms += invocation.duration_ms.unwrap_or(0);
+        if invocation.failed {
+            project.failures += 1;
+        }
+
+        for (i, command) in parsed.iter().enumerate() {
+            let acc = commands.entry(command.key.clone()).or_default();
+            acc.family = command.family;
+            acc.runs += 1;
+            if acc.example.is_empty() {
+                acc.example = example(&invocation.script);
+            }
+            families.entry(command.family).or_default().runs += 1;
+            project_families.entry(command.family).or_default().runs += 1;
+
+            if Some(i) != primary || !invocation.answered {
+                continue;
+            }
+            acc.attributed_runs += 1;
+            acc.total_duration_ms += invocation.duration_ms.unwrap_or(0);
+            families.entry(command.family).or_default().add(invocation.failed, invocation.duration_ms);
+            project_families.entry(command.family).or_default().add(invocation.failed, invocation.duration_ms);
+            if !invocation.failed {
+                continue;
+            }
+            acc.failures += 1;
+            if let Some(code) = invocation.exit_code.filter(|c| *c != 0) {
+                *acc.exit_codes.entry(code).or_insert(0) += 1;
+            }
+
+            let message = error_signature(invocation.error.as_deref().unwrap_or(""));
+            let failure = failures.entry((command.key.clone(), message.clone())).or_insert_with(|| CommandFailure {
+                command: command.key.clone(),
+                message,
+                count: 0,
+                last_seen: String::new(),
+                example: String::new(),
+            });
+            failure.count += 1;
+            if invocation.timestamp >= failure.last_seen {
+                failure.last_seen = invocation.timestamp.clone();
+                failure.example = example(&invocation.script);
+            }
+        }
+    }
+
+    let mut commands: Vec<CommandStats> = commands
+        .into_iter()
+        .map(|(command, acc)| {
+            let mut exit_codes: Vec<ExitCodeCount> =
+                acc.exit_codes.into_iter().map(|(code, count)| ExitCodeCount { code, count }).collect();
+            exit_codes.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.code.cmp(&b.code)));
+            CommandStats {
+                command,
+                family: acc.family.to_string(),
+                runs: acc.runs,
+                attributed_runs: acc.attributed_runs,
+                failures: acc.failures,
+                failure_rate: percent(acc.failures, acc.attributed_runs),
+                total_duration_ms: acc.total_duration_ms,
+                mean_duration_ms: if acc.attributed_runs == 0 {
+                    0.0
+                } else {
+                    acc.total_duration_ms as f64 / acc.attributed_runs as f64
+                },
+                exit_codes,
+                example: acc.example,
+            }
+        })
+        .collect();
+    commands.sort_by(|a, b| b.runs.cmp(&a.runs).then_with(|| a.command.cmp(&b.command)));
+    commands.truncate(MAX_COMMANDS);
+
+    let mut projects: Vec<ProjectShellStats> = projects
+        .into_values()
+        .map(|(mut project, families)| {
+            project.failure_rate = percent(project.failures, project.invocations);
+            project.families = FamilyStats::sorted(families);
+            project
+        })
+        .collect();
+    projects.sort_by(|a, b| b.total_duration_ms.cmp(&a.total_duration_ms).then_with(|| a.project_id.cmp(&b.project_id)));
+
+    let mut failures: Vec<CommandFailure> = failures.into_values().collect();
+    failures.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| b.last_seen.cmp(&a.last_seen)));
+    failures.truncate(MAX_FAILURES);
+
+    let answered = invocations.iter().filter(|i| i.answered).count();
+    let failed_invocations = invocations.iter().filter(|i| i.failed).count();
+
+    ShellAnalytics {
+        total_invocations: invocations.len(),
+        total_commands,
+        failed_invocations,
+        failure_rate: percent(failed_invocations, answered),
+        total_duration_ms: invocations.iter().filter_map(|i| i.duration_ms).sum(),
+        commands,
+        families: FamilyStats::sorted(families),
+        projects,
+        failures,
+    }
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Claude Code shell calls: command text from the session lines, outcome from the index.
+/// Sessions are read oldest first and each tool_use id counted once, so calls repeated by a resume are not.
+fn claude_invocations(project_filter: Option<&str>) -> Result<Vec<ShellInvocation>, String> {
+    // (file, project id, offsets of Bash lines, tool use id -> (is_error, error, result timestamp))
+    type Candidate = (String, String, Vec<u64>, HashMap<String, (bool, Option<String>, String)>);
+
+    let candidates: Vec<Candidate> = with_index(|index| {
+        let sessions = index
+            .sessions
+            .values()
+            .filter(|s| project_filter.map(|p| p == s.project_id).unwrap_or(true));
+        sessions_oldest_first(sessions)
+            .into_iter()
+            .filter_map(|session| {
+                let offsets: Vec<u64> =
+                    session.entries.iter().filter(|e| e.uses_any_tool(SHELL_TOOLS)).map(|e| e.offset).collect();
+                if offsets.is_empty() {
+                    return None;
+                }
+                let results = session
+                    .entries
+                    .iter()
+                    .flat_map(|e| {
+                        e.tool_results
+                            .iter()
+                            .map(move |r| (r.tool_use_id.clone(), (r.is_error, r.error.clone(), e.timestamp.clone())))
+                    })
+                    .collect();
+                Some((session.file_path.clone(), session.project_id.clone(), offsets, results))
+            })
+            .collect()
+    })?;
+
+    let mut invocations = Vec::new();
+    let mut seen: HashSet<String> = HashSet::new();
+    for (file_path, project_id, offsets, results) in candidates {
+        for line in read_lines_at(Path::new(&file_path), &offsets) {
+            let Ok(entry) = serde_json::from_str::<Value>(&line) else { continue };
+            let timestamp = entry.get("timestamp").and_then(|v| v.as_str()).unwrap_or("").to_string();
+            let Some(blocks) = entry.get("message").and_then(|m| m.get("content")).and_then(|c| c.as_array()) else {
+                continue;
+            };
+
+            for block in blocks {
+                if block.get("type").and_then(|v| v.as_str()) != Some("tool_use") {
+                    continue;
+                }
+                let name = block.get("name").and_then(|v| v.as_str()).unwrap_or("");
+                if !SHELL_TOOLS.contains(&name) {
+                    continue;
+                }
+                let id = block.get("id").and_then(|v| v.as_str());
+                if id.is_some_and(|id| !seen.insert(id.to_string())) {
+                    continue;
+                }
+                let Some(script) = block.get("input").and_then(shell_script) else { continue };
+                let result = id.and_then(|id| results.get(id));
+
+                invocations.push(ShellInvocation {
+                    project_id: project_id.clone(),
+                    timestamp: timestamp.clone(),
+                    script,
+                    answered: result.is_some(),
+                    failed: result.map(|(is_error, _, _)| *is_error).unwrap_or(false),
+                    exit_code: result.and_then(|(_, error, _)| error.as_deref()).and_then(exit_code),
+                    error: result.and_then(|(_, error, _)| error.clone()),
+                    duration_ms: result.and_then(|(_, _, at)| latency_ms(&timestamp, at)),
+                });
+            }
+        }
+    }
+
+    Ok(invocations)
+}
+
+/// Shell calls of one Codex rollout
+fn codex_rollout_invocations(path: &Path, project_id: &str) -> Vec<ShellInvocation> {
+    codex_rollout_tool_calls(path)
+        .into_iter()
+        .filter(|call| SHELL_TOOLS.contains(&call.name.as_str()))
+        .filter_map(|call| {
+            let block = codex_tool_call_block(&call.payload)?;
+            let script = block.get("input").and_then(shell_script)?;
+            let output = call.output.as_ref().map(|(output, _)| output.as_str());
+            let error = output.and_then(codex_output_error);
+
+            Some(ShellInvocation {
+                project_id: project_id.to_string(),
+                script,
+                answered: output.is_some(),
+                failed: error.is_some(),
+                exit_code: output.and_then(exit_code),
+                error,
+                duration_ms: call.output.as_ref().and_then(|(_, at)| latency_ms(&call.timestamp, at)),
+                timestamp: call.timestamp,
+            })
+        })
+        .collect()
+}
+
+fn codex_invocations(project_filter: Option<&str>) -> Vec<ShellInvocation> {
+    let mut invocations = Vec::new();
+
+    for (root, path) in codex_session_files() {
+        let Some((local_project_id, _, _)) = codex_session_identity(&path) else { continue };
+        let project_id = root.qualify_id(&local_project_id);
+        if project_filter.map(|p| p != project_id).unwrap_or(false) {
+            continue;
+        }
+        invocations.extend(codex_rollout_invocations(&path, &project_id));
+    }
+
+    invocations
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Most-run shell commands, failure rates and time spent, optionally for one project
+#[tauri::command]
+pub async fn get_shell_analytics(project_id: Option<String>) -> Result<ShellAnalytics, String> {
+    tokio::task::spawn_blocking(move || {
+        let mut invocations = claude_invocations(project_id.as_deref())?;
+        invocations.extend(codex_invocations(project_id.as_deref()));
+        Ok(summarize(&invocations))
+    })
+    .await
+    .map_err(|e| format!("Shell analytics task failed: {}", e))?
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(script: &str) -> Vec<String> {
+        parse_script(script).0.into_iter().map(|c| c.key).collect()
+    }
+
+    #[test]
+    fn test_split_and_normalize() {
+        assert_eq!(
+            keys("cd app && RUST_LOG=debug cargo +nightly test --all 2>&1 | tail -n 20; git -C app status"),
+            vec!["cd", "cargo test", "tail", "git status"]
+        );
+        assert_eq!(keys("sudo -E npm run build && npx jest --watch=false"), vec!["npm run build", "npx jest"]);
+        assert_eq!(keys("echo 'a && b; c' | grep \"x|y\" || true"), vec!["echo", "grep", "true"]);
+        assert_eq!(
+            keys("cat > notes.md <<'EOF'\nrm -rf /\nEOF\n# comment\npython3 -m pytest -q"),
+            vec!["cat", "python3 -m pytest"]
+        );
+        assert_eq!(keys("timeout 60 /usr/bin/make -j4 all"), vec!["make all"]);
+
+        let (commands, primary) = parse_script("cd app && cargo test 2>&1 | tail -20");
+        assert_eq!(commands[primary.unwrap()].key, "cargo test");
+        assert_eq!(commands[1].family, FAMILY_TEST);
+    }
+
+    #[test]
+    fn test_command_words_separates_redirections() {
+        let words = command_words("echo \"1 > 2\" \"2\">out 2>&1 >>log.txt <<-'EOF'");
+        assert_eq!(words.words, vec!["echo", "1 > 2", "2"]);
+        let redirections: Vec<(Option<&str>, &str, &str)> =
+            words.redirections.iter().map(|r| (r.fd.as_deref(), r.op.as_str(), r.target.as_str())).collect();
+        assert_eq!(
+            redirections,
+            vec![(None, ">", "out"), (Some("2"), ">&", "1"), (None, ">>", "log.txt"), (None, "<<-", "EOF")]
+        );
+    }
+
+    #[test]
+    fn test_summarize_attributes_failures_to_primary_command() {
+        let invocation = |script: &str, error: Option<&str>, duration_ms: u64| ShellInvocation {
+            project_id: "p".to_string(),
+            timestamp: "2025-01-01T00:00:00Z".to_string(),
+            script: script.to_string(),
+            answered: true,
+            failed: error.is_some(),
+            exit_code: error.and_then(exit_code),
+            error: error.map(String::from),
+            duration_ms: Some(duration_ms),
+        };
+        let report = summarize(&[
+            invocation("cd app && cargo test", Some("Exit code 101\ntest failed"), 5000),
+            invocation("cargo test", None, 3000),
+            invocation("ls", None, 10),
+        ]);
+
+        let cargo = report.commands.iter().find(|c| c.command == "cargo test").unwrap();
+        assert_eq!((cargo.runs, cargo.failures, cargo.total_duration_ms), (2, 1, 8000));
+        assert_eq!(cargo.exit_codes[0].code, 101);
+        assert_eq!(report.commands.iter().find(|c| c.command == "cd").unwrap().failures, 0);
+        assert_eq!(report.failures[0].message, "Exit code N");
+        assert_eq!(report.projects[0].total_duration_ms, 8010);
+        assert_eq!((report.total_commands, report.failed_invocations), (4, 1));
+    }
+}
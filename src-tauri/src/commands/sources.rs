@@ -0,0 +1,343 @@
+/**
+ * Transcript sources
+ *
+ * A transcript source is one coding agent's on-disk log format (Claude Code,
+ * Codex, ...). Each source knows how to list its projects and sessions, load a
+ * normalized session transcript, read prompt history and tail a live session.
+ * Sources also reduce their transcripts to normalized usage entries for the
+ * analytics, and hand documentation the prompts and files of a session.
+ * Commands take an optional `source` id and dispatch through the registry here,
+ * so supporting a new agent means adding one `TranscriptSource` implementation.
+ */
+
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+
+use super::analytics_v2::{claude_usage_entries, codex_usage_entries};
+use super::conversation_tree::TreeEntry;
+use super::data_roots::{data_roots, resolve_qualified_id, DataRoot};
+use super::docs::{claude_session_doc_input, codex_session_doc_input, SessionDocInput};
+use super::history::{read_codex_history_entries, read_history_entries, HistoryEntry};
+use super::pricing::TokenCounts;
+use super::transcripts::{
+    find_codex_session_file, list_claude_projects, list_claude_sessions, list_codex_projects,
+    list_codex_sessions, load_claude_branch_transcript, load_claude_transcript, load_claude_tree_entries,
+    load_codex_transcript, load_codex_tree_entries, ClaudeSessionTail, CodexSessionTail, Message, Project,
+    Session, SessionStats,
+};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+pub const CLAUDE_CODE_SOURCE: &str = "code";
+pub const CODEX_SOURCE: &str = "codex";
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// Boxed future returned by source methods, so sources can be used as trait objects
+pub type SourceFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, String>> + Send + 'a>>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptSourceInfo {
+    pub id: String,
+    #[serde(rename = "displayName")]
+    pub display_name: String,
+}
+
+/// A whole session: listing metadata plus every message in display order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionTranscript {
+    pub session: Session,
+    pub messages: Vec<Message>,
+}
+
+/// One transcript line reduced to what the usage analytics count
+#[derive(Debug, Clone)]
+pub struct UsageEntry<'a> {
+    /// Data root id and root-qualified project id of the session
+    pub root: &'a str,
+    pub project_id: &'a str,
+    pub session_id: &'a str,
+    pub timestamp: &'a str,
+    /// A user or assistant message, counted in message totals and hourly activity
+    pub is_message: bool,
+    pub tool_calls: usize,
+    /// Model the message or usage is attributed to
+    pub model: Option<&'a str>,
+    /// One model response, counted per model
+    pub is_response: bool,
+    /// Tokens billed for this line, None when it carries no usage
+    pub tokens: Option<TokenCounts>,
+    pub cost_usd: f64,
+    /// Context window the model ran with, 0 when unknown
+    pub context_window: i32,
+}
+
+/// Incremental reader over a session that is still being written
+pub trait SessionTailer: Send {
+    /// New messages and refreshed stats since the last poll, or None if nothing changed
+    fn poll(&mut self) -> Result<Option<(Vec<Message>, SessionStats)>, String>;
+}
+
+pub trait TranscriptSource: Send + Sync {
+    /// Stable id used by commands and stored on rows ("code", "codex")
+    fn id(&self) -> &'static str;
+
+    fn display_name(&self) -> &'static str;
+
+    fn projects(&self) -> SourceFuture<'_, Vec<Project>>;
+
+    fn project_sessions(&self, project_id: String) -> SourceFuture<'_, Vec<Session>>;
+
+    fn session_transcript(&self, project_id: String, session_id: String) -> SourceFuture<'_, SessionTranscript>;
+    /// Transcript of one branch of the conversation tree, ending at `leaf_uuid`
+    fn branch_transcript(&self, project_id: String, session_id: String, leaf_uuid: String) -> SourceFuture<'_, SessionTranscript>;
+    /// Entries with their parent links, in file order, for rebuilding the conversation tree
+    fn tree_entries(&self, project_id: String, session_id: String) -> SourceFuture<'_, Vec<TreeEntry>>;
+
+    /// Prompt history, newest first
+    fn history(&self) -> SourceFuture<'_, Vec<HistoryEntry>>;
+
+    fn tail_session(&self, project_id: &str, session_id: &str) -> Result<Box<dyn SessionTailer>, String>;
+
+    /// Visit the usage entries of every session under `roots`, session by session in file order
+    fn usage_entries(&self, roots: &[DataRoot], visit: &mut dyn FnMut(&UsageEntry)) -> Result<(), String>;
+
+    /// Usage totals the agent keeps itself for `root`, read instead of the transcripts when present
+    fn stats_cache_file(&self, _root: &DataRoot) -> Option<PathBuf> {
+        None
+    }
+
+    /// Prompts, written files and working directory of a session, for documentation.
+    /// `project_id` is the project selected in the dashboard, which may be another source's id.
+    fn session_doc_input(&self, project_id: String, session_id: String) -> SourceFuture<'_, SessionDocInput>;
+}
+
+// ============================================================================
+// Shared Paths
+// ============================================================================
+
+/// Folder of a Claude Code project; accepts root-qualified project ids
+pub fn claude_project_dir(project_id: &str) -> Result<PathBuf, String> {
+    let (root, id) = resolve_qualified_id(project_id)?;
+    Ok(root.claude_projects_dir().join(id))
+}
+
+/// Every Claude Code project folder across data roots, with its qualified project id
+pub fn claude_project_dirs() -> Vec<(DataRoot, String, PathBuf)> {
+    let mut dirs = Vec::new();
+
+    for root in data_roots() {
+        let Ok(entries) = std::fs::read_dir(root.claude_projects_dir()) else { continue };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else { continue };
+            let project_id = root.qualify_id(name);
+            dirs.push((root.clone(), project_id, path));
+        }
+    }
+
+    dirs
+}
+
+/// Every Codex rollout file across data roots
+pub fn codex_session_files() -> Vec<(DataRoot, PathBuf)> {
+    let mut files = Vec::new();
+
+    for root in data_roots() {
+        let Some(sessions_dir) = root.codex_sessions_dir() else { continue };
+        for file in list_codex_session_files(&sessions_dir) {
+            files.push((root.clone(), file));
+        }
+    }
+
+    files
+}
+
+/// All Codex rollout files below `root` (sessions are nested by date)
+pub fn list_codex_session_files(root: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let Ok(entries) = std::fs::read_dir(root) else { return files };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            files.extend(list_codex_session_files(&path));
+        } else if path.extension().map(|e| e == "jsonl").unwrap_or(false) {
+            files.push(path);
+        }
+    }
+
+    files
+}
+
+// ============================================================================
+// Claude Code
+// ============================================================================
+
+pub struct ClaudeCodeSource;
+
+impl SessionTailer for ClaudeSessionTail {
+    fn poll(&mut self) -> Result<Option<(Vec<Message>, SessionStats)>, String> {
+        ClaudeSessionTail::poll(self)
+    }
+}
+
+impl TranscriptSource for ClaudeCodeSource {
+    fn id(&self) -> &'static str {
+        CLAUDE_CODE_SOURCE
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Claude Code"
+    }
+
+    fn projects(&self) -> SourceFuture<'_, Vec<Project>> {
+        Box::pin(list_claude_projects())
+    }
+
+    fn project_sessions(&self, project_id: String) -> SourceFuture<'_, Vec<Session>> {
+        Box::pin(list_claude_sessions(project_id))
+    }
+
+    fn session_transcript(&self, project_id: String, session_id: String) -> SourceFuture<'_, SessionTranscript> {
+        Box::pin(load_claude_transcript(project_id, session_id))
+    }
+
+    fn branch_transcript(&self, project_id: String, session_id: String, leaf_uuid: String) -> SourceFuture<'_, SessionTranscript> {
+        Box::pin(load_claude_branch_transcript(project_id, session_id, leaf_uuid))
+    }
+
+    fn tree_entries(&self, project_id: String, session_id: String) -> SourceFuture<'_, Vec<TreeEntry>> {
+        Box::pin(load_claude_tree_entries(project_id, session_id))
+    }
+
+    fn history(&self) -> SourceFuture<'_, Vec<HistoryEntry>> {
+        Box::pin(read_history_entries())
+    }
+
+    fn tail_session(&self, project_id: &str, session_id: &str) -> Result<Box<dyn SessionTailer>, String> {
+        let session_path = claude_project_dir(project_id)?.join(format!("{}.jsonl", session_id));
+        if !session_path.exists() {
+            return Err("Session not found".to_string());
+        }
+        Ok(Box::new(ClaudeSessionTail::new(session_path, project_id)))
+    }
+
+    fn usage_entries(&self, roots: &[DataRoot], visit: &mut dyn FnMut(&UsageEntry)) -> Result<(), String> {
+        claude_usage_entries(roots, visit)
+    }
+
+    fn stats_cache_file(&self, root: &DataRoot) -> Option<PathBuf> {
+        let path = root.stats_cache_file();
+        path.exists().then_some(path)
+    }
+
+    fn session_doc_input(&self, project_id: String, session_id: String) -> SourceFuture<'_, SessionDocInput> {
+        Box::pin(claude_session_doc_input(project_id, session_id))
+    }
+}
+
+// ============================================================================
+// Codex
+// ============================================================================
+
+pub struct CodexSource;
+
+impl SessionTailer for CodexSessionTail {
+    fn poll(&mut self) -> Result<Option<(Vec<Message>, SessionStats)>, String> {
+        CodexSessionTail::poll(self)
+    }
+}
+
+impl TranscriptSource for CodexSource {
+    fn id(&self) -> &'static str {
+        CODEX_SOURCE
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Codex"
+    }
+
+    fn projects(&self) -> SourceFuture<'_, Vec<Project>> {
+        Box::pin(list_codex_projects())
+    }
+
+    fn project_sessions(&self, project_id: String) -> SourceFuture<'_, Vec<Session>> {
+        Box::pin(list_codex_sessions(project_id))
+    }
+
+    fn session_transcript(&self, project_id: String, session_id: String) -> SourceFuture<'_, SessionTranscript> {
+        Box::pin(load_codex_transcript(project_id, session_id))
+    }
+
+    /// Codex sessions are linear, so the only branch is the whole session
+    fn branch_transcript(&self, project_id: String, session_id: String, _leaf_uuid: String) -> SourceFuture<'_, SessionTranscript> {
+        Box::pin(load_codex_transcript(project_id, session_id))
+    }
+
+    fn tree_entries(&self, project_id: String, session_id: String) -> SourceFuture<'_, Vec<TreeEntry>> {
+        Box::pin(load_codex_tree_entries(project_id, session_id))
+    }
+
+    fn history(&self) -> SourceFuture<'_, Vec<HistoryEntry>> {
+        Box::pin(read_codex_history_entries())
+    }
+
+    fn tail_session(&self, project_id: &str, session_id: &str) -> Result<Box<dyn SessionTailer>, String> {
+        let session_path = find_codex_session_file(project_id, session_id)?;
+        Ok(Box::new(CodexSessionTail::new(session_path)))
+    }
+
+    fn usage_entries(&self, roots: &[DataRoot], visit: &mut dyn FnMut(&UsageEntry)) -> Result<(), String> {
+        codex_usage_entries(roots, visit);
+        Ok(())
+    }
+
+    /// Rollouts are found by session id alone, since the dashboard may pass a Claude Code project id
+    fn session_doc_input(&self, _project_id: String, session_id: String) -> SourceFuture<'_, SessionDocInput> {
+        Box::pin(codex_session_doc_input(session_id))
+    }
+}
+
+// ============================================================================
+// Registry
+// ============================================================================
+
+/// Every supported source, in display order
+pub fn registered_sources() -> Vec<Box<dyn TranscriptSource>> {
+    vec![Box::new(ClaudeCodeSource), Box::new(CodexSource)]
+}
+
+/// Look up a source by id; `None` selects Claude Code
+pub fn find_source(id: Option<&str>) -> Result<Box<dyn TranscriptSource>, String> {
+    let id = id.unwrap_or(CLAUDE_CODE_SOURCE);
+    registered_sources()
+        .into_iter()
+        .find(|s| s.id() == id)
+        .ok_or_else(|| format!("Unknown transcript source: {}", id))
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// List the registered transcript sources
+#[tauri::command]
+pub async fn get_transcript_sources() -> Result<Vec<TranscriptSourceInfo>, String> {
+    Ok(registered_sources()
+        .iter()
+        .map(|s| TranscriptSourceInfo {
+            id: s.id().to_string(),
+            display_name: s.display_name().to_string(),
+        })
+        .collect())
+}
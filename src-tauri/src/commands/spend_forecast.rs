@@ -0,0 +1,449 @@
+/**
+ * Spend forecasting and anomaly detection
+ *
+ * Works on the daily usage series of the analytics (local days, per project,
+ * session and model).
+ *
+ * Forecasts project end-of-month spend per project, per model and in total:
+ * month-to-date spend plus the mean daily spend of the last four weeks for
+ * each remaining day, with a 95% band from the day-to-day variation.
+ *
+ * Anomalies are days and sessions far above their project's baseline. The
+ * baseline is the median of the project's recent active days (or of its
+ * sessions), and "far" means beyond both a robust z-score (median absolute
+ * deviation) and a multiple of the median. Each anomalous day lists the
+ * sessions that spent the most on it.
+ */
+
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+use super::analytics_v2::{daily_usage, DailyUsage};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Days of history behind forecasts and day baselines
+const BASELINE_DAYS: i64 = 28;
+/// Active days a project needs before its days are judged
+const MIN_BASELINE_DAYS: usize = 5;
+/// Sessions a project needs before its sessions are judged
+const MIN_BASELINE_SESSIONS: usize = 5;
+/// z for the 95% forecast band
+const BAND_Z: f64 = 1.96;
+/// Robust z-score (in scaled MADs) above which spend is anomalous
+const ANOMALY_Z: f64 = 3.5;
+/// Anomalous spend must also be this multiple of the baseline median
+const MIN_SPIKE_RATIO: f64 = 3.0;
+/// Spikes cheaper than this are not worth flagging
+const MIN_ANOMALY_USD: f64 = 1.0;
+/// Sessions linked to an anomalous day
+const MAX_LINKED_SESSIONS: usize = 5;
+/// Scales the median absolute deviation to a standard deviation for normal data
+const MAD_SCALE: f64 = 1.4826;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Forecast {
+    /// Project id, model id, or "total"
+    pub key: String,
+    pub month_to_date_usd: f64,
+    pub month_to_date_tokens: i64,
+    /// Mean daily spend over the history the forecast is based on
+    pub daily_mean_usd: f64,
+    /// Days of history behind the mean
+    pub history_days: usize,
+    pub projected_usd: f64,
+    /// 95% band of the projection; the lower bound never drops below month-to-date spend
+    pub lower_usd: f64,
+    pub upper_usd: f64,
+    pub projected_tokens: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpendForecast {
+    /// "YYYY-MM"
+    pub month: String,
+    pub days_elapsed: u32,
+    pub days_in_month: u32,
+    pub total: Forecast,
+    /// Highest projection first
+    pub by_project: Vec<Forecast>,
+    pub by_model: Vec<Forecast>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSpend {
+    pub session_id: String,
+    pub project_id: String,
+    pub source: String,
+    pub cost_usd: f64,
+    pub tokens: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnomalousDay {
+    pub date: String,
+    pub project_id: String,
+    pub cost_usd: f64,
+    pub tokens: i64,
+    /// Median spend of the project's active days in the preceding four weeks
+    pub baseline_usd: f64,
+    /// Spend above which the day counts as anomalous
+    pub threshold_usd: f64,
+    /// Spend as a multiple of the baseline
+    pub ratio: f64,
+    /// Costliest sessions of the day
+    pub sessions: Vec<SessionSpend>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnomalousSession {
+    pub session: SessionSpend,
+    /// First day the session spent on
+    pub date: String,
+    /// Median spend of the project's sessions
+    pub baseline_usd: f64,
+    pub threshold_usd: f64,
+    pub ratio: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpendAnomalies {
+    /// Newest first
+    pub days: Vec<AnomalousDay>,
+    /// Highest ratio first
+    pub sessions: Vec<AnomalousSession>,
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn parse_date(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+fn median(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let mid = sorted.len() / 2;
+    if sorted.len() % 2 == 0 {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+/// Median and the spend above which a value is anomalous, if there is enough data
+fn spike_threshold(baseline: &[f64], min_len: usize) -> Option<(f64, f64)> {
+    if baseline.len() < min_len {
+        return None;
+    }
+    let center = median(baseline);
+    let deviations: Vec<f64> = baseline.iter().map(|v| (v - center).abs()).collect();
+    let spread = MAD_SCALE * median(&deviations);
+    let threshold = (center + ANOMALY_Z * spread).max(center * MIN_SPIKE_RATIO).max(MIN_ANOMALY_USD);
+    Some((center, threshold))
+}
+
+fn ratio(value: f64, baseline: f64) -> f64 {
+    if baseline > 0.0 {
+        value / baseline
+    } else {
+        0.0
+    }
+}
+
+fn days_in_month(date: NaiveDate) -> u32 {
+    let (year, month) = if date.month() == 12 { (date.year() + 1, 1) } else { (date.year(), date.month() + 1) };
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|next| next.pred_opt())
+        .map(|last| last.day())
+        .unwrap_or(30)
+}
+
+/// Forecast for one group from its daily (cost, tokens)
+fn forecast_group(key: &str, daily: &BTreeMap<NaiveDate, (f64, i64)>, today: NaiveDate) -> Forecast {
+    let month_start = today.with_day(1).unwrap_or(today);
+    let days_total = days_in_month(today);
+    let remaining = (days_total - today.day()) as f64;
+
+    let (month_to_date_usd, month_to_date_tokens) = daily
+        .range(month_start..=today)
+        .fold((0.0, 0), |(cost, tokens), (_, (c, t))| (cost + c, tokens + t));
+
+    // Complete days of the last four weeks, counting idle days but nothing before the first activity
+    let first_active = daily.keys().next().copied().unwrap_or(today);
+    let history_start = (today - Duration::days(BASELINE_DAYS)).max(first_active);
+    let mut history: Vec<(f64, i64)> = Vec::new();
+    let mut day = history_start;
+    while day < today {
+        history.push(daily.get(&day).copied().unwrap_or((0.0, 0)));
+        day += Duration::days(1);
+    }
+    // A group that started today only has today to go on
+    if history.is_empty() {
+        history.push(daily.get(&today).copied().unwrap_or((0.0, 0)));
+    }
+
+    let n = history.len() as f64;
+    let mean_cost = history.iter().map(|(c, _)| c).sum::<f64>() / n;
+    let mean_tokens = history.iter().map(|(_, t)| *t as f64).sum::<f64>() / n;
+    let variance = if history.len() > 1 {
+        history.iter().map(|(c, _)| (c - mean_cost).powi(2)).sum::<f64>() / (n - 1.0)
+    } else {
+        0.0
+    };
+
+    let projected_usd = month_to_date_usd + mean_cost * remaining;
+    let band = BAND_Z * variance.sqrt() * remaining.sqrt();
+
+    Forecast {
+        key: key.to_string(),
+        month_to_date_usd,
+        month_to_date_tokens,
+        daily_mean_usd: mean_cost,
+        history_days: history.len(),
+        projected_usd,
+        lower_usd: (projected_usd - band).max(month_to_date_usd),
+        upper_usd: projected_usd + band,
+        projected_tokens: month_to_date_tokens + (mean_tokens * remaining).round() as i64,
+    }
+}
+
+fn forecast_by(rows: &[DailyUsage], today: NaiveDate, key: impl Fn(&DailyUsage) -> &str) -> Vec<Forecast> {
+    let mut groups: HashMap<&str, BTreeMap<NaiveDate, (f64, i64)>> = HashMap::new();
+    for row in rows {
+        let Some(date) = parse_date(&row.date).filter(|d| *d <= today) else { continue };
+        let totals = groups.entry(key(row)).or_default().entry(date).or_insert((0.0, 0));
+        totals.0 += row.cost_usd;
+        totals.1 += row.tokens;
+    }
+
+    let mut forecasts: Vec<Forecast> = groups
+        .iter()
+        .map(|(key, daily)| forecast_group(key, daily, today))
+        // Groups idle for the whole window have nothing left to forecast
+        .filter(|f| f.projected_usd > 0.0)
+        .collect();
+    forecasts.sort_by(|a, b| b.projected_usd.total_cmp(&a.projected_usd));
+    forecasts
+}
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+/// End-of-month projection for the month containing `today`
+pub fn forecast(rows: &[DailyUsage], today: NaiveDate) -> SpendForecast {
+    let total = forecast_by(rows, today, |_| "total").into_iter().next().unwrap_or_else(|| {
+        forecast_group("total", &BTreeMap::new(), today)
+    });
+
+    SpendForecast {
+        month: today.format("%Y-%m").to_string(),
+        days_elapsed: today.day(),
+        days_in_month: days_in_month(today),
+        total,
+        by_project: forecast_by(rows, today, |r| &r.project_id),
+        by_model: forecast_by(rows, today, |r| &r.model),
+    }
+}
+
+/// Days and sessions far above their project's baseline
+pub fn detect_anomalies(rows: &[DailyUsage]) -> SpendAnomalies {
+    // project -> date -> session -> spend
+    let mut projects: HashMap<&str, BTreeMap<NaiveDate, HashMap<&str, SessionSpend>>> = HashMap::new();
+    // project -> session -> (spend, first date)
+    let mut sessions: HashMap<&str, HashMap<&str, (SessionSpend, String)>> = HashMap::new();
+
+    for row in rows {
+        let Some(date) = parse_date(&row.date) else { continue };
+        let spend = || SessionSpend {
+            session_id: row.session_id.clone(),
+            project_id: row.project_id.clone(),
+            source: row.source.clone(),
+            cost_usd: 0.0,
+            tokens: 0,
+        };
+
+        let day = projects.entry(&row.project_id).or_default().entry(date).or_default();
+        let session = day.entry(&row.session_id).or_insert_with(spend);
+        session.cost_usd += row.cost_usd;
+        session.tokens += row.tokens;
+
+        let (total, first_date) = sessions
+            .entry(&row.project_id)
+            .or_default()
+            .entry(&row.session_id)
+            .or_insert_with(|| (spend(), row.date.clone()));
+        total.cost_usd += row.cost_usd;
+        total.tokens += row.tokens;
+        if row.date < *first_date {
+            *first_date = row.date.clone();
+        }
+    }
+
+    let mut anomalous_days = Vec::new();
+    for (project_id, days) in &projects {
+        let daily: Vec<(NaiveDate, f64)> = days
+            .iter()
+            .map(|(date, sessions)| (*date, sessions.values().map(|s| s.cost_usd).sum()))
+            .collect();
+
+        for (date, cost) in &daily {
+            let window_start = *date - Duration::days(BASELINE_DAYS);
+            let baseline: Vec<f64> = daily
+                .iter()
+                .filter(|(d, c)| *d >= window_start && d < date && *c > 0.0)
+                .map(|(_, c)| *c)
+                .collect();
+            let Some((center, threshold)) = spike_threshold(&baseline, MIN_BASELINE_DAYS) else { continue };
+            if *cost <= threshold {
+                continue;
+            }
+
+            let mut linked: Vec<SessionSpend> = days[date].values().cloned().collect();
+            linked.sort_by(|a, b| b.cost_usd.total_cmp(&a.cost_usd));
+            linked.truncate(MAX_LINKED_SESSIONS);
+            anomalous_days.push(AnomalousDay {
+                date: date.format("%Y-%m-%d").to_string(),
+                project_id: project_id.to_string(),
+                cost_usd: *cost,
+                tokens: days[date].values().map(|s| s.tokens).sum(),
+                baseline_usd: center,
+                threshold_usd: threshold,
+                ratio: ratio(*cost, center),
+                sessions: linked,
+            });
+        }
+    }
+    anomalous_days.sort_by(|a, b| b.date.cmp(&a.date).then_with(|| b.cost_usd.total_cmp(&a.cost_usd)));
+
+    let mut anomalous_sessions = Vec::new();
+    for project_sessions in sessions.values() {
+        let costs: Vec<f64> = project_sessions.values().map(|(s, _)| s.cost_usd).collect();
+        let Some((center, threshold)) = spike_threshold(&costs, MIN_BASELINE_SESSIONS) else { continue };
+
+        for (session, date) in project_sessions.values() {
+            if session.cost_usd > threshold {
+                anomalous_sessions.push(AnomalousSession {
+                    session: session.clone(),
+                    date: date.clone(),
+                    baseline_usd: center,
+                    threshold_usd: threshold,
+                    ratio: ratio(session.cost_usd, center),
+                });
+            }
+        }
+    }
+    anomalous_sessions.sort_by(|a, b| b.ratio.total_cmp(&a.ratio).then_with(|| b.date.cmp(&a.date)));
+
+    SpendAnomalies { days: anomalous_days, sessions: anomalous_sessions }
+}
+
+fn project_usage(project_id: Option<&str>) -> Result<Vec<DailyUsage>, String> {
+    let mut rows = daily_usage()?;
+    if let Some(project_id) = project_id {
+        rows.retain(|r| r.project_id == project_id);
+    }
+    Ok(rows)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Projected end-of-month spend in total, per project and per model, optionally for one project
+#[tauri::command]
+pub async fn get_spend_forecast(project_id: Option<String>) -> Result<SpendForecast, String> {
+    tokio::task::spawn_blocking(move || {
+        let rows = project_usage(project_id.as_deref())?;
+        Ok(forecast(&rows, chrono::Local::now().date_naive()))
+    })
+    .await
+    .map_err(|e| format!("Spend forecast task failed: {}", e))?
+}
+
+/// Anomalous days and sessions, optionally for one project
+#[tauri::command]
+pub async fn get_spend_anomalies(project_id: Option<String>) -> Result<SpendAnomalies, String> {
+    tokio::task::spawn_blocking(move || {
+        let rows = project_usage(project_id.as_deref())?;
+        Ok(detect_anomalies(&rows))
+    })
+    .await
+    .map_err(|e| format!("Spend anomaly task failed: {}", e))?
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(date: &str, session_id: &str, model: &str, cost_usd: f64) -> DailyUsage {
+        DailyUsage {
+            date: date.to_string(),
+            root: "local".to_string(),
+            project_id: "p1".to_string(),
+            session_id: session_id.to_string(),
+            source: "code".to_string(),
+            model: model.to_string(),
+            cost_usd,
+            tokens: (cost_usd * 1000.0) as i64,
+        }
+    }
+
+    #[test]
+    fn test_forecast_projects_mean_over_remaining_days() {
+        // $2 every day from May 1st; today is June 10th with $20 spent so far
+        let mut rows = Vec::new();
+        let mut day = NaiveDate::from_ymd_opt(2025, 5, 1).unwrap();
+        let today = NaiveDate::from_ymd_opt(2025, 6, 10).unwrap();
+        while day <= today {
+            rows.push(row(&day.format("%Y-%m-%d").to_string(), "s", "sonnet", 2.0));
+            day += Duration::days(1);
+        }
+
+        let forecast = forecast(&rows, today);
+        assert_eq!((forecast.days_elapsed, forecast.days_in_month), (10, 30));
+        assert!((forecast.total.month_to_date_usd - 20.0).abs() < 1e-9);
+        assert!((forecast.total.projected_usd - 60.0).abs() < 1e-9);
+        assert!((forecast.total.upper_usd - forecast.total.lower_usd).abs() < 1e-9);
+        assert_eq!(forecast.by_model[0].key, "sonnet");
+    }
+
+    #[test]
+    fn test_spike_day_and_session_are_flagged() {
+        let mut rows: Vec<DailyUsage> = (1..=10)
+            .map(|d| row(&format!("2025-06-{:02}", d), &format!("s{}", d), "sonnet", 2.0 + (d % 3) as f64 * 0.1))
+            .collect();
+        rows.push(row("2025-06-11", "big", "opus", 30.0));
+        rows.push(row("2025-06-11", "small", "sonnet", 1.0));
+
+        let anomalies = detect_anomalies(&rows);
+        assert_eq!(anomalies.days.len(), 1);
+        assert_eq!(anomalies.days[0].date, "2025-06-11");
+        assert_eq!(anomalies.days[0].sessions[0].session_id, "big");
+        assert_eq!(anomalies.sessions.len(), 1);
+        assert_eq!(anomalies.sessions[0].session.session_id, "big");
+    }
+}
@@ -0,0 +1,518 @@
+/**
+ * Tool reliability analytics
+ *
+ * Pairs every tool call with its result to measure which tools fail or stall.
+ * Claude Code calls come from the transcript index (`tool_use` blocks matched to
+ * `tool_result` blocks by id, failures flagged by `is_error`); Codex calls are
+ * read from the rollouts (`function_call` matched to `function_call_output` by
+ * call id, failures detected from a non-zero exit code). Latency is the time
+ * between the call and its result. Stats are reported per tool, and per tool
+ * within each project, model and source.
+ */
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use super::git_correlation::parse_time_ms;
+use super::sources::{codex_session_files, CLAUDE_CODE_SOURCE, CODEX_SOURCE};
+use super::transcript_index::{sessions_oldest_first, with_index, IndexedToolResult};
+use super::transcripts::codex_session_identity;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+const UNKNOWN_MODEL: &str = "unknown";
+
+/// Most common error messages kept per tool
+const TOP_ERRORS: usize = 5;
+
+const MAX_ERROR_SIGNATURE: usize = 120;
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// A Codex `function_call` or `custom_tool_call`, paired with its output by call id
+#[derive(Debug, Clone)]
+pub struct CodexCall {
+    pub name: String,
+    /// The call's payload, for callers that need its arguments
+    pub payload: Value,
+    pub timestamp: String,
+    /// Model of the turn the call was made in
+    pub model: String,
+    /// Output text and its timestamp; None when the call was never answered
+    pub output: Option<(String, String)>,
+}
+
+/// One tool call paired with its result
+#[derive(Debug, Clone)]
+pub struct ToolCall {
+    pub tool: String,
+    pub source: String,
+    pub project_id: String,
+    pub model: String,
+    /// Whether a result was recorded (false when interrupted or still running)
+    pub answered: bool,
+    pub is_error: bool,
+    pub error: Option<String>,
+    pub latency_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencyStats {
+    pub samples: usize,
+    pub mean_ms: f64,
+    pub p50_ms: u64,
+    pub p90_ms: u64,
+    pub p99_ms: u64,
+    pub max_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorCount {
+    /// First line of the error, with numbers replaced by N
+    pub message: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolStats {
+    pub tool: String,
+    pub calls: usize,
+    pub errors: usize,
+    /// errors / answered calls * 100
+    pub error_rate: f64,
+    /// Calls without a result
+    pub unanswered: usize,
+    pub latency: LatencyStats,
+    /// Most frequent first
+    pub top_errors: Vec<ErrorCount>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolBreakdown {
+    /// Project id, model or source
+    pub key: String,
+    pub calls: usize,
+    pub errors: usize,
+    pub error_rate: f64,
+    /// Most calls first
+    pub tools: Vec<ToolStats>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolReliability {
+    pub total_calls: usize,
+    pub total_errors: usize,
+    pub error_rate: f64,
+    /// Most calls first
+    pub tools: Vec<ToolStats>,
+    pub by_project: Vec<ToolBreakdown>,
+    pub by_model: Vec<ToolBreakdown>,
+    pub by_source: Vec<ToolBreakdown>,
+}
+
+#[derive(Default)]
+struct Accumulator {
+    calls: usize,
+    errors: usize,
+    unanswered: usize,
+    latencies: Vec<u64>,
+    messages: HashMap<String, usize>,
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Milliseconds between two timestamps, None if either is unparseable or they are out of order
+pub fn latency_ms(start: &str, end: &str) -> Option<u64> {
+    let elapsed = parse_time_ms(end)? - parse_time_ms(start)?;
+    u64::try_from(elapsed).ok()
+}
+
+/// `part` as a percentage of `whole`, 0 when `whole` is 0
+pub fn percent(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64 * 100.0
+    }
+}
+
+/// Groupable form of an error: first non-empty line, numbers replaced by N
+pub fn error_signature(text: &str) -> String {
+    let text = text.replace("<tool_use_error>", "").replace("</tool_use_error>", "");
+    let line = text.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or("(no message)");
+
+    let mut signature = String::new();
+    let mut in_number = false;
+    for c in line.chars() {
+        if c.is_ascii_digit() {
+            if !in_number {
+                signature.push('N');
+            }
+            in_number = true;
+        } else {
+            signature.push(c);
+            in_number = false;
+        }
+    }
+    signature.chars().take(MAX_ERROR_SIGNATURE).collect()
+}
+
+/// Exit code reported by a shell tool's output: Codex JSON `metadata.exit_code`,
+/// or a leading "Exit code: N" (Codex) or "Exit code N" (Claude Code) line
+pub fn exit_code(output: &str) -> Option<i64> {
+    if let Ok(value) = serde_json::from_str::<Value>(output) {
+        return value.get("metadata").and_then(|m| m.get("exit_code")).and_then(|v| v.as_i64());
+    }
+    let first = output.lines().next()?.trim().strip_prefix("Exit code")?;
+    first.trim_start_matches(':').trim().parse().ok()
+}
+
+/// Error text of a Codex tool output, or None when the call succeeded
+pub fn codex_output_error(output: &str) -> Option<String> {
+    let code = exit_code(output).filter(|code| *code != 0)?;
+
+    if let Ok(value) = serde_json::from_str::<Value>(output) {
+        let text = value.get("output").and_then(|v| v.as_str()).filter(|s| !s.trim().is_empty());
+        return Some(text.map(String::from).unwrap_or_else(|| format!("Exit code {}", code)));
+    }
+    // The command's own output is more telling than the exit code header
+    let body = output.split_once("\nOutput:\n").map(|(_, body)| body).filter(|b| !b.trim().is_empty());
+    Some(body.unwrap_or(output).to_string())
+}
+
+fn percentile(sorted: &[u64], pct: f64) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = ((pct / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+impl Accumulator {
+    fn add(&mut self, call: &ToolCall) {
+        self.calls += 1;
+        if !call.answered {
+            self.unanswered += 1;
+        }
+        if call.is_error {
+            self.errors += 1;
+            *self.messages.entry(error_signature(call.error.as_deref().unwrap_or(""))).or_insert(0) += 1;
+        }
+        if let Some(latency) = call.latency_ms {
+            self.latencies.push(latency);
+        }
+    }
+
+    fn finish(mut self, tool: String) -> ToolStats {
+        self.latencies.sort_unstable();
+        let samples = self.latencies.len();
+        let latency = LatencyStats {
+            samples,
+            mean_ms: if samples == 0 { 0.0 } else { self.latencies.iter().sum::<u64>() as f64 / samples as f64 },
+            p50_ms: percentile(&self.latencies, 50.0),
+            p90_ms: percentile(&self.latencies, 90.0),
+            p99_ms: percentile(&self.latencies, 99.0),
+            max_ms: self.latencies.last().copied().unwrap_or(0),
+        };
+
+        let mut top_errors: Vec<ErrorCount> =
+            self.messages.into_iter().map(|(message, count)| ErrorCount { message, count }).collect();
+        top_errors.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.message.cmp(&b.message)));
+        top_errors.truncate(TOP_ERRORS);
+
+        ToolStats {
+            tool,
+            calls: self.calls,
+            errors: self.errors,
+            error_rate: percent(self.errors, self.calls - self.unanswered),
+            unanswered: self.unanswered,
+            latency,
+            top_errors,
+        }
+    }
+}
+
+/// Per-tool stats, most calls first
+fn tool_stats<'a>(calls: impl Iterator<Item = &'a ToolCall>) -> Vec<ToolStats> {
+    let mut by_tool: HashMap<&str, Accumulator> = HashMap::new();
+    for call in calls {
+        by_tool.entry(call.tool.as_str()).or_default().add(call);
+    }
+    let mut tools: Vec<ToolStats> = by_tool.into_iter().map(|(tool, acc)| acc.finish(tool.to_string())).collect();
+    tools.sort_by(|a, b| b.calls.cmp(&a.calls).then_with(|| a.tool.cmp(&b.tool)));
+    tools
+}
+
+fn breakdown(calls: &[ToolCall], key: impl Fn(&ToolCall) -> &str) -> Vec<ToolBreakdown> {
+    let mut groups: HashMap<&str, Vec<&ToolCall>> = HashMap::new();
+    for call in calls {
+        groups.entry(key(call)).or_default().push(call);
+    }
+
+    let mut breakdowns: Vec<ToolBreakdown> = groups
+        .into_iter()
+        .map(|(key, calls)| {
+            let tools = tool_stats(calls.into_iter());
+            let total: usize = tools.iter().map(|t| t.calls).sum();
+            let answered: usize = tools.iter().map(|t| t.calls - t.unanswered).sum();
+            let errors: usize = tools.iter().map(|t| t.errors).sum();
+            ToolBreakdown { key: key.to_string(), calls: total, errors, error_rate: percent(errors, answered), tools }
+        })
+        .collect();
+    breakdowns.sort_by(|a, b| b.calls.cmp(&a.calls).then_with(|| a.key.cmp(&b.key)));
+    breakdowns
+}
+
+pub fn summarize(calls: &[ToolCall]) -> ToolReliability {
+    let total_errors = calls.iter().filter(|c| c.is_error).count();
+    let answered = calls.iter().filter(|c| c.answered).count();
+
+    ToolReliability {
+        total_calls: calls.len(),
+        total_errors,
+        error_rate: percent(total_errors, answered),
+        tools: tool_stats(calls.iter()),
+        by_project: breakdown(calls, |c| &c.project_id),
+        by_model: breakdown(calls, |c| &c.model),
+        by_source: breakdown(calls, |c| &c.source),
+    }
+}
+
+/// Claude Code tool calls from the transcript index. A resumed session repeats
+/// earlier lines, so each tool_use id is counted once, in the session that logged it first.
+fn claude_calls(project_filter: Option<&str>) -> Result<Vec<ToolCall>, String> {
+    with_index(|index| {
+        let mut calls = Vec::new();
+        let mut seen: HashSet<&str> = HashSet::new();
+
+        let sessions = index
+            .sessions
+            .values()
+            .filter(|s| project_filter.map(|p| p == s.project_id).unwrap_or(true));
+        for session in sessions_oldest_first(sessions) {
+            let results: HashMap<&str, (&IndexedToolResult, &str)> = session
+                .entries
+                .iter()
+                .flat_map(|e| e.tool_results.iter().map(move |r| (r.tool_use_id.as_str(), (r, e.timestamp.as_str()))))
+                .collect();
+
+            for entry in &session.entries {
+                for tool_use in &entry.tool_uses {
+                    if tool_use.id.as_deref().is_some_and(|id| !seen.insert(id)) {
+                        continue;
+                    }
+                    let result = tool_use.id.as_deref().and_then(|id| results.get(id));
+                    calls.push(ToolCall {
+                        tool: tool_use.name.clone(),
+                        source: CLAUDE_CODE_SOURCE.to_string(),
+                        project_id: session.project_id.clone(),
+                        model: entry.model.clone().unwrap_or_else(|| UNKNOWN_MODEL.to_string()),
+                        answered: result.is_some(),
+                        is_error: result.map(|(r, _)| r.is_error).unwrap_or(false),
+                        error: result.and_then(|(r, _)| r.error.clone()),
+                        latency_ms: result.and_then(|(_, at)| latency_ms(&entry.timestamp, at)),
+                    });
+                }
+            }
+        }
+
+        calls
+    })
+}
+
+/// Tool calls of one Codex rollout in file order, each paired with its output
+pub fn codex_rollout_tool_calls(path: &Path) -> Vec<CodexCall> {
+    let Ok(file) = fs::File::open(path) else { return Vec::new() };
+
+    let mut calls: Vec<CodexCall> = Vec::new();
+    let mut model = UNKNOWN_MODEL.to_string();
+    // call id -> index of the call, until its output shows up
+    let mut pending: HashMap<String, usize> = HashMap::new();
+
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        if !line.contains("\"turn_context\"") && !line.contains("_call") {
+            continue;
+        }
+        let Ok(entry) = serde_json::from_str::<Value>(&line) else { continue };
+        let Some(payload) = entry.get("payload") else { continue };
+        let timestamp = entry.get("timestamp").and_then(|v| v.as_str()).unwrap_or("");
+
+        if entry.get("type").and_then(|v| v.as_str()) == Some("turn_context") {
+            if let Some(m) = payload.get("model").and_then(|v| v.as_str()) {
+                model = m.to_string();
+            }
+            continue;
+        }
+
+        let call_id = payload.get("call_id").and_then(|v| v.as_str()).unwrap_or("").to_string();
+        match payload.get("type").and_then(|v| v.as_str()) {
+            Some("function_call") | Some("custom_tool_call") => {
+                pending.insert(call_id, calls.len());
+                calls.push(CodexCall {
+                    name: payload.get("name").and_then(|v| v.as_str()).unwrap_or("tool").to_string(),
+                    payload: payload.clone(),
+                    timestamp: timestamp.to_string(),
+                    model: model.clone(),
+                    output: None,
+                });
+            }
+            Some("function_call_output") | Some("custom_tool_call_output") => {
+                let Some(index) = pending.remove(&call_id) else { continue };
+                let output = payload.get("output").and_then(|v| v.as_str()).unwrap_or("");
+                calls[index].output = Some((output.to_string(), timestamp.to_string()));
+            }
+            _ => {}
+        }
+    }
+
+    calls
+}
+
+/// Tool calls of one Codex rollout
+fn codex_rollout_calls(path: &Path, project_id: &str) -> Vec<ToolCall> {
+    codex_rollout_tool_calls(path)
+        .into_iter()
+        .map(|call| {
+            let error = call.output.as_ref().and_then(|(output, _)| codex_output_error(output));
+            ToolCall {
+                answered: call.output.is_some(),
+                is_error: error.is_some(),
+                error,
+                latency_ms: call.output.as_ref().and_then(|(_, at)| latency_ms(&call.timestamp, at)),
+                tool: call.name,
+                source: CODEX_SOURCE.to_string(),
+                project_id: project_id.to_string(),
+                model: call.model,
+            }
+        })
+        .collect()
+}
+
+fn codex_calls(project_filter: Option<&str>) -> Vec<ToolCall> {
+    let mut calls = Vec::new();
+
+    for (root, path) in codex_session_files() {
+        let Some((local_project_id, _, _)) = codex_session_identity(&path) else { continue };
+        let project_id = root.qualify_id(&local_project_id);
+        if project_filter.map(|p| p != project_id).unwrap_or(false) {
+            continue;
+        }
+        calls.extend(codex_rollout_calls(&path, &project_id));
+    }
+
+    calls
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Call counts, error rates, common errors and latency of every tool, optionally for one project
+#[tauri::command]
+pub async fn get_tool_reliability(project_id: Option<String>) -> Result<ToolReliability, String> {
+    tokio::task::spawn_blocking(move || {
+        let mut calls = claude_calls(project_id.as_deref())?;
+        calls.extend(codex_calls(project_id.as_deref()));
+        Ok(summarize(&calls))
+    })
+    .await
+    .map_err(|e| format!("Tool reliability task failed: {}", e))?
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(tool: &str, model: &str, error: Option<&str>, latency_ms: Option<u64>) -> ToolCall {
+        ToolCall {
+            tool: tool.to_string(),
+            source: CLAUDE_CODE_SOURCE.to_string(),
+            project_id: "p".to_string(),
+            model: model.to_string(),
+            answered: latency_ms.is_some(),
+            is_error: error.is_some(),
+            error: error.map(String::from),
+            latency_ms,
+        }
+    }
+
+    #[test]
+    fn test_summarize_tools() {
+        let calls = vec![
+            call("Bash", "opus", None, Some(100)),
+            call("Bash", "opus", Some("Exit code 1\nerror: test failed"), Some(3000)),
+            call("Bash", "sonnet", Some("Exit code 2"), Some(200)),
+            call("Bash", "sonnet", None, None),
+            call("Edit", "opus", Some("<tool_use_error>String to replace not found in file.</tool_use_error>"), Some(50)),
+        ];
+        let report = summarize(&calls);
+
+        let bash = &report.tools[0];
+        assert_eq!((bash.tool.as_str(), bash.calls, bash.errors, bash.unanswered), ("Bash", 4, 2, 1));
+        assert!((bash.error_rate - 200.0 / 3.0).abs() < 1e-9);
+        assert_eq!((bash.latency.p50_ms, bash.latency.max_ms), (200, 3000));
+        assert_eq!(bash.top_errors[0].message, "Exit code N");
+        assert_eq!(bash.top_errors[0].count, 2);
+        assert_eq!(report.tools[1].top_errors[0].message, "String to replace not found in file.");
+
+        assert_eq!(report.by_model.iter().map(|b| (b.key.as_str(), b.calls)).collect::<Vec<_>>(), vec![("opus", 3), ("sonnet", 2)]);
+    }
+
+    #[test]
+    fn test_codex_output_error() {
+        assert_eq!(codex_output_error(r#"{"output":"ok","metadata":{"exit_code":0}}"#), None);
+        assert_eq!(
+            codex_output_error(r#"{"output":"No such file","metadata":{"exit_code":1}}"#).as_deref(),
+            Some("No such file")
+        );
+        assert_eq!(
+            codex_output_error("Exit code: 127\nWall time: 0.1 seconds\nOutput:\nbash: foo: command not found\n").as_deref(),
+            Some("bash: foo: command not found\n")
+        );
+        assert_eq!(codex_output_error("Exit code: 0\nOutput:\ndone"), None);
+        assert_eq!(codex_output_error("Success. Updated the following files:\nM a.rs"), None);
+    }
+
+    #[test]
+    fn test_codex_rollout_pairs_calls_with_outputs() {
+        let path = std::env::temp_dir().join(format!("tool-reliability-test-{}.jsonl", std::process::id()));
+        let lines = [
+            r#"{"timestamp":"2025-06-01T10:00:00.000Z","type":"turn_context","payload":{"model":"gpt-5-codex"}}"#,
+            r#"{"timestamp":"2025-06-01T10:00:01.000Z","type":"response_item","payload":{"type":"function_call","name":"shell","call_id":"c1","arguments":"{\"command\":[\"ls\"]}"}}"#,
+            r#"{"timestamp":"2025-06-01T10:00:02.000Z","type":"response_item","payload":{"type":"custom_tool_call","name":"apply_patch","call_id":"c2","input":"*** Begin Patch"}}"#,
+            r#"{"timestamp":"2025-06-01T10:00:03.500Z","type":"response_item","payload":{"type":"function_call_output","call_id":"c1","output":"Exit code: 2\nOutput:\nls: cannot access"}}"#,
+        ];
+        fs::write(&path, lines.join("\n")).unwrap();
+
+        let pairs = codex_rollout_tool_calls(&path);
+        assert_eq!(pairs.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), vec!["shell", "apply_patch"]);
+        assert_eq!(pairs[0].model, "gpt-5-codex");
+        assert_eq!(pairs[0].output.as_ref().map(|(_, at)| at.as_str()), Some("2025-06-01T10:00:03.500Z"));
+        assert!(pairs[1].output.is_none());
+
+        let calls = codex_rollout_calls(&path, "p");
+        let _ = fs::remove_file(&path);
+        assert_eq!((calls[0].is_error, calls[0].latency_ms), (true, Some(2500)));
+        assert_eq!(calls[0].error.as_deref(), Some("ls: cannot access"));
+        assert!(!calls[1].answered);
+    }
+}
@@ -0,0 +1,894 @@
+/**
+ * Persistent incremental transcript index
+ *
+ * Keeps a compact, on-disk record of every Claude Code session file in the
+ * projects/ folder of each data root so commands don't re-read and re-parse every JSONL file
+ * on each call. Each file tracks its size, mtime and the byte offset of the
+ * last complete line ingested; a refresh only parses newly appended lines.
+ * A file that shrank, or whose bytes before that offset changed, is re-read
+ * from the start.
+ *
+ * A response is logged once per content block, each line repeating its usage.
+ * The index keeps that usage on the first line of each message id only, in the
+ * oldest session of the data root that logged it, so consumers can sum it as is.
+ *
+ * The index lives in ~/.claude-dashboard/transcript-index/, one JSON file per
+ * session file, so an append only rewrites that session's record. Records that
+ * are unreadable or from an older version are rebuilt from scratch.
+ */
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::hash::Hasher;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::sources::claude_project_dirs;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+const INDEX_VERSION: u32 = 1;
+
+/// Maximum length of user prompt text kept per entry (used for summaries)
+const MAX_INDEXED_TEXT: usize = 2000;
+
+/// Maximum length of tool error text kept per result
+const MAX_INDEXED_ERROR: usize = 300;
+
+/// Size of each block of the ingested part of a file that is hashed to detect rewrites
+const EDGE_BYTES: u64 = 4096;
+
+/// Blocks hashed between the head and the end of the ingested part, evenly spaced
+const MIDDLE_SAMPLES: u64 = 14;
+
+/// In-process copy of the index, loaded lazily on first use
+static INDEX: Mutex<Option<TranscriptIndex>> = Mutex::new(None);
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptIndex {
+    /// Version for future migrations; mismatches trigger a rebuild
+    pub version: u32,
+    /// Indexed session files keyed by absolute file path
+    pub sessions: HashMap<String, IndexedSession>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedSession {
+    /// Data root the file belongs to
+    pub root: String,
+    /// Host of that data root
+    pub host: String,
+    /// Root-qualified project id
+    #[serde(rename = "projectId")]
+    pub project_id: String,
+    #[serde(rename = "sessionId")]
+    pub session_id: String,
+    #[serde(rename = "filePath")]
+    pub file_path: String,
+    /// File size when last refreshed
+    pub size: u64,
+    /// File mtime (Unix ms) when last refreshed
+    #[serde(rename = "modifiedMs")]
+    pub modified_ms: i64,
+    /// Byte offset just past the last complete line ingested
+    pub offset: u64,
+    /// Hash of sampled blocks (head, evenly spaced middle and tail) of the bytes before `offset`
+    #[serde(rename = "prefixHash", default)]
+    pub prefix_hash: u64,
+    /// First `cwd` seen in the session
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    /// First `gitBranch` seen in the session
+    #[serde(rename = "gitBranch", skip_serializing_if = "Option::is_none")]
+    pub git_branch: Option<String>,
+    /// `summary` entries, in file order
+    #[serde(default)]
+    pub summaries: Vec<IndexedSummary>,
+    /// Timestamped log entries, in file order
+    #[serde(default)]
+    pub entries: Vec<IndexedEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedEntry {
+    /// Byte offset of the raw line in the session file
+    pub offset: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    #[serde(rename = "parentUuid", skip_serializing_if = "Option::is_none")]
+    pub parent_uuid: Option<String>,
+    #[serde(rename = "type")]
+    pub entry_type: String,
+    pub timestamp: String,
+    /// Whether the entry carries a `message` object
+    #[serde(rename = "hasMessage")]
+    pub has_message: bool,
+    /// API message id; responses are logged once per content block with the same id and usage
+    #[serde(rename = "messageId", skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Usage of the response, on the first line of its message id in the root's oldest session only
+    #[serde(skip)]
+    pub usage: Option<IndexedUsage>,
+    /// Logged cost, kept on the same line as `usage`
+    #[serde(skip)]
+    pub cost_usd: Option<f64>,
+    /// Usage as logged on this line
+    #[serde(rename = "usage", skip_serializing_if = "Option::is_none")]
+    pub(crate) logged_usage: Option<IndexedUsage>,
+    #[serde(rename = "costUsd", skip_serializing_if = "Option::is_none")]
+    pub(crate) logged_cost_usd: Option<f64>,
+    #[serde(rename = "durationMs", skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// First text block of user messages (truncated)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(rename = "toolUses", default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_uses: Vec<IndexedToolUse>,
+    #[serde(rename = "toolResults", default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_results: Vec<IndexedToolResult>,
+}
+
+/// A `summary` entry; it describes the conversation ending at `leaf_uuid`,
+/// which may live in an earlier session file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedSummary {
+    /// Raw summary text
+    pub summary: String,
+    #[serde(rename = "leafUuid", skip_serializing_if = "Option::is_none")]
+    pub leaf_uuid: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedUsage {
+    #[serde(rename = "inputTokens")]
+    pub input_tokens: u32,
+    #[serde(rename = "outputTokens")]
+    pub output_tokens: u32,
+    #[serde(rename = "cacheCreationTokens")]
+    pub cache_creation_input_tokens: u32,
+    /// Part of `cache_creation_input_tokens` written with the 1-hour TTL
+    #[serde(rename = "cacheCreation1hTokens", default)]
+    pub cache_creation_1h_input_tokens: u32,
+    #[serde(rename = "cacheReadTokens")]
+    pub cache_read_input_tokens: u32,
+    #[serde(rename = "costUsd", skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedToolUse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(rename = "filePath", skip_serializing_if = "Option::is_none")]
+    pub file_path: Option<String>,
+    /// Hash of the whole input, so repeated identical calls can be recognised
+    #[serde(rename = "inputHash", skip_serializing_if = "Option::is_none")]
+    pub input_hash: Option<u64>,
+    /// Hash of an Edit's old_string, which keys repeated edit misses
+    #[serde(rename = "oldStringHash", skip_serializing_if = "Option::is_none")]
+    pub old_string_hash: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedToolResult {
+    #[serde(rename = "toolUseId")]
+    pub tool_use_id: String,
+    #[serde(rename = "isError", default)]
+    pub is_error: bool,
+    /// Start of the result text, kept for errors only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Message ids already read from a transcript. A response is logged as one line per
+/// content block, each repeating the response's usage, so readers keep the usage of
+/// the first line of each message id only.
+#[derive(Default)]
+pub(crate) struct UsageDedup {
+    seen: HashSet<String>,
+}
+
+/// On-disk record of one session file
+#[derive(Serialize, Deserialize)]
+struct IndexFile<S> {
+    version: u32,
+    session: S,
+}
+
+/// Keys of the sessions a refresh changed
+#[derive(Debug, Default)]
+struct RefreshChanges {
+    updated: Vec<String>,
+    removed: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexStatus {
+    #[serde(rename = "sessionCount")]
+    pub session_count: usize,
+    #[serde(rename = "entryCount")]
+    pub entry_count: usize,
+    #[serde(rename = "indexPath")]
+    pub index_path: String,
+}
+
+impl TranscriptIndex {
+    fn empty() -> Self {
+        TranscriptIndex {
+            version: INDEX_VERSION,
+            sessions: HashMap::new(),
+        }
+    }
+
+    /// Sessions belonging to a project, newest file first
+    pub fn project_sessions(&self, project_id: &str) -> Vec<&IndexedSession> {
+        let mut sessions: Vec<&IndexedSession> = self
+            .sessions
+            .values()
+            .filter(|s| s.project_id == project_id)
+            .collect();
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.modified_ms));
+        sessions
+    }
+}
+
+impl IndexedEntry {
+    /// Whether any tool_use block in this entry uses one of the given tools
+    pub fn uses_any_tool(&self, names: &[&str]) -> bool {
+        self.tool_uses.iter().any(|t| names.contains(&t.name.as_str()))
+    }
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn get_index_dir() -> Result<PathBuf, String> {
+    let home_dir = dirs::home_dir().ok_or_else(|| "Could not determine home directory".to_string())?;
+    Ok(home_dir.join(".claude-dashboard").join("transcript-index"))
+}
+
+fn get_file_modified_time(metadata: &fs::Metadata) -> i64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// Hash of a JSON value, to compare tool inputs without keeping them
+fn value_hash(value: &Value) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hasher.write(value.to_string().as_bytes());
+    hasher.finish()
+}
+
+/// Record file of a session, named after the hash of its session file path
+fn session_record_path(dir: &Path, key: &str) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    hasher.write(key.as_bytes());
+    dir.join(format!("{:016x}.json", hasher.finish()))
+}
+
+/// Load the index from disk. Unreadable or outdated records are dropped and rebuilt.
+fn read_index_dir(dir: &Path) -> TranscriptIndex {
+    let mut index = TranscriptIndex::empty();
+
+    let Ok(entries) = fs::read_dir(dir) else { return index };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().map(|ext| ext != "json").unwrap_or(true) {
+            continue;
+        }
+        let record = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<IndexFile<IndexedSession>>(&content).ok());
+        match record {
+            Some(record) if record.version == INDEX_VERSION => {
+                index.sessions.insert(record.session.file_path.clone(), record.session);
+            }
+            _ => {
+                log::info!("Dropping outdated transcript index record {}", path.display());
+                let _ = fs::remove_file(&path);
+            }
+        }
+    }
+
+    index
+}
+
+/// Write one session's record atomically (temp file + rename)
+fn write_session_record(dir: &Path, session: &IndexedSession) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create index directory: {}", e))?;
+
+    let record = IndexFile { version: INDEX_VERSION, session };
+    let content = serde_json::to_string(&record).map_err(|e| format!("Failed to serialize index: {}", e))?;
+    let path = session_record_path(dir, &session.file_path);
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, content).map_err(|e| format!("Failed to write index: {}", e))?;
+    fs::rename(&tmp_path, &path).map_err(|e| format!("Failed to replace index: {}", e))?;
+
+    Ok(())
+}
+
+/// Persist the records a refresh changed
+fn write_index_changes(dir: &Path, index: &TranscriptIndex, changes: &RefreshChanges) {
+    for key in &changes.updated {
+        if let Some(session) = index.sessions.get(key) {
+            if let Err(e) = write_session_record(dir, session) {
+                log::warn!("{}", e);
+            }
+        }
+    }
+    for key in &changes.removed {
+        let _ = fs::remove_file(session_record_path(dir, key));
+    }
+}
+
+/// Hash of the blocks of `EDGE_BYTES` at the head, at evenly spaced points and just before
+/// `offset`; appending to a file leaves it unchanged. Files up to 16 blocks are hashed whole.
+fn prefix_hash(path: &Path, offset: u64) -> Option<u64> {
+    let mut file = fs::File::open(path).ok()?;
+    let mut hasher = DefaultHasher::new();
+    let mut buf = Vec::new();
+
+    let mut starts = vec![0];
+    if offset > EDGE_BYTES {
+        let span = offset - EDGE_BYTES;
+        starts.extend((1..=MIDDLE_SAMPLES + 1).map(|i| span * i / (MIDDLE_SAMPLES + 1)));
+    }
+
+    let mut end = 0;
+    for start in starts {
+        // Blocks never overlap, so a small file is read exactly once
+        let start = start.max(end);
+        let block_end = (start + EDGE_BYTES).min(offset);
+        if block_end <= start {
+            continue;
+        }
+        file.seek(SeekFrom::Start(start)).ok()?;
+        buf.clear();
+        file.by_ref().take(block_end - start).read_to_end(&mut buf).ok()?;
+        if buf.len() as u64 != block_end - start {
+            return None;
+        }
+        hasher.write(&buf);
+        end = block_end;
+    }
+
+    Some(hasher.finish())
+}
+
+/// Text of a tool_result block, whose content is a string or a list of text blocks
+fn tool_result_text(block: &Value) -> String {
+    match block.get("content") {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Array(parts)) => parts
+            .iter()
+            .filter_map(|part| part.get("text").and_then(|v| v.as_str()))
+            .collect::<Vec<&str>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// Extract the compact index record for one JSONL line
+fn index_entry_from_value(value: &Value, offset: u64) -> Option<IndexedEntry> {
+    let entry_type = value.get("type").and_then(|v| v.as_str())?;
+    let timestamp = value.get("timestamp").and_then(|v| v.as_str())?;
+    let message = value.get("message").filter(|m| m.is_object());
+
+    let usage = message.and_then(|m| m.get("usage")).filter(|u| u.is_object()).map(|u| IndexedUsage {
+        input_tokens: u.get("input_tokens").and_then(|v| v.as_u64()).unwrap_or(0) as u32,
+        output_tokens: u.get("output_tokens").and_then(|v| v.as_u64()).unwrap_or(0) as u32,
+        cache_creation_input_tokens: u.get("cache_creation_input_tokens").and_then(|v| v.as_u64()).unwrap_or(0) as u32,
+        cache_read_input_tokens: u.get("cache_read_input_tokens").and_then(|v| v.as_u64()).unwrap_or(0) as u32,
+        cache_creation_1h_input_tokens: u
+            .get("cache_creation")
+            .and_then(|c| c.get("ephemeral_1h_input_tokens"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as u32,
+        cost_usd: u.get("costUsd").and_then(|v| v.as_f64()),
+    });
+
+    let content = message.and_then(|m| m.get("content"));
+
+    let text = if entry_type == "user" {
+        match content {
+            Some(Value::String(s)) => Some(s.clone()),
+            Some(Value::Array(arr)) => arr
+                .iter()
+                .find(|block| block.get("type").and_then(|v| v.as_str()) == Some("text"))
+                .and_then(|block| block.get("text").and_then(|v| v.as_str()))
+                .map(|s| s.to_string()),
+            _ => None,
+        }
+        .map(|s| s.chars().take(MAX_INDEXED_TEXT).collect())
+    } else {
+        None
+    };
+
+    let mut tool_uses = Vec::new();
+    let mut tool_results = Vec::new();
+    if let Some(Value::Array(blocks)) = content {
+        for block in blocks {
+            if block.get("type").and_then(|v| v.as_str()) == Some("tool_result") {
+                let Some(tool_use_id) = block.get("tool_use_id").and_then(|v| v.as_str()) else { continue };
+                let is_error = block.get("is_error").and_then(|v| v.as_bool()).unwrap_or(false);
+                tool_results.push(IndexedToolResult {
+                    tool_use_id: tool_use_id.to_string(),
+                    is_error,
+                    error: if is_error { Some(tool_result_text(block).chars().take(MAX_INDEXED_ERROR).collect()) } else { None },
+                });
+                continue;
+            }
+            if block.get("type").and_then(|v| v.as_str()) != Some("tool_use") {
+                continue;
+            }
+            let input = block.get("input");
+            tool_uses.push(IndexedToolUse {
+                id: block.get("id").and_then(|v| v.as_str()).map(|s| s.to_string()),
+                name: block.get("name").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                file_path: input
+                    .and_then(|i| i.get("file_path").or_else(|| i.get("notebook_path")))
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string()),
+                input_hash: input.map(value_hash),
+                old_string_hash: input.and_then(|i| i.get("old_string")).map(value_hash),
+            });
+        }
+    }
+
+    Some(IndexedEntry {
+        offset,
+        uuid: value.get("uuid").and_then(|v| v.as_str()).map(|s| s.to_string()),
+        parent_uuid: value.get("parentUuid").and_then(|v| v.as_str()).map(|s| s.to_string()),
+        entry_type: entry_type.to_string(),
+        timestamp: timestamp.to_string(),
+        has_message: message.is_some(),
+        message_id: message.and_then(|m| m.get("id")).and_then(|v| v.as_str()).map(|s| s.to_string()),
+        model: message.and_then(|m| m.get("model")).and_then(|v| v.as_str()).map(|s| s.to_string()),
+        usage: None,
+        cost_usd: None,
+        logged_usage: usage,
+        logged_cost_usd: value.get("costUsd").and_then(|v| v.as_f64()),
+        duration_ms: value.get("durationMs").and_then(|v| v.as_u64()),
+        text,
+        tool_uses,
+        tool_results,
+    })
+}
+
+/// Ingest complete lines appended since `session.offset`.
+/// A trailing line without a newline is left for the next refresh.
+fn ingest_appended_lines(session: &mut IndexedSession, path: &Path) -> Result<(), String> {
+    let mut file = fs::File::open(path).map_err(|e| format!("Failed to open session file: {}", e))?;
+    file.seek(SeekFrom::Start(session.offset))
+        .map_err(|e| format!("Failed to seek session file: {}", e))?;
+
+    let mut reader = BufReader::new(file);
+    let mut buf: Vec<u8> = Vec::new();
+    let mut offset = session.offset;
+
+    loop {
+        buf.clear();
+        let read = reader
+            .read_until(b'\n', &mut buf)
+            .map_err(|e| format!("Error reading line: {}", e))?;
+        if read == 0 || buf.last() != Some(&b'\n') {
+            break;
+        }
+
+        let line_offset = offset;
+        offset += read as u64;
+
+        let line = String::from_utf8_lossy(&buf);
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Ok(value) = serde_json::from_str::<Value>(&line) else { continue };
+
+        if session.cwd.is_none() {
+            session.cwd = value.get("cwd").and_then(|v| v.as_str()).map(|s| s.to_string());
+        }
+        if session.git_branch.is_none() {
+            session.git_branch = value.get("gitBranch").and_then(|v| v.as_str()).map(|s| s.to_string());
+        }
+
+        if value.get("type").and_then(|v| v.as_str()) == Some("summary") {
+            if let Some(summary) = value.get("summary").and_then(|v| v.as_str()) {
+                session.summaries.push(IndexedSummary {
+                    summary: summary.to_string(),
+                    leaf_uuid: value.get("leafUuid").and_then(|v| v.as_str()).map(|s| s.to_string()),
+                });
+            }
+            continue;
+        }
+
+        if let Some(entry) = index_entry_from_value(&value, line_offset) {
+            session.entries.push(entry);
+        }
+    }
+
+    session.offset = offset;
+    Ok(())
+}
+
+/// Bring one session up to date with its file of the given size and mtime.
+/// Returns true when the session changed.
+fn refresh_session(session: &mut IndexedSession, path: &Path, size: u64, modified_ms: i64) -> Result<bool, String> {
+    if session.size == size && session.modified_ms == modified_ms && session.offset > 0 {
+        return Ok(false);
+    }
+
+    // Shrunk or rewritten in place: start over for this file
+    let rewritten = size < session.offset
+        || (size == session.size && modified_ms != session.modified_ms)
+        || (session.offset > 0 && prefix_hash(path, session.offset) != Some(session.prefix_hash));
+    if rewritten {
+        session.offset = 0;
+        session.cwd = None;
+        session.git_branch = None;
+        session.summaries.clear();
+        session.entries.clear();
+    }
+
+    ingest_appended_lines(session, path)?;
+    session.prefix_hash = prefix_hash(path, session.offset).unwrap_or(0);
+    session.size = size;
+    session.modified_ms = modified_ms;
+    Ok(true)
+}
+
+/// Bring the index up to date with the session files of every data root
+fn refresh_index(index: &mut TranscriptIndex) -> RefreshChanges {
+    let mut changes = RefreshChanges::default();
+    let mut seen: HashSet<String> = HashSet::new();
+
+    for (root, project_id, project_path) in claude_project_dirs() {
+        let Ok(session_entries) = fs::read_dir(&project_path) else { continue };
+        for session_entry in session_entries.flatten() {
+            let path = session_entry.path();
+            if !path.is_file() || !path.extension().map(|ext| ext == "jsonl").unwrap_or(false) {
+                continue;
+            }
+            let Ok(metadata) = fs::metadata(&path) else { continue };
+
+            let key = path.to_string_lossy().to_string();
+            seen.insert(key.clone());
+
+            let session = index.sessions.entry(key.clone()).or_insert_with(|| IndexedSession {
+                root: root.id.clone(),
+                host: root.host.clone(),
+                project_id: project_id.clone(),
+                session_id: path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("unknown")
+                    .to_string(),
+                file_path: key.clone(),
+                size: 0,
+                modified_ms: 0,
+                offset: 0,
+                prefix_hash: 0,
+                cwd: None,
+                git_branch: None,
+                summaries: Vec::new(),
+                entries: Vec::new(),
+            });
+
+            let mut changed = false;
+            if session.host != root.host {
+                session.host = root.host.clone();
+                changed = true;
+            }
+
+            match refresh_session(session, &path, metadata.len(), get_file_modified_time(&metadata)) {
+                Ok(refreshed) => changed |= refreshed,
+                Err(e) => log::warn!("Failed to index {}: {}", key, e),
+            }
+            if changed {
+                changes.updated.push(key);
+            }
+        }
+    }
+
+    index.sessions.retain(|key, _| {
+        let keep = seen.contains(key);
+        if !keep {
+            changes.removed.push(key.clone());
+        }
+        keep
+    });
+    changes
+}
+
+/// Attach usage and cost to the first line of each message id only. Sessions of a root are visited
+/// oldest first, so a response a resumed session repeats stays with the session that logged it first.
+fn attribute_usage(index: &mut TranscriptIndex) {
+    let order: Vec<String> = sessions_oldest_first(index.sessions.values()).iter().map(|s| s.file_path.clone()).collect();
+    let mut dedup: HashMap<String, UsageDedup> = HashMap::new();
+
+    for key in order {
+        let Some(session) = index.sessions.get_mut(&key) else { continue };
+        let dedup = dedup.entry(session.root.clone()).or_default();
+        for entry in &mut session.entries {
+            let first = dedup.counts(entry.message_id.as_deref());
+            entry.usage = entry.logged_usage.clone().filter(|_| first);
+            entry.cost_usd = entry.logged_cost_usd.filter(|_| first);
+        }
+    }
+}
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+/// Refresh the index and run `f` against it.
+/// The lock is held for the duration of `f`, so `f` must not block on other index users.
+pub fn with_index<T>(f: impl FnOnce(&TranscriptIndex) -> T) -> Result<T, String> {
+    let index_dir = get_index_dir()?;
+
+    let mut guard = INDEX.lock().map_err(|_| "Transcript index lock poisoned".to_string())?;
+    let loaded = guard.is_none();
+    let index = guard.get_or_insert_with(|| read_index_dir(&index_dir));
+
+    let changes = refresh_index(index);
+    write_index_changes(&index_dir, index, &changes);
+    if loaded || !changes.updated.is_empty() || !changes.removed.is_empty() {
+        attribute_usage(index);
+    }
+
+    Ok(f(index))
+}
+
+impl UsageDedup {
+    /// Whether a line of the message carries its usage: only the first one, and always lines without an id
+    pub(crate) fn counts(&mut self, message_id: Option<&str>) -> bool {
+        match message_id {
+            Some(id) if self.seen.contains(id) => false,
+            Some(id) => self.seen.insert(id.to_string()),
+            None => true,
+        }
+    }
+}
+
+/// Sessions oldest first, so a response repeated by a resume is counted for the session that logged it first
+pub(crate) fn sessions_oldest_first<'a>(sessions: impl Iterator<Item = &'a IndexedSession>) -> Vec<&'a IndexedSession> {
+    let mut sessions: Vec<&IndexedSession> = sessions.collect();
+    let first_timestamp = |s: &IndexedSession| s.entries.first().map(|e| e.timestamp.clone()).unwrap_or_default();
+    sessions.sort_by_cached_key(|s| (first_timestamp(s), s.file_path.clone()));
+    sessions
+}
+
+/// Read the raw JSONL lines starting at the given byte offsets
+pub fn read_lines_at(path: &Path, offsets: &[u64]) -> Vec<String> {
+    let mut lines = Vec::new();
+    let Ok(file) = fs::File::open(path) else { return lines };
+    let mut reader = BufReader::new(file);
+
+    for &offset in offsets {
+        if reader.seek(SeekFrom::Start(offset)).is_err() {
+            continue;
+        }
+        let mut buf = Vec::new();
+        if reader.by_ref().read_until(b'\n', &mut buf).is_ok() && !buf.is_empty() {
+            lines.push(String::from_utf8_lossy(&buf).trim_end().to_string());
+        }
+    }
+
+    lines
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Drop the transcript index and rebuild it from every session file
+#[tauri::command]
+pub async fn rebuild_transcript_index() -> Result<IndexStatus, String> {
+    let index_path = get_index_dir()?;
+
+    {
+        let mut guard = INDEX.lock().map_err(|_| "Transcript index lock poisoned".to_string())?;
+        if index_path.exists() {
+            fs::remove_dir_all(&index_path).map_err(|e| format!("Failed to remove index: {}", e))?;
+        }
+        *guard = Some(TranscriptIndex::empty());
+    }
+
+    with_index(|index| IndexStatus {
+        session_count: index.sessions.len(),
+        entry_count: index.sessions.values().map(|s| s.entries.len()).sum(),
+        index_path: index_path.to_string_lossy().to_string(),
+    })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::analytics_v2::indexed_entry_cost;
+
+    fn line(uuid: &str, text: &str) -> String {
+        format!(
+            "{{\"type\":\"user\",\"uuid\":\"{}\",\"timestamp\":\"2025-06-01T10:00:00Z\",\"message\":{{\"role\":\"user\",\"content\":\"{}\"}}}}\n",
+            uuid, text
+        )
+    }
+
+    fn session(path: &Path) -> IndexedSession {
+        IndexedSession {
+            root: "local".to_string(),
+            host: "local".to_string(),
+            project_id: "p".to_string(),
+            session_id: "s".to_string(),
+            file_path: path.to_string_lossy().to_string(),
+            size: 0,
+            modified_ms: 0,
+            offset: 0,
+            prefix_hash: 0,
+            cwd: None,
+            git_branch: None,
+            summaries: Vec::new(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Directory of its own for each test, removed by the test when it is done
+    fn temp_dir(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("transcript-index-test-{}-{}", std::process::id(), test));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn refresh(session: &mut IndexedSession, path: &Path, modified_ms: i64) -> bool {
+        let size = fs::metadata(path).unwrap().len();
+        refresh_session(session, path, size, modified_ms).unwrap()
+    }
+
+    fn uuids(session: &IndexedSession) -> Vec<&str> {
+        session.entries.iter().filter_map(|e| e.uuid.as_deref()).collect()
+    }
+
+    #[test]
+    fn test_refresh_ingests_only_appended_lines() {
+        let dir = temp_dir("append");
+        let path = dir.join("session.jsonl");
+        let partial = line("b", "second");
+        fs::write(&path, format!("{}{}", line("a", "first"), &partial[..20])).unwrap();
+
+        let mut indexed = session(&path);
+        assert!(refresh(&mut indexed, &path, 1));
+        // The unfinished line waits for the next refresh
+        assert_eq!(uuids(&indexed), vec!["a"]);
+        assert!(!refresh(&mut indexed, &path, 1));
+
+        fs::write(&path, format!("{}{}{}", line("a", "first"), partial, line("c", "third"))).unwrap();
+        assert!(refresh(&mut indexed, &path, 2));
+        assert_eq!(uuids(&indexed), vec!["a", "b", "c"]);
+        assert_eq!(indexed.offset, fs::metadata(&path).unwrap().len());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_refresh_restarts_after_rewrite() {
+        let dir = temp_dir("rewrite");
+        let path = dir.join("session.jsonl");
+        fs::write(&path, format!("{}{}", line("a", "first"), line("b", "second"))).unwrap();
+        let mut indexed = session(&path);
+        refresh(&mut indexed, &path, 1);
+
+        // Rewritten in place and larger than before
+        fs::write(&path, format!("{}{}{}", line("x", "replaced"), line("y", "second"), line("z", "third"))).unwrap();
+        assert!(refresh(&mut indexed, &path, 2));
+        assert_eq!(uuids(&indexed), vec!["x", "y", "z"]);
+
+        // Truncated
+        fs::write(&path, line("q", "only")).unwrap();
+        assert!(refresh(&mut indexed, &path, 3));
+        assert_eq!(uuids(&indexed), vec!["q"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_refresh_restarts_after_mid_file_rewrite() {
+        let dir = temp_dir("middle");
+        let path = dir.join("session.jsonl");
+        let lines: Vec<String> = (0..200).map(|i| line(&format!("u{:03}", i), "some prompt text")).collect();
+        fs::write(&path, lines.concat()).unwrap();
+        let mut indexed = session(&path);
+        refresh(&mut indexed, &path, 1);
+        assert!(indexed.offset > 2 * EDGE_BYTES);
+
+        // Same-length edit far from both ends, then an append
+        let mut rewritten = lines.clone();
+        rewritten[100] = line("x100", "some prompt text");
+        rewritten.push(line("u200", "appended"));
+        fs::write(&path, rewritten.concat()).unwrap();
+        assert!(refresh(&mut indexed, &path, 2));
+        assert_eq!(indexed.entries.len(), 201);
+        assert_eq!(uuids(&indexed)[100], "x100");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_multi_block_response_carries_usage_once() {
+        // One response logged as a thinking line and a text line, each with the response's usage
+        let block = |uuid: &str, kind: &str| {
+            format!(
+                "{{\"type\":\"assistant\",\"uuid\":\"{}\",\"timestamp\":\"2025-06-01T10:00:01Z\",\"message\":{{\"id\":\"msg_1\",\"role\":\"assistant\",\"model\":\"claude-sonnet-4-5-20250929\",\"content\":[{{\"type\":\"{}\"}}],\"usage\":{{\"input_tokens\":1000,\"output_tokens\":500}}}}}}\n",
+                uuid, kind
+            )
+        };
+        let dir = temp_dir("two-blocks");
+        let original = dir.join("original.jsonl");
+        fs::write(&original, format!("{}{}{}", line("a", "prompt"), block("b", "thinking"), block("c", "text"))).unwrap();
+        // A resumed session repeats the response, and an imported copy lives in another root
+        let resumed = dir.join("resumed.jsonl");
+        fs::write(&resumed, format!("{}{}", block("b", "thinking"), line("d", "next prompt"))).unwrap();
+
+        let mut index = TranscriptIndex::empty();
+        for (path, root) in [(&resumed, "local"), (&original, "local"), (&original, "archive")] {
+            let mut indexed = session(path);
+            indexed.root = root.to_string();
+            indexed.file_path = format!("{}/{}", root, indexed.file_path);
+            refresh(&mut indexed, path, 1);
+            index.sessions.insert(indexed.file_path.clone(), indexed);
+        }
+        attribute_usage(&mut index);
+
+        let priced = |key: String| -> Vec<f64> { index.sessions[&key].entries.iter().map(indexed_entry_cost).collect() };
+        let original_key = |root: &str| format!("{}/{}", root, original.to_string_lossy());
+        let cost = priced(original_key("local"));
+        assert!(cost[1] > 0.0);
+        assert_eq!(cost[2], 0.0);
+        assert!(index.sessions[&original_key("local")].entries[2].usage.is_none());
+        assert_eq!(priced(format!("local/{}", resumed.to_string_lossy())), vec![0.0, 0.0]);
+        assert_eq!(priced(original_key("archive")), cost);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_corrupt_record_is_dropped_and_rebuilt() {
+        let dir = temp_dir("records");
+        let records = dir.join("records");
+        let path = dir.join("session.jsonl");
+        fs::write(&path, line("a", "first")).unwrap();
+
+        let mut indexed = session(&path);
+        refresh(&mut indexed, &path, 1);
+        write_session_record(&records, &indexed).unwrap();
+        let record_path = session_record_path(&records, &indexed.file_path);
+        assert_eq!(read_index_dir(&records).sessions.len(), 1);
+
+        fs::write(&record_path, "{\"session\": {\"truncated").unwrap();
+        let index = read_index_dir(&records);
+        assert!(index.sessions.is_empty());
+        assert!(!record_path.exists());
+
+        // The next refresh starts the session from scratch
+        let mut rebuilt = session(&path);
+        assert!(refresh(&mut rebuilt, &path, 1));
+        assert_eq!(uuids(&rebuilt), vec!["a"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
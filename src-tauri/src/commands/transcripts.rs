@@ -8,12 +8,24 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use tokio::io::{AsyncBufReadExt, BufReader};
 use uuid::Uuid;
 
+use super::analytics_v2::indexed_entry_cost;
+use super::conversation_tree::{branch_members, TreeEntry};
+use super::conversations::{attribute_summaries, group_sessions};
+use super::data_roots::{ensure_writable, resolve_qualified_id};
+use super::pricing::{calculate_cost, TokenCounts};
+use super::session_health::{classify, health_thresholds, session_status, HealthMetrics, HealthThresholds};
+use super::transcript_index::{with_index, IndexedEntry, IndexedSession, UsageDedup};
+use super::sources::{
+    claude_project_dir, claude_project_dirs, codex_session_files, find_source, list_codex_session_files, SessionTranscript,
+    CODEX_SOURCE,
+};
+
 // ============================================================================
 // Constants
 // ============================================================================
@@ -34,6 +46,11 @@ pub struct Project {
     pub session_count: usize,
     #[serde(rename = "lastModified")]
     pub last_modified: i64, // Unix timestamp in ms
+    /// Data root the project was read from
+    #[serde(default)]
+    pub root: String,
+    #[serde(default)]
+    pub host: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,6 +66,11 @@ pub struct Session {
     pub message_count: usize,
     pub summary: String,
     pub stats: Option<SessionStats>,
+    /// Data root the session was read from
+    #[serde(default)]
+    pub root: String,
+    #[serde(default)]
+    pub host: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -127,6 +149,9 @@ pub struct TokenUsage {
     pub output_tokens: u32,
     #[serde(rename = "cacheCreationTokens")]
     pub cache_creation_tokens: u32,
+    /// Part of `cache_creation_tokens` written with the 1-hour TTL
+    #[serde(rename = "cacheCreation1hTokens", default)]
+    pub cache_creation_1h_tokens: u32,
     #[serde(rename = "cacheReadTokens")]
     pub cache_read_tokens: u32,
     #[serde(rename = "totalTokens")]
@@ -135,6 +160,20 @@ pub struct TokenUsage {
     pub cost_usd: f64,
 }
 
+impl TokenUsage {
+    /// Token counts for pricing, with cache writes split by TTL
+    pub fn token_counts(&self) -> TokenCounts {
+        let cache_write_1h = self.cache_creation_1h_tokens.min(self.cache_creation_tokens);
+        TokenCounts {
+            input: self.input_tokens as u64,
+            output: self.output_tokens as u64,
+            cache_read: self.cache_read_tokens as u64,
+            cache_write_5m: (self.cache_creation_tokens - cache_write_1h) as u64,
+            cache_write_1h: cache_write_1h as u64,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionDetails {
     pub id: String,
@@ -148,6 +187,8 @@ pub struct SessionDetails {
     pub message_count: usize,
     pub summary: String,
     pub stats: Option<SessionStats>,
+    pub root: String,
+    pub host: String,
     pub messages: Vec<Message>,
 }
 
@@ -203,8 +244,8 @@ struct RawSummaryEntry {
     #[serde(rename = "type")]
     entry_type: String,
     summary: String,
-    #[serde(rename = "leafUuid")]
-    leaf_uuid: String,
+    #[serde(rename = "leafUuid", default)]
+    leaf_uuid: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -253,16 +294,6 @@ struct RawUsage {
 // Helper Functions
 // ============================================================================
 
-fn get_projects_dir() -> Result<PathBuf, String> {
-    let home_dir = dirs::home_dir().ok_or_else(|| "Could not determine home directory".to_string())?;
-    Ok(home_dir.join(".claude").join("projects"))
-}
-
-fn get_codex_sessions_dir() -> Result<PathBuf, String> {
-    let home_dir = dirs::home_dir().ok_or_else(|| "Could not determine home directory".to_string())?;
-    Ok(home_dir.join(".codex").join("sessions"))
-}
-
 fn encode_project_id(path: &str) -> String {
     let mut out = String::with_capacity(path.len());
     for b in path.as_bytes() {
@@ -279,13 +310,6 @@ fn encode_project_id(path: &str) -> String {
     out
 }
 
-fn parse_project_id(path: &Path) -> String {
-    path.file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("unknown")
-        .to_string()
-}
-
 /// Resolve a human-readable display name for a project.
 ///
 /// Resolution order (strict, stop at first match):
@@ -326,7 +350,7 @@ fn resolve_project_name(project_dir: &Path) -> String {
 
 /// Find the actual project working directory from session files.
 /// Reads the first .jsonl file and extracts the "cwd" field.
-fn find_project_cwd(project_dir: &Path) -> Option<PathBuf> {
+pub(crate) fn find_project_cwd(project_dir: &Path) -> Option<PathBuf> {
     let entries = fs::read_dir(project_dir).ok()?;
 
     for entry in entries.flatten() {
@@ -424,30 +448,14 @@ fn package_name(cwd: &Path) -> Option<String> {
 // Codex Session Parsing
 // ============================================================================
 
-#[derive(Debug, Clone)]
-struct CodexSessionMeta {
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CodexSessionMeta {
     id: Option<String>,
     cwd: Option<String>,
     timestamp: Option<String>,
     git_branch: Option<String>,
 }
 
-fn list_codex_session_files(root: &Path) -> Vec<PathBuf> {
-    let mut files = Vec::new();
-    let Ok(entries) = fs::read_dir(root) else { return files };
-
-    for entry in entries.flatten() {
-        let path = entry.path();
-        if path.is_dir() {
-            files.extend(list_codex_session_files(&path));
-        } else if path.extension().map(|e| e == "jsonl").unwrap_or(false) {
-            files.push(path);
-        }
-    }
-
-    files
-}
-
 fn resolve_project_name_from_cwd(cwd: &str) -> String {
     let path = PathBuf::from(cwd);
     if let Some(name) = git_repo_name(&path) {
@@ -529,6 +537,23 @@ fn codex_tool_use_block(name: &str, call_id: &str, args: Option<&str>) -> Value
     })
 }
 
+/// tool_use block of a Codex `function_call` (JSON arguments) or `custom_tool_call`
+/// (free-form input, e.g. an apply_patch envelope)
+pub(crate) fn codex_tool_call_block(payload: &Value) -> Option<Value> {
+    let name = payload.get("name").and_then(|v| v.as_str()).unwrap_or("tool");
+    let call_id = payload.get("call_id").and_then(|v| v.as_str()).unwrap_or("");
+    match payload.get("type").and_then(|v| v.as_str()) {
+        Some("function_call") => Some(codex_tool_use_block(name, call_id, payload.get("arguments").and_then(|v| v.as_str()))),
+        Some("custom_tool_call") => Some(serde_json::json!({
+            "type": "tool_use",
+            "id": call_id,
+            "name": name,
+            "input": { "raw": payload.get("input").and_then(|v| v.as_str()).unwrap_or("") }
+        })),
+        _ => None,
+    }
+}
+
 fn codex_tool_result_block(call_id: &str, output: &str) -> Value {
     serde_json::json!({
         "type": "tool_result",
@@ -544,55 +569,73 @@ fn codex_thinking_block(summary: &str) -> Value {
     })
 }
 
-async fn read_codex_session_data(session_path: &Path) -> Result<(Vec<Message>, SessionStats, String, CodexSessionMeta), String> {
-    let file = tokio::fs::File::open(session_path)
-        .await
-        .map_err(|e| format!("Failed to open session file: {}", e))?;
-
-    let reader = BufReader::new(file);
-    let mut lines = reader.lines();
-    let mut messages: Vec<Message> = Vec::new();
-    let mut meta = CodexSessionMeta { id: None, cwd: None, timestamp: None, git_branch: None };
-
-    let mut prompt_count = 0;
-    let mut tool_call_count = 0;
-    let mut tool_counts: HashMap<String, usize> = HashMap::new();
-    let mut first_timestamp: Option<DateTime<Utc>> = None;
-    let mut last_timestamp: Option<DateTime<Utc>> = None;
-    let mut summary: Option<String> = None;
+/// Messages and running stats of a Codex rollout, fed one line at a time in file order
+#[derive(Default)]
+pub(crate) struct CodexRolloutParser {
+    messages: Vec<Message>,
+    meta: CodexSessionMeta,
+    prompt_count: usize,
+    tool_call_count: usize,
+    tool_counts: HashMap<String, usize>,
+    first_timestamp: Option<DateTime<Utc>>,
+    last_timestamp: Option<DateTime<Utc>>,
+    summary: Option<String>,
+    current_model: Option<String>,
+    total_cost: f64,
+}
 
-    while let Some(line) = lines
-        .next_line()
-        .await
-        .map_err(|e| format!("Error reading line: {}", e))?
-    {
+impl CodexRolloutParser {
+    fn push_line(&mut self, line: &str) {
         if line.trim().is_empty() {
-            continue;
+            return;
         }
 
-        let value: Value = match serde_json::from_str(&line) {
+        let value: Value = match serde_json::from_str(line) {
             Ok(v) => v,
-            Err(_) => continue,
+            Err(_) => return,
         };
 
         let entry_type = value.get("type").and_then(|v| v.as_str()).unwrap_or("");
         let timestamp = value.get("timestamp").and_then(|v| v.as_str()).map(|s| s.to_string());
 
+        if entry_type == "turn_context" {
+            if let Some(model) = value.get("payload").and_then(|p| p.get("model")).and_then(|v| v.as_str()) {
+                self.current_model = Some(model.to_string());
+            }
+            return;
+        }
+
+        if entry_type == "event_msg" {
+            let payload = value.get("payload").cloned().unwrap_or(Value::Null);
+            if payload.get("type").and_then(|v| v.as_str()) == Some("token_count") {
+                let last = payload.get("info").and_then(|i| i.get("last_token_usage")).cloned().unwrap_or(Value::Null);
+                let input = last.get("input_tokens").and_then(|v| v.as_u64()).unwrap_or(0);
+                let cached = last.get("cached_input_tokens").and_then(|v| v.as_u64()).unwrap_or(0);
+                let output = last.get("output_tokens").and_then(|v| v.as_u64()).unwrap_or(0);
+                self.total_cost += calculate_cost(
+                    self.current_model.as_deref().unwrap_or("codex"),
+                    timestamp.as_deref(),
+                    &TokenCounts { input: input.saturating_sub(cached), output, cache_read: cached, ..Default::default() },
+                );
+            }
+            return;
+        }
+
         if entry_type == "session_meta" {
             let payload = value.get("payload").cloned().unwrap_or(Value::Null);
-            meta.id = payload.get("id").and_then(|v| v.as_str()).map(|s| s.to_string());
-            meta.cwd = payload.get("cwd").and_then(|v| v.as_str()).map(|s| s.to_string());
-            meta.timestamp = payload.get("timestamp").and_then(|v| v.as_str()).map(|s| s.to_string());
-            meta.git_branch = payload
+            self.meta.id = payload.get("id").and_then(|v| v.as_str()).map(|s| s.to_string());
+            self.meta.cwd = payload.get("cwd").and_then(|v| v.as_str()).map(|s| s.to_string());
+            self.meta.timestamp = payload.get("timestamp").and_then(|v| v.as_str()).map(|s| s.to_string());
+            self.meta.git_branch = payload
                 .get("git")
                 .and_then(|g| g.get("branch"))
                 .and_then(|v| v.as_str())
                 .map(|s| s.to_string());
-            continue;
+            return;
         }
 
         if entry_type != "response_item" {
-            continue;
+            return;
         }
 
         let payload = value.get("payload").cloned().unwrap_or(Value::Null);
@@ -601,11 +644,11 @@ async fn read_codex_session_data(session_path: &Path) -> Result<(Vec<Message>, S
         let timestamp_str = timestamp.clone().unwrap_or_default();
         if let Ok(ts) = DateTime::parse_from_rfc3339(&timestamp_str) {
             let ts_utc = ts.with_timezone(&Utc);
-            if first_timestamp.is_none() || ts_utc < first_timestamp.unwrap() {
-                first_timestamp = Some(ts_utc);
+            if self.first_timestamp.is_none() || ts_utc < self.first_timestamp.unwrap() {
+                self.first_timestamp = Some(ts_utc);
             }
-            if last_timestamp.is_none() || ts_utc > last_timestamp.unwrap() {
-                last_timestamp = Some(ts_utc);
+            if self.last_timestamp.is_none() || ts_utc > self.last_timestamp.unwrap() {
+                self.last_timestamp = Some(ts_utc);
             }
         }
 
@@ -615,17 +658,17 @@ async fn read_codex_session_data(session_path: &Path) -> Result<(Vec<Message>, S
                 let content = payload.get("content").cloned().unwrap_or(Value::Null);
                 let blocks = codex_text_blocks_from_content(role, &content);
                 if blocks.is_empty() {
-                    continue;
+                    return;
                 }
 
                 if role == "user" {
-                    prompt_count += 1;
-                    if summary.is_none() {
+                    self.prompt_count += 1;
+                    if self.summary.is_none() {
                         if let Some(first) = blocks.first() {
                             if let Some(text) = first.get("text").and_then(|v| v.as_str()) {
                                 let cleaned = clean_summary(text);
                                 if !cleaned.is_empty() && !is_garbage_summary(&cleaned) {
-                                    summary = Some(cleaned);
+                                    self.summary = Some(cleaned);
                                 }
                             }
                         }
@@ -633,7 +676,7 @@ async fn read_codex_session_data(session_path: &Path) -> Result<(Vec<Message>, S
                 }
 
                 let msg_type = if role == "user" { "user" } else { "assistant" };
-                messages.push(Message {
+                self.messages.push(Message {
                     uuid: Uuid::new_v4().to_string(),
                     msg_type: msg_type.to_string(),
                     timestamp: timestamp.unwrap_or_default(),
@@ -642,16 +685,15 @@ async fn read_codex_session_data(session_path: &Path) -> Result<(Vec<Message>, S
                     usage: None,
                 });
             }
-            "function_call" => {
+            "function_call" | "custom_tool_call" => {
+                let Some(block) = codex_tool_call_block(&payload) else { return };
                 let name = payload.get("name").and_then(|v| v.as_str()).unwrap_or("tool");
                 let call_id = payload.get("call_id").and_then(|v| v.as_str()).unwrap_or("");
-                let args = payload.get("arguments").and_then(|v| v.as_str());
 
-                tool_call_count += 1;
-                *tool_counts.entry(name.to_string()).or_insert(0) += 1;
+                self.tool_call_count += 1;
+                *self.tool_counts.entry(name.to_string()).or_insert(0) += 1;
 
-                let block = codex_tool_use_block(name, call_id, args);
-                messages.push(Message {
+                self.messages.push(Message {
                     uuid: if !call_id.is_empty() { call_id.to_string() } else { Uuid::new_v4().to_string() },
                     msg_type: "tool_use".to_string(),
                     timestamp: timestamp.unwrap_or_default(),
@@ -660,11 +702,11 @@ async fn read_codex_session_data(session_path: &Path) -> Result<(Vec<Message>, S
                     usage: None,
                 });
             }
-            "function_call_output" => {
+            "function_call_output" | "custom_tool_call_output" => {
                 let call_id = payload.get("call_id").and_then(|v| v.as_str()).unwrap_or("");
                 let output = payload.get("output").and_then(|v| v.as_str()).unwrap_or("");
                 let block = codex_tool_result_block(call_id, output);
-                messages.push(Message {
+                self.messages.push(Message {
                     uuid: Uuid::new_v4().to_string(),
                     msg_type: "tool_result".to_string(),
                     timestamp: timestamp.unwrap_or_default(),
@@ -687,7 +729,7 @@ async fn read_codex_session_data(session_path: &Path) -> Result<(Vec<Message>, S
 
                 if !summary_text.is_empty() {
                     let block = codex_thinking_block(&summary_text);
-                    messages.push(Message {
+                    self.messages.push(Message {
                         uuid: Uuid::new_v4().to_string(),
                         msg_type: "assistant".to_string(),
                         timestamp: timestamp.unwrap_or_default(),
@@ -701,40 +743,105 @@ async fn read_codex_session_data(session_path: &Path) -> Result<(Vec<Message>, S
         }
     }
 
-    let message_count = messages.len();
-    let total_pages = (message_count + MESSAGES_PER_PAGE - 1) / MESSAGES_PER_PAGE;
-    let start_timestamp = first_timestamp.map(|ts| ts.to_rfc3339());
-    let end_timestamp = last_timestamp.map(|ts| ts.to_rfc3339());
+    fn stats(&self) -> SessionStats {
+        let message_count = self.messages.len();
+        let total_pages = (message_count + MESSAGES_PER_PAGE - 1) / MESSAGES_PER_PAGE;
 
-    let duration_ms = if let (Some(first), Some(last)) = (first_timestamp, last_timestamp) {
-        last.signed_duration_since(first).num_milliseconds().max(0) as u64
-    } else {
-        0
-    };
+        let duration_ms = if let (Some(first), Some(last)) = (self.first_timestamp, self.last_timestamp) {
+            last.signed_duration_since(first).num_milliseconds().max(0) as u64
+        } else {
+            0
+        };
 
-    let stats = SessionStats {
-        prompt_count,
-        message_count,
-        tool_call_count,
-        total_cost_usd: 0.0,
-        total_pages,
-        duration_ms,
-        start_timestamp,
-        end_timestamp,
-        git_branch: meta.git_branch.clone(),
-        health: None,
-        tool_breakdown: Some(tool_counts),
-        tech_stack: None,
-    };
+        SessionStats {
+            prompt_count: self.prompt_count,
+            message_count,
+            tool_call_count: self.tool_call_count,
+            total_cost_usd: self.total_cost,
+            total_pages,
+            duration_ms,
+            start_timestamp: self.first_timestamp.map(|ts| ts.to_rfc3339()),
+            end_timestamp: self.last_timestamp.map(|ts| ts.to_rfc3339()),
+            git_branch: self.meta.git_branch.clone(),
+            health: None,
+            tool_breakdown: Some(self.tool_counts.clone()),
+            tech_stack: None,
+        }
+    }
+}
+
+pub(crate) async fn read_codex_session_data(session_path: &Path) -> Result<(Vec<Message>, SessionStats, String, CodexSessionMeta), String> {
+    let file = tokio::fs::File::open(session_path)
+        .await
+        .map_err(|e| format!("Failed to open session file: {}", e))?;
+
+    let reader = BufReader::new(file);
+    let mut lines = reader.lines();
+    let mut parser = CodexRolloutParser::default();
+
+    while let Some(line) = lines
+        .next_line()
+        .await
+        .map_err(|e| format!("Error reading line: {}", e))?
+    {
+        parser.push_line(&line);
+    }
+
+    let stats = parser.stats();
+    let summary_text = parser.summary.unwrap_or_else(|| "Untitled Session".to_string());
+
+    Ok((parser.messages, stats, summary_text, parser.meta))
+}
+
+/// Messages of a Codex rollout in file order, each with the byte offset of the line it came from,
+/// and the session summary
+pub(crate) fn read_codex_message_lines(session_path: &Path) -> Result<(Vec<(u64, Message)>, String), String> {
+    use std::io::BufRead;
+
+    let file = fs::File::open(session_path).map_err(|e| format!("Failed to open session file: {}", e))?;
+    let mut reader = std::io::BufReader::new(file);
+    let mut parser = CodexRolloutParser::default();
+    let mut offsets: Vec<u64> = Vec::new();
+    let mut offset = 0u64;
+    let mut buf: Vec<u8> = Vec::new();
+
+    loop {
+        buf.clear();
+        let read = reader
+            .read_until(b'\n', &mut buf)
+            .map_err(|e| format!("Error reading line: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        parser.push_line(&String::from_utf8_lossy(&buf));
+        offsets.resize(parser.messages.len(), offset);
+        offset += read as u64;
+    }
 
-    let summary_text = summary.unwrap_or_else(|| "Untitled Session".to_string());
+    let summary = parser.summary.unwrap_or_else(|| "Untitled Session".to_string());
+    Ok((offsets.into_iter().zip(parser.messages).collect(), summary))
+}
+
+/// Message of one rollout line, as `read_codex_message_lines` builds it
+pub(crate) fn codex_line_message(line: &str) -> Option<Message> {
+    let mut parser = CodexRolloutParser::default();
+    parser.push_line(line);
+    parser.messages.pop()
+}
 
-    Ok((messages, stats, summary_text, meta))
+/// (unqualified project id, session id, cwd) of a Codex rollout
+pub(crate) fn codex_session_identity(session_path: &Path) -> Option<(String, String, String)> {
+    let meta = read_codex_session_meta(session_path).ok()?;
+    let cwd = meta.cwd?;
+    Some((encode_project_id(&cwd), meta.id?, cwd))
 }
 
-fn find_codex_session_file(project_id: &str, session_id: &str) -> Result<PathBuf, String> {
-    let root = get_codex_sessions_dir()?;
-    let files = list_codex_session_files(&root);
+pub(crate) fn find_codex_session_file(project_id: &str, session_id: &str) -> Result<PathBuf, String> {
+    let (root, project_id) = resolve_qualified_id(project_id)?;
+    let Some(sessions_dir) = root.codex_sessions_dir() else {
+        return Err("Session not found".to_string());
+    };
+    let files = list_codex_session_files(&sessions_dir);
 
     for file in files {
         if let Ok(meta) = read_codex_session_meta(&file) {
@@ -758,11 +865,7 @@ fn find_codex_session_file(project_id: &str, session_id: &str) -> Result<PathBuf
     Err("Session not found".to_string())
 }
 
-fn parse_session_id(filename: &str) -> String {
-    filename.trim_end_matches(".jsonl").to_string()
-}
-
-fn get_file_modified_time(path: &Path) -> i64 {
+pub(crate) fn get_file_modified_time(path: &Path) -> i64 {
     fs::metadata(path)
         .and_then(|m| m.modified())
         .ok()
@@ -855,9 +958,10 @@ async fn read_session_data(session_path: &Path) -> Result<(Vec<RawLogEntry>, Str
     let reader = BufReader::new(file);
     let mut lines = reader.lines();
     let mut entries = Vec::new();
-    let mut summary = String::new();
+    let mut summaries: Vec<RawSummaryEntry> = Vec::new();
     let mut best_user_message = String::new();
     let mut best_score = 0;
+    let mut dedup = UsageDedup::default();
 
     while let Some(line) = lines
         .next_line()
@@ -872,10 +976,7 @@ async fn read_session_data(session_path: &Path) -> Result<(Vec<RawLogEntry>, Str
         if let Ok(entry) = serde_json::from_str::<RawEntry>(&line) {
             match entry {
                 RawEntry::Summary(s) => {
-                    // Only use summary if it's not garbage
-                    if !is_garbage_summary(&s.summary) {
-                        summary = clean_summary(&s.summary);
-                    }
+                    summaries.push(s);
                     continue;
                 }
                 RawEntry::Other => {}
@@ -884,7 +985,8 @@ async fn read_session_data(session_path: &Path) -> Result<(Vec<RawLogEntry>, Str
 
         // Try to parse as RawLogEntry (resilient)
         match serde_json::from_str::<RawLogEntry>(&line) {
-            Ok(log_entry) => {
+            Ok(mut log_entry) => {
+                keep_first_usage(&mut log_entry, &mut dedup);
                 // Collect user messages and find the best one
                 if log_entry.entry_type == "user" {
                     if let Some(message) = &log_entry.message {
@@ -923,19 +1025,153 @@ async fn read_session_data(session_path: &Path) -> Result<(Vec<RawLogEntry>, Str
         }
     }
 
-    // Try to generate a better title from files changed
-    let file_title = generate_title_from_files(&entries);
+    // A summary describes the conversation ending at its leaf; resumed sessions
+    // carry summaries of earlier files, which must not title this one
+    let uuids: HashSet<&str> = entries.iter().filter_map(|e| e.uuid.as_deref()).collect();
+    let summary = summaries
+        .iter()
+        .rev()
+        .filter(|s| s.leaf_uuid.as_deref().map_or(true, |leaf| uuids.contains(leaf)))
+        .find(|s| !is_garbage_summary(&s.summary))
+        .map(|s| clean_summary(&s.summary))
+        .unwrap_or_default();
+    let summary = pick_session_summary(summary, best_user_message, &entries);
+
+    Ok((entries, summary))
+}
+
+/// Choose the session title.
+/// Priority: 1) Good summary, 2) File-based title, 3) Best user message
+fn pick_session_summary(summary: String, best_user_message: String, entries: &[RawLogEntry]) -> String {
+    if !summary.is_empty() && !is_garbage_summary(&summary) {
+        return summary;
+    }
+
+    if let Some(file_title) = generate_title_from_files(entries) {
+        file_title
+    } else if !best_user_message.is_empty() {
+        best_user_message
+    } else {
+        summary
+    }
+}
+
+/// Rebuild the fields of a log entry that stats and summaries rely on from the transcript index
+fn raw_entry_from_index(entry: &IndexedEntry, git_branch: Option<&str>) -> RawLogEntry {
+    let message = if entry.has_message {
+        let mut content: Vec<Value> = Vec::new();
+        if let Some(text) = &entry.text {
+            content.push(serde_json::json!({ "type": "text", "text": text }));
+        }
+        // Inputs are only kept as hashes: equal inputs stay equal, which is what loop detection compares
+        for tool in &entry.tool_uses {
+            content.push(serde_json::json!({
+                "type": "tool_use",
+                "id": tool.id,
+                "name": tool.name,
+                "input": {
+                    "file_path": tool.file_path,
+                    "old_string": tool.old_string_hash.map(|h| format!("{:016x}", h)),
+                    "inputHash": tool.input_hash,
+                },
+            }));
+        }
+        for result in &entry.tool_results {
+            content.push(serde_json::json!({
+                "type": "tool_result",
+                "tool_use_id": result.tool_use_id,
+                "is_error": result.is_error,
+                "content": result.error.clone().unwrap_or_default(),
+            }));
+        }
+
+        Some(RawMessage {
+            role: entry.entry_type.clone(),
+            content: Value::Array(content),
+            model: entry.model.clone(),
+            usage: entry.usage.as_ref().map(|u| RawUsage {
+                input_tokens: u.input_tokens,
+                output_tokens: u.output_tokens,
+                cache_creation_input_tokens: u.cache_creation_input_tokens,
+                cache_read_input_tokens: u.cache_read_input_tokens,
+                cost_usd: u.cost_usd,
+                _extra: serde_json::json!({
+                    "cache_creation": { "ephemeral_1h_input_tokens": u.cache_creation_1h_input_tokens },
+                }),
+            }),
+            _extra: serde_json::json!({ "id": entry.message_id }),
+        })
+    } else {
+        None
+    };
+
+    RawLogEntry {
+        parent_uuid: entry.parent_uuid.clone(),
+        uuid: entry.uuid.clone(),
+        entry_type: entry.entry_type.clone(),
+        message,
+        timestamp: entry.timestamp.clone(),
+        cost_usd: entry.cost_usd,
+        duration_ms: entry.duration_ms,
+        _extra: serde_json::json!({ "gitBranch": git_branch }),
+    }
+}
+
+/// Build a listing row from the index records of one session file, or of several
+/// files read as one conversation (first file's id). `summaries` are the summary
+/// texts attributed to these files, in file order.
+fn session_from_index(files: &[&IndexedSession], summaries: &[String], thresholds: &HealthThresholds) -> Session {
+    let first = files[0];
+    let last = files[files.len() - 1];
+
+    // Resumed sessions may repeat earlier entries; keep the first copy of each uuid
+    let mut seen: HashSet<&str> = HashSet::new();
+    let indexed_entries: Vec<&IndexedEntry> = files
+        .iter()
+        .flat_map(|f| f.entries.iter())
+        .filter(|e| e.uuid.as_deref().map_or(true, |u| seen.insert(u)))
+        .collect();
+
+    let entries: Vec<RawLogEntry> = indexed_entries
+        .iter()
+        .map(|e| raw_entry_from_index(e, first.git_branch.as_deref()))
+        .collect();
+
+    let summary = summaries
+        .iter()
+        .rev()
+        .find(|s| !is_garbage_summary(s))
+        .map(|s| clean_summary(s))
+        .unwrap_or_default();
 
-    // Priority: 1) Good summary, 2) File-based title, 3) Best user message
-    if summary.is_empty() || is_garbage_summary(&summary) {
-        if let Some(ft) = file_title {
-            summary = ft;
-        } else if !best_user_message.is_empty() {
-            summary = best_user_message;
+    let mut best_user_message = String::new();
+    let mut best_score = 0;
+    for text in indexed_entries.iter().filter(|e| e.entry_type == "user").filter_map(|e| e.text.as_ref()) {
+        let cleaned = clean_summary(text);
+        if !cleaned.is_empty() && !is_garbage_summary(&cleaned) {
+            let score = score_user_message(&cleaned);
+            if score > best_score {
+                best_score = score;
+                best_user_message = cleaned;
+            }
         }
     }
 
-    Ok((entries, summary))
+    let summary = pick_session_summary(summary, best_user_message, &entries);
+    let (total_cost, _, total_tokens) = sum_session_metrics(&entries);
+    let stats = calculate_session_stats(&entries, total_cost, total_tokens, thresholds);
+
+    Session {
+        id: first.session_id.clone(),
+        project_id: first.project_id.clone(),
+        file_path: first.file_path.clone(),
+        last_modified: files.iter().map(|f| f.modified_ms).max().unwrap_or(last.modified_ms),
+        message_count: stats.message_count,
+        summary,
+        stats: Some(stats),
+        root: first.root.clone(),
+        host: first.host.clone(),
+    }
 }
 
 /// Score a user message for how descriptive it is
@@ -983,9 +1219,9 @@ fn generate_title_from_files(entries: &[RawLogEntry]) -> Option<String> {
 
                     let tool_name = block.get("name").and_then(|n| n.as_str()).unwrap_or("");
 
-                    if tool_name == "Edit" || tool_name == "Write" {
+                    if matches!(tool_name, "Edit" | "Write" | "MultiEdit" | "NotebookEdit") {
                         if let Some(input) = block.get("input") {
-                            if let Some(file_path) = input.get("file_path").and_then(|p| p.as_str()) {
+                            if let Some(file_path) = input.get("file_path").or(input.get("notebook_path")).and_then(|p| p.as_str()) {
                                 // Extract just the filename
                                 let filename = file_path.split('/').last().unwrap_or(file_path);
                                 if !files.contains(&filename.to_string()) && files.len() < 5 {
@@ -1020,6 +1256,15 @@ async fn read_session_entries(session_path: &Path) -> Result<Vec<RawLogEntry>, S
     Ok(entries)
 }
 
+/// Drop the usage and cost of a response's later lines; each content block line repeats them
+fn keep_first_usage(entry: &mut RawLogEntry, dedup: &mut UsageDedup) {
+    let Some(message) = &mut entry.message else { return };
+    if !dedup.counts(message._extra.get("id").and_then(|v| v.as_str())) {
+        message.usage = None;
+        entry.cost_usd = None;
+    }
+}
+
 fn transform_message(entry: &RawLogEntry) -> Message {
     let content = if let Some(message) = &entry.message {
         match &message.content {
@@ -1037,6 +1282,7 @@ fn transform_message(entry: &RawLogEntry) -> Message {
         input_tokens: u.input_tokens,
         output_tokens: u.output_tokens,
         cache_creation_tokens: u.cache_creation_input_tokens,
+        cache_creation_1h_tokens: token_counts_from_usage(u).cache_write_1h as u32,
         cache_read_tokens: u.cache_read_input_tokens,
         total_tokens: u.input_tokens + u.output_tokens,
         cost_usd: entry.cost_usd.unwrap_or(0.0),
@@ -1087,44 +1333,86 @@ fn transform_hook_message(entry: &RawLogEntry) -> Option<Message> {
     None
 }
 
-fn sum_session_metrics(entries: &[RawLogEntry]) -> (f64, u64, u64) {
-    let mut total_cost = 0.0;
-    let mut total_duration = 0;
-    let mut total_tokens = 0;
-
-    for entry in entries {
-        let mut entry_cost = 0.0;
-        let mut has_cost = false;
-
-        if let Some(cost) = entry.cost_usd {
-            entry_cost = cost;
-            has_cost = true;
-        } else if let Some(message) = &entry.message {
-            if let Some(usage) = &message.usage {
-                if let Some(cost) = usage.cost_usd {
-                    entry_cost = cost;
-                    has_cost = true;
-                }
+/// First text of a message, or the names of the tools it calls, for tree previews
+fn entry_preview(entry: &RawLogEntry) -> Option<String> {
+    let message = entry.message.as_ref()?;
+    match &message.content {
+        Value::String(s) => Some(s.clone()),
+        Value::Array(blocks) => {
+            let text = blocks
+                .iter()
+                .find(|b| b.get("type").and_then(|t| t.as_str()) == Some("text"))
+                .and_then(|b| b.get("text").and_then(|t| t.as_str()));
+            if let Some(text) = text {
+                return Some(text.to_string());
             }
+            let tools: Vec<&str> = blocks
+                .iter()
+                .filter(|b| b.get("type").and_then(|t| t.as_str()) == Some("tool_use"))
+                .filter_map(|b| b.get("name").and_then(|n| n.as_str()))
+                .collect();
+            (!tools.is_empty()).then(|| tools.join(", "))
         }
+        _ => None,
+    }
+}
 
-        // Fallback to manual calculation if cost is not provided in the log
-        if !has_cost {
-            if let Some(message) = &entry.message {
-                if let Some(usage) = &message.usage {
-                    if let Some(model) = &message.model {
-                        entry_cost = calculate_manual_cost(model, usage);
-                    }
-                }
+fn tree_entry(entry: &RawLogEntry) -> TreeEntry {
+    TreeEntry {
+        uuid: entry.uuid.clone(),
+        parent_uuid: entry.parent_uuid.clone(),
+        logical_parent_uuid: entry._extra.get("logicalParentUuid").and_then(|v| v.as_str()).map(String::from),
+        entry_type: entry.entry_type.clone(),
+        timestamp: entry.timestamp.clone(),
+        is_sidechain: entry._extra.get("isSidechain").and_then(|v| v.as_bool()).unwrap_or(false),
+        is_tool_result: entry.message.as_ref().is_some_and(|m| match &m.content {
+            Value::Array(blocks) => {
+                !blocks.is_empty() && blocks.iter().all(|b| b.get("type").and_then(|t| t.as_str()) == Some("tool_result"))
             }
-        }
+            _ => false,
+        }),
+        cost_usd: entry_cost(entry),
+        tokens: entry
+            .message
+            .as_ref()
+            .and_then(|m| m.usage.as_ref())
+            .map(|u| (u.input_tokens + u.output_tokens) as u64)
+            .unwrap_or(0),
+        preview: entry_preview(entry),
+    }
+}
+
+/// Cost of one entry: the logged cost when present, else priced from its usage
+fn entry_cost(entry: &RawLogEntry) -> f64 {
+    if let Some(cost) = entry.cost_usd {
+        return cost;
+    }
+
+    let Some(message) = &entry.message else { return 0.0 };
+    let Some(usage) = &message.usage else { return 0.0 };
+    if let Some(cost) = usage.cost_usd {
+        return cost;
+    }
+
+    // Fallback to manual calculation if cost is not provided in the log
+    match &message.model {
+        Some(model) => calculate_cost(model, Some(&entry.timestamp), &token_counts_from_usage(usage)),
+        None => 0.0,
+    }
+}
 
-        total_cost += entry_cost;
+fn sum_session_metrics(entries: &[RawLogEntry]) -> (f64, u64, u64) {
+    let mut total_cost = 0.0;
+    let mut total_duration = 0;
+    let mut total_tokens = 0;
 
+    for entry in entries {
         if let Some(duration) = entry.duration_ms {
             total_duration += duration;
         }
 
+        total_cost += entry_cost(entry);
+
         if let Some(message) = &entry.message {
             if let Some(usage) = &message.usage {
                 total_tokens += (usage.input_tokens + usage.output_tokens) as u64;
@@ -1135,22 +1423,35 @@ fn sum_session_metrics(entries: &[RawLogEntry]) -> (f64, u64, u64) {
     (total_cost, total_duration, total_tokens)
 }
 
-fn calculate_manual_cost(model: &str, usage: &RawUsage) -> f64 {
-    // Anthropic pricing per million tokens
-    // Using current Claude 3.5/4 prices
-    let (input_rate, output_rate, cache_write_rate, cache_read_rate) = match model {
-        m if m.contains("opus") => (15.0, 75.0, 18.75, 1.5),
-        m if m.contains("sonnet") => (3.0, 15.0, 3.75, 0.3),
-        m if m.contains("haiku") => (0.25, 1.25, 0.3125, 0.03),
-        _ => (3.0, 15.0, 3.75, 0.3), // Default to Sonnet pricing
-    };
+/// Cost of a transformed message: the logged cost when present, else priced from its usage
+pub(crate) fn message_cost(message: &Message) -> f64 {
+    let Some(usage) = &message.usage else { return 0.0 };
+    if usage.cost_usd > 0.0 {
+        return usage.cost_usd;
+    }
+    match &message.model {
+        Some(model) => calculate_cost(model, Some(&message.timestamp), &usage.token_counts()),
+        None => 0.0,
+    }
+}
 
-    let input_cost = (usage.input_tokens as f64 / 1_000_000.0) * input_rate;
-    let output_cost = (usage.output_tokens as f64 / 1_000_000.0) * output_rate;
-    let cache_write_cost = (usage.cache_creation_input_tokens as f64 / 1_000_000.0) * cache_write_rate;
-    let cache_read_cost = (usage.cache_read_input_tokens as f64 / 1_000_000.0) * cache_read_rate;
+/// Token counts of a Claude usage record, with cache writes split by TTL
+fn token_counts_from_usage(usage: &RawUsage) -> TokenCounts {
+    let cache_write_1h = usage
+        ._extra
+        .get("cache_creation")
+        .and_then(|c| c.get("ephemeral_1h_input_tokens"))
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0)
+        .min(usage.cache_creation_input_tokens as u64);
 
-    input_cost + output_cost + cache_write_cost + cache_read_cost
+    TokenCounts {
+        input: usage.input_tokens as u64,
+        output: usage.output_tokens as u64,
+        cache_read: usage.cache_read_input_tokens as u64,
+        cache_write_5m: usage.cache_creation_input_tokens as u64 - cache_write_1h,
+        cache_write_1h,
+    }
 }
 
 fn extract_agent_id(value: &Value) -> Option<String> {
@@ -1257,13 +1558,13 @@ fn detect_tech_stack(entries: &[RawLogEntry]) -> Option<TechStack> {
                     }
 
                     let tool_name = block.get("name").and_then(|n| n.as_str()).unwrap_or("");
-                    if tool_name != "Write" && tool_name != "Edit" {
+                    if !matches!(tool_name, "Write" | "Edit" | "MultiEdit" | "NotebookEdit") {
                         continue;
                     }
 
                     if let Some(file_path) = block
                         .get("input")
-                        .and_then(|i| i.get("file_path"))
+                        .and_then(|i| i.get("file_path").or(i.get("notebook_path")))
                         .and_then(|p| p.as_str())
                     {
                         if let Some(ext) = file_path.rsplit('.').next() {
@@ -1313,7 +1614,8 @@ fn detect_tech_stack(entries: &[RawLogEntry]) -> Option<TechStack> {
     })
 }
 
-fn calculate_session_stats(entries: &[RawLogEntry], total_cost: f64, total_tokens: u64) -> SessionStats {
+/// Stats of a session; `thresholds` are the health thresholds of its project
+fn calculate_session_stats(entries: &[RawLogEntry], total_cost: f64, total_tokens: u64, thresholds: &HealthThresholds) -> SessionStats {
     let mut prompt_count = 0;
     let mut tool_call_count = 0;
     let mut tool_counts: HashMap<String, usize> = HashMap::new();
@@ -1371,14 +1673,21 @@ fn calculate_session_stats(entries: &[RawLogEntry], total_cost: f64, total_token
     let start_timestamp = first_timestamp.map(|ts| ts.to_rfc3339());
     let end_timestamp = last_timestamp.map(|ts| ts.to_rfc3339());
 
-    let health = Some(calculate_session_health(
+    let messages: Vec<Message> = entries
+        .iter()
+        .filter(|e| e.entry_type == "user" || e.entry_type == "assistant")
+        .map(transform_message)
+        .collect();
+    let mut health = calculate_session_health(
         prompt_count,
         tool_call_count,
         message_count,
         duration_ms,
         total_cost,
         total_tokens,
-    ));
+        thresholds,
+    );
+    (health.status, health.verdict) = session_status((health.status.clone(), health.verdict.clone()), &messages, thresholds);
 
     let tech_stack = detect_tech_stack(entries);
 
@@ -1392,7 +1701,7 @@ fn calculate_session_stats(entries: &[RawLogEntry], total_cost: f64, total_token
         start_timestamp,
         end_timestamp,
         git_branch,
-        health,
+        health: Some(health),
         tool_breakdown: Some(tool_counts),
         tech_stack,
     }
@@ -1405,6 +1714,7 @@ fn calculate_session_health(
     duration_ms: u64,
     total_cost_usd: f64,
     total_tokens: u64,
+    thresholds: &HealthThresholds,
 ) -> SessionHealth {
     // 1. Prompts per Hour
     let duration_hours = (duration_ms as f64) / 1000.0 / 3600.0;
@@ -1445,43 +1755,15 @@ fn calculate_session_health(
         0.0
     };
 
-
-    // Diagnostics logic
-    let mut status = "healthy".to_string();
-    let mut verdict = "continue".to_string();
-
-    // Check for "Frantic" or "Stalled" based on Prompts/Hour
-    if prompts_per_hour > 20.0 {
-        status = "frantic".to_string();
-        verdict = "constrain".to_string();
-    } else if prompts_per_hour < 2.0 && duration_hours > 1.0 {
-        status = "stalled".to_string();
-    }
-
-    // Check for "Looping" based on Tool Density
-    if tool_calls_per_prompt > 8.0 {
-        status = "looping".to_string();
-        verdict = "constrain".to_string();
-    }
-
-    // Check for "Explosion"
-    if assistant_messages_per_prompt > 5.0 {
-        status = "exploding".to_string();
-        verdict = "restart".to_string();
-    }
-
-    // Check for "Expensive" / "Heavy"
-    if tokens_per_minute > 50_000.0 {
-         status = "heavy".to_string(); // Renamed from expensive
-         if prompts_per_hour < 5.0 {
-             verdict = "restart".to_string();
-         }
-    } else if cost_per_minute > 0.50 {
-         status = "expensive".to_string();
-         if prompts_per_hour < 5.0 {
-             verdict = "restart".to_string();
-         }
-    }
+    let metrics = HealthMetrics {
+        prompts_per_hour,
+        tool_calls_per_prompt,
+        assistant_messages_per_prompt,
+        tokens_per_minute,
+        cost_per_minute,
+    };
+    // Whole-session rates only; calculate_session_stats weighs in windows and loops
+    let (status, verdict) = classify(&metrics, thresholds, duration_hours > 1.0, false);
 
     SessionHealth {
         prompts_per_hour,
@@ -1494,161 +1776,167 @@ fn calculate_session_health(
 }
 
 // ============================================================================
-// Tauri Commands
+// Live Tailing
 // ============================================================================
 
-/// Get all projects from ~/.claude/projects/
-#[tauri::command]
-pub async fn get_projects() -> Result<Vec<Project>, String> {
-    let projects_dir = get_projects_dir()?;
-
-    if !projects_dir.exists() {
-        return Ok(Vec::new());
+/// Incremental reader for a Claude Code session file that is still being written.
+/// Keeps every parsed entry so stats can be recomputed without re-reading the file.
+pub(crate) struct ClaudeSessionTail {
+    path: PathBuf,
+    offset: u64,
+    entries: Vec<RawLogEntry>,
+    /// Message ids read so far, so only the first line of a response keeps its usage
+    dedup: UsageDedup,
+    /// Health thresholds of the session's project, read once when tailing starts
+    thresholds: HealthThresholds,
+}
+
+impl ClaudeSessionTail {
+    pub(crate) fn new(path: PathBuf, project_id: &str) -> Self {
+        ClaudeSessionTail {
+            path,
+            offset: 0,
+            entries: Vec::new(),
+            dedup: UsageDedup::default(),
+            thresholds: health_thresholds(Some(project_id)),
+        }
     }
 
-    let mut projects = Vec::new();
+    /// Parse complete lines appended since the last poll.
+    /// Returns the new messages (file order) and refreshed stats, or None if nothing changed.
+    pub(crate) fn poll(&mut self) -> Result<Option<(Vec<Message>, SessionStats)>, String> {
+        use std::io::{BufRead, Seek, SeekFrom};
 
-    for entry in fs::read_dir(&projects_dir).map_err(|e| format!("Failed to read projects dir: {}", e))? {
-        let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
-        let path = entry.path();
+        let len = fs::metadata(&self.path)
+            .map_err(|e| format!("Failed to read session file: {}", e))?
+            .len();
 
-        if path.is_dir() {
-            let project_id = parse_project_id(&path);
-            let display_name = resolve_project_name(&path);
-            let resolved_path = find_project_cwd(&path).unwrap_or_else(|| path.clone());
-
-            // Count sessions
-            let session_count = fs::read_dir(&path)
-                .ok()
-                .map(|entries| {
-                    entries
-                        .filter_map(|e| {
-                            e.ok().and_then(|entry| {
-                                let p = entry.path();
-                                if p.extension().map(|ext| ext == "jsonl").unwrap_or(false) {
-                                    Some(())
-                                } else {
-                                    None
-                                }
-                            })
-                        })
-                        .count()
-                })
-                .unwrap_or(0);
+        // Truncated or replaced: start over
+        if len < self.offset {
+            self.offset = 0;
+            self.entries.clear();
+            self.dedup = UsageDedup::default();
+        }
+        if len == self.offset {
+            return Ok(None);
+        }
 
-            let last_modified = get_file_modified_time(&path);
+        let mut file = fs::File::open(&self.path).map_err(|e| format!("Failed to open session file: {}", e))?;
+        file.seek(SeekFrom::Start(self.offset))
+            .map_err(|e| format!("Failed to seek session file: {}", e))?;
+        let mut reader = std::io::BufReader::new(file);
+
+        let first_new = self.entries.len();
+        let mut buf: Vec<u8> = Vec::new();
+        loop {
+            buf.clear();
+            let read = reader
+                .read_until(b'\n', &mut buf)
+                .map_err(|e| format!("Error reading line: {}", e))?;
+            // Leave a partially written line for the next poll
+            if read == 0 || buf.last() != Some(&b'\n') {
+                break;
+            }
+            self.offset += read as u64;
 
-            projects.push(Project {
-                id: project_id,
-                path: resolved_path.to_string_lossy().to_string(),
-                display_name,
-                session_count,
-                last_modified,
-            });
+            let line = String::from_utf8_lossy(&buf);
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Ok(RawEntry::Summary(_)) = serde_json::from_str::<RawEntry>(&line) {
+                continue;
+            }
+            if let Ok(mut entry) = serde_json::from_str::<RawLogEntry>(&line) {
+                keep_first_usage(&mut entry, &mut self.dedup);
+                self.entries.push(entry);
+            }
         }
-    }
 
-    // Sort by last modified descending
-    projects.sort_by(|a, b| b.last_modified.cmp(&a.last_modified));
+        if self.entries.len() == first_new {
+            return Ok(None);
+        }
 
-    Ok(projects)
-}
+        let mut messages = Vec::new();
+        for entry in &self.entries[first_new..] {
+            if let Some(hook_msg) = transform_hook_message(entry) {
+                messages.push(hook_msg);
+            } else if entry.entry_type == "user" || entry.entry_type == "assistant" || entry.entry_type == "agent_progress" {
+                messages.push(transform_message(entry));
+            }
+        }
 
-/// Get all sessions for a project
-#[tauri::command]
-pub async fn get_project_sessions(project_id: String) -> Result<Vec<Session>, String> {
-    let projects_dir = get_projects_dir()?;
-    let project_path = projects_dir.join(&project_id);
+        let (total_cost, _, total_tokens) = sum_session_metrics(&self.entries);
+        let stats = calculate_session_stats(&self.entries, total_cost, total_tokens, &self.thresholds);
 
-    eprintln!("[get_project_sessions] project_id: {}", project_id);
-    eprintln!("[get_project_sessions] project_path: {:?}", project_path);
-    eprintln!("[get_project_sessions] exists: {}", project_path.exists());
+        Ok(Some((messages, stats)))
+    }
+}
 
-    if !project_path.exists() {
-        eprintln!("[get_project_sessions] Project directory not found at: {:?}", project_path);
-        return Err(format!("Project not found: {}", project_id));
+/// Incremental reader for a Codex rollout file
+pub(crate) struct CodexSessionTail {
+    path: PathBuf,
+    offset: u64,
+    parser: CodexRolloutParser,
+}
+
+impl CodexSessionTail {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        CodexSessionTail { path, offset: 0, parser: CodexRolloutParser::default() }
     }
 
-    let mut sessions = Vec::new();
+    /// Parse complete lines appended since the last poll.
+    /// Returns the new messages (file order) and refreshed stats, or None if nothing changed.
+    pub(crate) fn poll(&mut self) -> Result<Option<(Vec<Message>, SessionStats)>, String> {
+        use std::io::{BufRead, Seek, SeekFrom};
 
-    match fs::read_dir(&project_path) {
-        Ok(entries) => {
-            for entry in entries {
-                match entry {
-                    Ok(entry) => {
-                        let path = entry.path();
-                        eprintln!("[get_project_sessions] Found entry: {:?}", path);
-
-                        if path.is_file() && path.extension().map(|ext| ext == "jsonl").unwrap_or(false) {
-                            let filename = path.file_name().unwrap().to_string_lossy().to_string();
-                            let session_id = parse_session_id(&filename);
-                            eprintln!("[get_project_sessions] Found session file: {}", filename);
-
-                            // Load session data for summary and stats
-                            if let Ok((entries, summary)) = read_session_data(&path).await {
-                                let (total_cost, _, total_tokens) = sum_session_metrics(&entries);
-                                let stats = calculate_session_stats(&entries, total_cost, total_tokens);
-                                let last_modified = get_file_modified_time(&path);
-
-                                sessions.push(Session {
-                                    id: session_id,
-                                    project_id: project_id.clone(),
-                                    file_path: path.to_string_lossy().to_string(),
-                                    last_modified,
-                                    message_count: stats.message_count,
-                                    summary,
-                                    stats: Some(stats),
-                                });
-                            } else {
-                                // Fallback if file can't be read fully
-                                let last_modified = get_file_modified_time(&path);
-                                sessions.push(Session {
-                                    id: session_id,
-                                    project_id: project_id.clone(),
-                                    file_path: path.to_string_lossy().to_string(),
-                                    last_modified,
-                                    message_count: 0,
-                                    summary: String::new(),
-                                    stats: None,
-                                });
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("[get_project_sessions] Failed to read entry: {}", e);
-                    }
-                }
-            }
+        let len = fs::metadata(&self.path)
+            .map_err(|e| format!("Failed to read session file: {}", e))?
+            .len();
+
+        // Truncated or replaced: start over
+        if len < self.offset {
+            self.offset = 0;
+            self.parser = CodexRolloutParser::default();
         }
-        Err(e) => {
-            eprintln!("[get_project_sessions] Failed to read sessions dir: {}", e);
-            return Err(format!("Failed to read sessions: {}", e));
+        if len == self.offset {
+            return Ok(None);
         }
-    }
 
-    eprintln!("[get_project_sessions] Found {} sessions", sessions.len());
-
-    // Sort by last modified descending
-    sessions.sort_by(|a, b| b.last_modified.cmp(&a.last_modified));
-
-    Ok(sessions)
-}
+        let mut file = fs::File::open(&self.path).map_err(|e| format!("Failed to open session file: {}", e))?;
+        file.seek(SeekFrom::Start(self.offset))
+            .map_err(|e| format!("Failed to seek session file: {}", e))?;
+        let mut reader = std::io::BufReader::new(file);
+
+        let first_new = self.parser.messages.len();
+        let mut buf: Vec<u8> = Vec::new();
+        loop {
+            buf.clear();
+            let read = reader
+                .read_until(b'\n', &mut buf)
+                .map_err(|e| format!("Error reading line: {}", e))?;
+            // Leave a partially written line for the next poll
+            if read == 0 || buf.last() != Some(&b'\n') {
+                break;
+            }
+            self.offset += read as u64;
+            self.parser.push_line(&String::from_utf8_lossy(&buf));
+        }
 
-/// Get session details with paginated messages
-#[tauri::command]
-pub async fn get_session_details(project_id: String, session_id: String, page: Option<usize>) -> Result<SessionDetails, String> {
-    let projects_dir = get_projects_dir()?;
-    let session_path = projects_dir.join(&project_id).join(format!("{}.jsonl", session_id));
+        if self.parser.messages.len() == first_new {
+            return Ok(None);
+        }
 
-    if !session_path.exists() {
-        return Err("Session not found".to_string());
+        Ok(Some((self.parser.messages[first_new..].to_vec(), self.parser.stats())))
     }
+}
 
-    let (entries, summary) = read_session_data(&session_path).await?;
-    let (total_cost, _, total_tokens) = sum_session_metrics(&entries);
-    let stats = calculate_session_stats(&entries, total_cost, total_tokens);
+// ============================================================================
+// Source Readers
+// ============================================================================
 
-    // Multi-pass parsing to link agent IDs
+/// Build display messages from log entries, linking subagent ids to their Task calls.
+/// Messages are returned newest first.
+fn build_claude_messages(entries: Vec<RawLogEntry>) -> Vec<Message> {
     let mut messages: Vec<Message> = Vec::new();
     let mut tool_use_map: HashMap<String, usize> = HashMap::new(); // tool_use_id -> message index
 
@@ -1665,7 +1953,7 @@ pub async fn get_session_details(project_id: String, session_id: String, page: O
         }
 
         let mut msg = transform_message(&entry);
-        
+
         // Populate agentId from metadata if available
         if entry.entry_type == "assistant" {
            for block in &mut msg.content {
@@ -1741,156 +2029,467 @@ pub async fn get_session_details(project_id: String, session_id: String, page: O
         b_ts.cmp(&a_ts)
     });
 
+    messages
+}
+
+/// Message of one session line with the content blocks the transcript shows for it
+pub(crate) fn claude_line_message(line: &str) -> Option<Message> {
+    let entry = serde_json::from_str::<RawLogEntry>(line).ok()?;
+    transform_hook_message(&entry).or_else(|| Some(transform_message(&entry)))
+}
+
+/// Page (1-based) on which the message at `index` is shown
+pub(crate) fn message_page(index: usize) -> usize {
+    index / MESSAGES_PER_PAGE + 1
+}
+
+/// Slice one page (1-based) out of a full message list. A page past the end is an error
+/// when `out_of_range_error` is set (Claude Code), else an empty page (Codex).
+pub(crate) fn paginate_messages(
+    messages: Vec<Message>,
+    page: Option<usize>,
+    out_of_range_error: bool,
+) -> Result<PaginatedMessages, String> {
+    let total_messages = messages.len();
+    let total_pages = (total_messages + MESSAGES_PER_PAGE - 1) / MESSAGES_PER_PAGE;
     let page_num = page.unwrap_or(1).saturating_sub(1);
-    let start_idx = page_num * MESSAGES_PER_PAGE;
-    let end_idx = (start_idx + MESSAGES_PER_PAGE).min(messages.len());
 
-    let paginated_messages = messages[start_idx..end_idx].to_vec();
-    let message_count = messages.len();
-    let last_modified = get_file_modified_time(&session_path);
+    if out_of_range_error && page_num >= total_pages && total_messages > 0 {
+        return Err("Page out of range".to_string());
+    }
 
-    Ok(SessionDetails {
-        id: session_id,
-        project_id,
-        file_path: session_path.to_string_lossy().to_string(),
-        last_modified,
-        message_count,
-        summary,
-        stats: Some(stats),
-        messages: paginated_messages,
+    let start_idx = (page_num * MESSAGES_PER_PAGE).min(total_messages);
+    let end_idx = (start_idx + MESSAGES_PER_PAGE).min(total_messages);
+
+    Ok(PaginatedMessages {
+        messages: messages[start_idx..end_idx].to_vec(),
+        total_pages,
+        current_page: page_num + 1,
+        total_messages,
     })
 }
 
-/// Get paginated messages for a session
-#[tauri::command]
-pub async fn get_session_paginated(project_id: String, session_id: String, page: Option<usize>) -> Result<PaginatedMessages, String> {
-    let projects_dir = get_projects_dir()?;
-    let session_path = projects_dir.join(&project_id).join(format!("{}.jsonl", session_id));
+/// List Claude Code projects from the projects/ folder of every data root
+pub(crate) async fn list_claude_projects() -> Result<Vec<Project>, String> {
+    let mut projects = Vec::new();
+
+    for (root, project_id, path) in claude_project_dirs() {
+        let display_name = resolve_project_name(&path);
+        let resolved_path = find_project_cwd(&path).unwrap_or_else(|| path.clone());
+
+        // Count sessions
+        let session_count = fs::read_dir(&path)
+            .ok()
+            .map(|entries| {
+                entries
+                    .filter_map(|e| {
+                        e.ok().and_then(|entry| {
+                            let p = entry.path();
+                            if p.extension().map(|ext| ext == "jsonl").unwrap_or(false) {
+                                Some(())
+                            } else {
+                                None
+                            }
+                        })
+                    })
+                    .count()
+            })
+            .unwrap_or(0);
+
+        let last_modified = get_file_modified_time(&path);
+
+        projects.push(Project {
+            id: project_id,
+            path: resolved_path.to_string_lossy().to_string(),
+            display_name,
+            session_count,
+            last_modified,
+            root: root.id,
+            host: root.host,
+        });
+    }
+
+    // Sort by last modified descending
+    projects.sort_by(|a, b| b.last_modified.cmp(&a.last_modified));
+
+    Ok(projects)
+}
 
+/// List sessions of a Claude Code project, newest first
+pub(crate) async fn list_claude_sessions(project_id: String) -> Result<Vec<Session>, String> {
+    let project_path = claude_project_dir(&project_id)?;
+
+    if !project_path.exists() {
+        return Err(format!("Project not found: {}", project_id));
+    }
+
+    let thresholds = health_thresholds(Some(&project_id));
+
+    // Already sorted by last modified descending
+    with_index(|index| {
+        let files = index.project_sessions(&project_id);
+        let summaries = attribute_summaries(&files);
+        files
+            .iter()
+            .zip(&summaries)
+            .map(|(file, summaries)| session_from_index(&[file], summaries, &thresholds))
+            .collect()
+    })
+}
+
+/// Listing rows of the logical conversations of a project: session files linked
+/// by resume/continue are merged, most recently active first. Returns each row
+/// with the ids of its files in chronological order.
+pub(crate) async fn list_claude_conversations(project_id: String) -> Result<Vec<(Session, Vec<String>)>, String> {
+    let project_path = claude_project_dir(&project_id)?;
+
+    if !project_path.exists() {
+        return Err(format!("Project not found: {}", project_id));
+    }
+
+    let thresholds = health_thresholds(Some(&project_id));
+
+    with_index(|index| {
+        let files = index.project_sessions(&project_id);
+        let summaries = attribute_summaries(&files);
+        group_sessions(&files)
+            .into_iter()
+            .map(|group| {
+                let group_files: Vec<&IndexedSession> = group.iter().map(|&i| files[i]).collect();
+                let group_summaries: Vec<String> = group.iter().flat_map(|&i| summaries[i].clone()).collect();
+                let session_ids = group_files.iter().map(|f| f.session_id.clone()).collect();
+                (session_from_index(&group_files, &group_summaries, &thresholds), session_ids)
+            })
+            .collect()
+    })
+}
+
+/// Merged transcript of several session files of one conversation, in the given
+/// (chronological) order; entries repeated by a resume are kept once
+pub(crate) async fn load_claude_conversation(
+    project_id: String,
+    session_ids: &[String],
+) -> Result<(Vec<Message>, SessionStats), String> {
+    let project_path = claude_project_dir(&project_id)?;
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut entries: Vec<RawLogEntry> = Vec::new();
+
+    for session_id in session_ids {
+        let session_path = project_path.join(format!("{}.jsonl", session_id));
+        for entry in read_session_entries(&session_path).await? {
+            if entry.uuid.as_ref().map_or(true, |u| seen.insert(u.clone())) {
+                entries.push(entry);
+            }
+        }
+    }
+
+    let (total_cost, _, total_tokens) = sum_session_metrics(&entries);
+    let stats = calculate_session_stats(&entries, total_cost, total_tokens, &health_thresholds(Some(&project_id)));
+    Ok((build_claude_messages(entries), stats))
+}
+
+/// Load a full Claude Code session
+pub(crate) async fn load_claude_transcript(project_id: String, session_id: String) -> Result<SessionTranscript, String> {
+    read_claude_transcript(project_id, session_id, None).await
+}
+
+/// Transcript of a single branch: the path from the root to the message `leaf_uuid`
+pub(crate) async fn load_claude_branch_transcript(
+    project_id: String,
+    session_id: String,
+    leaf_uuid: String,
+) -> Result<SessionTranscript, String> {
+    read_claude_transcript(project_id, session_id, Some(&leaf_uuid)).await
+}
+
+pub(crate) async fn load_claude_tree_entries(project_id: String, session_id: String) -> Result<Vec<TreeEntry>, String> {
+    let session_path = claude_project_dir(&project_id)?.join(format!("{}.jsonl", session_id));
     if !session_path.exists() {
         return Err("Session not found".to_string());
     }
 
     let entries = read_session_entries(&session_path).await?;
-    
-    // Use the same robust multi-pass logic as get_session_details
-    let mut messages: Vec<Message> = Vec::new();
-    let mut tool_use_map: HashMap<String, usize> = HashMap::new();
+    Ok(entries.iter().map(tree_entry).collect())
+}
+
+/// Messages of a subagent run (oldest first), read from its own transcript file
+/// (`<session>/subagents/agent-<id>.jsonl`, or the legacy `agent-<id>.jsonl`) when
+/// one exists, otherwise from the progress entries of the parent session
+pub(crate) async fn load_claude_subagent_messages(
+    project_id: String,
+    session_id: String,
+    agent_id: String,
+) -> Result<Vec<Message>, String> {
+    let project_dir = claude_project_dir(&project_id)?;
+    let file_name = format!("{}.jsonl", agent_id);
+    let candidates = [
+        project_dir.join(&session_id).join("subagents").join(&file_name),
+        project_dir.join(&file_name),
+    ];
 
-    for entry in entries {
-        // Try to parse as hook first
-        if let Some(hook_msg) = transform_hook_message(&entry) {
-            messages.push(hook_msg);
-            continue;
+    match candidates.into_iter().find(|p| p.is_file()) {
+        Some(path) => {
+            let entries = read_session_entries(&path).await?;
+            let mut messages = build_claude_messages(entries);
+            messages.reverse();
+            Ok(messages)
         }
+        None => get_agent_transcript(project_id, session_id, agent_id).await,
+    }
+}
 
-        if entry.entry_type != "user" && entry.entry_type != "assistant" && entry.entry_type != "agent_progress" {
-            continue;
+async fn read_claude_transcript(
+    project_id: String,
+    session_id: String,
+    branch: Option<&str>,
+) -> Result<SessionTranscript, String> {
+    let (root, _) = resolve_qualified_id(&project_id)?;
+    let session_path = claude_project_dir(&project_id)?.join(format!("{}.jsonl", session_id));
+
+    if !session_path.exists() {
+        return Err("Session not found".to_string());
+    }
+
+    let (mut entries, summary) = read_session_data(&session_path).await?;
+    if let Some(leaf) = branch {
+        let tree_entries: Vec<TreeEntry> = entries.iter().map(tree_entry).collect();
+        let members = branch_members(&tree_entries, leaf)?;
+        entries.retain(|e| e.uuid.as_ref().map_or(true, |u| members.contains(u)));
+    }
+
+    let (total_cost, _, total_tokens) = sum_session_metrics(&entries);
+    let stats = calculate_session_stats(&entries, total_cost, total_tokens, &health_thresholds(Some(&project_id)));
+    let messages = build_claude_messages(entries);
+
+    Ok(SessionTranscript {
+        session: Session {
+            id: session_id,
+            project_id,
+            file_path: session_path.to_string_lossy().to_string(),
+            last_modified: get_file_modified_time(&session_path),
+            message_count: messages.len(),
+            summary,
+            stats: Some(stats),
+            root: root.id,
+            host: root.host,
+        },
+        messages,
+    })
+}
+
+/// List Codex projects (grouped by session cwd) from ~/.codex/sessions/
+pub(crate) async fn list_codex_projects() -> Result<Vec<Project>, String> {
+    let mut projects: HashMap<String, Project> = HashMap::new();
+
+    for (root, file) in codex_session_files() {
+        let meta = read_codex_session_meta(&file)?;
+        let Some(cwd) = meta.cwd else { continue };
+
+        let project_id = root.qualify_id(&encode_project_id(&cwd));
+        let display_name = resolve_project_name_from_cwd(&cwd);
+        let last_modified = get_file_modified_time(&file);
+
+        let entry = projects.entry(project_id.clone()).or_insert(Project {
+            id: project_id.clone(),
+            path: cwd.clone(),
+            display_name,
+            session_count: 0,
+            last_modified,
+            root: root.id.clone(),
+            host: root.host.clone(),
+        });
+
+        entry.session_count += 1;
+        if last_modified > entry.last_modified {
+            entry.last_modified = last_modified;
         }
+    }
 
-        let mut msg = transform_message(&entry);
-        
-        if entry.entry_type == "assistant" {
-           for block in &mut msg.content {
-               if block.get("type").and_then(|t| t.as_str()) == Some("tool_use") {
-                   if let Some(id) = block.get("id").and_then(|i| i.as_str()) {
-                       tool_use_map.insert(id.to_string(), messages.len());
-                       if block.get("name").and_then(|n| n.as_str()) == Some("Task") {
-                           let subagent_id = block.get("input")
-                               .and_then(|i| i.get("subagent_type"))
-                               .and_then(|s| s.as_str())
-                               .and_then(|s| if s.contains(':') { s.split(':').nth(1).map(|id| id.to_string()) } else { None });
-
-                           if let Some(id) = subagent_id {
-                               block.as_object_mut().unwrap().insert("agentId".to_string(), Value::String(id));
-                           }
-                       }
-                   }
-               }
-           }
-        } else if entry.entry_type == "user" {
-            for block in &msg.content {
-                if block.get("type").and_then(|t| t.as_str()) == Some("tool_result") {
-                    if let Some(tool_use_id) = block.get("tool_use_id").and_then(|i| i.as_str()) {
-                        if let Some(&msg_idx) = tool_use_map.get(tool_use_id) {
-                            if let Some(agent_id) = extract_agent_id(block.get("content").unwrap_or(&Value::Null)) {
-                                for tool_block in &mut messages[msg_idx].content {
-                                    if tool_block.get("id").and_then(|i: &Value| i.as_str()) == Some(tool_use_id) {
-                                        tool_block.as_object_mut().unwrap().insert("agentId".to_string(), Value::String(agent_id.clone()));
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        } else if entry.entry_type == "agent_progress" {
-            msg.msg_type = "progress".to_string();
-            if let Some(data) = entry._extra.get("data") {
-                 let prompt = data.get("prompt").and_then(|p| p.as_str()).unwrap_or("");
-                 let agent_id = data.get("agentId")
-                     .and_then(|a| a.as_str().map(|s| s.to_string()))
-                     .or_else(|| extract_agent_id(data));
-                 // parentToolUseID is at top level, not inside data
-                 let parent_id = entry._extra.get("parentToolUseID").and_then(|p| p.as_str());
+    let mut result: Vec<Project> = projects.into_values().collect();
+    result.sort_by(|a, b| b.last_modified.cmp(&a.last_modified));
+    Ok(result)
+}
 
-                 msg.content = vec![serde_json::json!({
-                     "type": "progress",
-                     "text": prompt,
-                     "agentId": agent_id,
-                 })];
+/// List sessions of a Codex project, newest first
+pub(crate) async fn list_codex_sessions(project_id: String) -> Result<Vec<Session>, String> {
+    let (root, local_id) = resolve_qualified_id(&project_id)?;
+    let Some(sessions_dir) = root.codex_sessions_dir().filter(|d| d.exists()) else {
+        return Ok(Vec::new());
+    };
 
-                 if let (Some(pid), Some(ref aid)) = (parent_id, agent_id) {
-                     if let Some(&msg_idx) = tool_use_map.get(pid) {
-                         for tool_block in &mut messages[msg_idx].content {
-                             if tool_block.get("id").and_then(|i: &Value| i.as_str()) == Some(pid) {
-                                 tool_block.as_object_mut().unwrap().insert("agentId".to_string(), Value::String(aid.clone()));
-                             }
-                         }
-                     }
-                 }
-            }
+    let files = list_codex_session_files(&sessions_dir);
+    let mut sessions = Vec::new();
+
+    for file in files {
+        let meta = read_codex_session_meta(&file)?;
+        let Some(cwd) = meta.cwd else { continue };
+        if encode_project_id(&cwd) != local_id {
+            continue;
         }
-        messages.push(msg);
+
+        let last_modified = get_file_modified_time(&file);
+        let session_id = meta.id.clone().unwrap_or_else(|| {
+            file.file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("unknown")
+                .to_string()
+        });
+
+        let (messages, stats, summary, _) = read_codex_session_data(&file).await?;
+
+        sessions.push(Session {
+            id: session_id,
+            project_id: project_id.clone(),
+            file_path: file.to_string_lossy().to_string(),
+            last_modified,
+            message_count: messages.len(),
+            summary,
+            stats: Some(stats),
+            root: root.id.clone(),
+            host: root.host.clone(),
+        });
     }
 
-    // Sort newest first
-    messages.sort_by(|a, b| {
-        let a_ts = DateTime::parse_from_rfc3339(&a.timestamp).ok();
-        let b_ts = DateTime::parse_from_rfc3339(&b.timestamp).ok();
-        b_ts.cmp(&a_ts)
-    });
+    sessions.sort_by(|a, b| b.last_modified.cmp(&a.last_modified));
+    Ok(sessions)
+}
 
-    let total_messages = messages.len();
-    let total_pages = (total_messages + MESSAGES_PER_PAGE - 1) / MESSAGES_PER_PAGE;
-    let page_num = page.unwrap_or(1).saturating_sub(1);
+/// Load a full Codex session; messages stay in file order
+pub(crate) async fn load_codex_transcript(project_id: String, session_id: String) -> Result<SessionTranscript, String> {
+    let (root, _) = resolve_qualified_id(&project_id)?;
+    let session_path = find_codex_session_file(&project_id, &session_id)?;
+    let (messages, stats, summary, _) = read_codex_session_data(&session_path).await?;
 
-    if page_num >= total_pages && total_messages > 0 {
-        return Err("Page out of range".to_string());
+    Ok(SessionTranscript {
+        session: Session {
+            id: session_id,
+            project_id,
+            file_path: session_path.to_string_lossy().to_string(),
+            last_modified: get_file_modified_time(&session_path),
+            message_count: messages.len(),
+            summary,
+            stats: Some(stats),
+            root: root.id,
+            host: root.host,
+        },
+        messages,
+    })
+}
+
+/// Codex rollouts have no branches: every message follows the previous one
+pub(crate) async fn load_codex_tree_entries(project_id: String, session_id: String) -> Result<Vec<TreeEntry>, String> {
+    let mut messages = load_codex_transcript(project_id, session_id).await?.messages;
+    messages.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+
+    let mut previous: Option<String> = None;
+    let mut entries = Vec::new();
+    for (i, message) in messages.into_iter().enumerate() {
+        let uuid = if message.uuid.is_empty() { format!("codex-{}", i) } else { message.uuid };
+        let preview = message
+            .content
+            .iter()
+            .find_map(|b| b.get("text").and_then(|t| t.as_str()).map(String::from));
+        entries.push(TreeEntry {
+            uuid: Some(uuid.clone()),
+            parent_uuid: previous.replace(uuid),
+            entry_type: message.msg_type,
+            timestamp: message.timestamp,
+            cost_usd: message.usage.as_ref().map(|u| u.cost_usd).unwrap_or(0.0),
+            tokens: message.usage.as_ref().map(|u| u.total_tokens as u64).unwrap_or(0),
+            preview,
+            ..Default::default()
+        });
     }
 
-    let start_idx = page_num * MESSAGES_PER_PAGE;
-    let end_idx = (start_idx + MESSAGES_PER_PAGE).min(total_messages);
+    Ok(entries)
+}
 
-    Ok(PaginatedMessages {
-        messages: messages[start_idx..end_idx].to_vec(),
-        total_pages,
-        current_page: page_num + 1,
-        total_messages,
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Load a whole session, or only the branch ending at `branch` (a leaf message uuid)
+async fn load_transcript(
+    project_id: String,
+    session_id: String,
+    source: Option<&str>,
+    branch: Option<String>,
+) -> Result<SessionTranscript, String> {
+    let source = find_source(source)?;
+    match branch {
+        Some(leaf) => source.branch_transcript(project_id, session_id, leaf).await,
+        None => source.session_transcript(project_id, session_id).await,
+    }
+}
+
+/// Get all projects of a transcript source (default: Claude Code)
+#[tauri::command]
+pub async fn get_projects(source: Option<String>) -> Result<Vec<Project>, String> {
+    find_source(source.as_deref())?.projects().await
+}
+
+/// Get all sessions for a project
+#[tauri::command]
+pub async fn get_project_sessions(project_id: String, source: Option<String>) -> Result<Vec<Session>, String> {
+    find_source(source.as_deref())?.project_sessions(project_id).await
+}
+
+/// Get session details with paginated messages
+#[tauri::command]
+pub async fn get_session_details(
+    project_id: String,
+    session_id: String,
+    page: Option<usize>,
+    source: Option<String>,
+    branch: Option<String>,
+) -> Result<SessionDetails, String> {
+    let out_of_range_error = source.as_deref() != Some(CODEX_SOURCE);
+    let transcript = load_transcript(project_id, session_id, source.as_deref(), branch).await?;
+    let session = transcript.session;
+    let message_count = transcript.messages.len();
+    let page = paginate_messages(transcript.messages, page, out_of_range_error)?;
+
+    Ok(SessionDetails {
+        id: session.id,
+        project_id: session.project_id,
+        file_path: session.file_path,
+        last_modified: session.last_modified,
+        message_count,
+        summary: session.summary,
+        stats: session.stats,
+        root: session.root,
+        host: session.host,
+        messages: page.messages,
     })
 }
 
+/// Get paginated messages for a session
+#[tauri::command]
+pub async fn get_session_paginated(
+    project_id: String,
+    session_id: String,
+    page: Option<usize>,
+    source: Option<String>,
+    branch: Option<String>,
+) -> Result<PaginatedMessages, String> {
+    let out_of_range_error = source.as_deref() != Some(CODEX_SOURCE);
+    let transcript = load_transcript(project_id, session_id, source.as_deref(), branch).await?;
+    paginate_messages(transcript.messages, page, out_of_range_error)
+}
+
 /// Get aggregated statistics for a project
 #[tauri::command]
 pub async fn get_project_stats(project_id: String) -> Result<ProjectStats, String> {
-    let projects_dir = get_projects_dir()?;
-    let project_path = projects_dir.join(&project_id);
+    let project_path = claude_project_dir(&project_id)?;
 
     if !project_path.exists() {
         return Err(format!("Project not found: {}", project_id));
     }
 
-    let mut total_cost = 0.0;
+    // Priced from the index, where a response a resumed session repeats counts once
+    let total_cost: f64 = with_index(|index| {
+        index.project_sessions(&project_id).iter().flat_map(|s| &s.entries).map(indexed_entry_cost).sum()
+    })?;
     let mut total_messages = 0;
     let mut total_tool_calls = 0;
     let mut lines_written = 0;
@@ -1906,7 +2505,7 @@ pub async fn get_project_stats(project_id: String) -> Result<ProjectStats, Strin
         if path.is_file() && path.extension().map(|ext| ext == "jsonl").unwrap_or(false) {
             if let Ok(entries) = read_session_entries(&path).await {
                 session_count += 1;
-                
+
                 // Track date range (once per session file is enough)
                 let modified = get_file_modified_time(&path);
                 if first_session.is_none() || modified < first_session.unwrap() {
@@ -1916,9 +2515,6 @@ pub async fn get_project_stats(project_id: String) -> Result<ProjectStats, Strin
                     last_session = Some(modified);
                 }
 
-                let (session_cost, _, _) = sum_session_metrics(&entries);
-                total_cost += session_cost;
-
                 let mut session_first_ts: Option<DateTime<Utc>> = None;
                 let mut session_last_ts: Option<DateTime<Utc>> = None;
 
@@ -1996,12 +2592,10 @@ pub async fn get_project_stats(project_id: String) -> Result<ProjectStats, Strin
     })
 }
 
-
 /// Get agent workshop activity - shows what the agent was doing
 #[tauri::command]
 pub async fn get_agent_transcript(project_id: String, session_id: String, agent_id: String) -> Result<Vec<Message>, String> {
-    let projects_dir = get_projects_dir()?;
-    let session_path = projects_dir.join(&project_id).join(format!("{}.jsonl", session_id));
+    let session_path = claude_project_dir(&project_id)?.join(format!("{}.jsonl", session_id));
 
     if !session_path.exists() {
         return Err(format!("Session not found: {}", session_id));
@@ -2178,8 +2772,8 @@ pub async fn get_agent_transcript(project_id: String, session_id: String, agent_
 /// Delete a session file
 #[tauri::command]
 pub async fn delete_session(project_id: String, session_id: String) -> Result<bool, String> {
-    let projects_dir = get_projects_dir()?;
-    let session_path = projects_dir.join(&project_id).join(format!("{}.jsonl", session_id));
+    ensure_writable(&project_id)?;
+    let session_path = claude_project_dir(&project_id)?.join(format!("{}.jsonl", session_id));
 
     if !session_path.exists() {
         return Err("Session not found".to_string());
@@ -2194,251 +2788,84 @@ pub async fn delete_session(project_id: String, session_id: String) -> Result<bo
 
 /// Get all messages for a session (no pagination)
 #[tauri::command]
-pub async fn get_all_session_messages(project_id: String, session_id: String) -> Result<Vec<Message>, String> {
-    let projects_dir = get_projects_dir()?;
-    let session_path = projects_dir.join(&project_id).join(format!("{}.jsonl", session_id));
-
-    if !session_path.exists() {
-        return Err("Session not found".to_string());
-    }
-
-    let (entries, _) = read_session_data(&session_path).await?;
-    
-    let mut messages: Vec<Message> = Vec::new();
-    let mut tool_use_map: HashMap<String, usize> = HashMap::new(); // tool_use_id -> message index
-
-    for entry in entries {
-        // Try to parse as hook first
-        if let Some(hook_msg) = transform_hook_message(&entry) {
-            messages.push(hook_msg);
-            continue;
-        }
-
-        // We only care about user, assistant, and agent_progress for the transcript
-        if entry.entry_type != "user" && entry.entry_type != "assistant" && entry.entry_type != "agent_progress" {
-            continue;
-        }
-
-        let mut msg = transform_message(&entry);
-        
-        // Populate agentId from metadata if available
-        if entry.entry_type == "assistant" {
-           for block in &mut msg.content {
-               if block.get("type").and_then(|t| t.as_str()) == Some("tool_use") {
-                   if let Some(id) = block.get("id").and_then(|i| i.as_str()) {
-                       tool_use_map.insert(id.to_string(), messages.len());
-                       // Early check for agentId in input
-                       let subagent_id = block.get("input")
-                           .and_then(|i| i.get("subagent_type"))
-                           .and_then(|s| s.as_str())
-                           .and_then(|s| if s.contains(':') { s.split(':').nth(1).map(|id| id.to_string()) } else { None });
-
-                       if let Some(id) = subagent_id {
-                           block.as_object_mut().unwrap().insert("agentId".to_string(), Value::String(id));
-                       }
-                   }
-               }
-           }
-        } else if entry.entry_type == "user" {
-            // Check tool_result for agentIds
-            for block in &msg.content {
-                if block.get("type").and_then(|t| t.as_str()) == Some("tool_result") {
-                    if let Some(tool_use_id) = block.get("tool_use_id").and_then(|i| i.as_str()) {
-                        if let Some(&msg_idx) = tool_use_map.get(tool_use_id) {
-                            if let Some(agent_id) = extract_agent_id(block.get("content").unwrap_or(&Value::Null)) {
-                                // Link back to tool_use block
-                                for tool_block in &mut messages[msg_idx].content {
-                                    if tool_block.get("id").and_then(|i: &Value| i.as_str()) == Some(tool_use_id) {
-                                        tool_block.as_object_mut().unwrap().insert("agentId".to_string(), Value::String(agent_id.clone()));
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        } else if entry.entry_type == "agent_progress" {
-            // Convert to progress message
-            msg.msg_type = "progress".to_string();
-            if let Some(data) = entry._extra.get("data") {
-                 let prompt = data.get("prompt").and_then(|p| p.as_str()).unwrap_or("");
-                 let agent_id = data.get("agentId")
-                     .and_then(|a| a.as_str().map(|s| s.to_string()))
-                     .or_else(|| extract_agent_id(data));
-                 // parentToolUseID is at top level, not inside data
-                 let parent_id = entry._extra.get("parentToolUseID").and_then(|p| p.as_str());
-
-                 msg.content = vec![serde_json::json!({
-                     "type": "progress",
-                     "text": prompt,
-                     "agentId": agent_id,
-                 })];
-
-                 if let (Some(pid), Some(ref aid)) = (parent_id, agent_id) {
-                     if let Some(&msg_idx) = tool_use_map.get(pid) {
-                         for tool_block in &mut messages[msg_idx].content {
-                             if tool_block.get("id").and_then(|i: &Value| i.as_str()) == Some(pid) {
-                                 tool_block.as_object_mut().unwrap().insert("agentId".to_string(), Value::String(aid.clone()));
-                             }
-                         }
-                     }
-                 }
-            }
-        }
-
-        messages.push(msg);
-    }
-
-    // Sort newest first
-    messages.sort_by(|a, b| {
-        let a_ts = DateTime::parse_from_rfc3339(&a.timestamp).ok();
-        let b_ts = DateTime::parse_from_rfc3339(&b.timestamp).ok();
-        b_ts.cmp(&a_ts)
-    });
-
-    Ok(messages)
+pub async fn get_all_session_messages(
+    project_id: String,
+    session_id: String,
+    source: Option<String>,
+    branch: Option<String>,
+) -> Result<Vec<Message>, String> {
+    let transcript = load_transcript(project_id, session_id, source.as_deref(), branch).await?;
+    Ok(transcript.messages)
 }
 
 // ============================================================================
 // Codex Session Commands
 // ============================================================================
+// Kept for existing callers; equivalent to the generic commands with source "codex".
 
 /// Get all Codex projects from ~/.codex/sessions/
 #[tauri::command]
 pub async fn get_codex_projects() -> Result<Vec<Project>, String> {
-    let sessions_dir = get_codex_sessions_dir()?;
-    if !sessions_dir.exists() {
-        return Ok(Vec::new());
-    }
-
-    let files = list_codex_session_files(&sessions_dir);
-    let mut projects: HashMap<String, Project> = HashMap::new();
-
-    for file in files {
-        let meta = read_codex_session_meta(&file)?;
-        let Some(cwd) = meta.cwd else { continue };
-
-        let project_id = encode_project_id(&cwd);
-        let display_name = resolve_project_name_from_cwd(&cwd);
-        let last_modified = get_file_modified_time(&file);
-
-        let entry = projects.entry(project_id.clone()).or_insert(Project {
-            id: project_id.clone(),
-            path: cwd.clone(),
-            display_name,
-            session_count: 0,
-            last_modified,
-        });
-
-        entry.session_count += 1;
-        if last_modified > entry.last_modified {
-            entry.last_modified = last_modified;
-        }
-    }
-
-    let mut result: Vec<Project> = projects.into_values().collect();
-    result.sort_by(|a, b| b.last_modified.cmp(&a.last_modified));
-    Ok(result)
+    get_projects(Some(CODEX_SOURCE.to_string())).await
 }
 
 /// Get all Codex sessions for a project
 #[tauri::command]
 pub async fn get_codex_project_sessions(project_id: String) -> Result<Vec<Session>, String> {
-    let sessions_dir = get_codex_sessions_dir()?;
-    if !sessions_dir.exists() {
-        return Ok(Vec::new());
-    }
-
-    let files = list_codex_session_files(&sessions_dir);
-    let mut sessions = Vec::new();
-
-    for file in files {
-        let meta = read_codex_session_meta(&file)?;
-        let Some(cwd) = meta.cwd else { continue };
-        if encode_project_id(&cwd) != project_id {
-            continue;
-        }
-
-        let last_modified = get_file_modified_time(&file);
-        let session_id = meta.id.clone().unwrap_or_else(|| {
-            file.file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("unknown")
-                .to_string()
-        });
-
-        let (messages, stats, summary, _) = read_codex_session_data(&file).await?;
-
-        sessions.push(Session {
-            id: session_id,
-            project_id: project_id.clone(),
-            file_path: file.to_string_lossy().to_string(),
-            last_modified,
-            message_count: messages.len(),
-            summary,
-            stats: Some(stats),
-        });
-    }
-
-    sessions.sort_by(|a, b| b.last_modified.cmp(&a.last_modified));
-    Ok(sessions)
+    get_project_sessions(project_id, Some(CODEX_SOURCE.to_string())).await
 }
 
 /// Get Codex session details with paginated messages
 #[tauri::command]
 pub async fn get_codex_session_details(project_id: String, session_id: String, page: Option<usize>) -> Result<SessionDetails, String> {
-    let session_path = find_codex_session_file(&project_id, &session_id)?;
-    let (messages, stats, summary, _) = read_codex_session_data(&session_path).await?;
-
-    let last_modified = get_file_modified_time(&session_path);
-    let page_num = page.unwrap_or(1);
-    let start = (page_num.saturating_sub(1)) * MESSAGES_PER_PAGE;
-    let end = (start + MESSAGES_PER_PAGE).min(messages.len());
-    let page_messages = if start < messages.len() {
-        messages[start..end].to_vec()
-    } else {
-        Vec::new()
-    };
-
-    Ok(SessionDetails {
-        id: session_id,
-        project_id,
-        file_path: session_path.to_string_lossy().to_string(),
-        last_modified,
-        message_count: messages.len(),
-        summary,
-        stats: Some(stats),
-        messages: page_messages,
-    })
+    get_session_details(project_id, session_id, page, Some(CODEX_SOURCE.to_string()), None).await
 }
 
 /// Get Codex session paginated messages
 #[tauri::command]
 pub async fn get_codex_session_paginated(project_id: String, session_id: String, page: Option<usize>) -> Result<PaginatedMessages, String> {
-    let session_path = find_codex_session_file(&project_id, &session_id)?;
-    let (messages, stats, _, _) = read_codex_session_data(&session_path).await?;
-
-    let page_num = page.unwrap_or(1);
-    let start = (page_num.saturating_sub(1)) * MESSAGES_PER_PAGE;
-    let end = (start + MESSAGES_PER_PAGE).min(messages.len());
-    let page_messages = if start < messages.len() {
-        messages[start..end].to_vec()
-    } else {
-        Vec::new()
-    };
-
-    Ok(PaginatedMessages {
-        messages: page_messages,
-        total_pages: stats.total_pages,
-        current_page: page_num,
-        total_messages: messages.len(),
-    })
+    get_session_paginated(project_id, session_id, page, Some(CODEX_SOURCE.to_string()), None).await
 }
 
 /// Get all Codex session messages (no pagination)
 #[tauri::command]
 pub async fn get_codex_all_session_messages(project_id: String, session_id: String) -> Result<Vec<Message>, String> {
-    let session_path = find_codex_session_file(&project_id, &session_id)?;
-    let (messages, _, _, _) = read_codex_session_data(&session_path).await?;
-    Ok(messages)
+    get_all_session_messages(project_id, session_id, Some(CODEX_SOURCE.to_string()), None).await
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multi_block_response_keeps_usage_on_first_line() {
+        let usage = r#"{"input_tokens":100,"output_tokens":50,"costUsd":0.25}"#;
+        let lines = [
+            r#"{"type":"user","uuid":"u1","parentUuid":null,"timestamp":"2025-11-01T10:00:00Z","message":{"role":"user","content":"hi"}}"#.to_string(),
+            format!(
+                r#"{{"type":"assistant","uuid":"a1","parentUuid":"u1","timestamp":"2025-11-01T10:00:01Z","message":{{"id":"msg_1","role":"assistant","content":[{{"type":"text","text":"hello"}}],"usage":{}}}}}"#,
+                usage
+            ),
+            format!(
+                r#"{{"type":"assistant","uuid":"a2","parentUuid":"a1","timestamp":"2025-11-01T10:00:02Z","message":{{"id":"msg_1","role":"assistant","content":[{{"type":"tool_use","id":"t1","name":"Read","input":{{}}}}],"usage":{}}}}}"#,
+                usage
+            ),
+        ];
+        let path = std::env::temp_dir().join(format!("transcripts-test-{}.jsonl", std::process::id()));
+        fs::write(&path, lines.join("\n") + "\n").unwrap();
+
+        let mut tail = ClaudeSessionTail::new(path.clone(), "p");
+        let (messages, stats) = tail.poll().unwrap().unwrap();
+        let _ = fs::remove_file(&path);
+        assert_eq!(messages.iter().filter(|m| m.usage.is_some()).count(), 1);
+        assert_eq!(stats.total_cost_usd, 0.25);
+
+        let tree: Vec<TreeEntry> = tail.entries.iter().map(tree_entry).collect();
+        assert_eq!(tree.iter().map(|e| e.cost_usd).sum::<f64>(), 0.25);
+        assert_eq!(tree.iter().map(|e| e.tokens).sum::<u64>(), 150);
+    }
 }
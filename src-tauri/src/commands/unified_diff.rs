@@ -0,0 +1,656 @@
+/**
+ * Unified diffs and patch export
+ *
+ * Computes line-based unified diffs (Myers algorithm, 3 lines of context) for
+ * single file changes and for everything a session or a date range changed in
+ * a project. Full before/after contents come from replaying the session's
+ * history (see file_reconstruction), so hunks carry real line numbers. Patches
+ * use the `git diff` format with paths relative to the repository root and can
+ * be applied elsewhere with `git apply`.
+ */
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::data_roots::resolve_qualified_id;
+use super::file_reconstruction::{file_timeline, FileTimeline, Replay, BASELINE_NONE};
+use super::files::{project_file_changes, session_file_changes, FileChange};
+use super::git_correlation::repo_root;
+use super::transcripts::find_project_cwd;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+const CONTEXT_LINES: usize = 3;
+
+/// Beyond this many differing lines the middle of a file is diffed as one replacement
+const MAX_EDIT_DISTANCE: usize = 4000;
+
+pub const LINE_CONTEXT: &str = "context";
+pub const LINE_ADD: &str = "add";
+pub const LINE_REMOVE: &str = "remove";
+
+pub const STATUS_ADDED: &str = "added";
+pub const STATUS_MODIFIED: &str = "modified";
+pub const STATUS_DELETED: &str = "deleted";
+
+const NO_NEWLINE_MARKER: &str = "\\ No newline at end of file";
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// One step of a line diff: indices into the old and new line lists
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffLine {
+    /// "context", "add" or "remove"
+    pub kind: String,
+    /// 1-based line in the old contents (context and removed lines)
+    pub old_line: Option<usize>,
+    /// 1-based line in the new contents (context and added lines)
+    pub new_line: Option<usize>,
+    /// Line text without its line terminator
+    pub text: String,
+    /// The line is the last one and has no trailing newline
+    pub no_newline: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffHunk {
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileChangeDiff {
+    pub project_id: String,
+    pub session_id: String,
+    pub hash: String,
+    pub message_uuid: String,
+    pub file_path: String,
+    #[serde(rename = "type")]
+    pub change_type: String,
+    /// Hunks are against the full file; otherwise they only cover the edited snippet
+    pub exact: bool,
+    pub additions: usize,
+    pub deletions: usize,
+    pub hunks: Vec<DiffHunk>,
+    /// Unified diff text of the change
+    pub patch: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchFile {
+    pub file_path: String,
+    /// Path used in the patch, relative to the repository root
+    pub relative_path: String,
+    /// "added", "modified" or "deleted"
+    pub status: String,
+    pub additions: usize,
+    pub deletions: usize,
+    pub change_count: usize,
+    /// Both end states were replayed from a backup or Write without divergences
+    pub exact: bool,
+    pub hunks: Vec<DiffHunk>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedFile {
+    pub file_path: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchExport {
+    pub project_id: String,
+    pub session_id: Option<String>,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    /// Directory patch paths are relative to
+    pub base_dir: Option<String>,
+    pub files: Vec<PatchFile>,
+    /// Files that changed but could not be included in the patch
+    pub skipped: Vec<SkippedFile>,
+    pub file_name: String,
+    /// The patch, in `git diff` format
+    pub content: String,
+    /// Set when the patch was written to disk
+    pub output_path: Option<String>,
+}
+
+// ============================================================================
+// Line Diff
+// ============================================================================
+
+/// Lines including their terminators, so a missing final newline counts as a difference
+fn split_lines(text: &str) -> Vec<&str> {
+    text.split_inclusive('\n').collect()
+}
+
+/// Walk the saved frontiers back from (n, m) to recover the edit script
+fn backtrack(trace: &[Vec<isize>], n: isize, m: isize) -> Vec<Op> {
+    let mut ops = Vec::new();
+    let (mut x, mut y) = (n, m);
+
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        // Frontier saved at the start of round d covers diagonals -(d + 1)..=(d + 1)
+        let at = |k: isize| v[(k + d + 1) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) { k + 1 } else { k - 1 };
+        let prev_x = at(prev_k);
+        let prev_y = prev_x - prev_k;
+
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            ops.push(Op::Equal(x as usize, y as usize));
+        }
+        if d > 0 {
+            if x == prev_x {
+                y -= 1;
+                ops.push(Op::Insert(y as usize));
+            } else {
+                x -= 1;
+                ops.push(Op::Delete(x as usize));
+            }
+        }
+    }
+
+    ops.reverse();
+    ops
+}
+
+/// Shortest edit script between `a` and `b`, or None beyond MAX_EDIT_DISTANCE
+fn myers(a: &[&str], b: &[&str]) -> Option<Vec<Op>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = (n + m).min(MAX_EDIT_DISTANCE as isize);
+    let offset = max + 1;
+    let mut v = vec![0isize; (2 * max + 3) as usize];
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    for d in 0..=max {
+        trace.push(v[(offset - d - 1) as usize..=(offset + d + 1) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let i = (offset + k) as usize;
+            let mut x = if k == -d || (k != d && v[i - 1] < v[i + 1]) { v[i + 1] } else { v[i - 1] + 1 };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[i] = x;
+            if x >= n && y >= m {
+                return Some(backtrack(&trace, n, m));
+            }
+        }
+    }
+
+    None
+}
+
+/// Line diff of `old` against `new`; the common prefix and suffix are matched
+/// directly so only the changed middle goes through Myers
+fn diff_lines(old: &[&str], new: &[&str]) -> Vec<Op> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (old_mid, new_mid) = (&old[prefix..old.len() - suffix], &new[prefix..new.len() - suffix]);
+
+    let mut ops: Vec<Op> = (0..prefix).map(|i| Op::Equal(i, i)).collect();
+    match myers(old_mid, new_mid) {
+        Some(middle) => ops.extend(middle.into_iter().map(|op| match op {
+            Op::Equal(i, j) => Op::Equal(i + prefix, j + prefix),
+            Op::Delete(i) => Op::Delete(i + prefix),
+            Op::Insert(j) => Op::Insert(j + prefix),
+        })),
+        None => {
+            ops.extend((0..old_mid.len()).map(|i| Op::Delete(i + prefix)));
+            ops.extend((0..new_mid.len()).map(|j| Op::Insert(j + prefix)));
+        }
+    }
+    ops.extend((0..suffix).map(|i| Op::Equal(old.len() - suffix + i, new.len() - suffix + i)));
+    ops
+}
+
+fn diff_line(kind: &str, old_line: Option<usize>, new_line: Option<usize>, raw: &str) -> DiffLine {
+    let text = raw.strip_suffix('\n').unwrap_or(raw);
+    DiffLine {
+        kind: kind.to_string(),
+        old_line,
+        new_line,
+        no_newline: text.len() == raw.len(),
+        text: text.to_string(),
+    }
+}
+
+/// Unified diff hunks turning `old` into `new`
+pub fn diff_hunks(old: &str, new: &str) -> Vec<DiffHunk> {
+    let (old_lines, new_lines) = (split_lines(old), split_lines(new));
+    let ops = diff_lines(&old_lines, &new_lines);
+
+    // Old and new lines consumed before each op
+    let mut consumed = Vec::with_capacity(ops.len() + 1);
+    let (mut o, mut n) = (0, 0);
+    for op in &ops {
+        consumed.push((o, n));
+        match op {
+            Op::Equal(..) => {
+                o += 1;
+                n += 1;
+            }
+            Op::Delete(_) => o += 1,
+            Op::Insert(_) => n += 1,
+        }
+    }
+
+    // Changes closer than two contexts apart share a hunk
+    let changed: Vec<usize> = ops.iter().enumerate().filter(|(_, op)| !matches!(op, Op::Equal(..))).map(|(i, _)| i).collect();
+    let mut groups: Vec<(usize, usize)> = Vec::new();
+    for &i in &changed {
+        match groups.last_mut() {
+            Some((_, last)) if i - *last <= 2 * CONTEXT_LINES + 1 => *last = i,
+            _ => groups.push((i, i)),
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(first, last)| {
+            let start = first.saturating_sub(CONTEXT_LINES);
+            let end = (last + CONTEXT_LINES).min(ops.len() - 1);
+
+            let lines: Vec<DiffLine> = ops[start..=end]
+                .iter()
+                .map(|op| match *op {
+                    Op::Equal(i, j) => diff_line(LINE_CONTEXT, Some(i + 1), Some(j + 1), old_lines[i]),
+                    Op::Delete(i) => diff_line(LINE_REMOVE, Some(i + 1), None, old_lines[i]),
+                    Op::Insert(j) => diff_line(LINE_ADD, None, Some(j + 1), new_lines[j]),
+                })
+                .collect();
+
+            let old_count = lines.iter().filter(|l| l.kind != LINE_ADD).count();
+            let new_count = lines.iter().filter(|l| l.kind != LINE_REMOVE).count();
+            let (old_before, new_before) = consumed[start];
+
+            DiffHunk {
+                // An empty side starts at the line it follows
+                old_start: old_before + usize::from(old_count > 0),
+                old_lines: old_count,
+                new_start: new_before + usize::from(new_count > 0),
+                new_lines: new_count,
+                lines,
+            }
+        })
+        .collect()
+}
+
+fn hunk_range(start: usize, count: usize) -> String {
+    if count == 1 {
+        start.to_string()
+    } else {
+        format!("{},{}", start, count)
+    }
+}
+
+/// Hunks in unified diff text form
+pub fn render_hunks(hunks: &[DiffHunk]) -> String {
+    let mut out = String::new();
+    for hunk in hunks {
+        out.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(hunk.old_start, hunk.old_lines),
+            hunk_range(hunk.new_start, hunk.new_lines)
+        ));
+        for line in &hunk.lines {
+            let prefix = match line.kind.as_str() {
+                LINE_ADD => '+',
+                LINE_REMOVE => '-',
+                _ => ' ',
+            };
+            out.push(prefix);
+            out.push_str(&line.text);
+            out.push('\n');
+            if line.no_newline {
+                out.push_str(NO_NEWLINE_MARKER);
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+fn count_lines(hunks: &[DiffHunk], kind: &str) -> usize {
+    hunks.iter().flat_map(|h| &h.lines).filter(|l| l.kind == kind).count()
+}
+
+/// `git diff` section for one file; `None` contents mean the file does not exist
+pub fn file_patch(relative_path: &str, old: Option<&str>, new: Option<&str>) -> (String, Vec<DiffHunk>) {
+    if old == new {
+        return (String::new(), Vec::new());
+    }
+    let hunks = diff_hunks(old.unwrap_or(""), new.unwrap_or(""));
+
+    let mut out = format!("diff --git a/{path} b/{path}\n", path = relative_path);
+    match (old, new) {
+        (None, _) => out.push_str("new file mode 100644\n"),
+        (_, None) => out.push_str("deleted file mode 100644\n"),
+        _ => {}
+    }
+    if !hunks.is_empty() {
+        let from = if old.is_some() { format!("a/{}", relative_path) } else { "/dev/null".to_string() };
+        let to = if new.is_some() { format!("b/{}", relative_path) } else { "/dev/null".to_string() };
+        out.push_str(&format!("--- {}\n+++ {}\n", from, to));
+        out.push_str(&render_hunks(&hunks));
+    }
+
+    (out, hunks)
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Directory the patch paths are relative to: the repository root, else the project's working directory
+fn base_dir(project_id: &str) -> Result<Option<PathBuf>, String> {
+    let (root, dir_name) = resolve_qualified_id(project_id)?;
+    let cwd = find_project_cwd(&root.claude_projects_dir().join(dir_name));
+    let repo = if root.is_local() { cwd.as_deref().and_then(repo_root) } else { None };
+    Ok(repo.or(cwd))
+}
+
+fn relative_path(file_path: &str, base: Option<&Path>) -> Option<String> {
+    let relative = match base {
+        Some(base) => Path::new(file_path).strip_prefix(base).ok()?.to_string_lossy().to_string(),
+        None => file_path.trim_start_matches('/').to_string(),
+    };
+    Some(relative.replace('\\', "/"))
+}
+
+fn in_date_range(change: &FileChange, start_date: Option<&str>, end_date: Option<&str>) -> bool {
+    let day: String = change.timestamp.chars().take(10).collect();
+    start_date.map(|s| day.as_str() >= s).unwrap_or(true) && end_date.map(|e| day.as_str() <= e).unwrap_or(true)
+}
+
+async fn change_diff(project_id: &str, session_id: &str, hash: &str) -> Result<FileChangeDiff, String> {
+    let change = session_file_changes(project_id, session_id)
+        .await?
+        .into_iter()
+        .find(|c| c.hash == hash)
+        .ok_or_else(|| "Change not found".to_string())?;
+
+    let timeline = file_timeline(project_id, session_id, &change.file_path).await?;
+    let index = timeline
+        .change_index(&change, false)
+        .ok_or_else(|| "Change not found in file history".to_string())?;
+    let (before, after) = (timeline.at(index), timeline.at(index + 1));
+
+    // When the change could not be replayed, diff the snippet it replaced instead
+    let applied = after.applied_changes > before.applied_changes;
+    let (old, new, exact) = if applied {
+        (before.content.clone(), after.content.clone(), before.baseline.kind != BASELINE_NONE)
+    } else {
+        (Some(change.old_content.clone().unwrap_or_default()), Some(change.content.clone()), false)
+    };
+
+    let display_path = change.file_path.trim_start_matches('/');
+    let (patch, hunks) = file_patch(display_path, old.as_deref(), new.as_deref());
+
+    Ok(FileChangeDiff {
+        project_id: project_id.to_string(),
+        session_id: session_id.to_string(),
+        hash: change.hash.clone(),
+        message_uuid: change.message_uuid.clone(),
+        file_path: change.file_path.clone(),
+        change_type: change.change_type.clone(),
+        exact,
+        additions: count_lines(&hunks, LINE_ADD),
+        deletions: count_lines(&hunks, LINE_REMOVE),
+        hunks,
+        patch,
+    })
+}
+
+/// State of a file before `first` and after `last`, which may be in different sessions
+async fn file_span(
+    project_id: &str,
+    file_path: &str,
+    first: &FileChange,
+    last: &FileChange,
+) -> Result<(Replay, Replay), String> {
+    let first_timeline = file_timeline(project_id, &first.session_id, file_path).await?;
+    let last_timeline: FileTimeline;
+    let last_ref = if last.session_id == first.session_id {
+        &first_timeline
+    } else {
+        last_timeline = file_timeline(project_id, &last.session_id, file_path).await?;
+        &last_timeline
+    };
+
+    let start = first_timeline.change_index(first, false).ok_or_else(|| "Change not found in file history".to_string())?;
+    let end = last_ref.change_index(last, true).ok_or_else(|| "Change not found in file history".to_string())?;
+    Ok((first_timeline.at(start), last_ref.at(end + 1)))
+}
+
+/// Changes in the date range grouped by file, oldest first. The sort is stable, so
+/// changes sharing a timestamp (the edits of one MultiEdit, the hunks of one
+/// apply_patch) keep their transcript order.
+fn changes_by_file(
+    mut changes: Vec<FileChange>,
+    start_date: Option<&str>,
+    end_date: Option<&str>,
+) -> BTreeMap<String, Vec<FileChange>> {
+    changes.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+
+    let mut by_file: BTreeMap<String, Vec<FileChange>> = BTreeMap::new();
+    for change in changes.into_iter().filter(|c| in_date_range(c, start_date, end_date)) {
+        by_file.entry(change.file_path.clone()).or_default().push(change);
+    }
+    by_file
+}
+
+async fn build_patch(
+    project_id: &str,
+    session_id: Option<&str>,
+    start_date: Option<&str>,
+    end_date: Option<&str>,
+) -> Result<PatchExport, String> {
+    let changes = match session_id {
+        Some(session_id) => session_file_changes(project_id, session_id).await?,
+        None => project_file_changes(project_id)?,
+    };
+
+    let by_file = changes_by_file(changes, start_date, end_date);
+
+    let base = base_dir(project_id)?;
+    let mut files = Vec::new();
+    let mut skipped = Vec::new();
+    let mut content = String::new();
+
+    for (file_path, changes) in by_file {
+        let skip = |reason: &str| SkippedFile { file_path: file_path.clone(), reason: reason.to_string() };
+
+        let Some(relative) = relative_path(&file_path, base.as_deref()) else {
+            skipped.push(skip("Outside the repository"));
+            continue;
+        };
+        let (first, last) = (&changes[0], &changes[changes.len() - 1]);
+        let (before, after) = match file_span(project_id, &file_path, first, last).await {
+            Ok(span) => span,
+            Err(e) => {
+                skipped.push(skip(&e));
+                continue;
+            }
+        };
+        // Without a baseline only a Write tells the starting point: the file is treated as new
+        if before.baseline.kind == BASELINE_NONE && !matches!(first.change_type.as_str(), "write" | "delete") {
+            skipped.push(skip("Contents before the first change are unknown"));
+            continue;
+        }
+        if after.content.is_none() && after.baseline.kind == BASELINE_NONE {
+            skipped.push(skip("Contents after the last change are unknown"));
+            continue;
+        }
+
+        let (patch, hunks) = file_patch(&relative, before.content.as_deref(), after.content.as_deref());
+        if patch.is_empty() {
+            continue;
+        }
+        content.push_str(&patch);
+
+        files.push(PatchFile {
+            status: match (&before.content, &after.content) {
+                (None, _) => STATUS_ADDED,
+                (_, None) => STATUS_DELETED,
+                _ => STATUS_MODIFIED,
+            }
+            .to_string(),
+            relative_path: relative,
+            exact: before.is_exact() && after.is_exact(),
+            additions: count_lines(&hunks, LINE_ADD),
+            deletions: count_lines(&hunks, LINE_REMOVE),
+            change_count: changes.len(),
+            hunks,
+            file_path,
+        });
+    }
+
+    let file_name = match session_id {
+        Some(session_id) => format!("{}.patch", session_id),
+        None => format!("changes-{}-{}.patch", start_date.unwrap_or("start"), end_date.unwrap_or("end")),
+    };
+
+    Ok(PatchExport {
+        project_id: project_id.to_string(),
+        session_id: session_id.map(String::from),
+        start_date: start_date.map(String::from),
+        end_date: end_date.map(String::from),
+        base_dir: base.map(|b| b.to_string_lossy().to_string()),
+        files,
+        skipped,
+        file_name,
+        content,
+        output_path: None,
+    })
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Unified diff of a single file change against the full file contents
+#[tauri::command]
+pub async fn get_file_change_diff(project_id: String, session_id: String, hash: String) -> Result<FileChangeDiff, String> {
+    change_diff(&project_id, &session_id, &hash).await
+}
+
+/// `git apply`-compatible patch of everything a session, or the project's sessions
+/// within a date range (YYYY-MM-DD, inclusive), changed; writes to `output_path` when given
+#[tauri::command]
+pub async fn export_patch(
+    project_id: String,
+    session_id: Option<String>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+    output_path: Option<String>,
+) -> Result<PatchExport, String> {
+    let mut export = build_patch(&project_id, session_id.as_deref(), start_date.as_deref(), end_date.as_deref()).await?;
+
+    if let Some(path) = &output_path {
+        if let Some(parent) = Path::new(path).parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create directories: {}", e))?;
+        }
+        fs::write(path, &export.content).map_err(|e| format!("Failed to write patch: {}", e))?;
+    }
+    export.output_path = output_path;
+
+    Ok(export)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hunks_have_line_numbers_and_context() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\nn\n";
+        let new = "a\nb\nc\nD\ne\nf\ng\nh\ni\nj\nk\nl\nm\nn\no\n";
+        let hunks = diff_hunks(old, new);
+
+        assert_eq!(hunks.len(), 2);
+        assert_eq!((hunks[0].old_start, hunks[0].old_lines, hunks[0].new_start, hunks[0].new_lines), (1, 7, 1, 7));
+        assert_eq!(render_hunks(&hunks[1..]), "@@ -12,3 +12,4 @@\n l\n m\n n\n+o\n");
+
+        let removed = hunks[0].lines.iter().find(|l| l.kind == LINE_REMOVE).unwrap();
+        assert_eq!((removed.old_line, removed.text.as_str()), (Some(4), "d"));
+    }
+
+    #[test]
+    fn test_file_patch_headers() {
+        let (patch, _) = file_patch("src/new.rs", None, Some("fn main() {}"));
+        assert_eq!(
+            patch,
+            "diff --git a/src/new.rs b/src/new.rs\nnew file mode 100644\n--- /dev/null\n+++ b/src/new.rs\n\
+             @@ -0,0 +1 @@\n+fn main() {}\n\\ No newline at end of file\n"
+        );
+
+        let (patch, _) = file_patch("a.txt", Some("x\ny\n"), Some("x\nz\n"));
+        assert!(patch.ends_with("--- a/a.txt\n+++ b/a.txt\n@@ -1,2 +1,2 @@\n x\n-y\n+z\n"));
+        assert!(file_patch("a.txt", Some("same"), Some("same")).0.is_empty());
+    }
+    #[test]
+    fn test_changes_sharing_a_timestamp_keep_transcript_order() {
+        let change = |hash: &str, timestamp: &str| FileChange {
+            hash: hash.to_string(),
+            session_id: "s".to_string(),
+            project_id: "p".to_string(),
+            file_path: "/repo/a.txt".to_string(),
+            change_type: "edit".to_string(),
+            timestamp: timestamp.to_string(),
+            content: String::new(),
+            old_content: None,
+            message_uuid: "m".to_string(),
+            replace_all: false,
+            tool: "MultiEdit".to_string(),
+            notebook_cell: None,
+        };
+        // Newest first, as listed: a later Write, then a MultiEdit's three edits in transcript order
+        let changes = vec![
+            change("w", "2025-11-01T10:05:00Z"),
+            change("e1", "2025-11-01T10:00:00Z"),
+            change("e2", "2025-11-01T10:00:00Z"),
+            change("e3", "2025-11-01T10:00:00Z"),
+        ];
+
+        let by_file = changes_by_file(changes, None, None);
+        let order: Vec<&str> = by_file["/repo/a.txt"].iter().map(|c| c.hash.as_str()).collect();
+        assert_eq!(order, vec!["e1", "e2", "e3", "w"]);
+    }
+}
@@ -0,0 +1,407 @@
+/**
+ * Usage blocks
+ *
+ * Claude subscriptions meter usage in rolling 5-hour sessions plus a weekly
+ * cap. Assistant messages are grouped into 5-hour blocks the same way: a block
+ * starts at the hour of its first message and takes every message until five
+ * hours later; the next message after that (or after a 5-hour gap) opens a new
+ * block.
+ *
+ * For the current block the burn rate (tokens per minute since its first
+ * message) projects the tokens at reset and when the plan's limit would run
+ * out. Weekly totals cover the last seven days.
+ *
+ * Anthropic does not publish token limits, so the limits per plan are
+ * estimates: input plus output tokens per block (cache tokens are reported
+ * but not counted) and active hours per week. The plan comes from the CLI
+ * credentials (`get_auth_status`) unless one is given.
+ */
+
+use serde::{Deserialize, Serialize};
+
+use super::analytics_v2::indexed_entry_cost;
+use super::auth::get_auth_status;
+use super::data_roots::LOCAL_ROOT;
+use super::transcript_index::with_index;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+const BLOCK_MS: i64 = 5 * 60 * 60 * 1000;
+const HOUR_MS: i64 = 60 * 60 * 1000;
+const MINUTE_MS: i64 = 60 * 1000;
+const WEEK_MS: i64 = 7 * 24 * HOUR_MS;
+const DEFAULT_HISTORY_DAYS: u32 = 7;
+
+pub const PLAN_PRO: &str = "pro";
+pub const PLAN_MAX_5X: &str = "max5x";
+pub const PLAN_MAX_20X: &str = "max20x";
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// Estimated limits of a subscription plan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanLimits {
+    pub plan: String,
+    /// Input plus output tokens per 5-hour block
+    pub block_tokens: u64,
+    /// API-equivalent spend per 5-hour block
+    pub block_cost_usd: f64,
+    /// Active hours per week (low end of Anthropic's published Sonnet range)
+    pub weekly_hours: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageBlock {
+    pub start: String,
+    /// When the block resets
+    pub end: String,
+    pub first_activity: String,
+    pub last_activity: String,
+    pub is_active: bool,
+    pub messages: usize,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    /// Input plus output tokens, the part counted against limits
+    pub tokens: u64,
+    pub cost_usd: f64,
+    pub models: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrentBlock {
+    pub block: UsageBlock,
+    pub minutes_remaining: i64,
+    pub tokens_per_minute: f64,
+    pub cost_per_hour: f64,
+    /// Tokens and spend at reset if the burn rate holds
+    pub projected_tokens: u64,
+    pub projected_cost_usd: f64,
+    /// Tokens as a fraction of the plan's block limit
+    pub used: Option<f64>,
+    /// When the block limit runs out at the current burn rate, if before the reset
+    pub exhausts_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeeklyUsage {
+    pub start: String,
+    pub blocks: usize,
+    pub messages: usize,
+    pub tokens: u64,
+    pub cost_usd: f64,
+    /// Hours between the first and last message of each block, summed
+    pub active_hours: f64,
+    /// Active hours as a fraction of the plan's weekly hours
+    pub used: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageBlocks {
+    pub plan: Option<String>,
+    pub limits: Option<PlanLimits>,
+    pub current: Option<CurrentBlock>,
+    pub weekly: WeeklyUsage,
+    /// Newest first
+    pub blocks: Vec<UsageBlock>,
+}
+
+/// One assistant message with usage
+#[derive(Debug, Clone)]
+pub struct Activity {
+    pub ms: i64,
+    pub model: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cost_usd: f64,
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn ms_to_rfc3339(ms: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(ms).map(|t| t.to_rfc3339()).unwrap_or_default()
+}
+
+/// Plan id from the credentials' subscription type and rate limit tier
+pub fn resolve_plan(subscription: Option<&str>, rate_limit_tier: Option<&str>) -> Option<String> {
+    let tier = rate_limit_tier.unwrap_or("").to_lowercase();
+    let plan = match subscription.map(str::to_lowercase).as_deref() {
+        _ if tier.contains("20x") => PLAN_MAX_20X,
+        Some("max") => PLAN_MAX_5X,
+        _ if tier.contains("5x") => PLAN_MAX_5X,
+        Some("pro") => PLAN_PRO,
+        Some(other) if [PLAN_PRO, PLAN_MAX_5X, PLAN_MAX_20X].contains(&other) => return Some(other.to_string()),
+        _ => return None,
+    };
+    Some(plan.to_string())
+}
+
+pub fn plan_limits(plan: &str) -> Option<PlanLimits> {
+    let (block_tokens, block_cost_usd, weekly_hours) = match plan {
+        PLAN_PRO => (19_000, 18.0, 40.0),
+        PLAN_MAX_5X => (88_000, 35.0, 140.0),
+        PLAN_MAX_20X => (220_000, 140.0, 240.0),
+        _ => return None,
+    };
+    Some(PlanLimits { plan: plan.to_string(), block_tokens, block_cost_usd, weekly_hours })
+}
+
+fn new_block(first: &Activity) -> (i64, Vec<&Activity>) {
+    (first.ms - first.ms.rem_euclid(HOUR_MS), vec![first])
+}
+
+fn finish_block(start: i64, activity: &[&Activity], now_ms: i64) -> UsageBlock {
+    let first = activity.first().map(|a| a.ms).unwrap_or(start);
+    let last = activity.last().map(|a| a.ms).unwrap_or(start);
+    let sum = |f: fn(&Activity) -> u64| activity.iter().map(|a| f(a)).sum::<u64>();
+
+    let mut models: Vec<String> = activity.iter().map(|a| a.model.clone()).collect();
+    models.sort();
+    models.dedup();
+
+    let input_tokens = sum(|a| a.input_tokens);
+    let output_tokens = sum(|a| a.output_tokens);
+    UsageBlock {
+        start: ms_to_rfc3339(start),
+        end: ms_to_rfc3339(start + BLOCK_MS),
+        first_activity: ms_to_rfc3339(first),
+        last_activity: ms_to_rfc3339(last),
+        is_active: now_ms < start + BLOCK_MS && now_ms - last < BLOCK_MS,
+        messages: activity.len(),
+        input_tokens,
+        output_tokens,
+        cache_creation_tokens: sum(|a| a.cache_creation_tokens),
+        cache_read_tokens: sum(|a| a.cache_read_tokens),
+        tokens: input_tokens + output_tokens,
+        cost_usd: activity.iter().map(|a| a.cost_usd).sum(),
+        models,
+    }
+}
+
+fn block_ms(timestamp: &str) -> i64 {
+    chrono::DateTime::parse_from_rfc3339(timestamp).map(|t| t.timestamp_millis()).unwrap_or(0)
+}
+
+/// Assistant messages with usage since `since_ms`, oldest first. Only the local
+/// root counts: imported archives and other machines' folders are not this account's usage.
+fn claude_activity(since_ms: i64) -> Result<Vec<Activity>, String> {
+    let mut activity: Vec<Activity> = with_index(|index| {
+        index
+            .sessions
+            .values()
+            .filter(|session| session.root == LOCAL_ROOT)
+            .flat_map(|session| &session.entries)
+            .filter(|entry| entry.entry_type == "assistant")
+            .filter_map(|entry| {
+                let usage = entry.usage.as_ref()?;
+                let ms = chrono::DateTime::parse_from_rfc3339(&entry.timestamp).ok()?.timestamp_millis();
+                (ms >= since_ms).then(|| Activity {
+                    ms,
+                    model: entry.model.clone().unwrap_or_else(|| "unknown".to_string()),
+                    input_tokens: usage.input_tokens as u64,
+                    output_tokens: usage.output_tokens as u64,
+                    cache_creation_tokens: usage.cache_creation_input_tokens as u64,
+                    cache_read_tokens: usage.cache_read_input_tokens as u64,
+                    cost_usd: indexed_entry_cost(entry),
+                })
+            })
+            .collect()
+    })?;
+    activity.sort_by_key(|a| a.ms);
+    Ok(activity)
+}
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+/// Group activity (oldest first) into 5-hour blocks, oldest first
+pub fn group_blocks(activity: &[Activity], now_ms: i64) -> Vec<UsageBlock> {
+    let mut blocks = Vec::new();
+    let mut current: Option<(i64, Vec<&Activity>)> = None;
+
+    for item in activity {
+        current = match current.take() {
+            Some((start, mut items)) => {
+                let last = items.last().map(|a| a.ms).unwrap_or(start);
+                if item.ms >= start + BLOCK_MS || item.ms - last >= BLOCK_MS {
+                    blocks.push(finish_block(start, &items, now_ms));
+                    Some(new_block(item))
+                } else {
+                    items.push(item);
+                    Some((start, items))
+                }
+            }
+            None => Some(new_block(item)),
+        };
+    }
+    if let Some((start, items)) = current {
+        blocks.push(finish_block(start, &items, now_ms));
+    }
+
+    blocks
+}
+
+/// Burn rate and projections of the active block, if any
+pub fn current_block(blocks: &[UsageBlock], now_ms: i64, limits: Option<&PlanLimits>) -> Option<CurrentBlock> {
+    let block = blocks.iter().rev().find(|b| b.is_active)?.clone();
+    let end_ms = block_ms(&block.end);
+    let minutes_remaining = ((end_ms - now_ms) / MINUTE_MS).max(0);
+
+    // At least a minute, so the first message of a block does not read as an infinite rate
+    let elapsed_minutes = ((now_ms - block_ms(&block.first_activity)) as f64 / MINUTE_MS as f64).max(1.0);
+    let tokens_per_minute = block.tokens as f64 / elapsed_minutes;
+    let cost_per_minute = block.cost_usd / elapsed_minutes;
+    let remaining = (end_ms - now_ms).max(0) as f64 / MINUTE_MS as f64;
+
+    let exhausts_at = limits.and_then(|limits| {
+        let left = limits.block_tokens as f64 - block.tokens as f64;
+        if left <= 0.0 || tokens_per_minute <= 0.0 {
+            return None;
+        }
+        let at = now_ms + (left / tokens_per_minute * MINUTE_MS as f64) as i64;
+        (at < end_ms).then(|| ms_to_rfc3339(at))
+    });
+
+    Some(CurrentBlock {
+        minutes_remaining,
+        tokens_per_minute,
+        cost_per_hour: cost_per_minute * 60.0,
+        projected_tokens: block.tokens + (tokens_per_minute * remaining).round() as u64,
+        projected_cost_usd: block.cost_usd + cost_per_minute * remaining,
+        used: limits.map(|l| block.tokens as f64 / l.block_tokens as f64),
+        exhausts_at,
+        block,
+    })
+}
+
+/// Totals of the blocks with activity in the last seven days
+pub fn weekly_usage(blocks: &[UsageBlock], now_ms: i64, limits: Option<&PlanLimits>) -> WeeklyUsage {
+    let start_ms = now_ms - WEEK_MS;
+    let week: Vec<&UsageBlock> = blocks.iter().filter(|b| block_ms(&b.last_activity) >= start_ms).collect();
+
+    let active_hours = week
+        .iter()
+        .map(|b| (block_ms(&b.last_activity) - block_ms(&b.first_activity)) as f64 / HOUR_MS as f64)
+        .sum::<f64>();
+
+    WeeklyUsage {
+        start: ms_to_rfc3339(start_ms),
+        blocks: week.len(),
+        messages: week.iter().map(|b| b.messages).sum(),
+        tokens: week.iter().map(|b| b.tokens).sum(),
+        cost_usd: week.iter().map(|b| b.cost_usd).sum(),
+        active_hours,
+        used: limits.map(|l| active_hours / l.weekly_hours),
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Claude Code usage in 5-hour blocks over the last `days` (default 7), with the current
+/// block's burn rate and the weekly totals. `plan` overrides the plan from the credentials.
+#[tauri::command]
+pub async fn get_usage_blocks(days: Option<u32>, plan: Option<String>) -> Result<UsageBlocks, String> {
+    let plan = match plan {
+        Some(plan) => resolve_plan(Some(&plan), None),
+        None => {
+            let auth = get_auth_status().await?;
+            resolve_plan(auth.plan.as_deref(), auth.rate_limit_tier.as_deref())
+        }
+    };
+    let limits = plan.as_deref().and_then(plan_limits);
+
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    // A week plus one block, so the weekly totals and the oldest block are complete
+    let history_ms = days.unwrap_or(DEFAULT_HISTORY_DAYS).max(7) as i64 * 24 * HOUR_MS + BLOCK_MS;
+
+    let blocks = tokio::task::spawn_blocking(move || {
+        let activity = claude_activity(now_ms - history_ms)?;
+        Ok::<_, String>(group_blocks(&activity, now_ms))
+    })
+    .await
+    .map_err(|e| format!("Usage block task failed: {}", e))??;
+
+    let current = current_block(&blocks, now_ms, limits.as_ref());
+    let weekly = weekly_usage(&blocks, now_ms, limits.as_ref());
+    let days_ms = days.unwrap_or(DEFAULT_HISTORY_DAYS) as i64 * 24 * HOUR_MS;
+    let mut blocks: Vec<UsageBlock> = blocks
+        .into_iter()
+        .filter(|b| block_ms(&b.last_activity) >= now_ms - days_ms)
+        .collect();
+    blocks.reverse();
+
+    Ok(UsageBlocks { plan, limits, current, weekly, blocks })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const T0: i64 = 1_750_000_000_000 - 1_750_000_000_000 % HOUR_MS;
+
+    fn activity(minute: i64, tokens: u64) -> Activity {
+        Activity {
+            ms: T0 + minute * MINUTE_MS,
+            model: "claude-sonnet-4".to_string(),
+            input_tokens: tokens / 2,
+            output_tokens: tokens / 2,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 1000,
+            cost_usd: 0.1,
+        }
+    }
+
+    #[test]
+    fn test_group_blocks_by_five_hours_and_gaps() {
+        // 12:10 and 16:50 share a block; 17:05 opens the next; 23:00 follows a 5-hour gap
+        let items = vec![activity(10, 100), activity(290, 100), activity(305, 100), activity(660, 100)];
+        let blocks = group_blocks(&items, T0 + 700 * MINUTE_MS);
+
+        assert_eq!(blocks.iter().map(|b| b.messages).collect::<Vec<_>>(), vec![2, 1, 1]);
+        assert_eq!(blocks[0].start, ms_to_rfc3339(T0));
+        assert_eq!(blocks[1].start, ms_to_rfc3339(T0 + 5 * HOUR_MS));
+        assert_eq!(blocks[2].start, ms_to_rfc3339(T0 + 11 * HOUR_MS));
+        assert_eq!(blocks.iter().filter(|b| b.is_active).count(), 1);
+        assert_eq!((blocks[0].tokens, blocks[0].cache_read_tokens), (200, 2000));
+    }
+
+    #[test]
+    fn test_current_block_projects_exhaustion() {
+        // 44k tokens in the first hour of a Max 5x block: the 88k limit runs out an hour later
+        let items = vec![activity(0, 22_000), activity(60, 22_000)];
+        let now = T0 + 60 * MINUTE_MS;
+        let limits = plan_limits(PLAN_MAX_5X);
+        let blocks = group_blocks(&items, now);
+
+        let current = current_block(&blocks, now, limits.as_ref()).unwrap();
+        assert_eq!(current.minutes_remaining, 240);
+        assert_eq!(current.used, Some(0.5));
+        assert_eq!(current.exhausts_at, Some(ms_to_rfc3339(T0 + 120 * MINUTE_MS)));
+        assert_eq!(current.projected_tokens, 44_000 * 5);
+
+        let weekly = weekly_usage(&blocks, now, limits.as_ref());
+        assert_eq!((weekly.blocks, weekly.tokens, weekly.active_hours), (1, 44_000, 1.0));
+        assert_eq!(resolve_plan(Some("Max"), Some("default_claude_max_20x")).as_deref(), Some(PLAN_MAX_20X));
+    }
+}
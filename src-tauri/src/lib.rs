@@ -1,16 +1,21 @@
+pub mod cli;
 mod commands;
 mod docs;
 
 use commands::{
     fs_utils::*, analytics::*, analytics_v2::*, history::*, transcripts::*, plans::*, files::*, mcp::*,
     skills::*, ai_chat::*, settings::*, library::*, agents::*, commands::*, plugins::*, reports::*,
-    auth::*, docs::*,
+    auth::*, docs::*, transcript_index::*, live_sessions::*,
+    sources::*, pricing::*, data_roots::*, archives::*, secrets::*, search::*, conversation_tree::*, conversations::*, session_compare::*, session_export::*,
+    file_reconstruction::*, git_correlation::*, edit_survival::*, unified_diff::*, tool_reliability::*, shell_commands::*,
+    session_health::*, budgets::*, spend_forecast::*, usage_blocks::*, context_window::*,
 };
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
   tauri::Builder::default()
     .plugin(tauri_plugin_dialog::init())
+    .plugin(tauri_plugin_notification::init())
     .setup(|app| {
       if cfg!(debug_assertions) {
         app.handle().plugin(
@@ -19,6 +24,7 @@ pub fn run() {
             .build(),
         )?;
       }
+      start_budget_monitor(app.handle().clone());
       Ok(())
     })
     .invoke_handler(tauri::generate_handler![
@@ -46,6 +52,34 @@ pub fn run() {
       get_analytics_data,
       get_analytics_summary,
       get_analytics_v2,
+      get_tool_reliability,
+      get_shell_analytics,
+      rebuild_transcript_index,
+      // Data root commands
+      get_data_roots,
+      add_data_root,
+      remove_data_root,
+      // Archive commands
+      export_session_archive,
+      read_archive_manifest,
+      import_session_archive,
+      remove_imported_archive,
+      // Pricing commands
+      get_price_table,
+      save_price_overrides,
+      // Budget, forecast and usage limit commands
+      get_budgets,
+      save_budgets,
+      get_budget_status,
+      get_spend_forecast,
+      get_spend_anomalies,
+      get_usage_blocks,
+      // Secret detection commands
+      get_session_secret_findings,
+      get_project_secret_findings,
+      get_secret_config,
+      save_secret_config,
+      redact_secrets,
       // History commands
       get_history,
       get_codex_history,
@@ -55,7 +89,10 @@ pub fn run() {
       get_codex_history_stats,
       get_history_projects,
       get_codex_history_projects,
+      // Search commands
+      search_transcripts,
       // Transcripts commands
+      get_transcript_sources,
       get_projects,
       get_codex_projects,
       get_project_sessions,
@@ -68,7 +105,20 @@ pub fn run() {
       delete_session,
       get_agent_transcript,
       get_all_session_messages,
+      get_conversation_tree,
+      get_project_conversations,
+      get_conversation_details,
+      compare_sessions,
+      get_session_context,
+      get_session_health_timeline,
+      get_health_thresholds,
+      set_health_thresholds,
+      export_session,
       get_codex_all_session_messages,
+      // Live session commands
+      watch_session,
+      watch_codex_session,
+      unwatch_session,
       // Plans commands
       get_plans,
       get_plan_by_id,
@@ -79,6 +129,13 @@ pub fn run() {
       get_sessions_with_file_changes,
       get_session_file_changes,
       get_file_change_by_hash,
+      reconstruct_file_at,
+      get_file_change_diff,
+      export_patch,
+      // Git correlation and edit survival commands
+      get_project_git_correlation,
+      get_session_git_status,
+      get_edit_survival,
       // MCP commands
       get_mcp_data,
       check_server_status,
@@ -128,6 +185,11 @@ pub fn run() {
       generate_documentation,
       get_documentation_prompt,
     ])
-    .run(tauri::generate_context!())
-    .expect("error while running tauri application");
+    .build(tauri::generate_context!())
+    .expect("error while building tauri application")
+    .run(|_app, event| {
+      if let tauri::RunEvent::Exit = event {
+        stop_all_watchers();
+      }
+    });
 }
@@ -171,6 +171,7 @@ export interface TokenUsage {
   inputTokens: number
   outputTokens: number
   cacheCreationTokens: number
+  cacheCreation1hTokens?: number // Part of cacheCreationTokens with the 1-hour TTL
   cacheReadTokens: number
   totalTokens: number
   costUsd: number